@@ -0,0 +1,21 @@
+//! Wall-time benchmarks for scatter rendering at increasing point counts.
+//!
+//! Run with `cargo bench --bench render`.
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use plotffi::{PlotOptions, plot_scatter_rgba_impl, random_unit_points};
+
+fn scatter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scatter_rgba");
+    group.sample_size(10);
+    for n in [10_000, 100_000, 1_000_000] {
+        let (xs, ys) = random_unit_points(n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, _| {
+            b.iter(|| plot_scatter_rgba_impl(&xs, &ys, PlotOptions::default()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, scatter);
+criterion_main!(benches);
@@ -1,8 +1,9 @@
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 fn main() {
     let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    check_fonts(&PathBuf::from(&crate_dir).join("assets").join("fonts"));
     let output_dir = PathBuf::from(&crate_dir).join("include");
 
     // Ensure include directory exists
@@ -10,13 +11,111 @@ fn main() {
 
     let config = cbindgen::Config::from_file("cbindgen.toml").expect("Failed to read cbindgen.toml");
 
-    cbindgen::Builder::new()
+    let bindings = cbindgen::Builder::new()
         .with_crate(&crate_dir)
         .with_config(config)
         .generate()
-        .expect("Failed to generate bindings")
-        .write_to_file(output_dir.join("plotffi.h"));
+        .expect("Failed to generate bindings");
 
-    println!("cargo:rerun-if-changed=src/lib.rs");
+    let mut header = Vec::new();
+    bindings.write(&mut header);
+    let header = String::from_utf8(header).expect("Generated header is not valid UTF-8");
+    check_repr_c_types(&PathBuf::from(&crate_dir).join("src"), &header);
+
+    bindings.write_to_file(output_dir.join("plotffi.h"));
+
+    println!("cargo:rerun-if-changed=src");
     println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=assets/fonts");
+}
+
+/// With the `embedded-font` feature, fails the build if a bundled font file is missing.
+///
+/// Both files are fetched by `assets/fonts/script.sh`.
+fn check_fonts(font_dir: &Path) {
+    if env::var_os("CARGO_FEATURE_EMBEDDED_FONT").is_none() {
+        return;
+    }
+    for file in ["Inter-Regular.ttf", "Inter-Bold.ttf"] {
+        let font = font_dir.join(file);
+        if !font.is_file() {
+            panic!(
+                "Bundled font {} is missing. Run assets/fonts/script.sh to download it",
+                font.display()
+            );
+        }
+    }
+}
+
+/// Fails the build if a `#[repr(C)]` type under `src_dir` is not defined in the header.
+///
+/// cbindgen only emits types that are reachable from an exported function or listed in
+/// `[export] include`, and silently drops the rest.
+fn check_repr_c_types(src_dir: &Path, header: &str) {
+    let missing: Vec<String> = repr_c_types(src_dir)
+        .into_iter()
+        .filter(|(_, name)| {
+            !["struct", "enum", "union"]
+                .iter()
+                .any(|kind| header.contains(&format!("{} {} {{", kind, name)))
+        })
+        .map(|(file, name)| format!("{} ({})", name, file.display()))
+        .collect();
+    if !missing.is_empty() {
+        panic!(
+            "#[repr(C)] types missing from include/plotffi.h: {}. \
+             Use them in an exported function or add them to [export] include in cbindgen.toml",
+            missing.join(", ")
+        );
+    }
+}
+
+/// Lists the `(file, name)` of every `#[repr(C)]` struct, enum and union in the `.rs` files
+/// under `dir`.
+fn repr_c_types(dir: &Path) -> Vec<(PathBuf, String)> {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)
+        .expect("Failed to read source directory")
+        .map(|entry| entry.expect("Failed to read source directory").path())
+        .collect();
+    entries.sort();
+
+    let mut types = Vec::new();
+    for path in entries {
+        if path.is_dir() {
+            types.extend(repr_c_types(&path));
+            continue;
+        }
+        if path.extension().is_none_or(|ext| ext != "rs") {
+            continue;
+        }
+        let source = std::fs::read_to_string(&path).expect("Failed to read source file");
+        let mut after_repr_c = false;
+        for line in source.lines().map(str::trim) {
+            if line.starts_with("#[repr(C") {
+                after_repr_c = true;
+                continue;
+            }
+            // Other attributes and comments may sit between the attribute and the item
+            if !after_repr_c || line.starts_with("#[") || line.starts_with("//") {
+                continue;
+            }
+            after_repr_c = false;
+            let item = line
+                .strip_prefix("pub(crate) ")
+                .or_else(|| line.strip_prefix("pub "))
+                .unwrap_or(line);
+            let name = ["struct ", "enum ", "union "]
+                .iter()
+                .find_map(|kind| item.strip_prefix(kind))
+                .map(|rest| {
+                    rest.split(|c: char| !c.is_alphanumeric() && c != '_')
+                        .next()
+                        .unwrap_or("")
+                });
+            if let Some(name) = name.filter(|name| !name.is_empty()) {
+                types.push((path.clone(), name.to_string()));
+            }
+        }
+    }
+    types
 }
@@ -14,6 +14,12 @@
 #include "plotffi.hpp"
 
 int main() {
+    // Refuse to run against a library built from a different header
+    if (plot_options_size() != PLOTFFI_OPTIONS_SIZE) {
+        std::cerr << "plotffi library does not match plotffi.h\n";
+        return 1;
+    }
+
     try {
         // Example 1: Simple scatter plot with auto-ranging
         {
@@ -65,10 +71,11 @@ int main() {
             std::vector<double> xs = {1.0, 2.0, 3.0};
             std::vector<double> ys = {1.0, 2.0, 3.0};
 
-            PlotOptions opt;
+            PlotOptions opt{};
             opt.width = 400;
             opt.height = 300;
             opt.marker_radius = 6;
+            opt.marker_alpha = 255;
             opt.auto_range = 1;
             opt.x_min = 0.0;
             opt.x_max = 0.0;
@@ -86,8 +93,9 @@ int main() {
             if (result == 0) {
                 std::cout << "Created scatter_c_api.png (C API)\n";
             } else {
-                const char* err = plot_last_error_message();
-                std::cerr << "C API error: " << (err ? err : "unknown") << "\n";
+                char err[512];
+                size_t len = plot_copy_last_error(err, sizeof(err));
+                std::cerr << "C API error: " << (len > 0 ? err : "unknown") << "\n";
             }
         }
 
@@ -8,8 +8,10 @@
 #ifndef PLOTFFI_HPP
 #define PLOTFFI_HPP
 
+#include <cstring>
 #include <stdexcept>
 #include <string>
+#include <utility>
 #include <vector>
 
 // Include the C header (already has extern "C" guards)
@@ -33,11 +35,86 @@ struct ScatterOptions {
     uint32_t width = 800;
     uint32_t height = 600;
     uint32_t markerRadius = 5;
+    uint8_t markerAlpha = 255;
     bool autoRange = true;
     double xMin = 0.0;
     double xMax = 1.0;
     double yMin = 0.0;
     double yMax = 1.0;
+    bool lockAspectRatio = false;
+    uint32_t xLabelAreaSize = 0;
+    uint32_t yLabelAreaSize = 0;
+    int32_t xLabelRotation = 0;
+    int32_t yLabelRotation = 0;
+    uint32_t dpi = 0;
+    bool xScientific = false;
+    bool yScientific = false;
+    bool keepTrailingZeros = false;
+    bool skipNaN = false;
+    uint32_t borderColor = 0;
+    uint32_t borderWidth = 0;
+    uint32_t axisColor = 0;
+    bool invertX = false;
+    bool invertY = false;
+    std::string xLabelFormat;
+    uint8_t lineMode = PLOT_LINE_LINEAR;
+    uint32_t polarGridRings = 0;
+    std::string title;
+    bool showRSquared = false;
+    double jitterAmount = 0.0;
+    uint64_t jitterSeed = 0;
+    std::string titleFontName;
+    std::string labelFontName;
+    uint8_t xTickFormat = PLOT_TICK_DEFAULT;
+    uint8_t yTickFormat = PLOT_TICK_DEFAULT;
+    uint32_t movingAvgWindow = 0;
+    uint32_t outerPadding = 0;
+    uint32_t lineStrokeWidth = 0;
+    uint8_t lineDashPattern = PLOT_DASH_SOLID;
+    uint8_t autoRangeMode = PLOT_RANGE_PADDED;
+    uint32_t xTickCount = 0;
+    uint32_t yTickCount = 0;
+    uint32_t xMinorTickCount = 0;
+    uint32_t yMinorTickCount = 0;
+    uint32_t gridColor = 0;
+    bool clipEnabled = false;
+    double clipXMin = 0.0;
+    double clipXMax = 0.0;
+    double clipYMin = 0.0;
+    double clipYMax = 0.0;
+    bool useSystemFontFallback = false;
+    bool base64DataUri = false;
+    bool antialias = false;
+    std::vector<double> xTicks;  // Explicit tick positions; empty = automatic
+    std::vector<double> yTicks;
+    uint64_t renderSeed = 0;  // 0 = fixed default seed
+    uint8_t colorScheme = PLOT_SCHEME_TABLEAU10;
+    double arrowScale = 0.0;  // 0 = auto
+    uint32_t labelFontSize = 0;  // 0 = 14 px
+    uint8_t legendPosition = PLOT_LEGEND_UPPER_RIGHT;
+    void (*postRenderFn)(void*) = nullptr;  // Called after the chart is drawn
+    void* postRenderCtx = nullptr;
+    bool disableLabelSkip = false;
+    uint8_t pixelEndian = 0;  // 0 = little-endian, 1 = big-endian RGB565 words
+    bool drawZeroXLine = false;
+    bool drawZeroYLine = false;
+    uint32_t zeroLineColor = 0;  // 0 = black
+    double plotAreaFraction = 0.0;  // 0 = margins from the label area sizes
+    bool forceLegend = false;
+    std::string pngTitle;
+    std::string pngAuthor;
+    std::string pngDescription;
+    std::vector<HBand> hBands;  // Shaded regions behind the data
+    std::vector<VBand> vBands;
+    uint32_t backgroundColor = 0;  // 0 = white
+    uint32_t textColor = 0;  // 0 = black
+    bool hideGrid = false;
+    bool snapRangeToTicks = false;  // Auto ranges end on grid lines
+    bool writeMetadata = false;  // Write <path>.json next to the PNG
+    uint8_t titleFontStyle = PLOT_FONT_NORMAL;
+    uint8_t axisDescFontStyle = PLOT_FONT_NORMAL;
+    bool densityOverlay = false;  // 2D histogram behind the markers
+    uint32_t densityBins = 0;  // 0 = 50 per axis
 
     ScatterOptions& setSize(uint32_t w, uint32_t h) {
         width = w;
@@ -50,11 +127,248 @@ struct ScatterOptions {
         return *this;
     }
 
+    ScatterOptions& setMarkerAlpha(uint8_t alpha) {
+        markerAlpha = alpha;
+        return *this;
+    }
+
+    ScatterOptions& setLineMode(uint8_t mode) {
+        lineMode = mode;
+        return *this;
+    }
+
+    ScatterOptions& setPolarGridRings(uint32_t rings) {
+        polarGridRings = rings;
+        return *this;
+    }
+
+    ScatterOptions& setTitle(const std::string& text) {
+        title = text;
+        return *this;
+    }
+
+    ScatterOptions& setShowRSquared(bool enabled) {
+        showRSquared = enabled;
+        return *this;
+    }
+
+    ScatterOptions& setJitter(double amount, uint64_t seed = 0) {
+        jitterAmount = amount;
+        jitterSeed = seed;
+        return *this;
+    }
+
+    /**
+     * Selects fonts registered with plot_register_font(); empty names use the bundled font.
+     */
+    ScatterOptions& setFonts(const std::string& titleFont, const std::string& labelFont) {
+        titleFontName = titleFont;
+        labelFontName = labelFont;
+        return *this;
+    }
+
+    ScatterOptions& setTickFormat(uint8_t xFormat, uint8_t yFormat) {
+        xTickFormat = xFormat;
+        yTickFormat = yFormat;
+        return *this;
+    }
+
+    ScatterOptions& setMovingAverage(uint32_t window) {
+        movingAvgWindow = window;
+        return *this;
+    }
+
+    ScatterOptions& setOuterPadding(uint32_t padding) {
+        outerPadding = padding;
+        return *this;
+    }
+
+    ScatterOptions& setLineStyle(uint32_t strokeWidth, uint8_t dashPattern = PLOT_DASH_SOLID) {
+        lineStrokeWidth = strokeWidth;
+        lineDashPattern = dashPattern;
+        return *this;
+    }
+
+    ScatterOptions& setTickCounts(uint32_t x, uint32_t y) {
+        xTickCount = x;
+        yTickCount = y;
+        return *this;
+    }
+
+    ScatterOptions& setMinorTickCounts(uint32_t x, uint32_t y) {
+        xMinorTickCount = x;
+        yMinorTickCount = y;
+        return *this;
+    }
+
+    ScatterOptions& setGridColor(uint32_t argb) {
+        gridColor = argb;
+        return *this;
+    }
+
+    /**
+     * Only draws points inside the given box; the axis ranges are unaffected.
+     */
+    ScatterOptions& setClipBox(double xMin, double xMax, double yMin, double yMax) {
+        clipEnabled = true;
+        clipXMin = xMin;
+        clipXMax = xMax;
+        clipYMin = yMin;
+        clipYMax = yMax;
+        return *this;
+    }
+
+    ScatterOptions& setSystemFontFallback(bool enabled) {
+        useSystemFontFallback = enabled;
+        return *this;
+    }
+
+    ScatterOptions& setBase64DataUri(bool enabled) {
+        base64DataUri = enabled;
+        return *this;
+    }
+
+    ScatterOptions& setAntialias(bool enabled) {
+        antialias = enabled;
+        return *this;
+    }
+
+    ScatterOptions& setTicks(std::vector<double> x, std::vector<double> y) {
+        xTicks = std::move(x);
+        yTicks = std::move(y);
+        return *this;
+    }
+
+    ScatterOptions& setRenderSeed(uint64_t seed) {
+        renderSeed = seed;
+        return *this;
+    }
+
+    ScatterOptions& setColorScheme(uint8_t scheme) {
+        colorScheme = scheme;
+        return *this;
+    }
+
+    ScatterOptions& setArrowScale(double scale) {
+        arrowScale = scale;
+        return *this;
+    }
+
+    ScatterOptions& setLabelFontSize(uint32_t size) {
+        labelFontSize = size;
+        return *this;
+    }
+
+    ScatterOptions& setLegendPosition(uint8_t position) {
+        legendPosition = position;
+        return *this;
+    }
+
+    ScatterOptions& setPostRender(void (*fn)(void*), void* ctx) {
+        postRenderFn = fn;
+        postRenderCtx = ctx;
+        return *this;
+    }
+
+    ScatterOptions& setDisableLabelSkip(bool disable) {
+        disableLabelSkip = disable;
+        return *this;
+    }
+
+    ScatterOptions& setPixelEndian(uint8_t endian) {
+        pixelEndian = endian;
+        return *this;
+    }
+
+    ScatterOptions& setZeroLines(bool x, bool y, uint32_t color = 0) {
+        drawZeroXLine = x;
+        drawZeroYLine = y;
+        zeroLineColor = color;
+        return *this;
+    }
+
+    ScatterOptions& setPlotAreaFraction(double fraction) {
+        plotAreaFraction = fraction;
+        return *this;
+    }
+
+    ScatterOptions& setForceLegend(bool force) {
+        forceLegend = force;
+        return *this;
+    }
+
+    /**
+     * Text stored in the PNG "Title", "Author" and "Description" chunks; empty strings are left out.
+     */
+    ScatterOptions& setPngMetadata(const std::string& title, const std::string& author,
+                                   const std::string& description) {
+        pngTitle = title;
+        pngAuthor = author;
+        pngDescription = description;
+        return *this;
+    }
+
+    ScatterOptions& addHBand(double lo, double hi, uint32_t color) {
+        hBands.push_back(HBand{lo, hi, color});
+        return *this;
+    }
+
+    ScatterOptions& addVBand(double lo, double hi, uint32_t color) {
+        vBands.push_back(VBand{lo, hi, color});
+        return *this;
+    }
+
+    ScatterOptions& setBackgroundColor(uint32_t argb) {
+        backgroundColor = argb;
+        return *this;
+    }
+
+    ScatterOptions& setTextColor(uint32_t argb) {
+        textColor = argb;
+        return *this;
+    }
+
+    ScatterOptions& setHideGrid(bool hide) {
+        hideGrid = hide;
+        return *this;
+    }
+
+    ScatterOptions& setSnapRangeToTicks(bool snap) {
+        snapRangeToTicks = snap;
+        return *this;
+    }
+
+    ScatterOptions& setWriteMetadata(bool write) {
+        writeMetadata = write;
+        return *this;
+    }
+
+    ScatterOptions& setTitleFontStyle(uint8_t style) {
+        titleFontStyle = style;
+        return *this;
+    }
+
+    ScatterOptions& setAxisDescFontStyle(uint8_t style) {
+        axisDescFontStyle = style;
+        return *this;
+    }
+
+    ScatterOptions& setDensityOverlay(bool enable, uint32_t bins = 0) {
+        densityOverlay = enable;
+        densityBins = bins;
+        return *this;
+    }
+
     ScatterOptions& setAutoRange(bool enabled) {
         autoRange = enabled;
         return *this;
     }
 
+    ScatterOptions& setAutoRangeMode(uint8_t mode) {
+        autoRangeMode = mode;
+        return *this;
+    }
+
     ScatterOptions& setXRange(double min, double max) {
         xMin = min;
         xMax = max;
@@ -69,19 +383,171 @@ struct ScatterOptions {
         return *this;
     }
 
+    ScatterOptions& setLockAspectRatio(bool enabled) {
+        lockAspectRatio = enabled;
+        return *this;
+    }
+
+    ScatterOptions& setLabelAreaSizes(uint32_t xArea, uint32_t yArea) {
+        xLabelAreaSize = xArea;
+        yLabelAreaSize = yArea;
+        return *this;
+    }
+
+    ScatterOptions& setDpi(uint32_t value) {
+        dpi = value;
+        return *this;
+    }
+
+    ScatterOptions& setScientific(bool xAxis, bool yAxis, bool keepZeros = false) {
+        xScientific = xAxis;
+        yScientific = yAxis;
+        keepTrailingZeros = keepZeros;
+        return *this;
+    }
+
+    /**
+     * If enabled, points with NaN or infinite coordinates are skipped instead of failing.
+     */
+    ScatterOptions& setSkipNaN(bool enabled) {
+        skipNaN = enabled;
+        return *this;
+    }
+
+    /**
+     * Sets the axis border color (packed ARGB) and width in pixels.
+     * Use PLOT_BORDER_NONE as the color to hide the border.
+     */
+    ScatterOptions& setBorder(uint32_t color, uint32_t widthPx) {
+        borderColor = color;
+        borderWidth = widthPx;
+        return *this;
+    }
+
+    /**
+     * Sets the color (packed ARGB) of the axis lines and tick marks.
+     */
+    ScatterOptions& setAxisColor(uint32_t color) {
+        axisColor = color;
+        return *this;
+    }
+
+    ScatterOptions& setInverted(bool xAxis, bool yAxis) {
+        invertX = xAxis;
+        invertY = yAxis;
+        return *this;
+    }
+
+    /**
+     * Sets the strftime-style format of time series X labels.
+     */
+    ScatterOptions& setXLabelFormat(const std::string& format) {
+        xLabelFormat = format;
+        return *this;
+    }
+
+    /**
+     * Sets tick label rotation in degrees clockwise (0, +/-45, or +/-90).
+     */
+    ScatterOptions& setLabelRotation(int32_t xDegrees, int32_t yDegrees) {
+        xLabelRotation = xDegrees;
+        yLabelRotation = yDegrees;
+        return *this;
+    }
+
     /**
      * Converts to the C API struct.
      */
     PlotOptions toCOptions() const {
-        PlotOptions opt;
+        PlotOptions opt{};
         opt.width = width;
         opt.height = height;
         opt.marker_radius = markerRadius;
+        opt.marker_alpha = markerAlpha;
+        opt.line_mode = lineMode;
+        opt.polar_grid_rings = polarGridRings;
         opt.auto_range = autoRange ? 1 : 0;
         opt.x_min = xMin;
         opt.x_max = xMax;
         opt.y_min = yMin;
         opt.y_max = yMax;
+        opt.lock_aspect_ratio = lockAspectRatio ? 1 : 0;
+        opt.x_label_area_size = xLabelAreaSize;
+        opt.y_label_area_size = yLabelAreaSize;
+        opt.x_label_rotation = xLabelRotation;
+        opt.y_label_rotation = yLabelRotation;
+        opt.dpi = dpi;
+        opt.x_scientific = xScientific ? 1 : 0;
+        opt.y_scientific = yScientific ? 1 : 0;
+        opt.keep_trailing_zeros = keepTrailingZeros ? 1 : 0;
+        opt.nan_policy = skipNaN ? 1 : 0;
+        opt.border_color = borderColor;
+        opt.border_width = borderWidth;
+        opt.axis_color = axisColor;
+        opt.invert_x = invertX ? 1 : 0;
+        opt.invert_y = invertY ? 1 : 0;
+        std::strncpy(opt.x_label_format, xLabelFormat.c_str(), sizeof(opt.x_label_format) - 1);
+        std::strncpy(opt.title, title.c_str(), sizeof(opt.title) - 1);
+        opt.show_r_squared = showRSquared ? 1 : 0;
+        opt.jitter_amount = jitterAmount;
+        opt.jitter_seed = jitterSeed;
+        std::strncpy(opt.title_font_name, titleFontName.c_str(), sizeof(opt.title_font_name) - 1);
+        opt.x_tick_format = xTickFormat;
+        opt.moving_avg_window = movingAvgWindow;
+        opt.outer_padding = outerPadding;
+        opt.line_stroke_width = lineStrokeWidth;
+        opt.line_dash_pattern = lineDashPattern;
+        opt.auto_range_mode = autoRangeMode;
+        opt.x_tick_count = xTickCount;
+        opt.y_tick_count = yTickCount;
+        opt.x_minor_tick_count = xMinorTickCount;
+        opt.y_minor_tick_count = yMinorTickCount;
+        opt.grid_color = gridColor;
+        opt.clip_enabled = clipEnabled ? 1 : 0;
+        opt.clip_x_min = clipXMin;
+        opt.clip_x_max = clipXMax;
+        opt.clip_y_min = clipYMin;
+        opt.clip_y_max = clipYMax;
+        opt.use_system_font_fallback = useSystemFontFallback ? 1 : 0;
+        opt.base64_data_uri = base64DataUri ? 1 : 0;
+        opt.antialias = antialias ? 1 : 0;
+        // The tick pointers borrow from this object, which must outlive the plot call
+        opt.x_ticks = xTicks.data();
+        opt.x_n_ticks = static_cast<uint32_t>(xTicks.size());
+        opt.y_ticks = yTicks.data();
+        opt.y_n_ticks = static_cast<uint32_t>(yTicks.size());
+        opt.render_seed = renderSeed;
+        opt.color_scheme = colorScheme;
+        opt.arrow_scale = arrowScale;
+        opt.label_font_size = labelFontSize;
+        opt.legend_position = legendPosition;
+        opt.post_render_fn = postRenderFn;
+        opt.post_render_ctx = postRenderCtx;
+        opt.disable_label_skip = disableLabelSkip ? 1 : 0;
+        opt.pixel_endian = pixelEndian;
+        opt.draw_zero_x_line = drawZeroXLine ? 1 : 0;
+        opt.draw_zero_y_line = drawZeroYLine ? 1 : 0;
+        opt.zero_line_color = zeroLineColor;
+        opt.plot_area_fraction = plotAreaFraction;
+        opt.force_legend = forceLegend ? 1 : 0;
+        std::strncpy(opt.png_title, pngTitle.c_str(), sizeof(opt.png_title) - 1);
+        std::strncpy(opt.png_author, pngAuthor.c_str(), sizeof(opt.png_author) - 1);
+        std::strncpy(opt.png_description, pngDescription.c_str(), sizeof(opt.png_description) - 1);
+        opt.h_bands = hBands.data();
+        opt.n_h_bands = static_cast<uint32_t>(hBands.size());
+        opt.v_bands = vBands.data();
+        opt.n_v_bands = static_cast<uint32_t>(vBands.size());
+        opt.background_color = backgroundColor;
+        opt.text_color = textColor;
+        opt.hide_grid = hideGrid ? 1 : 0;
+        opt.snap_range_to_ticks = snapRangeToTicks ? 1 : 0;
+        opt.write_metadata = writeMetadata ? 1 : 0;
+        opt.title_font_style = titleFontStyle;
+        opt.axis_desc_font_style = axisDescFontStyle;
+        opt.density_overlay = densityOverlay ? 1 : 0;
+        opt.density_bins = densityBins;
+        opt.y_tick_format = yTickFormat;
+        std::strncpy(opt.label_font_name, labelFontName.c_str(), sizeof(opt.label_font_name) - 1);
         return opt;
     }
 };
@@ -120,8 +586,8 @@ inline void scatterPng(
     );
 
     if (result != 0) {
-        const char* errorMsg = plot_last_error_message();
-        if (errorMsg != nullptr) {
+        char errorMsg[512];
+        if (plot_copy_last_error(errorMsg, sizeof(errorMsg)) > 0) {
             throw PlotError(std::string("Plot failed: ") + errorMsg);
         } else {
             throw PlotError("Plot failed with unknown error");
@@ -11,19 +11,310 @@
 
 #include <stdarg.h>
 #include <stdbool.h>
+#include <stddef.h>
 #include <stdint.h>
 #include <stdlib.h>
 
+#define PLOTFFI_OPTIONS_SIZE sizeof(struct PlotOptions)
+
+
+/**
+ * `PlotOptions::border_color` value that disables the axis border.
+ */
+#define PLOT_BORDER_NONE 4278190080
+
+/**
+ * Version of the C ABI, incremented whenever a struct layout or signature changes incompatibly.
+ *
+ * New `PlotOptions` fields are only ever appended, which changes the struct's size but
+ * not the offsets of existing fields, and does not bump this version. Callers detect
+ * that kind of mismatch by comparing the header's `PLOTFFI_OPTIONS_SIZE` with
+ * `plot_options_size()`.
+ */
+#define PLOTFFI_ABI_VERSION 5
+
+/**
+ * `SeriesStyle::marker_shape`: circle.
+ */
+#define PLOT_MARKER_CIRCLE 0
+
+/**
+ * `SeriesStyle::marker_shape`: axis-aligned square.
+ */
+#define PLOT_MARKER_SQUARE 1
+
+/**
+ * `SeriesStyle::marker_shape`: upward-pointing triangle.
+ */
+#define PLOT_MARKER_TRIANGLE 2
+
+/**
+ * `SeriesStyle::marker_shape`: diagonal cross (always drawn as an outline).
+ */
+#define PLOT_MARKER_CROSS 3
+
+/**
+ * `PlotOptions::color_scheme`: Tableau 10 categorical colors.
+ */
+#define PLOT_SCHEME_TABLEAU10 0
+
+/**
+ * `PlotOptions::color_scheme`: ColorBrewer Set1.
+ */
+#define PLOT_SCHEME_SET1 1
+
+/**
+ * `PlotOptions::color_scheme`: ColorBrewer Pastel1.
+ */
+#define PLOT_SCHEME_PASTEL 2
+
+/**
+ * `PlotOptions::color_scheme`: shades of blue, dark to light.
+ */
+#define PLOT_SCHEME_MONOCHROME 3
+
+/**
+ * `PlotOptions::legend_position`: no legend.
+ */
+#define PLOT_LEGEND_NONE 0
+
+/**
+ * `PlotOptions::legend_position`: upper-right corner.
+ */
+#define PLOT_LEGEND_UPPER_RIGHT 1
+
+/**
+ * `PlotOptions::legend_position`: upper-left corner.
+ */
+#define PLOT_LEGEND_UPPER_LEFT 2
+
+/**
+ * `PlotOptions::legend_position`: lower-right corner.
+ */
+#define PLOT_LEGEND_LOWER_RIGHT 3
+
+/**
+ * `PlotOptions::legend_position`: lower-left corner.
+ */
+#define PLOT_LEGEND_LOWER_LEFT 4
+
+/**
+ * `PlotOptions::legend_position`: the corner whose quadrant of the plot holds the fewest points.
+ */
+#define PLOT_LEGEND_AUTO 5
+
+/**
+ * `PlotOptions::line_mode`: straight segments between points.
+ */
+#define PLOT_LINE_LINEAR 0
+
+/**
+ * `PlotOptions::line_mode`: hold each value until the next X, then jump.
+ */
+#define PLOT_LINE_STEP_RIGHT 1
+
+/**
+ * `PlotOptions::line_mode`: jump to each value first, then hold it back to the previous X.
+ */
+#define PLOT_LINE_STEP_LEFT 2
+
+/**
+ * `PlotOptions::line_mode`: smooth curve through the points that does not overshoot
+ * between them (Fritsch-Carlson). X values must be strictly increasing.
+ */
+#define PLOT_LINE_MONOTONE_CUBIC 3
+
+/**
+ * `PlotOptions::auto_range_mode`: data extent plus 2% padding.
+ */
+#define PLOT_RANGE_PADDED 0
+
+/**
+ * `PlotOptions::auto_range_mode`: exactly the data extent.
+ */
+#define PLOT_RANGE_EXACT 1
+
+/**
+ * `PlotOptions::auto_range_mode`: data extent widened to nice round numbers.
+ */
+#define PLOT_RANGE_NICE 2
+
+/**
+ * `PlotOptions::line_dash_pattern` and `SeriesStyle::line_dash`: continuous line.
+ */
+#define PLOT_DASH_SOLID 0
+
+/**
+ * `PlotOptions::line_dash_pattern`: 8 pixels on, 4 pixels off.
+ */
+#define PLOT_DASH_DASHED 1
+
+/**
+ * `PlotOptions::line_dash_pattern`: 2 pixels on, 4 pixels off.
+ */
+#define PLOT_DASH_DOTTED 2
+
+/**
+ * `PlotOptions::line_dash_pattern`: alternating dashes and dots.
+ */
+#define PLOT_DASH_DASH_DOT 3
+
+/**
+ * `PlotOptions::title_font_style` and `axis_desc_font_style`: regular text.
+ */
+#define PLOT_FONT_NORMAL 0
+
+/**
+ * `PlotOptions::title_font_style` and `axis_desc_font_style`: bold text.
+ */
+#define PLOT_FONT_BOLD 1
+
+/**
+ * `PlotOptions::title_font_style` and `axis_desc_font_style`: italic text.
+ */
+#define PLOT_FONT_ITALIC 2
+
+/**
+ * `PlotOptions::title_font_style` and `axis_desc_font_style`: oblique text.
+ */
+#define PLOT_FONT_OBLIQUE 3
+
+/**
+ * `CompositeLayer::layer_type`: the layer data is a `ScatterLayer`.
+ */
+#define PLOT_LAYER_SCATTER 0
+
+/**
+ * `CompositeLayer::layer_type`: the layer data is a `HeatmapLayer`.
+ */
+#define PLOT_LAYER_HEATMAP 1
+
+/**
+ * `CompositeLayer::layer_type`: the layer data is a `ReferenceLine`.
+ */
+#define PLOT_LAYER_REFERENCE_LINE 2
+
+/**
+ * `ReferenceLine::orientation`: a horizontal line at a Y value.
+ */
+#define PLOT_REFERENCE_HORIZONTAL 0
+
+/**
+ * `ReferenceLine::orientation`: a vertical line at an X value.
+ */
+#define PLOT_REFERENCE_VERTICAL 1
+
+/**
+ * `plot_last_error_code()`: no error is stored.
+ */
+#define PLOT_ERR_NONE 0
+
+/**
+ * `plot_last_error_code()`: an error without a more specific code.
+ */
+#define PLOT_ERR_OTHER 1
+
+/**
+ * `plot_last_error_code()`: a NaN coordinate was rejected (`nan_policy == 0`).
+ */
+#define PLOT_ERR_NAN_VALUE 2
+
+/**
+ * `plot_last_error_code()`: an infinite coordinate was rejected (`nan_policy == 0`).
+ */
+#define PLOT_ERR_INFINITE_VALUE 3
+
+/**
+ * `plot_last_error_code()`: the library panicked internally.
+ */
+#define PLOT_ERR_INTERNAL_PANIC 4
+
+/**
+ * Tick label format: Plotters' default formatting.
+ */
+#define PLOT_TICK_DEFAULT 0
+
+/**
+ * Tick label format: rounded to an integer.
+ */
+#define PLOT_TICK_INTEGER 1
+
+/**
+ * Tick label format: two decimal places.
+ */
+#define PLOT_TICK_FIXED2 2
+
+/**
+ * Tick label format: four decimal places.
+ */
+#define PLOT_TICK_FIXED4 3
+
+/**
+ * Tick label format: SI prefixes (n, µ, m, k, M, G, T), e.g. `"1.2k"`.
+ */
+#define PLOT_TICK_SI 4
+
+/**
+ * Opaque rendering context created by `plot_begin()`.
+ *
+ * Collects the points of a scatter plot until `plot_finish()` renders them, and keeps
+ * the pixels of the last rendered image.
+ */
+typedef struct PlotContext PlotContext;
+
+/**
+ * Shaded region between two Y values across the whole X axis, for `PlotOptions::h_bands`.
+ */
+typedef struct HBand {
+  /**
+   * One edge of the band (the edges may be given in either order)
+   */
+  double lo;
+  /**
+   * The other edge of the band
+   */
+  double hi;
+  /**
+   * Fill color as packed ARGB; use an alpha below 0xFF to keep the grid visible
+   */
+  uint32_t color;
+} HBand;
+
+/**
+ * Shaded region between two X values across the whole Y axis, for `PlotOptions::v_bands`.
+ */
+typedef struct VBand {
+  /**
+   * One edge of the band (the edges may be given in either order)
+   */
+  double lo;
+  /**
+   * The other edge of the band
+   */
+  double hi;
+  /**
+   * Fill color as packed ARGB; use an alpha below 0xFF to keep the grid visible
+   */
+  uint32_t color;
+} VBand;
+
 /**
  * Options for configuring the scatter plot.
  */
 typedef struct PlotOptions {
   /**
-   * Width of the output image in pixels
+   * Width of the output image in pixels.
+   *
+   * Renders fail if the width or height exceeds the `PLOTFFI_MAX_WIDTH` or
+   * `PLOTFFI_MAX_HEIGHT` environment variable, read once per process.
    */
   uint32_t width;
   /**
-   * Height of the output image in pixels
+   * Height of the output image in pixels.
+   *
+   * For `plot_scatter_png()` and its variants, 0 (with no global default height set)
+   * means `width * y_range / x_range`, so one data unit spans as many pixels on both
+   * axes (up to 16384 pixels). Other functions require a nonzero height.
    */
   uint32_t height;
   /**
@@ -50,47 +341,2187 @@ typedef struct PlotOptions {
    * Maximum Y axis value (used when auto_range == 0)
    */
   double y_max;
-} PlotOptions;
-
-#ifdef __cplusplus
-extern "C" {
-#endif // __cplusplus
-
-/**
- * Renders a scatter plot to a PNG file.
- *
- * # Parameters
- * - `path`: NUL-terminated UTF-8 path to the output PNG file
- * - `xs`: Pointer to array of X coordinates
- * - `ys`: Pointer to array of Y coordinates
- * - `n`: Number of points (length of xs and ys arrays)
- * - `opt`: Plot configuration options
- *
- * # Returns
- * - 0 on success
- * - 1 on failure (call `plot_last_error_message()` for details)
- *
- * # Safety
- * - `path` must be a valid NUL-terminated UTF-8 string
- * - `xs` and `ys` must point to arrays of at least `n` elements
- * - `n` must be greater than 0
- */
-int32_t plot_scatter_png(const char *path,
-                         const double *xs,
-                         const double *ys,
-                         uintptr_t n,
-                         struct PlotOptions opt);
-
-/**
- * Returns the last error message, or NULL if no error has occurred.
- *
- * The returned pointer is valid until the next call to `plot_scatter_png()`.
- * The string is NUL-terminated UTF-8.
- *
- * # Safety
- * The returned pointer must not be freed by the caller.
- */
-const char *plot_last_error_message(void);
+  /**
+   * If nonzero, shrink the plot area so one X data unit spans as many pixels as one Y data unit.
+   *
+   * The image size is unchanged; the plot area is centered and the surrounding margins absorb
+   * the difference, so effective margins may be larger than configured.
+   */
+  uint8_t lock_aspect_ratio;
+  /**
+   * Height of the X axis label area in pixels (0 = default of 40)
+   */
+  uint32_t x_label_area_size;
+  /**
+   * Width of the Y axis label area in pixels (0 = default of 50)
+   */
+  uint32_t y_label_area_size;
+  /**
+   * Rotation of X axis tick labels in degrees clockwise (0, ±45, or ±90)
+   */
+  int32_t x_label_rotation;
+  /**
+   * Rotation of Y axis tick labels in degrees clockwise (0, ±45, or ±90)
+   */
+  int32_t y_label_rotation;
+  /**
+   * If nonzero, embed this resolution in dots per inch as a PNG pHYs chunk
+   */
+  uint32_t dpi;
+  /**
+   * If nonzero, format X axis tick labels in scientific notation (e.g. "1.5e-7")
+   */
+  uint8_t x_scientific;
+  /**
+   * If nonzero, format Y axis tick labels in scientific notation (e.g. "1.5e-7")
+   */
+  uint8_t y_scientific;
+  /**
+   * If nonzero, keep trailing zeros in scientific labels (e.g. "1.00e-7" instead of "1e-7")
+   */
+  uint8_t keep_trailing_zeros;
+  /**
+   * Handling of non-finite coordinates: 0 = fail with an error on NaN or infinite values,
+   * 1 = skip points with NaN or infinite coordinates
+   */
+  uint8_t nan_policy;
+  /**
+   * Color of the axis border as packed ARGB (0 = black when `border_width > 0`).
+   *
+   * The sentinel `PLOT_BORDER_NONE` (0xFF000000) disables the axis border entirely.
+   */
+  uint32_t border_color;
+  /**
+   * Width of the axis border in pixels (0 = Plotters default)
+   */
+  uint32_t border_width;
+  /**
+   * Color of the axis lines and tick marks as packed ARGB (0 = default).
+   *
+   * Takes precedence over `border_color`, which otherwise colors the same lines;
+   * `border_width` still sets their thickness and `PLOT_BORDER_NONE` still hides them.
+   * Grid lines between ticks are not affected.
+   */
+  uint32_t axis_color;
+  /**
+   * If nonzero, the X axis runs from right (minimum) to left (maximum)
+   */
+  uint8_t invert_x;
+  /**
+   * If nonzero, the Y axis runs from top (minimum) to bottom (maximum)
+   */
+  uint8_t invert_y;
+  /**
+   * NUL-terminated strftime-style format for time axis labels (empty = "%Y-%m-%d %H:%M").
+   *
+   * Only used by `plot_timeseries_png()`.
+   */
+  char x_label_format[64];
+  /**
+   * Marker opacity from 0 (invisible) to 255 (fully opaque, the default).
+   *
+   * Note that a zero-initialized struct yields invisible markers; set this to 255
+   * unless transparency is wanted.
+   */
+  uint8_t marker_alpha;
+  /**
+   * How `plot_line_png()` connects points: `PLOT_LINE_LINEAR`, `PLOT_LINE_STEP_RIGHT`,
+   * `PLOT_LINE_STEP_LEFT` or `PLOT_LINE_MONOTONE_CUBIC`
+   */
+  uint8_t line_mode;
+  /**
+   * Number of concentric grid circles in polar plots (0 = default of 5)
+   */
+  uint32_t polar_grid_rings;
+  /**
+   * NUL-terminated UTF-8 chart title drawn above the plot (empty = no title)
+   */
+  char title[128];
+  /**
+   * If nonzero, `plot_scatter_with_regression_png()` shows the R² value in the title
+   */
+  uint8_t show_r_squared;
+  /**
+   * Width in data units of the random X offset added to each scatter point (0 = no jitter).
+   *
+   * Offsets are drawn from `[-jitter_amount/2, +jitter_amount/2]` and do not affect the axis range.
+   */
+  double jitter_amount;
+  /**
+   * Seed for the jitter offsets (0 = use `render_seed`)
+   */
+  uint64_t jitter_seed;
+  /**
+   * NUL-terminated name of a registered font for the title (empty = bundled font)
+   */
+  char title_font_name[64];
+  /**
+   * NUL-terminated name of a registered font for tick and axis labels (empty = bundled font)
+   */
+  char label_font_name[64];
+  /**
+   * X tick label format: `PLOT_TICK_DEFAULT`, `PLOT_TICK_INTEGER`, `PLOT_TICK_FIXED2`, `PLOT_TICK_FIXED4` or `PLOT_TICK_SI`.
+   *
+   * `x_scientific` takes precedence when set.
+   */
+  uint8_t x_tick_format;
+  /**
+   * Y tick label format, see `x_tick_format`
+   */
+  uint8_t y_tick_format;
+  /**
+   * Width of a centered moving average drawn over scatter and line charts (0 = none)
+   */
+  uint32_t moving_avg_window;
+  /**
+   * White border in pixels added around PNG output (0 = none).
+   *
+   * `width` and `height` still describe the chart, so the image is `width + 2 * outer_padding` wide.
+   */
+  uint32_t outer_padding;
+  /**
+   * Line width in pixels for `plot_line_png()` (0 = 1 pixel)
+   */
+  uint32_t line_stroke_width;
+  /**
+   * Line pattern for `plot_line_png()`: `PLOT_DASH_SOLID`, `PLOT_DASH_DASHED`, `PLOT_DASH_DOTTED` or `PLOT_DASH_DASH_DOT`
+   */
+  uint8_t line_dash_pattern;
+  /**
+   * How `auto_range` derives axis ranges: `PLOT_RANGE_PADDED`, `PLOT_RANGE_EXACT` or `PLOT_RANGE_NICE`
+   */
+  uint8_t auto_range_mode;
+  /**
+   * Approximate number of major X ticks and labels (0 = Plotters' default)
+   */
+  uint32_t x_tick_count;
+  /**
+   * Approximate number of major Y ticks and labels (0 = Plotters' default)
+   */
+  uint32_t y_tick_count;
+  /**
+   * Maximum number of minor grid lines between two major X ticks (0 = Plotters' default).
+   *
+   * Minor lines are only drawn where the major ticks leave enough room for them,
+   * so lower `x_tick_count` to make space for more.
+   */
+  uint32_t x_minor_tick_count;
+  /**
+   * Maximum number of minor grid lines between two major Y ticks (0 = Plotters' default)
+   */
+  uint32_t y_minor_tick_count;
+  /**
+   * Major grid line color as packed ARGB (0 = Plotters' default); minor lines use it at half its alpha
+   */
+  uint32_t grid_color;
+  /**
+   * If nonzero, scatter points outside the clip box are not drawn; the axis ranges are unaffected
+   */
+  uint8_t clip_enabled;
+  /**
+   * Clip box lower X bound (inclusive)
+   */
+  double clip_x_min;
+  /**
+   * Clip box upper X bound (inclusive)
+   */
+  double clip_x_max;
+  /**
+   * Clip box lower Y bound (inclusive)
+   */
+  double clip_y_min;
+  /**
+   * Clip box upper Y bound (inclusive)
+   */
+  double clip_y_max;
+  /**
+   * If nonzero and the bundled font cannot be loaded, use a system font instead of failing.
+   *
+   * The substitution is reported through `plot_last_warning_message()`. Builds without
+   * the `embedded-font` feature always use a system font, without a warning.
+   */
+  uint8_t use_system_font_fallback;
+  /**
+   * If nonzero, `plot_scatter_png_base64()` returns a `data:image/png;base64,` URI
+   */
+  uint8_t base64_data_uri;
+  /**
+   * If nonzero, scatter markers get smooth, alpha-blended edges.
+   *
+   * This draws markers pixel by pixel and is several times slower than the default hard-edged
+   * circles, which also look crisper on small thumbnails. One-pixel lines are always
+   * anti-aliased by Plotters.
+   */
+  uint8_t antialias;
+  /**
+   * Explicit X tick positions (`x_n_ticks` values), overriding `x_tick_count`; may be null
+   * when `x_n_ticks` is 0.
+   *
+   * Values outside the X range are ignored. The array must stay valid until the call returns.
+   * Only the C functions read this field; the Rust API takes the ticks as a slice, see
+   * `RenderOptions::with_x_ticks`.
+   */
+  const double *x_ticks;
+  /**
+   * Number of values in `x_ticks` (0 = automatic ticks)
+   */
+  uint32_t x_n_ticks;
+  /**
+   * Explicit Y tick positions (`y_n_ticks` values), overriding `y_tick_count`; may be null
+   * when `y_n_ticks` is 0. Like `x_ticks`, only read by the C functions.
+   */
+  const double *y_ticks;
+  /**
+   * Number of values in `y_ticks` (0 = automatic ticks)
+   */
+  uint32_t y_n_ticks;
+  /**
+   * Seed for all random draws in a render call, such as jitter (0 = `0x2545F4914F6CDD1D`).
+   *
+   * Renders with the same seed and options produce identical images.
+   */
+  uint64_t render_seed;
+  /**
+   * Palette for series without an explicit color in multi-series charts: one of the
+   * `PLOT_SCHEME_*` constants. Colors cycle by series index; single-series charts ignore it.
+   */
+  uint8_t color_scheme;
+  /**
+   * Multiplier from vector components to arrow length in data units for
+   * `plot_quiver_png()` (0 = scale so the longest arrow spans 5% of the axis range)
+   */
+  double arrow_scale;
+  /**
+   * Font size of tick labels and the legend in pixels (0 = 14); axis descriptions are 2 pixels larger
+   */
+  uint32_t label_font_size;
+  /**
+   * Corner for the legend of multi-series charts: one of the `PLOT_LEGEND_*` constants.
+   *
+   * 0 suppresses the legend; `PlotOptions::default()` uses `PLOT_LEGEND_UPPER_RIGHT`.
+   */
+  uint8_t legend_position;
+  /**
+   * Hook called with `post_render_ctx` once the standard chart elements of a scatter
+   * plot have been drawn, before the image is encoded (may be null).
+   *
+   * Only the functions writing a scatter plot to a PNG file call it: `plot_scatter_png()`,
+   * `plot_scatter_png_ex()`, `plot_scatter_png_timed()`, `plot_scatter_f32_png()`,
+   * `plot_scatter_interleaved_png()`, `plot_scatter_strided_png()`, `plot_finish()` and
+   * `plot_scatter_png_batch_parallel()`. Other renderers and the Rust API ignore it.
+   *
+   * In C this is a plain `void (*)(void *)` function pointer, so a null pointer maps to
+   * `None`. The field is pointer-sized and pointer-aligned, which makes the struct
+   * layout depend on the target's pointer width like `x_ticks` does; callers built
+   * against an older header pass a shorter struct and must be rebuilt.
+   */
+  void (*post_render_fn)(void *ctx);
+  /**
+   * Opaque pointer passed to `post_render_fn`; the library never dereferences it.
+   */
+  void *post_render_ctx;
+  /**
+   * Nonzero keeps Plotters' automatic X tick count even when the labels overlap.
+   *
+   * By default, with `x_tick_count` 0 and unrotated labels, the number of X labels is
+   * halved until their estimated widths fit on the axis (down to 2 labels).
+   */
+  uint8_t disable_label_skip;
+  /**
+   * Byte order of the 16-bit words from `plot_scatter_rgb565()`: 0 = little-endian,
+   * 1 = big-endian
+   */
+  uint8_t pixel_endian;
+  /**
+   * Nonzero draws a line at x = 0 when it is inside the X range, over the grid and
+   * under the data
+   */
+  uint8_t draw_zero_x_line;
+  /**
+   * Nonzero draws a line at y = 0 when it is inside the Y range
+   */
+  uint8_t draw_zero_y_line;
+  /**
+   * Zero line color as packed ARGB (0 = black)
+   */
+  uint32_t zero_line_color;
+  /**
+   * Fraction of the image width and height taken by the plotting area, in (0, 1].
+   *
+   * 0 = margins from `x_label_area_size`/`y_label_area_size`. Otherwise the space left on
+   * each side is `(1 - plot_area_fraction) / 2` of the image, and on the axis sides it is
+   * split between the margin and the label area in their default proportions.
+   */
+  double plot_area_fraction;
+  /**
+   * Nonzero draws the legend of `plot_scatter_series_png()` even for a single series,
+   * which is otherwise left out as redundant
+   */
+  uint8_t force_legend;
+  /**
+   * NUL-terminated UTF-8 text stored as the PNG "Title" text chunk (empty = none)
+   */
+  char png_title[128];
+  /**
+   * NUL-terminated UTF-8 text stored as the PNG "Author" text chunk (empty = none)
+   */
+  char png_author[128];
+  /**
+   * NUL-terminated UTF-8 text stored as the PNG "Description" text chunk (empty = none)
+   */
+  char png_description[256];
+  /**
+   * Horizontal bands (`n_h_bands` values) shaded behind the data; may be null when
+   * `n_h_bands` is 0. The array must stay valid until the call returns.
+   *
+   * Only the C functions read this field; the Rust API takes the bands as a slice, see
+   * `RenderOptions::with_h_bands`.
+   */
+  const struct HBand *h_bands;
+  /**
+   * Number of values in `h_bands`
+   */
+  uint32_t n_h_bands;
+  /**
+   * Vertical bands (`n_v_bands` values) shaded behind the data; may be null when
+   * `n_v_bands` is 0. Like `h_bands`, only read by the C functions.
+   */
+  const struct VBand *v_bands;
+  /**
+   * Number of values in `v_bands`
+   */
+  uint32_t n_v_bands;
+  /**
+   * Background color as packed ARGB (0 = white)
+   */
+  uint32_t background_color;
+  /**
+   * Color of the title, tick labels and legend text as packed ARGB (0 = black)
+   */
+  uint32_t text_color;
+  /**
+   * Nonzero leaves out the grid lines; ticks and labels are still drawn
+   */
+  uint8_t hide_grid;
+  /**
+   * Nonzero makes auto ranges end on grid lines: each end moves out from the data to
+   * the nearest tick, spaced as Plotters would place `x_tick_count`/`y_tick_count`
+   * ticks on the `auto_range_mode` range. Has no effect with explicit ranges.
+   */
+  uint8_t snap_range_to_ticks;
+  /**
+   * Nonzero writes the image size, axis ranges and point count of a scatter PNG to
+   * `<path>.json` after it is rendered. Failing to write it only sets a warning.
+   */
+  uint8_t write_metadata;
+  /**
+   * Title font style: `PLOT_FONT_NORMAL`, `PLOT_FONT_BOLD`, `PLOT_FONT_ITALIC` or
+   * `PLOT_FONT_OBLIQUE`. Bold text in the bundled font uses the bundled bold face;
+   * other fonts fall back to their normal face if the style is not registered.
+   */
+  uint8_t title_font_style;
+  /**
+   * Axis description font style, see `title_font_style`
+   */
+  uint8_t axis_desc_font_style;
+  /**
+   * Nonzero draws a 2D histogram of the points behind scatter markers, each bin colored
+   * on the plasma scale by its count relative to the fullest bin, at 70% opacity.
+   * Empty bins are left out.
+   */
+  uint8_t density_overlay;
+  /**
+   * Bins per axis of the `density_overlay` histogram (0 = 50)
+   */
+  uint32_t density_bins;
+} PlotOptions;
+
+/**
+ * Symmetric error bars for `plot_scatter_errorbars_ex()`.
+ */
+typedef struct ErrorBarOptions {
+  /**
+   * Pointer to `n` X half-widths, one per point (NULL = no horizontal bars)
+   */
+  const double *x_err;
+  /**
+   * Pointer to `n` Y half-heights, one per point (NULL = no vertical bars)
+   */
+  const double *y_err;
+  /**
+   * Length in pixels of the cap drawn across each bar end (0 = no caps)
+   */
+  uint32_t cap_size_px;
+  /**
+   * Bar and cap stroke width in pixels (0 = 1)
+   */
+  uint32_t line_width;
+  /**
+   * Bar and cap color as packed ARGB (0 = the marker color)
+   */
+  uint32_t color;
+} ErrorBarOptions;
+
+/**
+ * Statistics about a completed render, filled by `plot_scatter_png_ex()`.
+ */
+typedef struct PlotStats {
+  /**
+   * Points inside the axis ranges that were drawn
+   */
+  size_t points_rendered;
+  /**
+   * Points dropped by the NaN policy
+   */
+  size_t points_skipped_nan;
+  /**
+   * Valid points outside the axis ranges
+   */
+  size_t points_clipped;
+  /**
+   * Wall-clock time of the whole call in microseconds
+   */
+  uint64_t render_time_us;
+} PlotStats;
+
+/**
+ * Where the data area of a rendered chart is, filled by `plot_scatter_png_ex()`.
+ *
+ * The data area is the rectangle inside the axes, without margins and labels. Pixel
+ * coordinates start at the top-left corner of the image and include any outer padding.
+ */
+typedef struct PlotAreaInfo {
+  /**
+   * Left edge of the data area in pixels
+   */
+  uint32_t chart_x;
+  /**
+   * Top edge of the data area in pixels
+   */
+  uint32_t chart_y;
+  /**
+   * Width of the data area in pixels
+   */
+  uint32_t chart_width;
+  /**
+   * Height of the data area in pixels
+   */
+  uint32_t chart_height;
+  /**
+   * X value at the left edge (the right edge if `invert_x` is set)
+   */
+  double x_min;
+  /**
+   * X value at the right edge (the left edge if `invert_x` is set)
+   */
+  double x_max;
+  /**
+   * Y value at the bottom edge (the top edge if `invert_y` is set)
+   */
+  double y_min;
+  /**
+   * Y value at the top edge (the bottom edge if `invert_y` is set)
+   */
+  double y_max;
+} PlotAreaInfo;
+
+/**
+ * How one series of a multi-series chart is drawn.
+ */
+typedef struct SeriesStyle {
+  /**
+   * Color of markers and line as packed ARGB (0 = next color of `PlotOptions::color_scheme`)
+   */
+  uint32_t color;
+  /**
+   * NUL-terminated UTF-8 series name shown in the legend (empty = not listed)
+   */
+  char name[64];
+  /**
+   * Marker shape: one of the `PLOT_MARKER_*` constants
+   */
+  uint8_t marker_shape;
+  /**
+   * Marker radius in pixels (0 = no markers)
+   */
+  uint32_t marker_radius;
+  /**
+   * If nonzero, connect the points in array order with a line
+   */
+  uint8_t draw_line;
+  /**
+   * Line width in pixels (0 = 1 pixel)
+   */
+  uint32_t line_width;
+  /**
+   * Line dash pattern: one of the `PLOT_DASH_*` constants
+   */
+  uint8_t line_dash;
+  /**
+   * If nonzero, markers are filled; otherwise only their outline is drawn
+   */
+  uint8_t marker_filled;
+} SeriesStyle;
+
+/**
+ * One data series of a multi-series chart and how to draw it.
+ */
+typedef struct ScatterSeries {
+  /**
+   * Pointer to array of X coordinates
+   */
+  const double *xs;
+  /**
+   * Pointer to array of Y coordinates
+   */
+  const double *ys;
+  /**
+   * Number of points (length of xs and ys arrays)
+   */
+  uint64_t n;
+  /**
+   * Marker and line style of the series
+   */
+  struct SeriesStyle style;
+  /**
+   * Drawing order: series with higher values are drawn later, on top of the others.
+   * Series with equal values keep their array order.
+   */
+  int32_t z_order;
+} ScatterSeries;
+
+/**
+ * One layer of a `plot_composite_png()` chart.
+ *
+ * `data` points to the layer-specific struct selected by `layer_type`: a `ScatterLayer`,
+ * a `HeatmapLayer` or a `ReferenceLine`.
+ */
+typedef struct CompositeLayer {
+  /**
+   * Kind of layer: one of the `PLOT_LAYER_*` constants
+   */
+  uint8_t layer_type;
+  /**
+   * Pointer to the layer data of the type given by `layer_type`
+   */
+  const void *data;
+} CompositeLayer;
+
+/**
+ * Range and style of the right Y axis of `plot_dual_axis_png()`.
+ *
+ * Image-level settings, the X axis and the left Y axis come from the chart's `PlotOptions`.
+ */
+typedef struct AxisOptions {
+  /**
+   * Lower bound of the axis when `auto_range` is 0
+   */
+  double y_min;
+  /**
+   * Upper bound of the axis when `auto_range` is 0
+   */
+  double y_max;
+  /**
+   * If nonzero, the range is computed from the data
+   */
+  uint8_t auto_range;
+  /**
+   * Fraction of the data extent added below and above automatic ranges (0 = none)
+   */
+  double auto_range_padding;
+  /**
+   * If nonzero, the axis is base-10 logarithmic; values and bounds must be positive
+   */
+  uint8_t log_scale;
+  /**
+   * Color of the series, tick labels and name as packed ARGB (0 = second color of
+   * `PlotOptions::color_scheme`)
+   */
+  uint32_t series_color;
+  /**
+   * NUL-terminated UTF-8 name shown along the axis (empty = none)
+   */
+  char series_name[64];
+} AxisOptions;
+
+/**
+ * One group of samples of a distribution chart, such as a violin plot.
+ */
+typedef struct BoxGroup {
+  /**
+   * Pointer to array of sample values
+   */
+  const double *values;
+  /**
+   * Number of samples (length of the values array)
+   */
+  uint64_t n;
+  /**
+   * NUL-terminated UTF-8 group name shown below the group (empty = no label)
+   */
+  char label[64];
+} BoxGroup;
+
+/**
+ * A single chart to render: output path, data and options.
+ */
+typedef struct BatchSpec {
+  /**
+   * NUL-terminated UTF-8 output path (ignored by `plot_grid_png`)
+   */
+  const char *path;
+  /**
+   * Pointer to array of X coordinates
+   */
+  const double *xs;
+  /**
+   * Pointer to array of Y coordinates
+   */
+  const double *ys;
+  /**
+   * Number of points (length of xs and ys arrays)
+   */
+  uint64_t n;
+  /**
+   * Plot configuration options
+   */
+  struct PlotOptions opt;
+} BatchSpec;
+
+/**
+ * One failed render of `plot_scatter_png_batch_parallel()`, read with `plot_batch_errors()`.
+ */
+typedef struct BatchError {
+  /**
+   * Index of the failed spec
+   */
+  size_t index;
+  /**
+   * NUL-terminated UTF-8 error message, truncated to fit
+   */
+  char message[256];
+} BatchError;
+
+/**
+ * The points of one frame of an animated chart.
+ */
+typedef struct GifFrame {
+  /**
+   * Pointer to array of X coordinates
+   */
+  const double *xs;
+  /**
+   * Pointer to array of Y coordinates
+   */
+  const double *ys;
+  /**
+   * Number of points (length of xs and ys arrays)
+   */
+  uint64_t n;
+} GifFrame;
+
+/**
+ * Scatter points of a composite chart layer.
+ */
+typedef struct ScatterLayer {
+  /**
+   * Pointer to array of X coordinates
+   */
+  const double *xs;
+  /**
+   * Pointer to array of Y coordinates
+   */
+  const double *ys;
+  /**
+   * Number of points (length of xs and ys arrays)
+   */
+  uint64_t n;
+  /**
+   * Marker color as packed ARGB (0 = blue with `PlotOptions::marker_alpha`)
+   */
+  uint32_t color;
+  /**
+   * Marker radius in pixels (0 = `PlotOptions::marker_radius`)
+   */
+  uint32_t marker_radius;
+} ScatterLayer;
+
+/**
+ * Point density heatmap of a composite chart layer, binned over the shared axis ranges.
+ */
+typedef struct HeatmapLayer {
+  /**
+   * Pointer to array of X coordinates
+   */
+  const double *xs;
+  /**
+   * Pointer to array of Y coordinates
+   */
+  const double *ys;
+  /**
+   * Number of points (length of xs and ys arrays)
+   */
+  uint64_t n;
+  /**
+   * Bins along X (0 = `ceil(sqrt(n))`)
+   */
+  uint32_t n_bins_x;
+  /**
+   * Bins along Y (0 = `ceil(sqrt(n))`)
+   */
+  uint32_t n_bins_y;
+  /**
+   * Opacity of the bins in (0, 1] (0 = 0.5)
+   */
+  double opacity;
+} HeatmapLayer;
+
+/**
+ * Straight line across the whole plot area at a fixed X or Y value.
+ */
+typedef struct ReferenceLine {
+  /**
+   * `PLOT_REFERENCE_HORIZONTAL` (at Y = `value`) or `PLOT_REFERENCE_VERTICAL` (at X = `value`)
+   */
+  uint8_t orientation;
+  /**
+   * Axis value the line is drawn at
+   */
+  double value;
+  /**
+   * Line color as packed ARGB (0 = black)
+   */
+  uint32_t color;
+  /**
+   * Line width in pixels (0 = 1 pixel)
+   */
+  uint32_t line_width;
+} ReferenceLine;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Renders a scatter plot to a PNG file.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output PNG file
+ * - `xs`: Pointer to array of X coordinates
+ * - `ys`: Pointer to array of Y coordinates
+ * - `n`: Number of points (length of xs and ys arrays)
+ * - `opt`: Plot configuration options
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ * - `n` must be greater than 0
+ */
+int32_t plot_scatter_png(const char *path,
+                         const double *xs,
+                         const double *ys,
+                         uint64_t n,
+                         struct PlotOptions opt);
+
+/**
+ * Renders a time series scatter plot to a PNG file.
+ *
+ * X values are Unix timestamps in seconds and are labelled as UTC dates using
+ * `opt.x_label_format` (default `"%Y-%m-%d %H:%M"`). The axis itself stays numeric.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output PNG file
+ * - `timestamps`: Pointer to array of Unix timestamps in seconds
+ * - `ys`: Pointer to array of Y coordinates
+ * - `n`: Number of points (length of timestamps and ys arrays)
+ * - `opt`: Plot configuration options
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure, including non-finite timestamps (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `timestamps` and `ys` must point to arrays of at least `n` elements
+ */
+int32_t plot_timeseries_png(const char *path,
+                            const double *timestamps,
+                            const double *ys,
+                            uint64_t n,
+                            struct PlotOptions opt);
+
+/**
+ * Renders a line chart to a PNG file.
+ *
+ * Points are connected in array order using `opt.line_mode`.
+ * Takes the same parameters as `plot_scatter_png()`.
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ */
+int32_t plot_line_png(const char *path,
+                      const double *xs,
+                      const double *ys,
+                      uint64_t n,
+                      struct PlotOptions opt);
+
+/**
+ * Renders the empirical cumulative distribution function of `data` to a PNG file.
+ *
+ * The CDF is drawn as a right-step line. The Y axis always spans 0 to 1, while the
+ * X axis follows `auto_range` or the explicit range. The caller's array is not modified.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output PNG file
+ * - `data`: Pointer to array of samples
+ * - `n`: Number of samples
+ * - `opt`: Plot configuration options
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `data` must point to an array of at least `n` elements
+ */
+int32_t plot_cdf_png(const char *path, const double *data, uint64_t n, struct PlotOptions opt);
+
+/**
+ * Renders a polar scatter plot to a PNG file.
+ *
+ * Each point is given by an angle in radians (counter-clockwise from the positive X axis)
+ * and a radius. The chart is square, with `opt.polar_grid_rings` concentric rings and
+ * radial lines every 45°. `auto_range` and the explicit ranges are ignored.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output PNG file
+ * - `angles_rad`: Pointer to array of angles in radians
+ * - `radii`: Pointer to array of radii
+ * - `n`: Number of points
+ * - `opt`: Plot configuration options
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `angles_rad` and `radii` must point to arrays of at least `n` elements
+ */
+int32_t plot_polar_scatter_png(const char *path,
+                               const double *angles_rad,
+                               const double *radii,
+                               uint64_t n,
+                               struct PlotOptions opt);
+
+/**
+ * Renders a scatter plot with its ordinary least-squares regression line to a PNG file.
+ *
+ * The red regression line spans the full X range of the chart. When
+ * `opt.show_r_squared` is set, `"R² = 0.9876"` is shown as the title, or appended
+ * to `opt.title` if one is given. Takes the same parameters as `plot_scatter_png()`.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure, including fewer than two points or all X values equal
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ */
+int32_t plot_scatter_with_regression_png(const char *path,
+                                         const double *xs,
+                                         const double *ys,
+                                         uint64_t n,
+                                         struct PlotOptions opt);
+
+/**
+ * Renders a median line with a shaded quantile band to a PNG file.
+ *
+ * The area between `p_low` and `p_high` is filled in translucent blue and `p_mid`
+ * is drawn as a solid blue line on top. Points are connected in array order, so
+ * `xs` should be sorted.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output PNG file
+ * - `xs`: Pointer to array of X coordinates
+ * - `p_low`: Pointer to array of lower quantiles, or NULL to omit the band
+ * - `p_mid`: Pointer to array of median values
+ * - `p_high`: Pointer to array of upper quantiles, or NULL to omit the band
+ * - `n`: Number of points in each array
+ * - `opt`: Plot configuration options
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure, including `p_low[i] <= p_mid[i] <= p_high[i]` not holding for some `i`
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs`, `p_mid` and the non-NULL quantile pointers must point to arrays of at least `n` elements
+ */
+int32_t plot_quantile_band_png(const char *path,
+                               const double *xs,
+                               const double *p_low,
+                               const double *p_mid,
+                               const double *p_high,
+                               uint64_t n,
+                               struct PlotOptions opt);
+
+/**
+ * Renders the area between two Y arrays as a shaded band to a PNG file.
+ *
+ * The band is filled in translucent blue and both bounds are drawn as solid lines.
+ * Points are connected in array order, so `xs` should be sorted.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output PNG file
+ * - `xs`: Pointer to array of X coordinates
+ * - `y_lower`: Pointer to array of lower bounds
+ * - `y_upper`: Pointer to array of upper bounds
+ * - `n`: Number of points in each array
+ * - `opt`: Plot configuration options
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure, including `y_lower[i] > y_upper[i]` for some `i`
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs`, `y_lower` and `y_upper` must point to arrays of at least `n` elements
+ */
+int32_t plot_band_png(const char *path,
+                      const double *xs,
+                      const double *y_lower,
+                      const double *y_upper,
+                      uint64_t n,
+                      struct PlotOptions opt);
+
+/**
+ * Renders a vector field as arrows to a PNG file.
+ *
+ * Each arrow runs from `(xs[i], ys[i])` to `(xs[i] + s * us[i], ys[i] + s * vs[i])`, where
+ * `s` is `opt.arrow_scale` or, if that is 0, chosen so the longest arrow spans 5% of the
+ * axis range. Arrowheads are 8-pixel triangles regardless of the arrow length.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output PNG file
+ * - `xs`, `ys`: Pointers to arrays of arrow base coordinates
+ * - `us`, `vs`: Pointers to arrays of vector components
+ * - `n`: Number of arrows
+ * - `opt`: Plot configuration options
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs`, `ys`, `us` and `vs` must point to arrays of at least `n` elements
+ */
+int32_t plot_quiver_png(const char *path,
+                        const double *xs,
+                        const double *ys,
+                        const double *us,
+                        const double *vs,
+                        uint64_t n,
+                        struct PlotOptions opt);
+
+/**
+ * Renders a 2D histogram (density heatmap) of points to a PNG file.
+ *
+ * The plot area is divided into `n_bins_x` x `n_bins_y` bins over the axis ranges
+ * (auto-ranged from the data or taken from the options). Each bin is colored on the
+ * viridis scale by its count divided by the largest count, and a colorbar shows the scale.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output PNG file
+ * - `xs`, `ys`: Pointers to arrays of point coordinates
+ * - `n`: Number of points
+ * - `n_bins_x`, `n_bins_y`: Bins per axis (0 = `ceil(sqrt(n))`)
+ * - `opt`: Plot configuration options
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ */
+int32_t plot_histogram2d_png(const char *path,
+                             const double *xs,
+                             const double *ys,
+                             uint64_t n,
+                             uint32_t n_bins_x,
+                             uint32_t n_bins_y,
+                             struct PlotOptions opt);
+
+/**
+ * Renders a scatter plot with symmetric error bars to a PNG file.
+ *
+ * Each point gets a vertical bar from `y - y_err[i]` to `y + y_err[i]` and/or a
+ * horizontal bar from `x - x_err[i]` to `x + x_err[i]`, depending on which arrays in
+ * `ebo` are non-NULL. Bar ends get caps of `ebo.cap_size_px` pixels.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output PNG file
+ * - `xs`, `ys`: Pointers to arrays of point coordinates
+ * - `n`: Number of points
+ * - `ebo`: Error values and bar style; at least one error array must be non-NULL
+ * - `opt`: Plot configuration options
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs`, `ys` and the non-NULL error arrays must point to at least `n` elements
+ */
+int32_t plot_scatter_errorbars_ex(const char *path,
+                                  const double *xs,
+                                  const double *ys,
+                                  uint64_t n,
+                                  struct ErrorBarOptions ebo,
+                                  struct PlotOptions opt);
+
+/**
+ * Renders a scatter plot with asymmetric error bars to a PNG file.
+ *
+ * Each point gets a vertical bar from `y - y_err_lo[i]` to `y + y_err_hi[i]` and/or a
+ * horizontal bar from `x - x_err_lo[i]` to `x + x_err_hi[i]`. Any error array may be
+ * NULL, which gives that side of the bars zero length; an axis gets no bars when both
+ * of its arrays are NULL. Bars are drawn in the marker color with the default caps of
+ * `ErrorBarOptions`.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output PNG file
+ * - `xs`, `ys`: Pointers to arrays of point coordinates
+ * - `x_err_lo`, `x_err_hi`: Non-negative lower and upper X error magnitudes (or NULL)
+ * - `y_err_lo`, `y_err_hi`: Non-negative lower and upper Y error magnitudes (or NULL)
+ * - `n`: Number of points
+ * - `opt`: Plot configuration options
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure, including negative errors or all four arrays NULL (call
+ *   `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs`, `ys` and the non-NULL error arrays must point to at least `n` elements
+ */
+int32_t plot_scatter_asymmetric_errorbars_png(const char *path,
+                                              const double *xs,
+                                              const double *ys,
+                                              const double *x_err_lo,
+                                              const double *x_err_hi,
+                                              const double *y_err_lo,
+                                              const double *y_err_hi,
+                                              uint64_t n,
+                                              struct PlotOptions opt);
+
+/**
+ * Registers a TrueType/OpenType font file for use in `title_font_name` and `label_font_name`.
+ *
+ * The bundled font is always available as `"app-font"`, and its bold face as
+ * `"app-font-bold"`. Registering the same name and style again replaces the
+ * earlier font.
+ *
+ * # Parameters
+ * - `name`: NUL-terminated UTF-8 family name to register the font under
+ * - `path`: NUL-terminated UTF-8 path to the font file
+ * - `style`: 0 = normal, 1 = bold, 2 = italic, 3 = oblique
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `name` and `path` must be valid NUL-terminated UTF-8 strings
+ */
+int32_t plot_register_font(const char *name, const char *path, uint8_t style);
+
+/**
+ * Registers a TrueType/OpenType font from memory, for hosts without a filesystem.
+ *
+ * Behaves like `plot_register_font()`: registering a name that is already registered
+ * replaces the earlier font and returns 0. The bytes are copied, so `data` only needs to
+ * be valid during the call; the copy is kept for the rest of the process.
+ *
+ * # Parameters
+ * - `name`: NUL-terminated UTF-8 family name to register the font under
+ * - `style`: 0 = normal, 1 = bold, 2 = italic, 3 = oblique
+ * - `data`: Pointer to the font file contents
+ * - `data_len`: Length of `data` in bytes
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure, including data that is not a font (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `name` must be a valid NUL-terminated UTF-8 string
+ * - `data` must point to at least `data_len` bytes
+ */
+int32_t plot_register_font_bytes(const char *name,
+                                 uint8_t style,
+                                 const uint8_t *data,
+                                 size_t data_len);
+
+/**
+ * Renders a scatter plot into a newly allocated RGBA pixel buffer.
+ *
+ * # Parameters
+ * - `xs`: Pointer to array of X coordinates
+ * - `ys`: Pointer to array of Y coordinates
+ * - `n`: Number of points (length of xs and ys arrays)
+ * - `opt`: Plot configuration options
+ * - `out_ptr`: Receives a pointer to `width * height * 4` bytes of RGBA pixels, row-major
+ * - `out_len`: Receives the buffer length in bytes
+ *
+ * The buffer must be released with `plot_free_buffer()`.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ * - `out_ptr` and `out_len` must be valid for writes
+ */
+int32_t plot_scatter_rgba(const double *xs,
+                          const double *ys,
+                          uint64_t n,
+                          struct PlotOptions opt,
+                          uint8_t **out_ptr,
+                          size_t *out_len);
+
+/**
+ * Renders a scatter plot into a newly allocated RGB565 pixel buffer.
+ *
+ * Each pixel is a 16-bit word with 5 red bits at the top, then 6 green and 5 blue bits.
+ * The words are stored little-endian, or big-endian with `opt.pixel_endian = 1`.
+ *
+ * # Parameters
+ * - `xs`: Pointer to array of X coordinates
+ * - `ys`: Pointer to array of Y coordinates
+ * - `n`: Number of points (length of xs and ys arrays)
+ * - `opt`: Plot configuration options
+ * - `out_ptr`: Receives a pointer to `width * height` pixels, row-major
+ * - `out_len`: Receives the buffer length in pixels (16-bit words)
+ *
+ * The buffer must be released with `plot_free_u16_buffer()`.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ * - `out_ptr` and `out_len` must be valid for writes
+ */
+int32_t plot_scatter_rgb565(const double *xs,
+                            const double *ys,
+                            uint64_t n,
+                            struct PlotOptions opt,
+                            uint16_t **out_ptr,
+                            size_t *out_len);
+
+/**
+ * Renders a scatter plot to an SVG file.
+ *
+ * Takes the same parameters as `plot_scatter_png()`. Text refers to the font by name,
+ * so the viewer must have a matching font installed.
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ */
+int32_t plot_scatter_svg(const char *path,
+                         const double *xs,
+                         const double *ys,
+                         uint64_t n,
+                         struct PlotOptions opt);
+
+/**
+ * Renders a scatter plot from single-precision coordinates to a PNG file.
+ *
+ * Behaves like `plot_scatter_png()`; coordinates are widened to double precision.
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ */
+int32_t plot_scatter_f32_png(const char *path,
+                             const float *xs,
+                             const float *ys,
+                             uint64_t n,
+                             struct PlotOptions opt);
+
+/**
+ * Renders a scatter plot from single-precision coordinates into an RGBA buffer.
+ *
+ * Behaves like `plot_scatter_rgba()`; release the buffer with `plot_free_buffer()`.
+ *
+ * # Safety
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ * - `out_ptr` and `out_len` must be valid for writes
+ */
+int32_t plot_scatter_f32_rgba(const float *xs,
+                              const float *ys,
+                              uint64_t n,
+                              struct PlotOptions opt,
+                              uint8_t **out_ptr,
+                              size_t *out_len);
+
+/**
+ * Renders a scatter plot from single-precision coordinates to an SVG file.
+ *
+ * Behaves like `plot_scatter_svg()`.
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ */
+int32_t plot_scatter_f32_svg(const char *path,
+                             const float *xs,
+                             const float *ys,
+                             uint64_t n,
+                             struct PlotOptions opt);
+
+/**
+ * Draws an additional series onto an existing scatter plot PNG and saves it in place.
+ *
+ * The image at `path` must have been rendered with the same `width`, `height`,
+ * `outer_padding`, label and title options as `opt`, and with explicit ranges:
+ * `opt.auto_range` is ignored and `x_min`..`y_max` must match the base chart.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the PNG file to update
+ * - `xs`: Pointer to array of X coordinates
+ * - `ys`: Pointer to array of Y coordinates
+ * - `n`: Number of points
+ * - `color`: Marker color as packed ARGB (`0xAARRGGBB`)
+ * - `opt`: Options of the base chart
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure, including a size mismatch between `opt` and the image
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ */
+int32_t plot_scatter_png_append(const char *path,
+                                const double *xs,
+                                const double *ys,
+                                uint64_t n,
+                                uint32_t color,
+                                struct PlotOptions opt);
+
+/**
+ * Renders a scatter plot to a PNG file and reports statistics about the render.
+ *
+ * Identical to `plot_scatter_png()`, except that on success `*stats` receives the number
+ * of drawn, NaN-skipped and clipped points and the elapsed time, and `*info` the pixel
+ * rectangle and axis ranges of the data area, for example to place interactive elements
+ * over the image. `stats` and `info` may be NULL.
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ * - `stats` and `info` must each be NULL or valid for writes
+ */
+int32_t plot_scatter_png_ex(const char *path,
+                            const double *xs,
+                            const double *ys,
+                            uint64_t n,
+                            struct PlotOptions opt,
+                            struct PlotStats *stats,
+                            struct PlotAreaInfo *info);
+
+/**
+ * Computes the pixel at which a data point is drawn, without rendering.
+ *
+ * Mirrors the mapping of `plot_scatter_png()` for the same options, including margins,
+ * label areas, the title, `lock_aspect_ratio`, `plot_area_fraction`, inverted axes and
+ * `outer_padding`. `(0, 0)` is the top-left pixel of the image. The options must use
+ * explicit ranges (`auto_range = 0`, see `plot_compute_range()`) and a nonzero height.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure, including "Point outside axis range" when the point is not within
+ *   the axis ranges
+ *
+ * # Safety
+ * `pixel_x` and `pixel_y` must be valid for writes.
+ */
+int32_t plot_data_to_pixel(struct PlotOptions opt,
+                           double data_x,
+                           double data_y,
+                           uint32_t *pixel_x,
+                           uint32_t *pixel_y);
+
+/**
+ * Finds the point nearest to a pixel, without rendering, for example to show a
+ * tooltip for the point under the mouse.
+ *
+ * The query pixel is converted to data coordinates with the same layout and option
+ * requirements as `plot_pixel_to_data()`, and the point nearest to them in data space is
+ * chosen. Points with a non-finite coordinate are skipped; ties go to the lowest index.
+ *
+ * # Parameters
+ * - `opt`: Options the chart was rendered with (`auto_range` must be 0)
+ * - `xs`, `ys`: Pointers to arrays of point coordinates
+ * - `n`: Number of points
+ * - `query_pixel_x`, `query_pixel_y`: Pixel to search from, including any outer padding
+ * - `out_index`: Receives the index of the nearest point
+ * - `out_dist_px`: Receives its distance from the query pixel in pixels
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure, including when `n` is 0 or the query pixel is outside the plot area
+ *   (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ * - `out_index` and `out_dist_px` must be valid for writes
+ */
+int32_t plot_find_nearest_point(struct PlotOptions opt,
+                                const double *xs,
+                                const double *ys,
+                                uint64_t n,
+                                uint32_t query_pixel_x,
+                                uint32_t query_pixel_y,
+                                size_t *out_index,
+                                double *out_dist_px);
+
+/**
+ * Finds all points drawn within a radius of a pixel, without rendering, for example to
+ * hit-test a mouse click.
+ *
+ * Points are mapped to pixels as in `plot_data_to_pixel()`, and the distance to the
+ * query pixel is measured in pixels; points exactly
+ * `radius_px` away count as inside. The indices of up to `out_capacity` points are
+ * written to `out_indices`, nearest first, with ties in index order. With an
+ * `out_capacity` of 0 nothing is written and `out_indices` may be NULL, which is enough
+ * to check whether any point is near the query pixel.
+ *
+ * # Parameters
+ * - `opt`: Options the chart was rendered with (`auto_range` must be 0)
+ * - `xs`, `ys`: Pointers to arrays of point coordinates
+ * - `n`: Number of points
+ * - `query_pixel_x`, `query_pixel_y`: Pixel to search from, including any outer padding
+ * - `radius_px`: Search radius in pixels
+ * - `out_indices`: Receives the indices of the points found
+ * - `out_capacity`: Number of indices `out_indices` can hold
+ *
+ * # Returns
+ * The number of points within the radius, which may exceed `out_capacity`. Returns 0 on
+ * failure, with the reason available from `plot_take_error()`.
+ *
+ * # Safety
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ * - `out_indices` must be valid for writing `out_capacity` elements
+ */
+size_t plot_points_in_radius(struct PlotOptions opt,
+                             const double *xs,
+                             const double *ys,
+                             uint64_t n,
+                             uint32_t query_pixel_x,
+                             uint32_t query_pixel_y,
+                             double radius_px,
+                             size_t *out_indices,
+                             size_t out_capacity);
+
+/**
+ * Computes the data coordinates of a pixel, without rendering.
+ *
+ * The inverse of `plot_data_to_pixel()`, with the same layout and option requirements.
+ * Useful for mapping mouse clicks on a rendered chart back to data space.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure, including "Pixel is in margin area" when the pixel is outside the
+ *   plotting area
+ *
+ * # Safety
+ * `data_x` and `data_y` must be valid for writes.
+ */
+int32_t plot_pixel_to_data(struct PlotOptions opt,
+                           uint32_t pixel_x,
+                           uint32_t pixel_y,
+                           double *data_x,
+                           double *data_y);
+
+/**
+ * Computes axis ranges from data and stores them as explicit ranges in `*out`.
+ *
+ * Sets `x_min`, `x_max`, `y_min` and `y_max` and clears `auto_range`; all other fields
+ * of `*out` are left as they are, so the struct can be passed straight to
+ * `plot_scatter_png()`. This makes it easy to give several plots the same axes.
+ *
+ * # Parameters
+ * - `xs`, `ys`: Pointers to arrays of `n` coordinates
+ * - `n`: Number of points
+ * - `padding`: Margin added on both ends of each axis, as a fraction of its extent
+ *   (0.02 matches `PLOT_RANGE_PADDED`)
+ * - `out`: Options to update
+ *
+ * Points with a NaN or infinite coordinate are skipped; it is an error if none are left.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ * - `out` must point to a valid `PlotOptions`
+ */
+int32_t plot_compute_range(const double *xs,
+                           const double *ys,
+                           uint64_t n,
+                           double padding,
+                           struct PlotOptions *out);
+
+/**
+ * Renders a scatter plot to a PNG file and reports how long the rendering took.
+ *
+ * Behaves exactly like `plot_scatter_png()`. On success, the microseconds spent drawing,
+ * encoding and writing the image (after validation) are stored in `*elapsed_us`.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ * - `elapsed_us` must be NULL or valid for writes
+ */
+int32_t plot_scatter_png_timed(const char *path,
+                               const double *xs,
+                               const double *ys,
+                               uint64_t n,
+                               struct PlotOptions opt,
+                               uint64_t *elapsed_us);
+
+/**
+ * Renders a scatter plot into an existing OpenGL texture.
+ *
+ * The chart is rendered as with `plot_scatter_rgba()` and uploaded as level 0 of the
+ * `GL_TEXTURE_2D` texture `gl_texture_id` (`GL_RGBA8` storage, `GL_RGBA`/`GL_UNSIGNED_BYTE`
+ * data), replacing its size and contents. The first texture row is the top row of the
+ * chart, so flip the V coordinate when sampling with OpenGL's bottom-left origin. The
+ * texture binding and unpack alignment of the context are left unchanged.
+ *
+ * Must be called from a thread on which the GL context owning the texture is current;
+ * without a current context, GL calls have no effect or undefined behavior depending on
+ * the driver. Requires the library to be built with the `opengl` Cargo feature, which
+ * links the system OpenGL library; otherwise the call always fails.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure, including GL errors (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - A GL context must be current on the calling thread
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ */
+int32_t plot_render_to_texture_gl(uint32_t gl_texture_id,
+                                  const double *xs,
+                                  const double *ys,
+                                  uint64_t n,
+                                  struct PlotOptions opt);
+
+/**
+ * Renders a scatter plot to a single-page PDF file.
+ *
+ * The chart is rendered as a bitmap and embedded as an image filling the page. The
+ * page size is `opt.width` x `opt.height` pixels at `opt.dpi` (0 = 72 DPI, where one
+ * pixel is one point).
+ *
+ * Requires the library to be built with the `pdf` Cargo feature; otherwise the call
+ * always fails.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ */
+int32_t plot_scatter_pdf(const char *path,
+                         const double *xs,
+                         const double *ys,
+                         uint64_t n,
+                         struct PlotOptions opt);
+
+/**
+ * Renders a scatter plot as a base64-encoded PNG string.
+ *
+ * With `opt.base64_data_uri` set, the string is prefixed with `"data:image/png;base64,"`
+ * so it can be used directly as an image source.
+ *
+ * # Parameters
+ * - `xs`: Pointer to array of X coordinates
+ * - `ys`: Pointer to array of Y coordinates
+ * - `n`: Number of points
+ * - `opt`: Plot configuration options
+ * - `out_str`: Receives a NUL-terminated ASCII string, to be released with `plot_free_string()`
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ * - `out_str` must be valid for writes
+ */
+int32_t plot_scatter_png_base64(const double *xs,
+                                const double *ys,
+                                uint64_t n,
+                                struct PlotOptions opt,
+                                char **out_str);
+
+/**
+ * Frees a string returned by `plot_scatter_png_base64()`, `plot_options_to_json()` or
+ * `plot_take_error()`.
+ *
+ * Passing NULL is a no-op.
+ *
+ * # Safety
+ * `ptr` must be NULL or a string returned by this library that has not been freed yet.
+ */
+void plot_free_string(char *ptr);
+
+/**
+ * Serializes plot options to a JSON object string.
+ *
+ * String options such as `title` become JSON strings. The result must be released
+ * with `plot_free_string()`.
+ *
+ * # Returns
+ * - A NUL-terminated UTF-8 string on success
+ * - NULL on failure (call `plot_take_error()` for details)
+ */
+char *plot_options_to_json(struct PlotOptions opt);
+
+/**
+ * Parses plot options from a JSON object string.
+ *
+ * Fields missing from the JSON keep their default values; unknown fields are an error.
+ *
+ * # Parameters
+ * - `json`: NUL-terminated UTF-8 JSON text, as produced by `plot_options_to_json()`
+ * - `out`: Receives the parsed options
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `json` must be a valid NUL-terminated string
+ * - `out` must be valid for writes
+ */
+int32_t plot_options_from_json(const char *json, struct PlotOptions *out);
+
+/**
+ * Sets a process-wide default from a string value.
+ *
+ * Whenever a rendering call receives a `PlotOptions` field that is 0, the global
+ * default for that field is used instead. Valid keys are `"width"`, `"height"`,
+ * `"marker_radius"`, `"dpi"`, `"label_font_size"` and `"color_scheme"`. Values are
+ * decimal integers; `"color_scheme"` also accepts `"tableau10"`, `"set1"`, `"pastel"`
+ * and `"monochrome"`.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `key` and `value` must be valid NUL-terminated UTF-8 strings
+ */
+int32_t plot_set_global_option(const char *key, const char *value);
+
+/**
+ * Sets a process-wide default from an integer value.
+ *
+ * Same keys and semantics as `plot_set_global_option()`.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `key` must be a valid NUL-terminated UTF-8 string
+ */
+int32_t plot_set_global_option_u32(const char *key, uint32_t value);
+
+/**
+ * Sets the directory that relative paths are resolved against.
+ *
+ * By default, relative paths passed to this library are relative to the process's
+ * current working directory, which is often unclear in sandboxed or embedded hosts.
+ * Once a base directory is set, every relative path argument (output files, and font
+ * files in `plot_register_font()`) is joined to it; absolute paths are unaffected. The
+ * setting is process-wide.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 if `dir` is not an existing directory (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `dir` must be a valid NUL-terminated UTF-8 string
+ */
+int32_t plot_set_base_directory(const char *dir);
+
+/**
+ * Resets relative paths to resolve against the current working directory.
+ */
+void plot_clear_base_directory(void);
+
+/**
+ * Removes all process-wide defaults set with `plot_set_global_option()`.
+ */
+void plot_reset_global_options(void);
+
+/**
+ * Returns the default options, with any process-wide defaults taking precedence.
+ */
+struct PlotOptions plot_options_default(void);
+
+/**
+ * Selects the theme `plot_options_default()` starts from.
+ *
+ * Themes set the background, text, grid and axis colors, the tick label font size and
+ * whether grid lines are drawn; any field can still be changed on the returned options.
+ * Available themes:
+ * - `"default"`: the built-in look
+ * - `"dark"`: dark gray background, white text and axes, no grid
+ * - `"minimal"`: gray text and axes, no grid
+ * - `"scientific"`: faint grid, black axes and larger tick labels
+ * - `"seaborn"`: light blue-gray background with a white grid and no axis lines
+ *
+ * The theme is process-wide and only affects options from `plot_options_default()`.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 if the name is unknown (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * `theme` must be a valid NUL-terminated UTF-8 string.
+ */
+int32_t plot_set_theme(const char *theme);
+
+/**
+ * Renders a scatter plot of interleaved `[x0, y0, x1, y1, ...]` data to a PNG file.
+ *
+ * Otherwise identical to `plot_scatter_png()`.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output PNG file
+ * - `xy`: Pointer to `2 * n` interleaved coordinates
+ * - `n`: Number of points
+ * - `opt`: Plot configuration options
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xy` must point to an array of at least `2 * n` elements
+ */
+int32_t plot_scatter_interleaved_png(const char *path,
+                                     const double *xy,
+                                     uint64_t n,
+                                     struct PlotOptions opt);
+
+/**
+ * Renders a scatter plot of coordinates spread out in memory to a PNG file.
+ *
+ * Point `i` is `(xs[i * x_stride], ys[i * y_stride])`, so rows or columns of a row-major
+ * matrix can be plotted without copying them out first. Strides count elements, not
+ * bytes. Otherwise identical to `plot_scatter_png()`.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output PNG file
+ * - `xs`, `x_stride`: Pointer to the first X coordinate and the element distance between
+ *   X coordinates (at least 1)
+ * - `ys`, `y_stride`: Pointer to the first Y coordinate and the element distance between
+ *   Y coordinates (at least 1)
+ * - `n`: Number of points
+ * - `opt`: Plot configuration options
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs.add((n - 1) * x_stride)` and `ys.add((n - 1) * y_stride)` must be within the same
+ *   allocations as `xs` and `ys`
+ */
+int32_t plot_scatter_strided_png(const char *path,
+                                 const double *xs,
+                                 size_t x_stride,
+                                 const double *ys,
+                                 size_t y_stride,
+                                 uint64_t n,
+                                 struct PlotOptions opt);
+
+/**
+ * Renders a scatter plot of interleaved `[x0, y0, x1, y1, ...]` data into a newly allocated RGBA buffer.
+ *
+ * Otherwise identical to `plot_scatter_rgba()`; release the buffer with `plot_free_buffer()`.
+ *
+ * # Safety
+ * - `xy` must point to an array of at least `2 * n` elements
+ * - `out_ptr` and `out_len` must be valid for writes
+ */
+int32_t plot_scatter_interleaved_rgba(const double *xy,
+                                      uint64_t n,
+                                      struct PlotOptions opt,
+                                      uint8_t **out_ptr,
+                                      size_t *out_len);
+
+/**
+ * Frees a buffer returned by `plot_scatter_rgba()` or `plot_scatter_f32_rgba()`.
+ *
+ * Passing NULL is a no-op.
+ *
+ * # Safety
+ * `ptr` and `len` must be exactly the values returned by the rendering call,
+ * and the buffer must not be freed twice.
+ */
+void plot_free_buffer(uint8_t *ptr, size_t len);
+
+/**
+ * Frees a buffer returned by `plot_scatter_rgb565()`.
+ *
+ * Passing NULL is a no-op.
+ *
+ * # Safety
+ * `ptr` and `len` must be exactly the values returned by the rendering call,
+ * and the buffer must not be freed twice.
+ */
+void plot_free_u16_buffer(uint16_t *ptr, size_t len);
+
+/**
+ * Starts a staged scatter plot whose points are supplied by `plot_append_points()`.
+ *
+ * The tick and band arrays in `opt` are copied, so they only need to be valid during this call.
+ * Nothing is drawn until `plot_finish()`.
+ *
+ * # Returns
+ * A context to pass to the other `plot_*` context functions; release it with
+ * `plot_context_free()`.
+ *
+ * # Safety
+ * Non-null `opt.x_ticks`/`opt.y_ticks` must point to `x_n_ticks`/`y_n_ticks` values,
+ * and non-null `opt.h_bands`/`opt.v_bands` to `n_h_bands`/`n_v_bands` values.
+ */
+struct PlotContext *plot_begin(struct PlotOptions opt);
+
+/**
+ * Adds `n` points to a context created by `plot_begin()`.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `ctx` must be a live context returned by `plot_begin()`
+ * - `xs` and `ys` must point to arrays of at least `n` elements
+ */
+int32_t plot_append_points(struct PlotContext *ctx, const double *xs, const double *ys, uint64_t n);
+
+/**
+ * Renders all points appended to a context to a PNG file.
+ *
+ * The output is identical to a single `plot_scatter_png()` call with all points and the
+ * options given to `plot_begin()`. The context stays valid and can receive more points,
+ * and keeps the rendered pixels for `plot_context_get_pixel()`.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure, including a context without points
+ *
+ * # Safety
+ * - `ctx` must be a live context returned by `plot_begin()`
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ */
+int32_t plot_finish(struct PlotContext *ctx, const char *path);
+
+/**
+ * Reads the RGBA color of one pixel of the image last rendered by `plot_finish()`.
+ *
+ * `(0, 0)` is the top-left corner; coordinates include any outer padding. Rendered
+ * images are opaque, so alpha is always 255. NULL output pointers are skipped.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure: no image rendered yet, or `x`/`y` outside the image
+ *
+ * # Safety
+ * - `ctx` must be a live context returned by `plot_begin()`
+ * - `r`, `g`, `b` and `a` must each be NULL or valid for writes
+ */
+int32_t plot_context_get_pixel(const struct PlotContext *ctx,
+                               uint32_t x,
+                               uint32_t y,
+                               uint8_t *r,
+                               uint8_t *g,
+                               uint8_t *b,
+                               uint8_t *a);
+
+/**
+ * Returns the number of points appended to a context so far.
+ *
+ * Returns 0 if `ctx` is NULL.
+ *
+ * # Safety
+ * `ctx` must be NULL or a live context returned by `plot_begin()`.
+ */
+size_t plot_context_point_count(const struct PlotContext *ctx);
+
+/**
+ * Writes the axis ranges `plot_finish()` would use for the points appended so far.
+ *
+ * Sets `x_min`, `x_max`, `y_min` and `y_max` of `out_opt` and clears its `auto_range`;
+ * other fields are left untouched. With auto-ranging enabled in the context's options
+ * these are the padded data bounds, otherwise the explicit ranges.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure, including a context without points
+ *
+ * # Safety
+ * - `ctx` must be a live context returned by `plot_begin()`
+ * - `out_opt` must be valid for reads and writes of a `PlotOptions`
+ */
+int32_t plot_context_bounds(const struct PlotContext *ctx, struct PlotOptions *out_opt);
+
+/**
+ * Frees a context returned by `plot_begin()`.
+ *
+ * Passing NULL is a no-op.
+ *
+ * # Safety
+ * `ctx` must have been returned by `plot_begin()` and must not be freed twice.
+ */
+void plot_context_free(struct PlotContext *ctx);
+
+/**
+ * Renders several data series into one chart in a single PNG file.
+ *
+ * Each series is drawn with its own `SeriesStyle`; `opt.marker_radius` is not used.
+ * The axis ranges cover all series, and named series are listed in a legend. With a
+ * single series the legend is left out unless `opt.force_legend` is set.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output PNG file
+ * - `series`: Pointer to array of series
+ * - `n_series`: Number of series (at least one)
+ * - `opt`: Plot configuration options
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `series` must point to an array of at least `n_series` elements
+ * - each series' `xs` and `ys` must point to arrays of at least `n` elements
+ */
+int32_t plot_scatter_series_png(const char *path,
+                                const struct ScatterSeries *series,
+                                size_t n_series,
+                                struct PlotOptions opt);
+
+/**
+ * Renders several overlapping layers into one chart in a single PNG file.
+ *
+ * Layers are drawn in array order, so later layers appear on top: scatter points,
+ * point density heatmaps (see `HeatmapLayer`) and reference lines. All layers share the
+ * axes; with `opt.auto_range` the ranges cover the points of all scatter layers, so at
+ * least one scatter layer is then required. Heatmap bins without points are left
+ * transparent and no colorbar is drawn.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output PNG file
+ * - `layers`: Pointer to array of layers
+ * - `n_layers`: Number of layers (at least one)
+ * - `opt`: Plot configuration options
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `layers` must point to an array of at least `n_layers` elements
+ * - each layer's `data` must point to the struct selected by its `layer_type`, whose
+ *   `xs` and `ys` must point to arrays of at least `n` elements
+ */
+int32_t plot_composite_png(const char *path,
+                           const struct CompositeLayer *layers,
+                           size_t n_layers,
+                           struct PlotOptions opt);
+
+/**
+ * Renders a line chart with two Y axes to a PNG file.
+ *
+ * Both series share `xs` and are drawn as lines connecting the points in array order:
+ * `ys_left` against the left axis, set up by `main_opt` like any other chart, and
+ * `ys_right` against a right axis set up by `right_axis`. `main_opt` controls all
+ * image-level settings. The right series, tick labels and name use
+ * `right_axis.series_color`; the left series uses the first color of
+ * `main_opt.color_scheme`.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output PNG file
+ * - `xs`: Pointer to array of X coordinates
+ * - `ys_left`, `ys_right`: Pointers to arrays of Y coordinates for each axis
+ * - `n`: Number of points (length of all three arrays)
+ * - `main_opt`: Plot configuration options
+ * - `right_axis`: Range and style of the right axis
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `xs`, `ys_left` and `ys_right` must point to arrays of at least `n` elements
+ */
+int32_t plot_dual_axis_png(const char *path,
+                           const double *xs,
+                           const double *ys_left,
+                           const double *ys_right,
+                           uint64_t n,
+                           struct PlotOptions main_opt,
+                           struct AxisOptions right_axis);
+
+/**
+ * Renders one violin per group of samples to a PNG file.
+ *
+ * Each violin mirrors a Gaussian kernel density estimate (bandwidth by Silverman's rule)
+ * over its group's data range, scaled so the widest violin fills 80% of its column.
+ * Thin lines mark the median and the interquartile range. Groups are placed left to
+ * right and labelled with their names; `opt.x_min`, `opt.x_max` and X tick options are not used.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output PNG file
+ * - `groups`: Pointer to array of groups, each with at least two distinct finite values
+ * - `n_groups`: Number of groups
+ * - `opt`: Plot configuration options
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `groups` must point to an array of at least `n_groups` elements
+ * - each group's `values` must point to an array of at least `n` elements
+ */
+int32_t plot_violin_png(const char *path,
+                        const struct BoxGroup *groups,
+                        size_t n_groups,
+                        struct PlotOptions opt);
+
+/**
+ * Renders several scatter plots into a grid in a single PNG file.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output PNG file
+ * - `specs`: Pointer to array of chart specs (their `path` fields are ignored)
+ * - `n_specs`: Number of specs
+ * - `grid_cols`: Number of grid columns
+ * - `opt`: Grid options; `width` and `height` give the size of each cell
+ *
+ * The image is `grid_cols * width` pixels wide and `ceil(n_specs / grid_cols) * height`
+ * pixels high. Cells without a spec are left white.
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `specs` must point to an array of at least `n_specs` elements
+ * - each spec's `xs` and `ys` must point to arrays of at least `n` elements
+ */
+int32_t plot_grid_png(const char *path,
+                      const struct BatchSpec *specs,
+                      size_t n_specs,
+                      uint32_t grid_cols,
+                      struct PlotOptions opt);
+
+/**
+ * Renders each spec to its own PNG file, spreading the work over threads.
+ *
+ * Every spec is rendered as by `plot_scatter_png()`, and a failing spec does not stop
+ * the others. With the `parallel` Cargo feature the specs are shared out among one
+ * thread per CPU core; otherwise they are rendered one after another on the calling
+ * thread. `post_render_fn` hooks may therefore run on worker threads.
+ *
+ * # Returns
+ * - the number of specs that failed to render (0 = all succeeded); read their errors with
+ *   `plot_batch_errors()`
+ * - -1 if `specs` is NULL (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `specs` must point to an array of at least `n_specs` elements
+ * - each spec's `path` must be a valid NUL-terminated UTF-8 string, and its `xs` and
+ *   `ys` must point to arrays of at least `n` elements
+ */
+int32_t plot_scatter_png_batch_parallel(const struct BatchSpec *specs, size_t n_specs);
+
+/**
+ * Copies the failures of the last `plot_scatter_png_batch_parallel()` call into `out_buf`.
+ *
+ * Failures are ordered by spec index. At most `buf_capacity` entries are written; NULL
+ * `out_buf` only counts them.
+ *
+ * # Returns
+ * The total number of failures, which may exceed `buf_capacity`.
+ *
+ * # Safety
+ * A non-NULL `out_buf` must be valid for writes of `buf_capacity` elements.
+ */
+size_t plot_batch_errors(struct BatchError *out_buf, size_t buf_capacity);
+
+/**
+ * Renders an animated GIF with one scatter plot per frame.
+ *
+ * With `opt.auto_range` the axis ranges cover the points of all frames, so the axes do
+ * not jump between frames. The animation loops forever. `opt.dpi` is ignored.
+ *
+ * # Parameters
+ * - `path`: NUL-terminated UTF-8 path to the output GIF file
+ * - `frames`: Pointer to array of frames
+ * - `n_frames`: Number of frames
+ * - `frame_delay_ms`: Display time of each frame in milliseconds, rounded up to 10 ms
+ * - `opt`: Plot configuration options
+ *
+ * # Returns
+ * - 0 on success
+ * - 1 on failure (call `plot_take_error()` for details)
+ *
+ * # Safety
+ * - `path` must be a valid NUL-terminated UTF-8 string
+ * - `frames` must point to an array of at least `n_frames` elements
+ * - each frame's `xs` and `ys` must point to arrays of at least `n` elements
+ */
+int32_t plot_scatter_gif(const char *path,
+                         const struct GifFrame *frames,
+                         size_t n_frames,
+                         uint32_t frame_delay_ms,
+                         struct PlotOptions opt);
+
+/**
+ * Returns the last error message, or NULL if no error has occurred.
+ *
+ * The returned pointer is valid until the next call to `plot_scatter_png()`.
+ * The string is NUL-terminated UTF-8.
+ *
+ * Deprecated: another thread's rendering call can free the string while it is being
+ * read. Use `plot_take_error()` or `plot_copy_last_error()` instead.
+ *
+ * # Safety
+ * The returned pointer must not be freed by the caller.
+ */
+const char *plot_last_error_message(void);
+
+/**
+ * Copies the last error message into a caller-provided buffer.
+ *
+ * Unlike `plot_last_error_message()`, the copy stays valid regardless of later calls.
+ * A message longer than `buf_len - 1` bytes is truncated (at a UTF-8 character
+ * boundary). The buffer is always NUL-terminated when `buf_len > 0`.
+ *
+ * # Returns
+ * The number of bytes written excluding the NUL terminator, or 0 if no error is stored
+ * (the buffer then holds an empty string).
+ *
+ * # Safety
+ * `buf` must be NULL or valid for writes of `buf_len` bytes.
+ */
+size_t plot_copy_last_error(char *buf, size_t buf_len);
+
+/**
+ * Removes the last error message and returns it as a newly allocated string.
+ *
+ * This is the recommended way to read errors: the caller owns the returned string, so
+ * it stays valid across later calls and other threads. Release it with
+ * `plot_free_string()`. Afterwards no error is stored, as after `plot_clear_last_error()`.
+ *
+ * # Returns
+ * The NUL-terminated UTF-8 message, or NULL if no error is stored.
+ */
+char *plot_take_error(void);
+
+/**
+ * Returns the last warning message, or NULL if the last call produced no warning.
+ *
+ * Warnings report non-fatal substitutions, such as rendering with a system font
+ * because the bundled font could not be loaded. Each rendering call clears the
+ * previous warning. The string is NUL-terminated UTF-8.
+ *
+ * # Safety
+ * The returned pointer must not be freed by the caller.
+ */
+const char *plot_last_warning_message(void);
+
+/**
+ * Clears the last error message.
+ *
+ * Calling this is optional: `plot_scatter_png()` already clears the error at its start.
+ * It is useful to reset the error state before a series of operations.
+ */
+void plot_clear_last_error(void);
+
+/**
+ * Returns 1 if an error message is currently stored, 0 otherwise.
+ *
+ * This allows checking for an error without reading the message.
+ */
+uint8_t plot_has_error(void);
+
+/**
+ * Returns the category of the stored error as a `PLOT_ERR_*` code.
+ *
+ * `PLOT_ERR_NONE` if no error is stored. Like the message, the code describes the last
+ * failed call and is cleared by the next call or by `plot_take_error()`.
+ */
+int32_t plot_last_error_code(void);
+
+/**
+ * Returns the `PLOTFFI_ABI_VERSION` the library was built with.
+ *
+ * Compare it with the `PLOTFFI_ABI_VERSION` macro from the header to detect a
+ * mismatched shared library at runtime.
+ */
+uint32_t plot_abi_version(void);
+
+/**
+ * Returns `sizeof(PlotOptions)` as compiled into the library.
+ *
+ * A caller built against a different header passes a struct of a different size by
+ * value, which corrupts every argument after it. Compare the result with the header's
+ * `PLOTFFI_OPTIONS_SIZE` at startup and refuse to render on a mismatch.
+ */
+size_t plot_options_size(void);
 
 #ifdef __cplusplus
 }  // extern "C"
@@ -0,0 +1,109 @@
+//! Staged rendering: points are collected over several calls and rendered at the end.
+
+use plotters::coord::Shift;
+use plotters::prelude::DrawingArea;
+use plotters_bitmap::BitMapBackend;
+
+use crate::{
+    HBand, PlotOptions, RenderOptions, VBand, compute_ranges, prepare_data, render_scatter_png, with_global_defaults,
+};
+
+/// Opaque rendering context created by `plot_begin()`.
+///
+/// Collects the points of a scatter plot until `plot_finish()` renders them, and keeps
+/// the pixels of the last rendered image.
+pub struct PlotContext {
+    opt: PlotOptions,
+    // Owned copies of the tick and band arrays; the pointers in `opt` are not read
+    x_ticks: Vec<f64>,
+    y_ticks: Vec<f64>,
+    h_bands: Vec<HBand>,
+    v_bands: Vec<VBand>,
+    xs: Vec<f64>,
+    ys: Vec<f64>,
+    // RGB pixels and size of the image written by the last successful `finish`
+    pixels: Option<(Vec<u8>, (u32, u32))>,
+}
+
+impl PlotContext {
+    /// Creates an empty context, copying the tick and band arrays of `opt`.
+    pub(crate) fn new(opt: RenderOptions<'_>) -> Self {
+        Self {
+            opt: opt.opt,
+            x_ticks: opt.x_ticks.to_vec(),
+            y_ticks: opt.y_ticks.to_vec(),
+            h_bands: opt.h_bands.to_vec(),
+            v_bands: opt.v_bands.to_vec(),
+            xs: Vec::new(),
+            ys: Vec::new(),
+            pixels: None,
+        }
+    }
+
+    /// Adds a batch of points.
+    pub(crate) fn append(&mut self, xs: &[f64], ys: &[f64]) {
+        self.xs.extend_from_slice(xs);
+        self.ys.extend_from_slice(ys);
+    }
+
+    /// Returns the number of points appended so far.
+    pub(crate) fn point_count(&self) -> usize {
+        self.xs.len()
+    }
+
+    /// Computes the `(x_min, x_max, y_min, y_max)` axis ranges `finish` would use for the
+    /// points appended so far.
+    pub(crate) fn bounds(&self) -> Result<(f64, f64, f64, f64), String> {
+        if self.xs.is_empty() {
+            return Err("No points were appended to the context".to_string());
+        }
+        let opt = with_global_defaults(self.opt);
+        let (xs, ys) = prepare_data(&self.xs, &self.ys, &opt)?;
+        compute_ranges(&xs, &ys, &opt)
+    }
+
+    /// Returns the options given to `new`.
+    pub(crate) fn options(&self) -> &PlotOptions {
+        &self.opt
+    }
+
+    /// Renders all points collected so far to a PNG file, keeping the rendered pixels.
+    ///
+    /// `post_render` runs once the chart is drawn, as for `RenderOptions::with_post_render`.
+    pub(crate) fn finish<F>(&mut self, path: &str, post_render: Option<&F>) -> Result<(), String>
+    where
+        F: Fn(&DrawingArea<BitMapBackend, Shift>) -> Result<(), String>,
+    {
+        if self.xs.is_empty() {
+            return Err("No points were appended to the context".to_string());
+        }
+        let opt = RenderOptions::from(self.opt)
+            .with_x_ticks(&self.x_ticks)
+            .with_y_ticks(&self.y_ticks)
+            .with_h_bands(&self.h_bands)
+            .with_v_bands(&self.v_bands)
+            .with_post_render(post_render);
+        let (_, rendered) = render_scatter_png(path, &self.xs, &self.ys, opt)?;
+        self.pixels = Some((rendered.rgb, rendered.size));
+        Ok(())
+    }
+
+    /// Returns the RGBA color of pixel `(x, y)` of the last rendered image.
+    ///
+    /// Coordinates start at the top-left corner and include any outer padding.
+    pub(crate) fn pixel(&self, x: u32, y: u32) -> Result<[u8; 4], String> {
+        let (rgb, (width, height)) = self
+            .pixels
+            .as_ref()
+            .ok_or("No image has been rendered with this context")?;
+        if x >= *width || y >= *height {
+            return Err(format!(
+                "Pixel ({}, {}) is outside the {}x{} image",
+                x, y, width, height
+            ));
+        }
+        let i = (y as usize * *width as usize + x as usize) * 3;
+        // The bitmap is opaque
+        Ok([rgb[i], rgb[i + 1], rgb[i + 2], 255])
+    }
+}
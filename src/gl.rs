@@ -0,0 +1,97 @@
+//! OpenGL output: the rendered RGBA image is uploaded into an existing texture.
+//!
+//! Only OpenGL 1.1 entry points are used, which the system GL library exports directly,
+//! so no function loader is needed.
+
+use std::ffi::c_void;
+
+type GLenum = u32;
+type GLint = i32;
+type GLsizei = i32;
+type GLuint = u32;
+
+const GL_NO_ERROR: GLenum = 0;
+const GL_TEXTURE_2D: GLenum = 0x0DE1;
+const GL_TEXTURE_BINDING_2D: GLenum = 0x8069;
+const GL_UNPACK_ALIGNMENT: GLenum = 0x0CF5;
+const GL_RGBA: GLenum = 0x1908;
+const GL_RGBA8: GLint = 0x8058;
+const GL_UNSIGNED_BYTE: GLenum = 0x1401;
+
+// Most error flags read before the upload; GL keeps at most one per error kind
+const MAX_PENDING_ERRORS: usize = 8;
+
+#[cfg_attr(target_os = "windows", link(name = "opengl32"))]
+#[cfg_attr(target_os = "macos", link(name = "OpenGL", kind = "framework"))]
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), link(name = "GL"))]
+unsafe extern "system" {
+    fn glGetError() -> GLenum;
+    fn glGetIntegerv(pname: GLenum, data: *mut GLint);
+    fn glBindTexture(target: GLenum, texture: GLuint);
+    fn glPixelStorei(pname: GLenum, param: GLint);
+    fn glTexImage2D(
+        target: GLenum,
+        level: GLint,
+        internal_format: GLint,
+        width: GLsizei,
+        height: GLsizei,
+        border: GLint,
+        format: GLenum,
+        kind: GLenum,
+        pixels: *const c_void,
+    );
+}
+
+/// Replaces level 0 of the 2D texture `texture` with the `size.0` x `size.1` RGBA image.
+///
+/// The texture binding and unpack alignment of the current context are restored.
+///
+/// # Safety
+/// A GL context must be current on the calling thread.
+pub(crate) unsafe fn upload_texture(texture: u32, rgba: &[u8], (width, height): (u32, u32)) -> Result<(), String> {
+    debug_assert_eq!(rgba.len(), width as usize * height as usize * 4);
+    let width = GLsizei::try_from(width).map_err(|_| "Image is too wide for a GL texture".to_string())?;
+    let height = GLsizei::try_from(height).map_err(|_| "Image is too tall for a GL texture".to_string())?;
+
+    // SAFETY: the caller guarantees a current context; `rgba` holds width * height pixels
+    let error = unsafe {
+        // Discard errors left by earlier calls so the check below only sees ours. A lost
+        // context reports an error forever, so the number of flags read is bounded.
+        for _ in 0..MAX_PENDING_ERRORS {
+            if glGetError() == GL_NO_ERROR {
+                break;
+            }
+        }
+
+        let (mut bound, mut alignment) = (0, 0);
+        glGetIntegerv(GL_TEXTURE_BINDING_2D, &mut bound);
+        glGetIntegerv(GL_UNPACK_ALIGNMENT, &mut alignment);
+
+        glBindTexture(GL_TEXTURE_2D, texture);
+        // Rows are tightly packed
+        glPixelStorei(GL_UNPACK_ALIGNMENT, 1);
+        glTexImage2D(
+            GL_TEXTURE_2D,
+            0,
+            GL_RGBA8,
+            width,
+            height,
+            0,
+            GL_RGBA,
+            GL_UNSIGNED_BYTE,
+            rgba.as_ptr().cast(),
+        );
+        let error = glGetError();
+
+        glPixelStorei(GL_UNPACK_ALIGNMENT, alignment);
+        glBindTexture(GL_TEXTURE_2D, bound as GLuint);
+        error
+    };
+    if error != GL_NO_ERROR {
+        return Err(format!(
+            "Failed to upload GL texture {}: GL error 0x{:04X}",
+            texture, error
+        ));
+    }
+    Ok(())
+}
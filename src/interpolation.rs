@@ -0,0 +1,114 @@
+//! Smooth curves through data points for line charts.
+
+/// Samples the Fritsch-Carlson monotone cubic interpolant through `points` every `step`
+/// along X.
+///
+/// The curve passes through every point and is monotone wherever the data is, so it never
+/// overshoots between neighbouring points. The original points are always included, and
+/// each segment gets at least one sample in between them.
+///
+/// `points` must have strictly increasing X values and `step` must be positive.
+pub(crate) fn monotone_cubic(points: &[(f64, f64)], step: f64) -> Result<Vec<(f64, f64)>, String> {
+    if !(step.is_finite() && step > 0.0) {
+        return Err(format!("Interpolation step must be positive, got {}", step));
+    }
+    if let Some(i) = points.windows(2).position(|w| w[1].0 <= w[0].0) {
+        return Err(format!(
+            "Monotone cubic line mode requires strictly increasing X values (index {})",
+            i + 1
+        ));
+    }
+    if points.len() < 2 {
+        return Ok(points.to_vec());
+    }
+
+    let tangents = tangents(points);
+    let mut vertices = vec![points[0]];
+    for (k, w) in points.windows(2).enumerate() {
+        let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+        let h = x1 - x0;
+        let samples = ((h / step).ceil() as usize).max(2);
+        for i in 1..samples {
+            let t = i as f64 / samples as f64;
+            vertices.push((x0 + t * h, hermite(t, h, (y0, y1), (tangents[k], tangents[k + 1]))));
+        }
+        vertices.push((x1, y1));
+    }
+    Ok(vertices)
+}
+
+/// Computes the tangent at each point, limited so each segment stays monotone.
+fn tangents(points: &[(f64, f64)]) -> Vec<f64> {
+    let slopes: Vec<f64> = points
+        .windows(2)
+        .map(|w| (w[1].1 - w[0].1) / (w[1].0 - w[0].0))
+        .collect();
+    let n = points.len();
+    let mut m = vec![0.0; n];
+    m[0] = slopes[0];
+    m[n - 1] = slopes[n - 2];
+    for k in 1..n - 1 {
+        // Local extrema get a flat tangent
+        if slopes[k - 1] * slopes[k] > 0.0 {
+            m[k] = (slopes[k - 1] + slopes[k]) / 2.0;
+        }
+    }
+
+    for (k, &d) in slopes.iter().enumerate() {
+        if d == 0.0 {
+            m[k] = 0.0;
+            m[k + 1] = 0.0;
+            continue;
+        }
+        let (a, b) = (m[k] / d, m[k + 1] / d);
+        let length = a.hypot(b);
+        // Tangents outside the circle of radius 3 can overshoot
+        if length > 3.0 {
+            let t = 3.0 / length;
+            m[k] = t * a * d;
+            m[k + 1] = t * b * d;
+        }
+    }
+    m
+}
+
+/// Evaluates the cubic Hermite segment of width `h` at `t` in `[0, 1]`.
+fn hermite(t: f64, h: f64, (y0, y1): (f64, f64), (m0, m1): (f64, f64)) -> f64 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (2.0 * t3 - 3.0 * t2 + 1.0) * y0 + (t3 - 2.0 * t2 + t) * h * m0 + (-2.0 * t3 + 3.0 * t2) * y1 + (t3 - t2) * h * m1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monotone_data_stays_monotone() {
+        let points = [(0.0, 0.0), (1.0, 0.1), (2.0, 0.5), (3.0, 3.0), (4.0, 3.1)];
+        let curve = monotone_cubic(&points, 0.01).unwrap();
+        assert!(curve.len() > 400);
+        assert!(curve.windows(2).all(|w| w[1].0 > w[0].0 && w[1].1 >= w[0].1));
+        for p in points {
+            assert!(curve.contains(&p));
+        }
+    }
+
+    #[test]
+    fn test_no_overshoot_at_extrema() {
+        let points = [(0.0, 0.0), (1.0, 2.0), (2.0, 3.0), (3.0, 1.0), (4.0, 0.0)];
+        let curve = monotone_cubic(&points, 0.01).unwrap();
+        assert!(curve.iter().all(|&(_, y)| (0.0..=3.0).contains(&y)));
+        // Rising up to the peak, falling after it
+        let peak = curve.iter().position(|&p| p == (2.0, 3.0)).unwrap();
+        assert!(curve[..=peak].windows(2).all(|w| w[1].1 >= w[0].1));
+        assert!(curve[peak..].windows(2).all(|w| w[1].1 <= w[0].1));
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(monotone_cubic(&[(1.0, 0.0), (1.0, 1.0)], 0.1).is_err());
+        assert!(monotone_cubic(&[(0.0, 0.0), (1.0, 1.0)], 0.0).is_err());
+        assert_eq!(monotone_cubic(&[(1.0, 2.0)], 0.1).unwrap(), vec![(1.0, 2.0)]);
+    }
+}
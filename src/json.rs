@@ -0,0 +1,209 @@
+//! JSON representation of `PlotOptions`.
+//!
+//! `PlotOptions` is a `#[repr(C)]` struct with fixed-size string buffers, so it is
+//! mirrored by a serde-friendly struct that uses `String` for those fields.
+//! The `x_ticks`/`y_ticks` and `h_bands`/`v_bands` arrays and the `post_render_fn` hook
+//! are pointers and are not serialized; they are null after deserialization.
+
+use crate::{PlotOptions, fixed_str};
+use serde::{Deserialize, Serialize};
+use std::ffi::c_char;
+
+/// Declares `PlotOptionsJson` and its conversions from the list of `PlotOptions` fields.
+///
+/// `plain` fields are copied as is; `strings` are NUL-terminated `c_char` buffers.
+macro_rules! options_json {
+    (plain: { $($plain:ident: $ty:ty),* $(,)? } strings: { $($string:ident),* $(,)? }) => {
+        /// Serde mirror of `PlotOptions`; missing fields take their default values.
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+        #[serde(default, deny_unknown_fields)]
+        pub(crate) struct PlotOptionsJson {
+            $($plain: $ty,)*
+            $($string: String,)*
+        }
+
+        impl PlotOptionsJson {
+            fn from_options(opt: &PlotOptions) -> Result<Self, String> {
+                Ok(Self {
+                    $($plain: opt.$plain,)*
+                    $($string: fixed_str(&opt.$string)?.to_string(),)*
+                })
+            }
+
+            fn to_options(&self) -> Result<PlotOptions, String> {
+                let mut opt = PlotOptions::default();
+                $(opt.$plain = self.$plain;)*
+                $(copy_string(&mut opt.$string, &self.$string, stringify!($string))?;)*
+                Ok(opt)
+            }
+        }
+
+        impl Default for PlotOptionsJson {
+            fn default() -> Self {
+                let opt = PlotOptions::default();
+                Self {
+                    $($plain: opt.$plain,)*
+                    $($string: String::new(),)*
+                }
+            }
+        }
+    };
+}
+
+options_json! {
+    plain: {
+        width: u32,
+        height: u32,
+        marker_radius: u32,
+        auto_range: u8,
+        x_min: f64,
+        x_max: f64,
+        y_min: f64,
+        y_max: f64,
+        lock_aspect_ratio: u8,
+        x_label_area_size: u32,
+        y_label_area_size: u32,
+        x_label_rotation: i32,
+        y_label_rotation: i32,
+        dpi: u32,
+        x_scientific: u8,
+        y_scientific: u8,
+        keep_trailing_zeros: u8,
+        nan_policy: u8,
+        border_color: u32,
+        border_width: u32,
+        axis_color: u32,
+        invert_x: u8,
+        invert_y: u8,
+        marker_alpha: u8,
+        line_mode: u8,
+        polar_grid_rings: u32,
+        show_r_squared: u8,
+        jitter_amount: f64,
+        jitter_seed: u64,
+        x_tick_format: u8,
+        y_tick_format: u8,
+        moving_avg_window: u32,
+        outer_padding: u32,
+        line_stroke_width: u32,
+        line_dash_pattern: u8,
+        auto_range_mode: u8,
+        x_tick_count: u32,
+        y_tick_count: u32,
+        x_minor_tick_count: u32,
+        y_minor_tick_count: u32,
+        grid_color: u32,
+        clip_enabled: u8,
+        clip_x_min: f64,
+        clip_x_max: f64,
+        clip_y_min: f64,
+        clip_y_max: f64,
+        use_system_font_fallback: u8,
+        base64_data_uri: u8,
+        antialias: u8,
+        render_seed: u64,
+        color_scheme: u8,
+        arrow_scale: f64,
+        label_font_size: u32,
+        legend_position: u8,
+        disable_label_skip: u8,
+        pixel_endian: u8,
+        draw_zero_x_line: u8,
+        draw_zero_y_line: u8,
+        zero_line_color: u32,
+        plot_area_fraction: f64,
+        force_legend: u8,
+        background_color: u32,
+        text_color: u32,
+        hide_grid: u8,
+        snap_range_to_ticks: u8,
+        write_metadata: u8,
+        title_font_style: u8,
+        axis_desc_font_style: u8,
+        density_overlay: u8,
+        density_bins: u32,
+    }
+    strings: {
+        x_label_format,
+        title,
+        title_font_name,
+        label_font_name,
+        png_title,
+        png_author,
+        png_description,
+    }
+}
+
+/// Copies `value` into a fixed-size string option, failing if it does not fit.
+fn copy_string(field: &mut [c_char], value: &str, name: &str) -> Result<(), String> {
+    if value.len() >= field.len() || value.contains('\0') {
+        return Err(format!(
+            "Field {} must be at most {} bytes without NUL characters",
+            name,
+            field.len() - 1
+        ));
+    }
+    for (dst, &src) in field.iter_mut().zip(value.as_bytes()) {
+        *dst = src as c_char;
+    }
+    field[value.len()] = 0;
+    Ok(())
+}
+
+/// Serializes options to a JSON object.
+pub(crate) fn options_to_json(opt: &PlotOptions) -> Result<String, String> {
+    let mirror = PlotOptionsJson::from_options(opt)?;
+    serde_json::to_string(&mirror).map_err(|e| format!("Failed to serialize options: {}", e))
+}
+
+/// Parses options from a JSON object; missing fields keep their defaults.
+pub(crate) fn options_from_json(json: &str) -> Result<PlotOptions, String> {
+    let mirror: PlotOptionsJson = serde_json::from_str(json).map_err(|e| format!("Invalid options JSON: {}", e))?;
+    mirror.to_options()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::write_fixed_str;
+
+    #[test]
+    fn test_round_trip() {
+        let mut opt = PlotOptions {
+            width: 1024,
+            marker_radius: 3,
+            x_min: -2.5,
+            y_label_rotation: -45,
+            jitter_amount: 0.25,
+            jitter_seed: u64::MAX,
+            grid_color: 0x80FF0000,
+            clip_y_max: 1e9,
+            ..Default::default()
+        };
+        write_fixed_str(&mut opt.title, "Round trip ✓");
+        write_fixed_str(&mut opt.x_label_format, "%H:%M");
+
+        let json = options_to_json(&opt).unwrap();
+        let parsed = options_from_json(&json).unwrap();
+        assert_eq!(
+            PlotOptionsJson::from_options(&parsed).unwrap(),
+            PlotOptionsJson::from_options(&opt).unwrap()
+        );
+        assert_eq!(parsed.title, opt.title);
+    }
+
+    #[test]
+    fn test_partial_and_invalid_json() {
+        let opt = options_from_json(r#"{"width": 320, "title": "Partial"}"#).unwrap();
+        assert_eq!(opt.width, 320);
+        assert_eq!(opt.height, PlotOptions::default().height);
+        assert_eq!(fixed_str(&opt.title).unwrap(), "Partial");
+
+        assert!(options_from_json(r#"{"widht": 320}"#).is_err());
+        let long = format!(r#"{{"title_font_name": "{}"}}"#, "f".repeat(64));
+        assert_eq!(
+            options_from_json(&long).unwrap_err(),
+            "Field title_font_name must be at most 63 bytes without NUL characters"
+        );
+    }
+}
@@ -2,30 +2,121 @@
 //!
 //! This library provides a C-compatible API for creating scatter plots.
 
-use once_cell::sync::OnceCell;
+use base64::Engine;
+use image::ImageEncoder;
+use once_cell::sync::{Lazy, OnceCell};
+use plotters::chart::SeriesAnno;
+use plotters::coord::ranged1d::ValueFormatter;
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::{ReverseCoordTranslate, Shift};
 use plotters::prelude::*;
 use plotters::style::register_font;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters_backend::DrawingErrorKind;
 use plotters_bitmap::BitMapBackend;
-use std::ffi::{CStr, CString, c_char, c_double};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString, c_char, c_double, c_void};
 use std::panic::{AssertUnwindSafe, catch_unwind};
-use std::sync::Mutex;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
 
-// Embed the font file at compile time
+mod context;
+#[cfg(feature = "opengl")]
+mod gl;
+mod interpolation;
+mod json;
+#[cfg(feature = "pdf")]
+mod pdf;
+pub mod safe;
+
+pub use context::PlotContext;
+
+// Embed the font files at compile time
+#[cfg(feature = "embedded-font")]
 static FONT_BYTES: &[u8] = include_bytes!("../assets/fonts/Inter-Regular.ttf");
+#[cfg(feature = "embedded-font")]
+static BOLD_FONT_BYTES: &[u8] = include_bytes!("../assets/fonts/Inter-Bold.ttf");
+
+// Global storage for the last error message and its kind
+static LAST_ERROR: Mutex<Option<(CString, ErrorKind)>> = Mutex::new(None);
+
+thread_local! {
+    // Kind of the error the running FFI call on this thread fails with, see `non_finite_error`
+    static ERROR_KIND: std::cell::Cell<ErrorKind> = const { std::cell::Cell::new(ErrorKind::Other) };
+}
+
+// Global storage for the last non-fatal warning
+static LAST_WARNING: Mutex<Option<CString>> = Mutex::new(None);
+
+// Image size limits set by the environment, see `max_image_size`
+static MAX_IMAGE_SIZE: OnceCell<(u32, u32)> = OnceCell::new();
+
+// Warning reported on every render once the bundled font has been replaced by a system font
+static FONT_FALLBACK_WARNING: Mutex<Option<String>> = Mutex::new(None);
+
+// Font files tried, in order, when the bundled font cannot be registered, or in its
+// place without the `embedded-font` feature
+const SYSTEM_FONT_PATHS: &[&str] = &[
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationSans-Regular.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/Library/Fonts/Arial.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+];
+
+// Registered font names and their styles, including the bundled font once it is set up
+static FONTS: Lazy<Mutex<HashMap<String, FontStyle>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Family name of the bundled font
+const DEFAULT_FONT: &str = "app-font";
+// Name of the bundled bold font
+const DEFAULT_BOLD_FONT: &str = "app-font-bold";
+
+// Failures of the last `plot_scatter_png_batch_parallel()` call: spec index and message
+static BATCH_ERRORS: Mutex<Vec<(usize, String)>> = Mutex::new(Vec::new());
+
+// Directory that relative output paths are resolved against (None = current directory)
+static BASE_DIRECTORY: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+// Theme applied by `plot_options_default()`, set by `plot_set_theme()` (None = "default")
+static THEME: Mutex<Option<Theme>> = Mutex::new(None);
+
+// Process-wide defaults for zero-valued options, set by `plot_set_global_option*()`
+static GLOBAL_OPTIONS: Lazy<Mutex<HashMap<String, u64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Option names accepted by `plot_set_global_option*()`
+const GLOBAL_OPTION_KEYS: &[&str] = &[
+    "width",
+    "height",
+    "marker_radius",
+    "dpi",
+    "label_font_size",
+    "color_scheme",
+];
 
-// Global storage for the last error message
-static LAST_ERROR: Mutex<Option<CString>> = Mutex::new(None);
+// Largest image height `height = 0` may produce, so extreme aspect ratios cannot exhaust memory
+const MAX_AUTO_HEIGHT: u32 = 16384;
 
-// Font registration happens once per process
-static FONT_REGISTERED: OnceCell<Result<(), String>> = OnceCell::new();
+// Tick label font size in pixels when `label_font_size` is 0
+const DEFAULT_LABEL_FONT_SIZE: u32 = 14;
 
 /// Options for configuring the scatter plot.
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct PlotOptions {
-    /// Width of the output image in pixels
+    /// Width of the output image in pixels.
+    ///
+    /// Renders fail if the width or height exceeds the `PLOTFFI_MAX_WIDTH` or
+    /// `PLOTFFI_MAX_HEIGHT` environment variable, read once per process.
     pub width: u32,
-    /// Height of the output image in pixels
+    /// Height of the output image in pixels.
+    ///
+    /// For `plot_scatter_png()` and its variants, 0 (with no global default height set)
+    /// means `width * y_range / x_range`, so one data unit spans as many pixels on both
+    /// axes (up to 16384 pixels). Other functions require a nonzero height.
     pub height: u32,
     /// Radius of scatter plot markers in pixels
     pub marker_radius: u32,
@@ -39,321 +130,10410 @@ pub struct PlotOptions {
     pub y_min: c_double,
     /// Maximum Y axis value (used when auto_range == 0)
     pub y_max: c_double,
+    /// If nonzero, shrink the plot area so one X data unit spans as many pixels as one Y data unit.
+    ///
+    /// The image size is unchanged; the plot area is centered and the surrounding margins absorb
+    /// the difference, so effective margins may be larger than configured.
+    pub lock_aspect_ratio: u8,
+    /// Height of the X axis label area in pixels (0 = default of 40)
+    pub x_label_area_size: u32,
+    /// Width of the Y axis label area in pixels (0 = default of 50)
+    pub y_label_area_size: u32,
+    /// Rotation of X axis tick labels in degrees clockwise (0, ±45, or ±90)
+    pub x_label_rotation: i32,
+    /// Rotation of Y axis tick labels in degrees clockwise (0, ±45, or ±90)
+    pub y_label_rotation: i32,
+    /// If nonzero, embed this resolution in dots per inch as a PNG pHYs chunk
+    pub dpi: u32,
+    /// If nonzero, format X axis tick labels in scientific notation (e.g. "1.5e-7")
+    pub x_scientific: u8,
+    /// If nonzero, format Y axis tick labels in scientific notation (e.g. "1.5e-7")
+    pub y_scientific: u8,
+    /// If nonzero, keep trailing zeros in scientific labels (e.g. "1.00e-7" instead of "1e-7")
+    pub keep_trailing_zeros: u8,
+    /// Handling of non-finite coordinates: 0 = fail with an error on NaN or infinite values,
+    /// 1 = skip points with NaN or infinite coordinates
+    pub nan_policy: u8,
+    /// Color of the axis border as packed ARGB (0 = black when `border_width > 0`).
+    ///
+    /// The sentinel `PLOT_BORDER_NONE` (0xFF000000) disables the axis border entirely.
+    pub border_color: u32,
+    /// Width of the axis border in pixels (0 = Plotters default)
+    pub border_width: u32,
+    /// Color of the axis lines and tick marks as packed ARGB (0 = default).
+    ///
+    /// Takes precedence over `border_color`, which otherwise colors the same lines;
+    /// `border_width` still sets their thickness and `PLOT_BORDER_NONE` still hides them.
+    /// Grid lines between ticks are not affected.
+    pub axis_color: u32,
+    /// If nonzero, the X axis runs from right (minimum) to left (maximum)
+    pub invert_x: u8,
+    /// If nonzero, the Y axis runs from top (minimum) to bottom (maximum)
+    pub invert_y: u8,
+    /// NUL-terminated strftime-style format for time axis labels (empty = "%Y-%m-%d %H:%M").
+    ///
+    /// Only used by `plot_timeseries_png()`.
+    pub x_label_format: [c_char; 64],
+    /// Marker opacity from 0 (invisible) to 255 (fully opaque, the default).
+    ///
+    /// Note that a zero-initialized struct yields invisible markers; set this to 255
+    /// unless transparency is wanted.
+    pub marker_alpha: u8,
+    /// How `plot_line_png()` connects points: `PLOT_LINE_LINEAR`, `PLOT_LINE_STEP_RIGHT`,
+    /// `PLOT_LINE_STEP_LEFT` or `PLOT_LINE_MONOTONE_CUBIC`
+    pub line_mode: u8,
+    /// Number of concentric grid circles in polar plots (0 = default of 5)
+    pub polar_grid_rings: u32,
+    /// NUL-terminated UTF-8 chart title drawn above the plot (empty = no title)
+    pub title: [c_char; 128],
+    /// If nonzero, `plot_scatter_with_regression_png()` shows the R² value in the title
+    pub show_r_squared: u8,
+    /// Width in data units of the random X offset added to each scatter point (0 = no jitter).
+    ///
+    /// Offsets are drawn from `[-jitter_amount/2, +jitter_amount/2]` and do not affect the axis range.
+    pub jitter_amount: c_double,
+    /// Seed for the jitter offsets (0 = use `render_seed`)
+    pub jitter_seed: u64,
+    /// NUL-terminated name of a registered font for the title (empty = bundled font)
+    pub title_font_name: [c_char; 64],
+    /// NUL-terminated name of a registered font for tick and axis labels (empty = bundled font)
+    pub label_font_name: [c_char; 64],
+    /// X tick label format: `PLOT_TICK_DEFAULT`, `PLOT_TICK_INTEGER`, `PLOT_TICK_FIXED2`, `PLOT_TICK_FIXED4` or `PLOT_TICK_SI`.
+    ///
+    /// `x_scientific` takes precedence when set.
+    pub x_tick_format: u8,
+    /// Y tick label format, see `x_tick_format`
+    pub y_tick_format: u8,
+    /// Width of a centered moving average drawn over scatter and line charts (0 = none)
+    pub moving_avg_window: u32,
+    /// White border in pixels added around PNG output (0 = none).
+    ///
+    /// `width` and `height` still describe the chart, so the image is `width + 2 * outer_padding` wide.
+    pub outer_padding: u32,
+    /// Line width in pixels for `plot_line_png()` (0 = 1 pixel)
+    pub line_stroke_width: u32,
+    /// Line pattern for `plot_line_png()`: `PLOT_DASH_SOLID`, `PLOT_DASH_DASHED`, `PLOT_DASH_DOTTED` or `PLOT_DASH_DASH_DOT`
+    pub line_dash_pattern: u8,
+    /// How `auto_range` derives axis ranges: `PLOT_RANGE_PADDED`, `PLOT_RANGE_EXACT` or `PLOT_RANGE_NICE`
+    pub auto_range_mode: u8,
+    /// Approximate number of major X ticks and labels (0 = Plotters' default)
+    pub x_tick_count: u32,
+    /// Approximate number of major Y ticks and labels (0 = Plotters' default)
+    pub y_tick_count: u32,
+    /// Maximum number of minor grid lines between two major X ticks (0 = Plotters' default).
+    ///
+    /// Minor lines are only drawn where the major ticks leave enough room for them,
+    /// so lower `x_tick_count` to make space for more.
+    pub x_minor_tick_count: u32,
+    /// Maximum number of minor grid lines between two major Y ticks (0 = Plotters' default)
+    pub y_minor_tick_count: u32,
+    /// Major grid line color as packed ARGB (0 = Plotters' default); minor lines use it at half its alpha
+    pub grid_color: u32,
+    /// If nonzero, scatter points outside the clip box are not drawn; the axis ranges are unaffected
+    pub clip_enabled: u8,
+    /// Clip box lower X bound (inclusive)
+    pub clip_x_min: c_double,
+    /// Clip box upper X bound (inclusive)
+    pub clip_x_max: c_double,
+    /// Clip box lower Y bound (inclusive)
+    pub clip_y_min: c_double,
+    /// Clip box upper Y bound (inclusive)
+    pub clip_y_max: c_double,
+    /// If nonzero and the bundled font cannot be loaded, use a system font instead of failing.
+    ///
+    /// The substitution is reported through `plot_last_warning_message()`. Builds without
+    /// the `embedded-font` feature always use a system font, without a warning.
+    pub use_system_font_fallback: u8,
+    /// If nonzero, `plot_scatter_png_base64()` returns a `data:image/png;base64,` URI
+    pub base64_data_uri: u8,
+    /// If nonzero, scatter markers get smooth, alpha-blended edges.
+    ///
+    /// This draws markers pixel by pixel and is several times slower than the default hard-edged
+    /// circles, which also look crisper on small thumbnails. One-pixel lines are always
+    /// anti-aliased by Plotters.
+    pub antialias: u8,
+    /// Explicit X tick positions (`x_n_ticks` values), overriding `x_tick_count`; may be null
+    /// when `x_n_ticks` is 0.
+    ///
+    /// Values outside the X range are ignored. The array must stay valid until the call returns.
+    /// Only the C functions read this field; the Rust API takes the ticks as a slice, see
+    /// `RenderOptions::with_x_ticks`.
+    pub x_ticks: *const f64,
+    /// Number of values in `x_ticks` (0 = automatic ticks)
+    pub x_n_ticks: u32,
+    /// Explicit Y tick positions (`y_n_ticks` values), overriding `y_tick_count`; may be null
+    /// when `y_n_ticks` is 0. Like `x_ticks`, only read by the C functions.
+    pub y_ticks: *const f64,
+    /// Number of values in `y_ticks` (0 = automatic ticks)
+    pub y_n_ticks: u32,
+    /// Seed for all random draws in a render call, such as jitter (0 = `0x2545F4914F6CDD1D`).
+    ///
+    /// Renders with the same seed and options produce identical images.
+    pub render_seed: u64,
+    /// Palette for series without an explicit color in multi-series charts: one of the
+    /// `PLOT_SCHEME_*` constants. Colors cycle by series index; single-series charts ignore it.
+    pub color_scheme: u8,
+    /// Multiplier from vector components to arrow length in data units for
+    /// `plot_quiver_png()` (0 = scale so the longest arrow spans 5% of the axis range)
+    pub arrow_scale: c_double,
+    /// Font size of tick labels and the legend in pixels (0 = 14); axis descriptions are 2 pixels larger
+    pub label_font_size: u32,
+    /// Corner for the legend of multi-series charts: one of the `PLOT_LEGEND_*` constants.
+    ///
+    /// 0 suppresses the legend; `PlotOptions::default()` uses `PLOT_LEGEND_UPPER_RIGHT`.
+    pub legend_position: u8,
+    /// Hook called with `post_render_ctx` once the standard chart elements of a scatter
+    /// plot have been drawn, before the image is encoded (may be null).
+    ///
+    /// Only the functions writing a scatter plot to a PNG file call it: `plot_scatter_png()`,
+    /// `plot_scatter_png_ex()`, `plot_scatter_png_timed()`, `plot_scatter_f32_png()`,
+    /// `plot_scatter_interleaved_png()`, `plot_scatter_strided_png()`, `plot_finish()` and
+    /// `plot_scatter_png_batch_parallel()`. Other renderers and the Rust API ignore it.
+    ///
+    /// In C this is a plain `void (*)(void *)` function pointer, so a null pointer maps to
+    /// `None`. The field is pointer-sized and pointer-aligned, which makes the struct
+    /// layout depend on the target's pointer width like `x_ticks` does; callers built
+    /// against an older header pass a shorter struct and must be rebuilt.
+    pub post_render_fn: Option<unsafe extern "C" fn(ctx: *mut c_void)>,
+    /// Opaque pointer passed to `post_render_fn`; the library never dereferences it.
+    pub post_render_ctx: *mut c_void,
+    /// Nonzero keeps Plotters' automatic X tick count even when the labels overlap.
+    ///
+    /// By default, with `x_tick_count` 0 and unrotated labels, the number of X labels is
+    /// halved until their estimated widths fit on the axis (down to 2 labels).
+    pub disable_label_skip: u8,
+    /// Byte order of the 16-bit words from `plot_scatter_rgb565()`: 0 = little-endian,
+    /// 1 = big-endian
+    pub pixel_endian: u8,
+    /// Nonzero draws a line at x = 0 when it is inside the X range, over the grid and
+    /// under the data
+    pub draw_zero_x_line: u8,
+    /// Nonzero draws a line at y = 0 when it is inside the Y range
+    pub draw_zero_y_line: u8,
+    /// Zero line color as packed ARGB (0 = black)
+    pub zero_line_color: u32,
+    /// Fraction of the image width and height taken by the plotting area, in (0, 1].
+    ///
+    /// 0 = margins from `x_label_area_size`/`y_label_area_size`. Otherwise the space left on
+    /// each side is `(1 - plot_area_fraction) / 2` of the image, and on the axis sides it is
+    /// split between the margin and the label area in their default proportions.
+    pub plot_area_fraction: c_double,
+    /// Nonzero draws the legend of `plot_scatter_series_png()` even for a single series,
+    /// which is otherwise left out as redundant
+    pub force_legend: u8,
+    /// NUL-terminated UTF-8 text stored as the PNG "Title" text chunk (empty = none)
+    pub png_title: [c_char; 128],
+    /// NUL-terminated UTF-8 text stored as the PNG "Author" text chunk (empty = none)
+    pub png_author: [c_char; 128],
+    /// NUL-terminated UTF-8 text stored as the PNG "Description" text chunk (empty = none)
+    pub png_description: [c_char; 256],
+    /// Horizontal bands (`n_h_bands` values) shaded behind the data; may be null when
+    /// `n_h_bands` is 0. The array must stay valid until the call returns.
+    ///
+    /// Only the C functions read this field; the Rust API takes the bands as a slice, see
+    /// `RenderOptions::with_h_bands`.
+    pub h_bands: *const HBand,
+    /// Number of values in `h_bands`
+    pub n_h_bands: u32,
+    /// Vertical bands (`n_v_bands` values) shaded behind the data; may be null when
+    /// `n_v_bands` is 0. Like `h_bands`, only read by the C functions.
+    pub v_bands: *const VBand,
+    /// Number of values in `v_bands`
+    pub n_v_bands: u32,
+    /// Background color as packed ARGB (0 = white)
+    pub background_color: u32,
+    /// Color of the title, tick labels and legend text as packed ARGB (0 = black)
+    pub text_color: u32,
+    /// Nonzero leaves out the grid lines; ticks and labels are still drawn
+    pub hide_grid: u8,
+    /// Nonzero makes auto ranges end on grid lines: each end moves out from the data to
+    /// the nearest tick, spaced as Plotters would place `x_tick_count`/`y_tick_count`
+    /// ticks on the `auto_range_mode` range. Has no effect with explicit ranges.
+    pub snap_range_to_ticks: u8,
+    /// Nonzero writes the image size, axis ranges and point count of a scatter PNG to
+    /// `<path>.json` after it is rendered. Failing to write it only sets a warning.
+    pub write_metadata: u8,
+    /// Title font style: `PLOT_FONT_NORMAL`, `PLOT_FONT_BOLD`, `PLOT_FONT_ITALIC` or
+    /// `PLOT_FONT_OBLIQUE`. Bold text in the bundled font uses the bundled bold face;
+    /// other fonts fall back to their normal face if the style is not registered.
+    pub title_font_style: u8,
+    /// Axis description font style, see `title_font_style`
+    pub axis_desc_font_style: u8,
+    /// Nonzero draws a 2D histogram of the points behind scatter markers, each bin colored
+    /// on the plasma scale by its count relative to the fullest bin, at 70% opacity.
+    /// Empty bins are left out.
+    pub density_overlay: u8,
+    /// Bins per axis of the `density_overlay` histogram (0 = 50)
+    pub density_bins: u32,
 }
 
-/// Stores an error message for later retrieval via plot_last_error_message().
-fn set_error(msg: String) {
-    if let Ok(mut guard) = LAST_ERROR.lock() {
-        // Convert to CString, replacing any interior NUL bytes
-        let sanitized = msg.replace('\0', "\\0");
-        *guard = CString::new(sanitized).ok();
+impl Default for PlotOptions {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 600,
+            marker_radius: 5,
+            auto_range: 1,
+            x_min: 0.0,
+            x_max: 1.0,
+            y_min: 0.0,
+            y_max: 1.0,
+            lock_aspect_ratio: 0,
+            x_label_area_size: 0,
+            y_label_area_size: 0,
+            x_label_rotation: 0,
+            y_label_rotation: 0,
+            dpi: 0,
+            x_scientific: 0,
+            y_scientific: 0,
+            keep_trailing_zeros: 0,
+            nan_policy: 0,
+            border_color: 0,
+            border_width: 0,
+            axis_color: 0,
+            invert_x: 0,
+            invert_y: 0,
+            x_label_format: [0; 64],
+            marker_alpha: 255,
+            line_mode: PLOT_LINE_LINEAR,
+            polar_grid_rings: 0,
+            title: [0; 128],
+            show_r_squared: 0,
+            jitter_amount: 0.0,
+            jitter_seed: 0,
+            title_font_name: [0; 64],
+            label_font_name: [0; 64],
+            x_tick_format: PLOT_TICK_DEFAULT,
+            y_tick_format: PLOT_TICK_DEFAULT,
+            moving_avg_window: 0,
+            outer_padding: 0,
+            line_stroke_width: 0,
+            line_dash_pattern: PLOT_DASH_SOLID,
+            auto_range_mode: PLOT_RANGE_PADDED,
+            x_tick_count: 0,
+            y_tick_count: 0,
+            x_minor_tick_count: 0,
+            y_minor_tick_count: 0,
+            grid_color: 0,
+            clip_enabled: 0,
+            clip_x_min: 0.0,
+            clip_x_max: 0.0,
+            clip_y_min: 0.0,
+            clip_y_max: 0.0,
+            use_system_font_fallback: 0,
+            base64_data_uri: 0,
+            antialias: 0,
+            x_ticks: std::ptr::null(),
+            x_n_ticks: 0,
+            y_ticks: std::ptr::null(),
+            y_n_ticks: 0,
+            render_seed: 0,
+            color_scheme: PLOT_SCHEME_TABLEAU10,
+            arrow_scale: 0.0,
+            label_font_size: 0,
+            legend_position: PLOT_LEGEND_UPPER_RIGHT,
+            post_render_fn: None,
+            post_render_ctx: std::ptr::null_mut(),
+            disable_label_skip: 0,
+            pixel_endian: 0,
+            draw_zero_x_line: 0,
+            draw_zero_y_line: 0,
+            zero_line_color: 0,
+            plot_area_fraction: 0.0,
+            force_legend: 0,
+            png_title: [0; 128],
+            png_author: [0; 128],
+            png_description: [0; 256],
+            h_bands: std::ptr::null(),
+            n_h_bands: 0,
+            v_bands: std::ptr::null(),
+            n_v_bands: 0,
+            background_color: 0,
+            text_color: 0,
+            hide_grid: 0,
+            snap_range_to_ticks: 0,
+            write_metadata: 0,
+            title_font_style: PLOT_FONT_NORMAL,
+            axis_desc_font_style: PLOT_FONT_NORMAL,
+            density_overlay: 0,
+            density_bins: 0,
+        }
     }
 }
 
-/// Clears the stored error message.
-fn clear_error() {
-    if let Ok(mut guard) = LAST_ERROR.lock() {
-        *guard = None;
+/// A single chart to render: output path, data and options.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BatchSpec {
+    /// NUL-terminated UTF-8 output path (ignored by `plot_grid_png`)
+    pub path: *const c_char,
+    /// Pointer to array of X coordinates
+    pub xs: *const c_double,
+    /// Pointer to array of Y coordinates
+    pub ys: *const c_double,
+    /// Number of points (length of xs and ys arrays)
+    pub n: u64,
+    /// Plot configuration options
+    pub opt: PlotOptions,
+}
+
+/// One failed render of `plot_scatter_png_batch_parallel()`, read with `plot_batch_errors()`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BatchError {
+    /// Index of the failed spec
+    pub index: usize,
+    /// NUL-terminated UTF-8 error message, truncated to fit
+    pub message: [c_char; 256],
+}
+
+/// How one series of a multi-series chart is drawn.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SeriesStyle {
+    /// Color of markers and line as packed ARGB (0 = next color of `PlotOptions::color_scheme`)
+    pub color: u32,
+    /// NUL-terminated UTF-8 series name shown in the legend (empty = not listed)
+    pub name: [c_char; 64],
+    /// Marker shape: one of the `PLOT_MARKER_*` constants
+    pub marker_shape: u8,
+    /// Marker radius in pixels (0 = no markers)
+    pub marker_radius: u32,
+    /// If nonzero, connect the points in array order with a line
+    pub draw_line: u8,
+    /// Line width in pixels (0 = 1 pixel)
+    pub line_width: u32,
+    /// Line dash pattern: one of the `PLOT_DASH_*` constants
+    pub line_dash: u8,
+    /// If nonzero, markers are filled; otherwise only their outline is drawn
+    pub marker_filled: u8,
+}
+
+impl Default for SeriesStyle {
+    fn default() -> Self {
+        Self {
+            color: 0,
+            name: [0; 64],
+            marker_shape: PLOT_MARKER_CIRCLE,
+            marker_radius: 0,
+            draw_line: 0,
+            line_width: 0,
+            line_dash: PLOT_DASH_SOLID,
+            marker_filled: 0,
+        }
     }
 }
 
-/// Ensures the bundled font is registered with Plotters.
-fn ensure_font_registered() -> Result<(), String> {
-    FONT_REGISTERED
-        .get_or_init(|| {
-            register_font("app-font", FontStyle::Normal, FONT_BYTES)
-                .map_err(|_| "Failed to register bundled font: invalid font data".to_string())
-        })
-        .clone()
+/// One data series of a multi-series chart and how to draw it.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ScatterSeries {
+    /// Pointer to array of X coordinates
+    pub xs: *const c_double,
+    /// Pointer to array of Y coordinates
+    pub ys: *const c_double,
+    /// Number of points (length of xs and ys arrays)
+    pub n: u64,
+    /// Marker and line style of the series
+    pub style: SeriesStyle,
+    /// Drawing order: series with higher values are drawn later, on top of the others.
+    /// Series with equal values keep their array order.
+    pub z_order: i32,
 }
 
-/// Internal implementation of scatter plot rendering.
+/// The points of one frame of an animated chart.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct GifFrame {
+    /// Pointer to array of X coordinates
+    pub xs: *const c_double,
+    /// Pointer to array of Y coordinates
+    pub ys: *const c_double,
+    /// Number of points (length of xs and ys arrays)
+    pub n: u64,
+}
+
+/// Shaded region between two Y values across the whole X axis, for `PlotOptions::h_bands`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HBand {
+    /// One edge of the band (the edges may be given in either order)
+    pub lo: c_double,
+    /// The other edge of the band
+    pub hi: c_double,
+    /// Fill color as packed ARGB; use an alpha below 0xFF to keep the grid visible
+    pub color: u32,
+}
+
+/// Shaded region between two X values across the whole Y axis, for `PlotOptions::v_bands`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VBand {
+    /// One edge of the band (the edges may be given in either order)
+    pub lo: c_double,
+    /// The other edge of the band
+    pub hi: c_double,
+    /// Fill color as packed ARGB; use an alpha below 0xFF to keep the grid visible
+    pub color: u32,
+}
+
+/// Drawing step run on a finished scatter chart, before it is encoded.
+pub(crate) type PostRender<'a> = &'a dyn Fn(&DrawingArea<BitMapBackend, Shift>) -> Result<(), String>;
+
+/// `PlotOptions` together with the arrays its pointer fields refer to, as slices.
 ///
-/// This function is public for benchmarking purposes.
-#[doc(hidden)]
-pub fn plot_scatter_png_impl(path: &str, xs: &[f64], ys: &[f64], opt: PlotOptions) -> Result<(), String> {
-    // Ensure font is registered
-    ensure_font_registered()?;
+/// The C functions read the tick and band arrays once, with `from_ffi`, so rendering
+/// never dereferences the raw pointers. Converting a `PlotOptions` with `From` ignores
+/// those pointers; Rust callers pass the arrays with the `with_*` methods instead.
+#[derive(Clone, Copy)]
+pub struct RenderOptions<'a> {
+    /// The options; `x_ticks`, `y_ticks`, `h_bands`, `v_bands` and their counts are not read
+    pub opt: PlotOptions,
+    /// Explicit X tick positions (empty = automatic ticks)
+    pub x_ticks: &'a [f64],
+    /// Explicit Y tick positions (empty = automatic ticks)
+    pub y_ticks: &'a [f64],
+    /// Horizontal bands shaded behind the data
+    pub h_bands: &'a [HBand],
+    /// Vertical bands shaded behind the data
+    pub v_bands: &'a [VBand],
+    /// Run by the scatter PNG renderers once the chart is drawn; set by the C functions
+    /// that honour `post_render_fn`
+    pub(crate) post_render: Option<PostRender<'a>>,
+}
 
-    // Validate dimensions
-    if opt.width == 0 || opt.height == 0 {
-        return Err("Width and height must be greater than zero".to_string());
+impl<'a> RenderOptions<'a> {
+    /// Reads the tick and band arrays of options passed through the C API.
+    ///
+    /// The `post_render_fn` hook is left out, see `render_with_ffi_hook`.
+    ///
+    /// # Safety
+    /// Non-null `opt.x_ticks`/`opt.y_ticks` must point to `x_n_ticks`/`y_n_ticks` values,
+    /// and non-null `opt.h_bands`/`opt.v_bands` to `n_h_bands`/`n_v_bands` values, all
+    /// valid and unchanged for `'a`.
+    pub(crate) unsafe fn from_ffi(opt: PlotOptions) -> Self {
+        unsafe {
+            Self {
+                opt,
+                x_ticks: option_slice(opt.x_ticks, opt.x_n_ticks),
+                y_ticks: option_slice(opt.y_ticks, opt.y_n_ticks),
+                h_bands: option_slice(opt.h_bands, opt.n_h_bands),
+                v_bands: option_slice(opt.v_bands, opt.n_v_bands),
+                post_render: None,
+            }
+        }
     }
 
-    // Compute axis ranges
-    let (x_min, x_max, y_min, y_max) = if opt.auto_range != 0 {
-        // Auto-compute from data with 2% padding
-        let x_data_min = xs.iter().copied().fold(f64::INFINITY, f64::min);
-        let x_data_max = xs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
-        let y_data_min = ys.iter().copied().fold(f64::INFINITY, f64::min);
-        let y_data_max = ys.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    /// Replaces the options, keeping the arrays.
+    pub(crate) fn with_options(self, opt: PlotOptions) -> Self {
+        Self { opt, ..self }
+    }
 
-        let x_range = x_data_max - x_data_min;
-        let y_range = y_data_max - y_data_min;
+    /// Sets explicit X tick positions, overriding `x_tick_count`.
+    ///
+    /// Values outside the X range are ignored.
+    pub fn with_x_ticks(self, x_ticks: &'a [f64]) -> Self {
+        Self { x_ticks, ..self }
+    }
 
-        // Handle case where all points have the same coordinate
-        let x_padding = if x_range.abs() < f64::EPSILON {
-            1.0
-        } else {
-            x_range * 0.02
-        };
-        let y_padding = if y_range.abs() < f64::EPSILON {
-            1.0
-        } else {
-            y_range * 0.02
-        };
+    /// Sets explicit Y tick positions, overriding `y_tick_count`.
+    pub fn with_y_ticks(self, y_ticks: &'a [f64]) -> Self {
+        Self { y_ticks, ..self }
+    }
 
-        (
-            x_data_min - x_padding,
-            x_data_max + x_padding,
-            y_data_min - y_padding,
-            y_data_max + y_padding,
-        )
-    } else {
-        // Use explicit ranges from options
-        if opt.x_min >= opt.x_max {
-            return Err(format!(
-                "Invalid X range: x_min ({}) must be less than x_max ({})",
-                opt.x_min, opt.x_max
-            ));
+    /// Sets the horizontal bands shaded behind the data.
+    pub fn with_h_bands(self, h_bands: &'a [HBand]) -> Self {
+        Self { h_bands, ..self }
+    }
+
+    /// Sets the vertical bands shaded behind the data.
+    pub fn with_v_bands(self, v_bands: &'a [VBand]) -> Self {
+        Self { v_bands, ..self }
+    }
+
+    /// Sets the drawing step run once a scatter chart has been drawn.
+    pub(crate) fn with_post_render<F>(self, post_render: Option<&'a F>) -> Self
+    where
+        F: Fn(&DrawingArea<BitMapBackend, Shift>) -> Result<(), String>,
+    {
+        Self {
+            post_render: post_render.map(|f| f as PostRender<'a>),
+            ..self
         }
-        if opt.y_min >= opt.y_max {
-            return Err(format!(
-                "Invalid Y range: y_min ({}) must be less than y_max ({})",
-                opt.y_min, opt.y_max
-            ));
+    }
+}
+
+impl From<PlotOptions> for RenderOptions<'_> {
+    fn from(opt: PlotOptions) -> Self {
+        Self {
+            opt,
+            x_ticks: &[],
+            y_ticks: &[],
+            h_bands: &[],
+            v_bands: &[],
+            post_render: None,
         }
-        (opt.x_min, opt.x_max, opt.y_min, opt.y_max)
-    };
+    }
+}
 
-    // Create the bitmap backend
-    let root = BitMapBackend::new(path, (opt.width, opt.height)).into_drawing_area();
+impl std::ops::Deref for RenderOptions<'_> {
+    type Target = PlotOptions;
 
-    // Fill background white
-    root.fill(&WHITE)
-        .map_err(|e| format!("Failed to fill background: {}", e))?;
+    fn deref(&self) -> &PlotOptions {
+        &self.opt
+    }
+}
 
-    // Build chart with label areas
-    let mut chart = ChartBuilder::on(&root)
-        .margin(10)
-        .x_label_area_size(40)
-        .y_label_area_size(50)
-        .build_cartesian_2d(x_min..x_max, y_min..y_max)
-        .map_err(|e| format!("Failed to build chart: {}", e))?;
+impl std::ops::DerefMut for RenderOptions<'_> {
+    fn deref_mut(&mut self) -> &mut PlotOptions {
+        &mut self.opt
+    }
+}
 
-    // Configure and draw mesh (ticks/grid) with bundled font
-    chart
-        .configure_mesh()
-        .label_style(("app-font", 14).into_font())
-        .axis_desc_style(("app-font", 16).into_font())
-        .draw()
-        .map_err(|e| format!("Failed to draw mesh: {}", e))?;
+/// One layer of a `plot_composite_png()` chart.
+///
+/// `data` points to the layer-specific struct selected by `layer_type`: a `ScatterLayer`,
+/// a `HeatmapLayer` or a `ReferenceLine`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CompositeLayer {
+    /// Kind of layer: one of the `PLOT_LAYER_*` constants
+    pub layer_type: u8,
+    /// Pointer to the layer data of the type given by `layer_type`
+    pub data: *const c_void,
+}
 
-    // Draw scatter points as filled circles
-    let marker_radius = opt.marker_radius as i32;
-    chart
-        .draw_series(
-            xs.iter()
-                .zip(ys.iter())
-                .map(|(&x, &y)| Circle::new((x, y), marker_radius, BLUE.filled())),
-        )
-        .map_err(|e| format!("Failed to draw points: {}", e))?;
+/// Scatter points of a composite chart layer.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ScatterLayer {
+    /// Pointer to array of X coordinates
+    pub xs: *const c_double,
+    /// Pointer to array of Y coordinates
+    pub ys: *const c_double,
+    /// Number of points (length of xs and ys arrays)
+    pub n: u64,
+    /// Marker color as packed ARGB (0 = blue with `PlotOptions::marker_alpha`)
+    pub color: u32,
+    /// Marker radius in pixels (0 = `PlotOptions::marker_radius`)
+    pub marker_radius: u32,
+}
 
-    // Finalize and write PNG
-    root.present().map_err(|e| format!("Failed to write PNG: {}", e))?;
+/// Point density heatmap of a composite chart layer, binned over the shared axis ranges.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct HeatmapLayer {
+    /// Pointer to array of X coordinates
+    pub xs: *const c_double,
+    /// Pointer to array of Y coordinates
+    pub ys: *const c_double,
+    /// Number of points (length of xs and ys arrays)
+    pub n: u64,
+    /// Bins along X (0 = `ceil(sqrt(n))`)
+    pub n_bins_x: u32,
+    /// Bins along Y (0 = `ceil(sqrt(n))`)
+    pub n_bins_y: u32,
+    /// Opacity of the bins in (0, 1] (0 = 0.5)
+    pub opacity: c_double,
+}
 
-    Ok(())
+/// Straight line across the whole plot area at a fixed X or Y value.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReferenceLine {
+    /// `PLOT_REFERENCE_HORIZONTAL` (at Y = `value`) or `PLOT_REFERENCE_VERTICAL` (at X = `value`)
+    pub orientation: u8,
+    /// Axis value the line is drawn at
+    pub value: c_double,
+    /// Line color as packed ARGB (0 = black)
+    pub color: u32,
+    /// Line width in pixels (0 = 1 pixel)
+    pub line_width: u32,
 }
 
-/// Renders a scatter plot to a PNG file.
-///
-/// # Parameters
-/// - `path`: NUL-terminated UTF-8 path to the output PNG file
-/// - `xs`: Pointer to array of X coordinates
-/// - `ys`: Pointer to array of Y coordinates
-/// - `n`: Number of points (length of xs and ys arrays)
-/// - `opt`: Plot configuration options
-///
-/// # Returns
-/// - 0 on success
-/// - 1 on failure (call `plot_last_error_message()` for details)
+/// Range and style of the right Y axis of `plot_dual_axis_png()`.
 ///
-/// # Safety
-/// - `path` must be a valid NUL-terminated UTF-8 string
-/// - `xs` and `ys` must point to arrays of at least `n` elements
-/// - `n` must be greater than 0
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn plot_scatter_png(
-    path: *const c_char,
-    xs: *const c_double,
-    ys: *const c_double,
-    n: usize,
-    opt: PlotOptions,
-) -> i32 {
-    // Clear any previous error
-    clear_error();
+/// Image-level settings, the X axis and the left Y axis come from the chart's `PlotOptions`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AxisOptions {
+    /// Lower bound of the axis when `auto_range` is 0
+    pub y_min: c_double,
+    /// Upper bound of the axis when `auto_range` is 0
+    pub y_max: c_double,
+    /// If nonzero, the range is computed from the data
+    pub auto_range: u8,
+    /// Fraction of the data extent added below and above automatic ranges (0 = none)
+    pub auto_range_padding: c_double,
+    /// If nonzero, the axis is base-10 logarithmic; values and bounds must be positive
+    pub log_scale: u8,
+    /// Color of the series, tick labels and name as packed ARGB (0 = second color of
+    /// `PlotOptions::color_scheme`)
+    pub series_color: u32,
+    /// NUL-terminated UTF-8 name shown along the axis (empty = none)
+    pub series_name: [c_char; 64],
+}
 
-    // Wrap everything in catch_unwind to prevent panics crossing FFI boundary
-    let result = catch_unwind(AssertUnwindSafe(|| {
-        // Validate path pointer
-        if path.is_null() {
-            return Err("Path pointer is NULL".to_string());
+impl Default for AxisOptions {
+    fn default() -> Self {
+        Self {
+            y_min: 0.0,
+            y_max: 1.0,
+            auto_range: 1,
+            auto_range_padding: 0.02,
+            log_scale: 0,
+            series_color: 0,
+            series_name: [0; 64],
         }
+    }
+}
 
-        // Validate data pointers
-        if xs.is_null() {
-            return Err("X data pointer is NULL".to_string());
-        }
-        if ys.is_null() {
-            return Err("Y data pointer is NULL".to_string());
-        }
+/// Symmetric error bars for `plot_scatter_errorbars_ex()`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorBarOptions {
+    /// Pointer to `n` X half-widths, one per point (NULL = no horizontal bars)
+    pub x_err: *const c_double,
+    /// Pointer to `n` Y half-heights, one per point (NULL = no vertical bars)
+    pub y_err: *const c_double,
+    /// Length in pixels of the cap drawn across each bar end (0 = no caps)
+    pub cap_size_px: u32,
+    /// Bar and cap stroke width in pixels (0 = 1)
+    pub line_width: u32,
+    /// Bar and cap color as packed ARGB (0 = the marker color)
+    pub color: u32,
+}
 
-        // Validate count
-        if n == 0 {
-            return Err("Point count (n) must be greater than zero".to_string());
+impl Default for ErrorBarOptions {
+    fn default() -> Self {
+        Self {
+            x_err: std::ptr::null(),
+            y_err: std::ptr::null(),
+            cap_size_px: 6,
+            line_width: 0,
+            color: 0,
         }
+    }
+}
 
-        // Convert path to Rust string
-        let path_cstr = unsafe { CStr::from_ptr(path) };
-        let path_str = path_cstr.to_str().map_err(|_| "Path is not valid UTF-8".to_string())?;
-
-        // Create slices from raw pointers
-        let xs_slice = unsafe { std::slice::from_raw_parts(xs, n) };
-        let ys_slice = unsafe { std::slice::from_raw_parts(ys, n) };
-
-        // Call implementation
-        plot_scatter_png_impl(path_str, xs_slice, ys_slice, opt)
-    }));
+/// One group of samples of a distribution chart, such as a violin plot.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BoxGroup {
+    /// Pointer to array of sample values
+    pub values: *const c_double,
+    /// Number of samples (length of the values array)
+    pub n: u64,
+    /// NUL-terminated UTF-8 group name shown below the group (empty = no label)
+    pub label: [c_char; 64],
+}
 
-    match result {
-        Ok(Ok(())) => 0,
-        Ok(Err(msg)) => {
-            set_error(msg);
-            1
-        },
-        Err(panic_info) => {
-            let msg = if let Some(s) = panic_info.downcast_ref::<&str>() {
-                format!("Internal panic: {}", s)
-            } else if let Some(s) = panic_info.downcast_ref::<String>() {
-                format!("Internal panic: {}", s)
-            } else {
-                "Internal panic (unknown cause)".to_string()
-            };
-            set_error(msg);
-            1
-        },
-    }
+/// Statistics about a completed render, filled by `plot_scatter_png_ex()`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlotStats {
+    /// Points inside the axis ranges that were drawn
+    pub points_rendered: usize,
+    /// Points dropped by the NaN policy
+    pub points_skipped_nan: usize,
+    /// Valid points outside the axis ranges
+    pub points_clipped: usize,
+    /// Wall-clock time of the whole call in microseconds
+    pub render_time_us: u64,
 }
 
-/// Returns the last error message, or NULL if no error has occurred.
+/// Where the data area of a rendered chart is, filled by `plot_scatter_png_ex()`.
 ///
-/// The returned pointer is valid until the next call to `plot_scatter_png()`.
-/// The string is NUL-terminated UTF-8.
-///
-/// # Safety
-/// The returned pointer must not be freed by the caller.
-#[unsafe(no_mangle)]
-pub extern "C" fn plot_last_error_message() -> *const c_char {
-    match LAST_ERROR.lock() {
-        Ok(guard) => match &*guard {
-            Some(cstring) => cstring.as_ptr(),
-            None => std::ptr::null(),
-        },
-        Err(_) => std::ptr::null(),
-    }
+/// The data area is the rectangle inside the axes, without margins and labels. Pixel
+/// coordinates start at the top-left corner of the image and include any outer padding.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PlotAreaInfo {
+    /// Left edge of the data area in pixels
+    pub chart_x: u32,
+    /// Top edge of the data area in pixels
+    pub chart_y: u32,
+    /// Width of the data area in pixels
+    pub chart_width: u32,
+    /// Height of the data area in pixels
+    pub chart_height: u32,
+    /// X value at the left edge (the right edge if `invert_x` is set)
+    pub x_min: c_double,
+    /// X value at the right edge (the left edge if `invert_x` is set)
+    pub x_max: c_double,
+    /// Y value at the bottom edge (the top edge if `invert_y` is set)
+    pub y_min: c_double,
+    /// Y value at the top edge (the bottom edge if `invert_y` is set)
+    pub y_max: c_double,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::ffi::CString;
-    use std::fs;
+/// `PlotOptions::border_color` value that disables the axis border.
+pub const PLOT_BORDER_NONE: u32 = 0xFF_00_00_00;
 
-    #[test]
-    fn test_basic_plot() {
-        let path = CString::new("/tmp/test_scatter.png").unwrap();
-        let xs: Vec<f64> = vec![1.0, 2.0, 3.0, 4.0, 5.0];
-        let ys: Vec<f64> = vec![1.0, 4.0, 2.0, 3.0, 5.0];
-        let opt = PlotOptions {
-            width: 800,
-            height: 600,
-            marker_radius: 5,
-            auto_range: 1,
-            x_min: 0.0,
-            x_max: 0.0,
-            y_min: 0.0,
-            y_max: 0.0,
-        };
+/// Version of the C ABI, incremented whenever a struct layout or signature changes incompatibly.
+///
+/// New `PlotOptions` fields are only ever appended, which changes the struct's size but
+/// not the offsets of existing fields, and does not bump this version. Callers detect
+/// that kind of mismatch by comparing the header's `PLOTFFI_OPTIONS_SIZE` with
+/// `plot_options_size()`.
+pub const PLOTFFI_ABI_VERSION: u32 = 5;
 
-        let result = unsafe { plot_scatter_png(path.as_ptr(), xs.as_ptr(), ys.as_ptr(), xs.len(), opt) };
+/// `SeriesStyle::marker_shape`: circle.
+pub const PLOT_MARKER_CIRCLE: u8 = 0;
+/// `SeriesStyle::marker_shape`: axis-aligned square.
+pub const PLOT_MARKER_SQUARE: u8 = 1;
+/// `SeriesStyle::marker_shape`: upward-pointing triangle.
+pub const PLOT_MARKER_TRIANGLE: u8 = 2;
+/// `SeriesStyle::marker_shape`: diagonal cross (always drawn as an outline).
+pub const PLOT_MARKER_CROSS: u8 = 3;
 
-        assert_eq!(result, 0, "Expected success");
-        assert!(fs::metadata("/tmp/test_scatter.png").is_ok());
-        fs::remove_file("/tmp/test_scatter.png").ok();
-    }
+/// `PlotOptions::color_scheme`: Tableau 10 categorical colors.
+pub const PLOT_SCHEME_TABLEAU10: u8 = 0;
+/// `PlotOptions::color_scheme`: ColorBrewer Set1.
+pub const PLOT_SCHEME_SET1: u8 = 1;
+/// `PlotOptions::color_scheme`: ColorBrewer Pastel1.
+pub const PLOT_SCHEME_PASTEL: u8 = 2;
+/// `PlotOptions::color_scheme`: shades of blue, dark to light.
+pub const PLOT_SCHEME_MONOCHROME: u8 = 3;
 
-    #[test]
-    fn test_null_path() {
-        let xs: Vec<f64> = vec![1.0, 2.0];
-        let ys: Vec<f64> = vec![1.0, 2.0];
-        let opt = PlotOptions {
-            width: 800,
-            height: 600,
-            marker_radius: 5,
-            auto_range: 1,
-            x_min: 0.0,
-            x_max: 0.0,
-            y_min: 0.0,
-            y_max: 0.0,
-        };
+/// `PlotOptions::legend_position`: no legend.
+pub const PLOT_LEGEND_NONE: u8 = 0;
+/// `PlotOptions::legend_position`: upper-right corner.
+pub const PLOT_LEGEND_UPPER_RIGHT: u8 = 1;
+/// `PlotOptions::legend_position`: upper-left corner.
+pub const PLOT_LEGEND_UPPER_LEFT: u8 = 2;
+/// `PlotOptions::legend_position`: lower-right corner.
+pub const PLOT_LEGEND_LOWER_RIGHT: u8 = 3;
+/// `PlotOptions::legend_position`: lower-left corner.
+pub const PLOT_LEGEND_LOWER_LEFT: u8 = 4;
+/// `PlotOptions::legend_position`: the corner whose quadrant of the plot holds the fewest points.
+pub const PLOT_LEGEND_AUTO: u8 = 5;
 
-        let result = unsafe { plot_scatter_png(std::ptr::null(), xs.as_ptr(), ys.as_ptr(), xs.len(), opt) };
+// Palettes for `color_scheme`, as packed ARGB
+const PALETTE_TABLEAU10: [u32; 10] = [
+    0xFF1F77B4, 0xFFFF7F0E, 0xFF2CA02C, 0xFFD62728, 0xFF9467BD, 0xFF8C564B, 0xFFE377C2, 0xFF7F7F7F, 0xFFBCBD22,
+    0xFF17BECF,
+];
+const PALETTE_SET1: [u32; 9] = [
+    0xFFE41A1C, 0xFF377EB8, 0xFF4DAF4A, 0xFF984EA3, 0xFFFF7F00, 0xFFFFFF33, 0xFFA65628, 0xFFF781BF, 0xFF999999,
+];
+const PALETTE_PASTEL: [u32; 9] = [
+    0xFFFBB4AE, 0xFFB3CDE3, 0xFFCCEBC5, 0xFFDECBE4, 0xFFFED9A6, 0xFFFFFFCC, 0xFFE5D8BD, 0xFFFDDAEC, 0xFFF2F2F2,
+];
+const PALETTE_MONOCHROME: [u32; 6] = [0xFF08306B, 0xFF08519C, 0xFF2171B5, 0xFF4292C6, 0xFF6BAED6, 0xFF9ECAE1];
 
-        assert_eq!(result, 1, "Expected failure for NULL path");
-        let err = plot_last_error_message();
-        assert!(!err.is_null());
-    }
+/// `PlotOptions::line_mode`: straight segments between points.
+pub const PLOT_LINE_LINEAR: u8 = 0;
+/// `PlotOptions::line_mode`: hold each value until the next X, then jump.
+pub const PLOT_LINE_STEP_RIGHT: u8 = 1;
+/// `PlotOptions::line_mode`: jump to each value first, then hold it back to the previous X.
+pub const PLOT_LINE_STEP_LEFT: u8 = 2;
+/// `PlotOptions::line_mode`: smooth curve through the points that does not overshoot
+/// between them (Fritsch-Carlson). X values must be strictly increasing.
+pub const PLOT_LINE_MONOTONE_CUBIC: u8 = 3;
 
-    #[test]
-    fn test_zero_count() {
-        let path = CString::new("/tmp/test_zero.png").unwrap();
-        let xs: Vec<f64> = vec![];
-        let ys: Vec<f64> = vec![];
-        let opt = PlotOptions {
-            width: 800,
-            height: 600,
-            marker_radius: 5,
-            auto_range: 1,
-            x_min: 0.0,
-            x_max: 0.0,
-            y_min: 0.0,
-            y_max: 0.0,
-        };
+/// `PlotOptions::auto_range_mode`: data extent plus 2% padding.
+pub const PLOT_RANGE_PADDED: u8 = 0;
+/// `PlotOptions::auto_range_mode`: exactly the data extent.
+pub const PLOT_RANGE_EXACT: u8 = 1;
+/// `PlotOptions::auto_range_mode`: data extent widened to nice round numbers.
+pub const PLOT_RANGE_NICE: u8 = 2;
 
-        let result = unsafe { plot_scatter_png(path.as_ptr(), xs.as_ptr(), ys.as_ptr(), 0, opt) };
+/// `PlotOptions::line_dash_pattern` and `SeriesStyle::line_dash`: continuous line.
+pub const PLOT_DASH_SOLID: u8 = 0;
+/// `PlotOptions::line_dash_pattern`: 8 pixels on, 4 pixels off.
+pub const PLOT_DASH_DASHED: u8 = 1;
+/// `PlotOptions::line_dash_pattern`: 2 pixels on, 4 pixels off.
+pub const PLOT_DASH_DOTTED: u8 = 2;
+/// `PlotOptions::line_dash_pattern`: alternating dashes and dots.
+pub const PLOT_DASH_DASH_DOT: u8 = 3;
 
-        assert_eq!(result, 1, "Expected failure for zero count");
-    }
+/// `PlotOptions::title_font_style` and `axis_desc_font_style`: regular text.
+pub const PLOT_FONT_NORMAL: u8 = 0;
+/// `PlotOptions::title_font_style` and `axis_desc_font_style`: bold text.
+pub const PLOT_FONT_BOLD: u8 = 1;
+/// `PlotOptions::title_font_style` and `axis_desc_font_style`: italic text.
+pub const PLOT_FONT_ITALIC: u8 = 2;
+/// `PlotOptions::title_font_style` and `axis_desc_font_style`: oblique text.
+pub const PLOT_FONT_OBLIQUE: u8 = 3;
 
-    #[test]
-    fn test_explicit_range() {
-        let path = CString::new("/tmp/test_explicit.png").unwrap();
+/// `CompositeLayer::layer_type`: the layer data is a `ScatterLayer`.
+pub const PLOT_LAYER_SCATTER: u8 = 0;
+/// `CompositeLayer::layer_type`: the layer data is a `HeatmapLayer`.
+pub const PLOT_LAYER_HEATMAP: u8 = 1;
+/// `CompositeLayer::layer_type`: the layer data is a `ReferenceLine`.
+pub const PLOT_LAYER_REFERENCE_LINE: u8 = 2;
+
+/// `ReferenceLine::orientation`: a horizontal line at a Y value.
+pub const PLOT_REFERENCE_HORIZONTAL: u8 = 0;
+/// `ReferenceLine::orientation`: a vertical line at an X value.
+pub const PLOT_REFERENCE_VERTICAL: u8 = 1;
+
+/// `plot_last_error_code()`: no error is stored.
+pub const PLOT_ERR_NONE: i32 = 0;
+/// `plot_last_error_code()`: an error without a more specific code.
+pub const PLOT_ERR_OTHER: i32 = 1;
+/// `plot_last_error_code()`: a NaN coordinate was rejected (`nan_policy == 0`).
+pub const PLOT_ERR_NAN_VALUE: i32 = 2;
+/// `plot_last_error_code()`: an infinite coordinate was rejected (`nan_policy == 0`).
+pub const PLOT_ERR_INFINITE_VALUE: i32 = 3;
+/// `plot_last_error_code()`: the library panicked internally.
+pub const PLOT_ERR_INTERNAL_PANIC: i32 = 4;
+
+// Message prefixes of the errors that have their own code
+const NAN_ERROR_PREFIX: &str = "NaN ";
+const INFINITE_ERROR_PREFIX: &str = "Infinite ";
+const PANIC_ERROR_PREFIX: &str = "Internal panic";
+
+/// Category of a stored error, reported by `plot_last_error_code()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ErrorKind {
+    /// No more specific category
+    Other,
+    /// A NaN value was rejected
+    NanValue,
+    /// An infinite value was rejected
+    InfiniteValue,
+    /// A panic was caught
+    InternalPanic,
+}
+
+impl ErrorKind {
+    /// Returns the `PLOT_ERR_*` code of the kind.
+    fn code(self) -> i32 {
+        match self {
+            ErrorKind::Other => PLOT_ERR_OTHER,
+            ErrorKind::NanValue => PLOT_ERR_NAN_VALUE,
+            ErrorKind::InfiniteValue => PLOT_ERR_INFINITE_VALUE,
+            ErrorKind::InternalPanic => PLOT_ERR_INTERNAL_PANIC,
+        }
+    }
+}
+
+/// Builds the error for rejecting the non-finite `values` of point `i` (`nan_policy == 0`),
+/// such as "NaN coordinate at index 3".
+///
+/// NaN takes precedence over infinity. The kind is recorded for the running FFI call, so
+/// `plot_last_error_code()` reports it even when the message gets a context prefix.
+fn non_finite_error(values: &[f64], what: &str, i: usize) -> String {
+    let (kind, prefix) = if values.iter().any(|v| v.is_nan()) {
+        (ErrorKind::NanValue, NAN_ERROR_PREFIX)
+    } else {
+        (ErrorKind::InfiniteValue, INFINITE_ERROR_PREFIX)
+    };
+    ERROR_KIND.set(kind);
+    format!("{}{} at index {}", prefix, what, i)
+}
+
+/// Tick label format: Plotters' default formatting.
+pub const PLOT_TICK_DEFAULT: u8 = 0;
+/// Tick label format: rounded to an integer.
+pub const PLOT_TICK_INTEGER: u8 = 1;
+/// Tick label format: two decimal places.
+pub const PLOT_TICK_FIXED2: u8 = 2;
+/// Tick label format: four decimal places.
+pub const PLOT_TICK_FIXED4: u8 = 3;
+/// Tick label format: SI prefixes (n, µ, m, k, M, G, T), e.g. `"1.2k"`.
+pub const PLOT_TICK_SI: u8 = 4;
+
+// Default chart layout in pixels
+const DEFAULT_MARGIN: u32 = 10;
+const DEFAULT_X_LABEL_AREA_SIZE: u32 = 40;
+const DEFAULT_Y_LABEL_AREA_SIZE: u32 = 50;
+const TITLE_AREA_SIZE: u32 = 30;
+
+// Number of labelled ticks Plotters draws per axis by default
+const DEFAULT_LABEL_COUNT: usize = 11;
+// Fewest X labels automatic label skipping reduces to
+const MIN_LABEL_COUNT: usize = 2;
+// Approximate width of a tick label character as a fraction of the font size
+const LABEL_CHAR_WIDTH: f64 = 0.6;
+// Distance between the axis and manually drawn tick labels in pixels
+const LABEL_GAP: i32 = 8;
+// Length in pixels of the tick marks drawn for explicit tick positions
+const TICK_MARK_SIZE: i32 = 5;
+// Polar charts: default ring count and the margin reserved for angle labels
+const DEFAULT_POLAR_GRID_RINGS: u32 = 5;
+const POLAR_LABEL_MARGIN: u32 = 30;
+
+/// Pixel layout of the chart inside the image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ChartLayout {
+    margin_top: u32,
+    margin_bottom: u32,
+    margin_left: u32,
+    margin_right: u32,
+    x_label_area_size: u32,
+    y_label_area_size: u32,
+}
+
+impl ChartLayout {
+    /// Width of the plotting area (excluding margins and label areas).
+    fn plot_width(&self, image_width: u32) -> u32 {
+        image_width.saturating_sub(self.margin_left + self.margin_right + self.y_label_area_size)
+    }
+
+    /// Height of the plotting area (excluding margins and label areas).
+    fn plot_height(&self, image_height: u32) -> u32 {
+        image_height.saturating_sub(self.margin_top + self.margin_bottom + self.x_label_area_size)
+    }
+}
+
+/// Validates `PlotOptions::plot_area_fraction`: 0 or a fraction in (0, 1].
+fn check_plot_area_fraction(fraction: f64) -> Result<(), String> {
+    if fraction == 0.0 || (fraction > 0.0 && fraction <= 1.0) {
+        Ok(())
+    } else {
+        Err(format!("Plot area fraction must be in (0, 1], got {}", fraction))
+    }
+}
+
+/// Computes the chart layout, enlarging the margins when the aspect ratio is locked.
+fn compute_layout(opt: &PlotOptions, x_range: f64, y_range: f64) -> ChartLayout {
+    let mut layout = ChartLayout {
+        margin_top: DEFAULT_MARGIN,
+        margin_bottom: DEFAULT_MARGIN,
+        margin_left: DEFAULT_MARGIN,
+        margin_right: DEFAULT_MARGIN,
+        x_label_area_size: if opt.x_label_area_size == 0 {
+            DEFAULT_X_LABEL_AREA_SIZE
+        } else {
+            opt.x_label_area_size
+        },
+        y_label_area_size: if opt.y_label_area_size == 0 {
+            DEFAULT_Y_LABEL_AREA_SIZE
+        } else {
+            opt.y_label_area_size
+        },
+    };
+
+    if opt.title[0] != 0 {
+        layout.margin_top += TITLE_AREA_SIZE;
+    }
+
+    if opt.plot_area_fraction > 0.0 && opt.plot_area_fraction <= 1.0 {
+        let side = |dimension: u32| ((1.0 - opt.plot_area_fraction) / 2.0 * dimension as f64).round() as u32;
+        // The label area keeps its share of the axis side
+        let split = |side: u32, label_area: u32, margin: u32| {
+            let label = (side as u64 * label_area as u64 / (label_area + margin).max(1) as u64) as u32;
+            (label, side - label)
+        };
+        let (side_x, side_y) = (side(opt.width), side(opt.height));
+        (layout.y_label_area_size, layout.margin_left) = split(side_x, layout.y_label_area_size, DEFAULT_MARGIN);
+        (layout.x_label_area_size, layout.margin_bottom) = split(side_y, layout.x_label_area_size, DEFAULT_MARGIN);
+        layout.margin_right = side_x;
+        // The title still needs its own space
+        layout.margin_top = if opt.title[0] != 0 {
+            side_y.max(TITLE_AREA_SIZE)
+        } else {
+            side_y
+        };
+    }
+
+    if opt.lock_aspect_ratio != 0 {
+        let area_w = layout.plot_width(opt.width);
+        let area_h = layout.plot_height(opt.height);
+
+        // Use the smaller pixels-per-unit scale for both axes
+        let scale = (area_w as f64 / x_range).min(area_h as f64 / y_range);
+        let extra_w = area_w.saturating_sub((x_range * scale).round() as u32);
+        let extra_h = area_h.saturating_sub((y_range * scale).round() as u32);
+
+        layout.margin_left += extra_w / 2;
+        layout.margin_right += extra_w - extra_w / 2;
+        layout.margin_top += extra_h / 2;
+        layout.margin_bottom += extra_h - extra_h / 2;
+    }
+
+    layout
+}
+
+/// Tick label orientation.
+#[derive(Clone)]
+enum LabelRotation {
+    /// Rotation handled natively by Plotters
+    Native(FontTransform),
+    /// Diagonal rotation in degrees clockwise, drawn manually
+    Diagonal(f64),
+}
+
+/// Validates a label rotation given in degrees clockwise.
+fn parse_label_rotation(degrees: i32) -> Result<LabelRotation, String> {
+    match degrees {
+        0 => Ok(LabelRotation::Native(FontTransform::None)),
+        90 => Ok(LabelRotation::Native(FontTransform::Rotate90)),
+        -90 => Ok(LabelRotation::Native(FontTransform::Rotate270)),
+        45 | -45 => Ok(LabelRotation::Diagonal(degrees as f64)),
+        _ => Err("Unsupported label rotation: must be 0, ±45, or ±90".to_string()),
+    }
+}
+
+/// Converts a packed ARGB value (0xAARRGGBB) into a Plotters color.
+fn color_from_argb(argb: u32) -> RGBAColor {
+    let [a, r, g, b] = argb.to_be_bytes();
+    RGBAColor(r, g, b, a as f64 / 255.0)
+}
+
+/// Returns the palette selected by a `PLOT_SCHEME_*` value.
+fn palette(scheme: u8) -> Result<&'static [u32], String> {
+    match scheme {
+        PLOT_SCHEME_TABLEAU10 => Ok(&PALETTE_TABLEAU10),
+        PLOT_SCHEME_SET1 => Ok(&PALETTE_SET1),
+        PLOT_SCHEME_PASTEL => Ok(&PALETTE_PASTEL),
+        PLOT_SCHEME_MONOCHROME => Ok(&PALETTE_MONOCHROME),
+        _ => Err(format!("Unsupported color scheme: {}", scheme)),
+    }
+}
+
+/// Returns the style of the axis lines and tick marks, or `None` to keep the Plotters default.
+fn axis_line_style(opt: &PlotOptions) -> Option<ShapeStyle> {
+    if opt.border_color == PLOT_BORDER_NONE {
+        return Some(TRANSPARENT.stroke_width(0));
+    }
+    if opt.border_width == 0 && opt.axis_color == 0 {
+        return None;
+    }
+    let color = if opt.axis_color != 0 {
+        color_from_argb(opt.axis_color)
+    } else if opt.border_color != 0 {
+        color_from_argb(opt.border_color)
+    } else {
+        BLACK.to_rgba()
+    };
+    Some(color.stroke_width(opt.border_width.max(1)))
+}
+
+/// Reads a NUL-terminated string from a fixed-size `PlotOptions` field.
+///
+/// A field without a NUL terminator is read in full.
+fn fixed_str(field: &[c_char]) -> Result<&str, String> {
+    // SAFETY: c_char and u8 have the same size and alignment
+    let bytes = unsafe { std::slice::from_raw_parts(field.as_ptr() as *const u8, field.len()) };
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    std::str::from_utf8(&bytes[..len]).map_err(|_| "String option is not valid UTF-8".to_string())
+}
+
+/// Formats a tick value with one of the `PLOT_TICK_*` formats.
+///
+/// Returns `None` for `PLOT_TICK_DEFAULT`, leaving formatting to Plotters.
+fn format_tick(value: f64, format: u8) -> Result<Option<String>, String> {
+    let text = match format {
+        PLOT_TICK_DEFAULT => return Ok(None),
+        PLOT_TICK_INTEGER => (value.round() as i64).to_string(),
+        PLOT_TICK_FIXED2 => format!("{:.2}", value),
+        PLOT_TICK_FIXED4 => format!("{:.4}", value),
+        PLOT_TICK_SI => format_si(value),
+        _ => return Err(format!("Unsupported tick format: {}", format)),
+    };
+    Ok(Some(text))
+}
+
+/// Formats a value with an SI prefix and up to two decimals, e.g. `1500.0` -> `"1.5k"`.
+fn format_si(value: f64) -> String {
+    const PREFIXES: [(f64, &str); 8] = [
+        (1e12, "T"),
+        (1e9, "G"),
+        (1e6, "M"),
+        (1e3, "k"),
+        (1.0, ""),
+        (1e-3, "m"),
+        (1e-6, "µ"),
+        (1e-9, "n"),
+    ];
+    if value == 0.0 || !value.is_finite() {
+        return value.to_string();
+    }
+    let magnitude = value.abs();
+    // Values below the smallest prefix still use nano
+    let (scale, prefix) = PREFIXES
+        .iter()
+        .copied()
+        .find(|&(scale, _)| magnitude >= scale)
+        .unwrap_or(PREFIXES[PREFIXES.len() - 1]);
+
+    let scaled = format!("{:.2}", value / scale);
+    let scaled = scaled.trim_end_matches('0').trim_end_matches('.');
+    format!("{}{}", scaled, prefix)
+}
+
+/// Copies `text` into a fixed-size string option, truncating at a character boundary.
+fn write_fixed_str(field: &mut [c_char], text: &str) {
+    let mut len = text.len().min(field.len() - 1);
+    while !text.is_char_boundary(len) {
+        len -= 1;
+    }
+    for (dst, &src) in field.iter_mut().zip(&text.as_bytes()[..len]) {
+        *dst = src as c_char;
+    }
+    field[len] = 0;
+}
+
+/// How X axis tick values are turned into label text.
+#[derive(Clone, Copy)]
+enum XLabels<'a> {
+    /// Numeric labels, honouring `x_scientific`
+    Numeric,
+    /// Unix timestamps in seconds, formatted with parsed strftime items
+    Timestamp(&'a [chrono::format::Item<'a>]),
+    /// Category names at the integer positions 0, 1, 2, ...
+    Categories(&'a [&'a str]),
+}
+
+/// Formats a Unix timestamp in seconds as UTC using the given strftime items.
+fn format_timestamp(value: f64, items: &[chrono::format::Item<'_>]) -> String {
+    let secs = value.floor();
+    let nanos = ((value - secs) * 1e9) as u32;
+    match chrono::DateTime::from_timestamp(secs as i64, nanos) {
+        Some(time) => time.naive_utc().format_with_items(items.iter()).to_string(),
+        None => format!("{}", value),
+    }
+}
+
+/// Formats a tick value in scientific notation with two decimal places.
+fn format_scientific(value: f64, keep_trailing_zeros: bool) -> String {
+    let formatted = format!("{:.2e}", value);
+    if keep_trailing_zeros {
+        return formatted;
+    }
+    match formatted.split_once('e') {
+        Some((mantissa, exponent)) if mantissa.contains('.') => {
+            let mantissa = mantissa.trim_end_matches('0').trim_end_matches('.');
+            format!("{}e{}", mantissa, exponent)
+        },
+        _ => formatted,
+    }
+}
+
+/// Draws text rotated by `angle` degrees clockwise, with its left (or right) middle at `anchor`.
+///
+/// Plotters only rotates text in steps of 90 degrees, so the text is rasterized into a
+/// scratch buffer and resampled onto the target area.
+fn draw_rotated_text<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    text: &str,
+    style: &TextStyle,
+    anchor: (i32, i32),
+    angle: f64,
+    anchor_at_end: bool,
+) -> Result<(), String> {
+    let (text_w, text_h) = area
+        .estimate_text_size(text, style)
+        .map_err(|e| drawing_error("Failed to measure label", e))?;
+    let pad = 2;
+    let (buf_w, buf_h) = (text_w + 2 * pad, text_h + 2 * pad);
+
+    // Rasterize the text black on white
+    let mut buffer = vec![255u8; (buf_w * buf_h * 3) as usize];
+    {
+        let scratch = BitMapBackend::with_buffer(&mut buffer, (buf_w, buf_h)).into_drawing_area();
+        scratch
+            .draw_text(text, style, (pad as i32, pad as i32))
+            .map_err(|e| drawing_error("Failed to draw label", e))?;
+        scratch
+            .present()
+            .map_err(|e| drawing_error("Failed to draw label", e))?;
+    }
+
+    let ref_x = if anchor_at_end {
+        (pad + text_w) as f64
+    } else {
+        pad as f64
+    };
+    let ref_y = (pad + text_h / 2) as f64;
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let rotate = |dx: f64, dy: f64| (dx * cos - dy * sin, dx * sin + dy * cos);
+
+    // Bounding box of the rotated buffer relative to the anchor
+    let (mut min_x, mut max_x, mut min_y, mut max_y) = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+    for (cx, cy) in [
+        (0.0, 0.0),
+        (buf_w as f64, 0.0),
+        (0.0, buf_h as f64),
+        (buf_w as f64, buf_h as f64),
+    ] {
+        let (rx, ry) = rotate(cx - ref_x, cy - ref_y);
+        min_x = min_x.min(rx);
+        max_x = max_x.max(rx);
+        min_y = min_y.min(ry);
+        max_y = max_y.max(ry);
+    }
+
+    // Inverse-map every target pixel into the scratch buffer
+    for ty in min_y.floor() as i32..=max_y.ceil() as i32 {
+        for tx in min_x.floor() as i32..=max_x.ceil() as i32 {
+            let (dx, dy) = (tx as f64, ty as f64);
+            let sx = ref_x + dx * cos + dy * sin;
+            let sy = ref_y - dx * sin + dy * cos;
+            if sx < 0.0 || sy < 0.0 || sx >= buf_w as f64 || sy >= buf_h as f64 {
+                continue;
+            }
+            let idx = ((sy as u32 * buf_w + sx as u32) * 3) as usize;
+            let coverage = 255 - buffer[idx];
+            if coverage > 0 {
+                let (r, g, b) = style.color.rgb;
+                area.draw_pixel(
+                    (anchor.0 + tx, anchor.1 + ty),
+                    &RGBAColor(r, g, b, coverage as f64 / 255.0),
+                )
+                .map_err(|e| drawing_error("Failed to draw label", e))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Locks the last error storage, recovering from poisoning.
+///
+/// The stored value is a plain `Option<(CString, ErrorKind)>` that is always valid, so a
+/// panic while holding the lock cannot leave it in an inconsistent state.
+fn lock_last_error() -> MutexGuard<'static, Option<(CString, ErrorKind)>> {
+    LAST_ERROR.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Poisons the last error storage by panicking on a thread that holds its lock.
+///
+/// Only for testing the recovery in `lock_last_error`; returns whether the mutex is
+/// poisoned afterwards.
+#[doc(hidden)]
+pub fn poison_last_error() -> bool {
+    let result = std::thread::spawn(|| {
+        let _guard = LAST_ERROR.lock();
+        panic!("Poisoning LAST_ERROR");
+    })
+    .join();
+    result.is_err() && LAST_ERROR.is_poisoned()
+}
+
+/// Stores an error message and its kind for later retrieval via plot_take_error().
+fn set_error(msg: String, kind: ErrorKind) {
+    // Convert to CString, replacing any interior NUL bytes
+    let sanitized = msg.replace('\0', "\\0");
+    *lock_last_error() = CString::new(sanitized).ok().map(|msg| (msg, kind));
+}
+
+/// Clears the stored error message.
+fn clear_error() {
+    *lock_last_error() = None;
+}
+
+/// Locks the last warning storage, recovering from poisoning.
+fn lock_last_warning() -> MutexGuard<'static, Option<CString>> {
+    LAST_WARNING.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Stores a warning message for later retrieval via plot_last_warning_message().
+fn set_warning(msg: String) {
+    let sanitized = msg.replace('\0', "\\0");
+    *lock_last_warning() = CString::new(sanitized).ok();
+}
+
+/// Clears the stored warning message.
+fn clear_warning() {
+    *lock_last_warning() = None;
+}
+
+/// Locks the font table, recovering from a poisoned mutex.
+fn lock_fonts() -> MutexGuard<'static, HashMap<String, FontStyle>> {
+    FONTS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Locks the base directory for relative paths, recovering from a poisoned mutex.
+fn lock_base_directory() -> MutexGuard<'static, Option<PathBuf>> {
+    BASE_DIRECTORY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Locks the global option defaults, recovering from a poisoned mutex.
+fn lock_global_options() -> MutexGuard<'static, HashMap<String, u64>> {
+    GLOBAL_OPTIONS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Validates and stores a process-wide default for one option.
+fn set_global_option(key: &str, value: u64) -> Result<(), String> {
+    if !GLOBAL_OPTION_KEYS.contains(&key) {
+        return Err(format!(
+            "Unknown global option: {} (expected one of {})",
+            key,
+            GLOBAL_OPTION_KEYS.join(", ")
+        ));
+    }
+    if u32::try_from(value).is_err() || (key == "color_scheme" && u8::try_from(value).is_err()) {
+        return Err(format!("Value {} is out of range for global option {}", value, key));
+    }
+    match key {
+        "width" | "height" if value == 0 => {
+            return Err(format!("Global option {} must be greater than zero", key));
+        },
+        "color_scheme" => {
+            palette(value as u8)?;
+        },
+        _ => {},
+    }
+    lock_global_options().insert(key.to_string(), value);
+    Ok(())
+}
+
+/// Replaces zero-valued options with the process-wide defaults that have been set.
+///
+/// `color_scheme` 0 is Tableau 10, so a global color scheme also replaces that value.
+fn with_global_defaults(mut opt: PlotOptions) -> PlotOptions {
+    let globals = lock_global_options();
+    if globals.is_empty() {
+        return opt;
+    }
+    let get = |key: &str| globals.get(key).map(|&v| v as u32);
+    for (field, key) in [
+        (&mut opt.width, "width"),
+        (&mut opt.height, "height"),
+        (&mut opt.marker_radius, "marker_radius"),
+        (&mut opt.dpi, "dpi"),
+        (&mut opt.label_font_size, "label_font_size"),
+    ] {
+        if *field == 0 {
+            *field = get(key).unwrap_or(0);
+        }
+    }
+    if opt.color_scheme == 0 {
+        opt.color_scheme = get("color_scheme").unwrap_or(0) as u8;
+    }
+    opt
+}
+
+/// Like `with_global_defaults`, for the options of one render.
+fn with_render_defaults<'a>(opt: impl Into<RenderOptions<'a>>) -> RenderOptions<'a> {
+    let opt = opt.into();
+    opt.with_options(with_global_defaults(opt.opt))
+}
+
+/// Colors and sizes of a named chart theme, applied by `plot_options_default()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Theme {
+    background_color: u32,
+    text_color: u32,
+    grid_color: u32,
+    axis_color: u32,
+    border_color: u32,
+    label_font_size: u32,
+    hide_grid: u8,
+}
+
+impl Theme {
+    /// Looks up a theme by name; `"default"` is the built-in look and has no theme.
+    fn named(name: &str) -> Result<Option<Self>, String> {
+        let base = Self {
+            background_color: 0,
+            text_color: 0,
+            grid_color: 0,
+            axis_color: 0,
+            border_color: 0,
+            label_font_size: 0,
+            hide_grid: 0,
+        };
+        match name {
+            "default" => Ok(None),
+            "dark" => Ok(Some(Self {
+                background_color: 0xFF1E1E1E,
+                text_color: 0xFFFFFFFF,
+                axis_color: 0xFFFFFFFF,
+                hide_grid: 1,
+                ..base
+            })),
+            "minimal" => Ok(Some(Self {
+                text_color: 0xFF404040,
+                axis_color: 0xFF808080,
+                hide_grid: 1,
+                ..base
+            })),
+            "scientific" => Ok(Some(Self {
+                grid_color: 0x26000000,
+                axis_color: 0xFF000000,
+                label_font_size: 16,
+                ..base
+            })),
+            "seaborn" => Ok(Some(Self {
+                background_color: 0xFFEAEAF2,
+                text_color: 0xFF262626,
+                grid_color: 0xFFFFFFFF,
+                border_color: PLOT_BORDER_NONE,
+                ..base
+            })),
+            _ => Err(format!(
+                "Unknown theme: {:?} (expected default, dark, minimal, scientific or seaborn)",
+                name
+            )),
+        }
+    }
+
+    /// Sets the theme's colors and sizes on `opt`.
+    fn apply(&self, opt: &mut PlotOptions) {
+        opt.background_color = self.background_color;
+        opt.text_color = self.text_color;
+        opt.grid_color = self.grid_color;
+        opt.axis_color = self.axis_color;
+        opt.border_color = self.border_color;
+        opt.hide_grid = self.hide_grid;
+        if self.label_font_size != 0 {
+            opt.label_font_size = self.label_font_size;
+        }
+    }
+}
+
+/// Locks the active theme, recovering from a poisoned mutex.
+fn lock_theme() -> MutexGuard<'static, Option<Theme>> {
+    THEME.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Returns the background color, white unless `background_color` is set.
+fn background_color(opt: &PlotOptions) -> RGBAColor {
+    if opt.background_color == 0 {
+        WHITE.to_rgba()
+    } else {
+        color_from_argb(opt.background_color)
+    }
+}
+
+/// Returns the color of titles and labels, black unless `text_color` is set.
+fn text_color(opt: &PlotOptions) -> RGBAColor {
+    if opt.text_color == 0 {
+        BLACK.to_rgba()
+    } else {
+        color_from_argb(opt.text_color)
+    }
+}
+
+/// Returns the tick label font size in pixels.
+fn label_font_size(opt: &PlotOptions) -> f64 {
+    if opt.label_font_size == 0 {
+        DEFAULT_LABEL_FONT_SIZE as f64
+    } else {
+        opt.label_font_size as f64
+    }
+}
+
+/// Ensures the bundled font is registered with Plotters.
+///
+/// With `allow_fallback`, a system font is registered in its place if the bundled font
+/// is unusable. Once that has happened, every call reports it as a warning.
+fn ensure_font_registered(allow_fallback: bool) -> Result<(), String> {
+    register_fonts_once(|| register_default_font(allow_fallback))
+}
+
+/// Registers `bytes` in place of the bundled font, falling back to `fallback_paths`.
+///
+/// Only for testing the fallback warning; it has no effect once the bundled font is
+/// registered, so it must run before any rendering call.
+#[doc(hidden)]
+pub fn register_default_font_with_fallback(bytes: &'static [u8], fallback_paths: &[&str]) -> Result<(), String> {
+    register_fonts_once(|| register_font_with_fallback(DEFAULT_FONT, bytes, fallback_paths))
+}
+
+/// Registers the default font with `register_default` unless that already happened, and
+/// repeats its fallback warning.
+fn register_fonts_once(register_default: impl FnOnce() -> Result<Option<String>, String>) -> Result<(), String> {
+    let mut fonts = lock_fonts();
+    if !fonts.contains_key(DEFAULT_FONT) {
+        let warning = register_default()?;
+        fonts.insert(DEFAULT_FONT.to_string(), FontStyle::Normal);
+        // Without the bold face, bold text in the bundled font uses the regular face
+        #[cfg(feature = "embedded-font")]
+        if register_font(DEFAULT_BOLD_FONT, FontStyle::Bold, BOLD_FONT_BYTES).is_ok() {
+            fonts.insert(DEFAULT_BOLD_FONT.to_string(), FontStyle::Bold);
+        }
+        *FONT_FALLBACK_WARNING.lock().unwrap_or_else(|e| e.into_inner()) = warning;
+    }
+    drop(fonts);
+
+    if let Some(warning) = FONT_FALLBACK_WARNING.lock().unwrap_or_else(|e| e.into_inner()).clone() {
+        set_warning(warning);
+    }
+    Ok(())
+}
+
+/// Registers the bundled font as `DEFAULT_FONT`, returning a warning if a system font
+/// was used instead.
+#[cfg(feature = "embedded-font")]
+fn register_default_font(allow_fallback: bool) -> Result<Option<String>, String> {
+    let fallback_paths = if allow_fallback { SYSTEM_FONT_PATHS } else { &[] };
+    register_font_with_fallback(DEFAULT_FONT, FONT_BYTES, fallback_paths)
+}
+
+/// Registers the first usable system font as `DEFAULT_FONT`, since no font is bundled.
+#[cfg(not(feature = "embedded-font"))]
+fn register_default_font(_allow_fallback: bool) -> Result<Option<String>, String> {
+    register_font_with_fallback(DEFAULT_FONT, &[], SYSTEM_FONT_PATHS)
+        .map(|_| None)
+        .map_err(|_| "No usable system font found; build with the embedded-font feature".to_string())
+}
+
+/// Registers `bytes` as the normal style of `name`, or else the first usable font file in `fallback_paths`.
+///
+/// Returns a warning naming the fallback file if one was used.
+fn register_font_with_fallback(
+    name: &str,
+    bytes: &'static [u8],
+    fallback_paths: &[&str],
+) -> Result<Option<String>, String> {
+    if register_font(name, FontStyle::Normal, bytes).is_ok() {
+        return Ok(None);
+    }
+    for path in fallback_paths {
+        let Ok(data) = std::fs::read(path) else {
+            continue;
+        };
+        // Plotters keeps a reference to the font data for the rest of the process
+        let data: &'static [u8] = Box::leak(data.into_boxed_slice());
+        if register_font(name, FontStyle::Normal, data).is_ok() {
+            return Ok(Some(format!(
+                "Bundled font could not be loaded; using system font {}",
+                path
+            )));
+        }
+    }
+    Err("Failed to register bundled font: invalid font data".to_string())
+}
+
+/// Registers the font file at `path` with Plotters under `name`.
+#[doc(hidden)]
+pub fn register_font_file(name: &str, path: &str, style: u8) -> Result<(), String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read font file: {}", e))?;
+    register_font_bytes(name, bytes, style)
+}
+
+/// Registers TrueType/OpenType font data with Plotters under `name`.
+#[doc(hidden)]
+pub fn register_font_bytes(name: &str, bytes: Vec<u8>, style: u8) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Font name must not be empty".to_string());
+    }
+    let style = font_style(style)?;
+    ensure_font_registered(false)?;
+
+    // Plotters keeps a reference to the font data for the rest of the process
+    let bytes: &'static [u8] = Box::leak(bytes.into_boxed_slice());
+    register_font(name, style, bytes).map_err(|_| "Failed to register font: invalid font data".to_string())?;
+    lock_fonts().insert(name.to_string(), style);
+    Ok(())
+}
+
+/// Converts a `PLOT_FONT_*` style to its Plotters equivalent.
+fn font_style(style: u8) -> Result<FontStyle, String> {
+    match style {
+        PLOT_FONT_NORMAL => Ok(FontStyle::Normal),
+        PLOT_FONT_BOLD => Ok(FontStyle::Bold),
+        PLOT_FONT_ITALIC => Ok(FontStyle::Italic),
+        PLOT_FONT_OBLIQUE => Ok(FontStyle::Oblique),
+        _ => Err(format!("Unsupported font style: {}", style)),
+    }
+}
+
+/// Returns the font named by a fixed-size option, or the bundled font if it is empty.
+fn resolve_font(field: &[c_char], size: f64) -> Result<FontDesc<'_>, String> {
+    resolve_styled_font(field, size, PLOT_FONT_NORMAL)
+}
+
+/// Like `resolve_font`, in the `PLOT_FONT_*` style `style`.
+///
+/// Bold text in the bundled font uses the bundled bold face if it is registered. Plotters
+/// draws styles that were never registered for a family with its normal face, so fonts
+/// registered without a normal face keep their registered style.
+fn resolve_styled_font(field: &[c_char], size: f64, style: u8) -> Result<FontDesc<'_>, String> {
+    let name = match fixed_str(field)? {
+        "" => DEFAULT_FONT,
+        name => name,
+    };
+    let style = font_style(style)?;
+    let fonts = lock_fonts();
+    let registered = *fonts
+        .get(name)
+        .ok_or_else(|| format!("Font not registered: {}", name))?;
+    let (name, style) = match (style, registered) {
+        (FontStyle::Normal, _) => (name, registered),
+        (FontStyle::Bold, _) if name == DEFAULT_FONT && fonts.contains_key(DEFAULT_BOLD_FONT) => {
+            (DEFAULT_BOLD_FONT, FontStyle::Bold)
+        },
+        (_, FontStyle::Normal) => (name, style),
+        _ => (name, registered),
+    };
+    Ok(FontDesc::new(FontFamily::Name(name), size, style))
+}
+
+/// X and Y coordinates, borrowed from the caller or filtered into new buffers.
+type Coords<'a> = (Cow<'a, [f64]>, Cow<'a, [f64]>);
+
+/// Returns whether `opt.nan_policy` skips non-finite points instead of rejecting them.
+fn skips_non_finite(opt: &PlotOptions) -> Result<bool, String> {
+    match opt.nan_policy {
+        0 => Ok(false),
+        1 => Ok(true),
+        other => Err(format!("Unsupported NaN policy: {}", other)),
+    }
+}
+
+/// Applies `opt.nan_policy` to the data, returning the points to plot.
+fn apply_nan_policy<'a>(xs: &'a [f64], ys: &'a [f64], opt: &PlotOptions) -> Result<Coords<'a>, String> {
+    if !skips_non_finite(opt)? {
+        if let Some(i) = xs.iter().zip(ys).position(|(x, y)| !x.is_finite() || !y.is_finite()) {
+            return Err(non_finite_error(&[xs[i], ys[i]], "coordinate", i));
+        }
+        return Ok((Cow::Borrowed(xs), Cow::Borrowed(ys)));
+    }
+
+    let (xs, ys) = xs
+        .iter()
+        .zip(ys)
+        .filter(|(x, y)| x.is_finite() && y.is_finite())
+        .map(|(&x, &y)| (x, y))
+        .unzip();
+    Ok((Cow::Owned(xs), Cow::Owned(ys)))
+}
+
+/// Computes the axis ranges `(x_min, x_max, y_min, y_max)` from the data or the explicit options.
+fn compute_ranges(xs: &[f64], ys: &[f64], opt: &PlotOptions) -> Result<(f64, f64, f64, f64), String> {
+    if opt.auto_range != 0 {
+        // Auto-compute from data
+        let x_data_min = xs.iter().copied().fold(f64::INFINITY, f64::min);
+        let x_data_max = xs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let y_data_min = ys.iter().copied().fold(f64::INFINITY, f64::min);
+        let y_data_max = ys.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+        let (mut x_min, mut x_max) = auto_axis_range(x_data_min, x_data_max, opt.auto_range_mode)?;
+        let (mut y_min, mut y_max) = auto_axis_range(y_data_min, y_data_max, opt.auto_range_mode)?;
+        if opt.snap_range_to_ticks != 0 {
+            (x_min, x_max) = snap_to_ticks((x_min, x_max), (x_data_min, x_data_max), opt.x_tick_count);
+            (y_min, y_max) = snap_to_ticks((y_min, y_max), (y_data_min, y_data_max), opt.y_tick_count);
+        }
+        Ok((x_min, x_max, y_min, y_max))
+    } else {
+        // Use explicit ranges from options
+        if opt.x_min >= opt.x_max {
+            return Err(format!(
+                "Invalid X range: x_min ({}) must be less than x_max ({})",
+                opt.x_min, opt.x_max
+            ));
+        }
+        if opt.y_min >= opt.y_max {
+            return Err(format!(
+                "Invalid Y range: y_min ({}) must be less than y_max ({})",
+                opt.y_min, opt.y_max
+            ));
+        }
+        Ok((opt.x_min, opt.x_max, opt.y_min, opt.y_max))
+    }
+}
+
+/// Computes one axis range from the data extent according to the auto-range mode.
+fn auto_axis_range(data_min: f64, data_max: f64, mode: u8) -> Result<(f64, f64), String> {
+    // Handle case where all points have the same coordinate
+    if (data_max - data_min).abs() < f64::EPSILON {
+        return match mode {
+            PLOT_RANGE_PADDED | PLOT_RANGE_EXACT => Ok((data_min - 1.0, data_max + 1.0)),
+            PLOT_RANGE_NICE => Ok(nice_range(data_min - 1.0, data_max + 1.0)),
+            _ => Err(format!("Unsupported auto range mode: {}", mode)),
+        };
+    }
+
+    match mode {
+        PLOT_RANGE_PADDED => {
+            // 2% padding on both ends
+            let padding = (data_max - data_min) * 0.02;
+            Ok((data_min - padding, data_max + padding))
+        },
+        PLOT_RANGE_EXACT => Ok((data_min, data_max)),
+        PLOT_RANGE_NICE => Ok(nice_range(data_min, data_max)),
+        _ => Err(format!("Unsupported auto range mode: {}", mode)),
+    }
+}
+
+/// Moves the ends of an axis range out from the data to the nearest ticks.
+///
+/// The tick spacing is the one Plotters uses for `tick_count(tick_option)` ticks on
+/// `range`. The result always covers the data; if snapping leaves no extent (a single
+/// value on a tick), `range` is returned unchanged.
+fn snap_to_ticks(range: (f64, f64), (data_min, data_max): (f64, f64), tick_option: u32) -> (f64, f64) {
+    let ticks = RangedCoordf64::from(range.0..range.1).key_points(tick_count(tick_option));
+    let [first, second, ..] = ticks[..] else {
+        return range;
+    };
+    let step = second - first;
+    let snapped = ((data_min / step).floor() * step, (data_max / step).ceil() * step);
+    if snapped.0 < snapped.1 { snapped } else { range }
+}
+
+/// Computes `(x_min, x_max, y_min, y_max)` from the finite points, widened on both ends
+/// by `padding` times each axis extent.
+///
+/// Points with a NaN or infinite coordinate are skipped. An axis whose values are all
+/// equal spans one unit either side of them, as with auto-ranging.
+fn padded_data_range(xs: &[f64], ys: &[f64], padding: f64) -> Result<(f64, f64, f64, f64), String> {
+    if !padding.is_finite() || padding < 0.0 {
+        return Err(format!("Padding must be a non-negative finite number, got {}", padding));
+    }
+    let mut finite = xs
+        .iter()
+        .zip(ys)
+        .filter(|(x, y)| x.is_finite() && y.is_finite())
+        .peekable();
+    if finite.peek().is_none() {
+        return Err("No finite points to compute a range from".to_string());
+    }
+    let (mut x_min, mut x_max, mut y_min, mut y_max) =
+        (f64::INFINITY, f64::NEG_INFINITY, f64::INFINITY, f64::NEG_INFINITY);
+    for (&x, &y) in finite {
+        x_min = x_min.min(x);
+        x_max = x_max.max(x);
+        y_min = y_min.min(y);
+        y_max = y_max.max(y);
+    }
+    let pad = |min: f64, max: f64| {
+        if (max - min).abs() < f64::EPSILON {
+            (min - 1.0, max + 1.0)
+        } else {
+            let margin = (max - min) * padding;
+            (min - margin, max + margin)
+        }
+    };
+    let (x_min, x_max) = pad(x_min, x_max);
+    let (y_min, y_max) = pad(y_min, y_max);
+    Ok((x_min, x_max, y_min, y_max))
+}
+
+// Number of ticks the nice range is designed around
+const NICE_TICK_COUNT: f64 = 5.0;
+
+/// Widens `[min, max]` to the smallest range that starts and ends on a nice tick.
+///
+/// Uses Heckbert's loose labeling: the tick step is a 1, 2 or 5 multiple of a power of
+/// ten, chosen so the range spans roughly `NICE_TICK_COUNT` ticks.
+fn nice_range(min: f64, max: f64) -> (f64, f64) {
+    let extent = nice_number(max - min, false);
+    let step = nice_number(extent / (NICE_TICK_COUNT - 1.0), true);
+    ((min / step).floor() * step, (max / step).ceil() * step)
+}
+
+/// Returns a 1, 2, 5 or 10 multiple of a power of ten close to `value`.
+///
+/// With `round` the nearest such number is chosen, otherwise the next one up.
+fn nice_number(value: f64, round: bool) -> f64 {
+    let magnitude = 10f64.powf(value.log10().floor());
+    let fraction = value / magnitude;
+    let nice = if round {
+        match fraction {
+            f if f < 1.5 => 1.0,
+            f if f < 3.0 => 2.0,
+            f if f < 7.0 => 5.0,
+            _ => 10.0,
+        }
+    } else {
+        match fraction {
+            f if f <= 1.0 => 1.0,
+            f if f <= 2.0 => 2.0,
+            f if f <= 5.0 => 5.0,
+            _ => 10.0,
+        }
+    };
+    nice * magnitude
+}
+
+/// Internal implementation of scatter plot rendering.
+///
+/// This function is public for benchmarking purposes.
+#[doc(hidden)]
+pub fn plot_scatter_png_impl<'a>(
+    path: &str,
+    xs: &[f64],
+    ys: &[f64],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    plot_scatter_png_ex_impl(path, xs, ys, opt).map(|_| ())
+}
+
+/// Internal implementation of PNG rendering that also reports render statistics.
+#[doc(hidden)]
+pub fn plot_scatter_png_ex_impl<'a>(
+    path: &str,
+    xs: &[f64],
+    ys: &[f64],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<PlotStats, String> {
+    let start = std::time::Instant::now();
+    let (mut stats, _) = render_scatter_png(path, xs, ys, opt)?;
+    stats.render_time_us = elapsed_us(start);
+    Ok(stats)
+}
+
+/// Internal implementation of PNG rendering that reports the rendering time in microseconds.
+///
+/// Unlike `PlotStats::render_time_us`, the time excludes option and data validation.
+#[doc(hidden)]
+pub fn plot_scatter_png_timed_impl<'a>(
+    path: &str,
+    xs: &[f64],
+    ys: &[f64],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<u64, String> {
+    render_scatter_png(path, xs, ys, opt).map(|(_, rendered)| rendered.elapsed_us)
+}
+
+/// Renders a scatter plot to a PNG file, returning its statistics and the rendered image.
+fn render_scatter_png<'a>(
+    path: &str,
+    xs: &[f64],
+    ys: &[f64],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(PlotStats, RenderedPng), String> {
+    let mut opt = with_render_defaults(opt);
+    if opt.height == 0 {
+        opt.height = auto_height(xs, ys, &opt)?;
+    }
+    let mut stats = PlotStats::default();
+    let rendered = render_png_timed(path, &opt, (opt.width, opt.height), |root| {
+        stats = draw_scatter(root, xs, ys, &opt, XLabels::Numeric)?;
+        match opt.post_render {
+            Some(post_render) => post_render(root),
+            None => Ok(()),
+        }
+    })?;
+    if opt.write_metadata != 0
+        && let Err(e) = write_metadata(path, xs, ys, &opt, &stats, rendered.size)
+    {
+        set_warning(e);
+    }
+    Ok((stats, rendered))
+}
+
+/// Writes the size, axis ranges and number of valid points of the chart at `path` to
+/// `<path>.json`.
+fn write_metadata(
+    path: &str,
+    xs: &[f64],
+    ys: &[f64],
+    opt: &PlotOptions,
+    stats: &PlotStats,
+    (width, height): (u32, u32),
+) -> Result<(), String> {
+    let (xs, ys) = prepare_data(xs, ys, opt)?;
+    let (x_min, x_max, y_min, y_max) = compute_ranges(&xs, &ys, opt)?;
+    // The ranges are finite, so Rust's float formatting is valid JSON
+    let json = format!(
+        "{{\"width\": {}, \"height\": {}, \"x_min\": {:?}, \"x_max\": {:?}, \"y_min\": {:?}, \"y_max\": {:?}, \"n_points\": {}}}\n",
+        width,
+        height,
+        x_min,
+        x_max,
+        y_min,
+        y_max,
+        stats.points_rendered + stats.points_clipped
+    );
+    let metadata_path = format!("{}.json", path);
+    std::fs::write(&metadata_path, json).map_err(|e| format!("Failed to write metadata file {}: {}", metadata_path, e))
+}
+
+/// Computes where `render_scatter_png` puts the data area for these points and options.
+///
+/// Uses the same ranges and layout as the render, on an empty chart.
+fn plot_area_info(xs: &[f64], ys: &[f64], opt: PlotOptions) -> Result<PlotAreaInfo, String> {
+    let mut opt = with_global_defaults(opt);
+    if opt.height == 0 {
+        opt.height = auto_height(xs, ys, &opt)?;
+    }
+    let (xs, ys) = prepare_data(xs, ys, &opt)?;
+    let (x_min, x_max, y_min, y_max) = compute_ranges(&xs, &ys, &opt)?;
+    let fixed = PlotOptions {
+        auto_range: 0,
+        x_min,
+        x_max,
+        y_min,
+        y_max,
+        ..opt
+    };
+    let (x_pixels, y_pixels) = with_chart_coords(fixed, |chart| chart.plotting_area().get_pixel_range())?;
+    let span = |pixels: std::ops::Range<i32>| (pixels.end - pixels.start).max(0) as u32;
+    Ok(PlotAreaInfo {
+        chart_x: x_pixels.start.max(0) as u32 + opt.outer_padding,
+        chart_y: y_pixels.start.max(0) as u32 + opt.outer_padding,
+        chart_width: span(x_pixels),
+        chart_height: span(y_pixels),
+        x_min,
+        x_max,
+        y_min,
+        y_max,
+    })
+}
+
+/// Computes the image height that gives the axis ranges the aspect ratio of the data:
+/// `width * y_range / x_range`.
+fn auto_height(xs: &[f64], ys: &[f64], opt: &PlotOptions) -> Result<u32, String> {
+    if opt.width == 0 {
+        return Err("Width and height must not both be zero".to_string());
+    }
+    let (xs, ys) = prepare_data(xs, ys, opt)?;
+    let (x_min, x_max, y_min, y_max) = compute_ranges(&xs, &ys, opt)?;
+    let height = (opt.width as f64 * (y_max - y_min).abs() / (x_max - x_min).abs()).round();
+    if height.is_nan() || height < 1.0 {
+        return Err("Automatic height is zero: the Y range is too small relative to the X range".to_string());
+    }
+    if height > MAX_AUTO_HEIGHT as f64 {
+        return Err(format!(
+            "Automatic height {} exceeds the maximum of {} pixels",
+            height, MAX_AUTO_HEIGHT
+        ));
+    }
+    Ok(height as u32)
+}
+
+/// Returns the microseconds since `start`, saturating at `u64::MAX`.
+fn elapsed_us(start: std::time::Instant) -> u64 {
+    u64::try_from(start.elapsed().as_micros()).unwrap_or(u64::MAX)
+}
+
+/// Renders a PNG file at `path` with the given drawing function.
+///
+/// The file is only created once rendering has succeeded, so a failed call leaves an
+/// existing file untouched.
+fn render_png<F>(path: &str, opt: &PlotOptions, size: (u32, u32), draw: F) -> Result<(), String>
+where
+    F: FnOnce(&DrawingArea<BitMapBackend, Shift>) -> Result<(), String>,
+{
+    render_png_timed(path, opt, size, draw).map(|_| ())
+}
+
+/// A PNG file written by `render_png_timed`.
+struct RenderedPng {
+    /// Microseconds spent drawing, encoding and writing, after validation
+    elapsed_us: u64,
+    /// The image as RGB bytes, including any outer padding
+    rgb: Vec<u8>,
+    /// Width and height of `rgb` in pixels
+    size: (u32, u32),
+}
+
+/// Like `render_png`, but also returns the rendered pixels and the time spent drawing,
+/// encoding and writing the file, after the options and output directory have been checked.
+fn render_png_timed<F>(path: &str, opt: &PlotOptions, size: (u32, u32), draw: F) -> Result<RenderedPng, String>
+where
+    F: FnOnce(&DrawingArea<BitMapBackend, Shift>) -> Result<(), String>,
+{
+    check_render_options(opt)?;
+
+    // Fail before rendering rather than at the final write
+    check_output_dir(path)?;
+
+    let start = std::time::Instant::now();
+    let (rgb, size) = render_rgb(opt, size, draw)?;
+    let png = png_from_rgb(opt, &rgb, size)?;
+    let file = std::fs::File::create(path).map_err(io_error)?;
+    write_all_to(std::io::BufWriter::new(file), &png)?;
+    Ok(RenderedPng {
+        elapsed_us: elapsed_us(start),
+        rgb,
+        size,
+    })
+}
+
+/// Renders a PNG with the given drawing function and writes it to `writer`.
+pub(crate) fn render_png_to_writer<W, F>(writer: W, opt: &PlotOptions, size: (u32, u32), draw: F) -> Result<(), String>
+where
+    W: std::io::Write,
+    F: FnOnce(&DrawingArea<BitMapBackend, Shift>) -> Result<(), String>,
+{
+    check_render_options(opt)?;
+    let png = encode_png(opt, size, draw)?;
+    write_all_to(writer, &png)
+}
+
+/// Registers the bundled font and validates the options' dimensions.
+fn check_render_options(opt: &PlotOptions) -> Result<(), String> {
+    ensure_font_registered(opt.use_system_font_fallback != 0)?;
+    if opt.width == 0 || opt.height == 0 {
+        return Err("Width and height must be greater than zero".to_string());
+    }
+    let (max_width, max_height) = max_image_size();
+    if opt.width > max_width {
+        return Err("Width exceeds PLOTFFI_MAX_WIDTH limit".to_string());
+    }
+    if opt.height > max_height {
+        return Err("Height exceeds PLOTFFI_MAX_HEIGHT limit".to_string());
+    }
+    Ok(())
+}
+
+/// Returns the largest width and height a render may request, from the
+/// `PLOTFFI_MAX_WIDTH` and `PLOTFFI_MAX_HEIGHT` environment variables.
+///
+/// The variables are read once per process. An unset, empty or unparsable variable
+/// means no limit.
+fn max_image_size() -> (u32, u32) {
+    *MAX_IMAGE_SIZE.get_or_init(|| {
+        let limit = |name| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(u32::MAX)
+        };
+        (limit("PLOTFFI_MAX_WIDTH"), limit("PLOTFFI_MAX_HEIGHT"))
+    })
+}
+
+/// Renders into an in-memory RGB bitmap, adding the outer padding.
+///
+/// Returns the pixels and the padded image size.
+fn render_rgb<F>(opt: &PlotOptions, size: (u32, u32), draw: F) -> Result<(Vec<u8>, (u32, u32)), String>
+where
+    F: FnOnce(&DrawingArea<BitMapBackend, Shift>) -> Result<(), String>,
+{
+    let mut rgb = vec![0u8; size.0 as usize * size.1 as usize * 3];
+    {
+        let root = BitMapBackend::with_buffer(&mut rgb, size).into_drawing_area();
+        draw(&root)?;
+        root.present().map_err(|e| drawing_error("Failed to render image", e))?;
+    }
+    if opt.outer_padding == 0 {
+        Ok((rgb, size))
+    } else {
+        pad_rgb(&rgb, size, opt.outer_padding)
+    }
+}
+
+/// Renders into an in-memory bitmap and encodes it as PNG, adding padding and metadata chunks.
+fn encode_png<F>(opt: &PlotOptions, size: (u32, u32), draw: F) -> Result<Vec<u8>, String>
+where
+    F: FnOnce(&DrawingArea<BitMapBackend, Shift>) -> Result<(), String>,
+{
+    let (rgb, size) = render_rgb(opt, size, draw)?;
+    png_from_rgb(opt, &rgb, size)
+}
+
+/// Encodes RGB pixels as PNG, adding metadata chunks.
+fn png_from_rgb(opt: &PlotOptions, rgb: &[u8], (width, height): (u32, u32)) -> Result<Vec<u8>, String> {
+    let mut png = Vec::new();
+    image::codecs::png::PngEncoder::new(&mut png)
+        .write_image(rgb, width, height, image::ColorType::Rgb8)
+        .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+    let chunks = metadata_chunks(opt)?;
+    if !chunks.is_empty() {
+        png = with_png_chunks(&png, &chunks)?;
+    }
+    Ok(png)
+}
+
+/// Writes encoded image bytes to `writer` and flushes it.
+fn write_all_to<W: std::io::Write>(mut writer: W, bytes: &[u8]) -> Result<(), String> {
+    writer.write_all(bytes).and_then(|_| writer.flush()).map_err(io_error)
+}
+
+/// Describes an error writing an output file, keeping the OS message.
+pub(crate) fn io_error(e: std::io::Error) -> String {
+    format!("IO error writing chart: {}", e)
+}
+
+/// Describes a Plotters drawing error after `context`, naming the kind of failure.
+///
+/// Plotters' own messages nest the backend error in two generic prefixes, so the
+/// variants are spelled out here instead.
+fn drawing_error<E: std::error::Error + Send + Sync>(context: &str, e: DrawingAreaErrorKind<E>) -> String {
+    match e {
+        DrawingAreaErrorKind::BackendError(DrawingErrorKind::DrawingError(e)) => {
+            format!("{}: backend error: {}", context, e)
+        },
+        DrawingAreaErrorKind::BackendError(DrawingErrorKind::FontError(e)) => {
+            format!("{}: font error: {}", context, e)
+        },
+        DrawingAreaErrorKind::SharingError => {
+            format!("{}: the drawing area is in use by another operation", context)
+        },
+        DrawingAreaErrorKind::LayoutError => format!("{}: the chart layout does not fit the image", context),
+    }
+}
+
+/// Checks that the directory `path` will be written to exists and is writable.
+fn check_output_dir(path: &str) -> Result<(), String> {
+    let parent = match std::path::Path::new(path).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        // A bare file name is written to the current directory
+        _ => std::path::Path::new("."),
+    };
+    match std::fs::metadata(parent) {
+        Ok(meta) if meta.is_dir() && !meta.permissions().readonly() => Ok(()),
+        _ => Err(format!(
+            "Output directory does not exist or is not writable: {}",
+            parent.display()
+        )),
+    }
+}
+
+/// Copies an RGB image into the centre of a white image that is `padding` pixels larger on each side.
+fn pad_rgb(rgb: &[u8], (width, height): (u32, u32), padding: u32) -> Result<(Vec<u8>, (u32, u32)), String> {
+    let extra = padding
+        .checked_mul(2)
+        .ok_or_else(|| "Outer padding overflows".to_string())?;
+    let padded_size = (
+        width
+            .checked_add(extra)
+            .ok_or_else(|| "Padded width overflows".to_string())?,
+        height
+            .checked_add(extra)
+            .ok_or_else(|| "Padded height overflows".to_string())?,
+    );
+
+    let row_len = width as usize * 3;
+    let padded_row_len = padded_size.0 as usize * 3;
+    let mut padded = vec![255u8; padded_row_len * padded_size.1 as usize];
+    let offset = padding as usize * padded_row_len + padding as usize * 3;
+    for (y, row) in rgb.chunks_exact(row_len).enumerate() {
+        let start = offset + y * padded_row_len;
+        padded[start..start + row_len].copy_from_slice(row);
+    }
+    Ok((padded, padded_size))
+}
+
+/// Internal implementation of in-memory rendering.
+///
+/// Returns the image as tightly packed RGBA bytes, row by row from the top-left corner.
+#[doc(hidden)]
+pub fn plot_scatter_rgba_impl<'a>(
+    xs: &[f64],
+    ys: &[f64],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<Vec<u8>, String> {
+    let opt = with_render_defaults(opt);
+    check_render_options(&opt)?;
+
+    let mut rgb = vec![0u8; opt.width as usize * opt.height as usize * 3];
+    {
+        let root = BitMapBackend::with_buffer(&mut rgb, (opt.width, opt.height)).into_drawing_area();
+        draw_scatter(&root, xs, ys, &opt, XLabels::Numeric)?;
+        root.present().map_err(|e| drawing_error("Failed to render image", e))?;
+    }
+
+    // BitMapBackend only renders RGB, so add an opaque alpha channel
+    let mut rgba = Vec::with_capacity(rgb.len() / 3 * 4);
+    for pixel in rgb.chunks_exact(3) {
+        rgba.extend_from_slice(pixel);
+        rgba.push(255);
+    }
+    Ok(rgba)
+}
+
+/// Internal implementation of RGB565 rendering.
+///
+/// Returns one 16-bit word per pixel (red in the top 5 bits, then 6 green and 5 blue
+/// bits), row by row from the top-left corner, stored in the byte order `opt.pixel_endian`.
+#[doc(hidden)]
+pub fn plot_scatter_rgb565_impl<'a>(
+    xs: &[f64],
+    ys: &[f64],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<Vec<u16>, String> {
+    let opt = opt.into();
+    let big_endian = match opt.pixel_endian {
+        0 => false,
+        1 => true,
+        other => return Err(format!("Unsupported pixel endianness: {}", other)),
+    };
+    let rgba = plot_scatter_rgba_impl(xs, ys, opt)?;
+    Ok(rgba
+        .chunks_exact(4)
+        .map(|p| {
+            let word = rgb565(p[0], p[1], p[2]);
+            if big_endian { word.to_be() } else { word.to_le() }
+        })
+        .collect())
+}
+
+/// Packs an 8-bit-per-channel color into an RGB565 word.
+fn rgb565(r: u8, g: u8, b: u8) -> u16 {
+    ((r as u16 >> 3) << 11) | ((g as u16 >> 2) << 5) | (b as u16 >> 3)
+}
+
+/// Internal implementation of in-memory PNG encoding.
+///
+/// Returns the same bytes `plot_scatter_png_impl` would write to a file.
+#[doc(hidden)]
+pub fn plot_scatter_png_bytes_impl<'a>(
+    xs: &[f64],
+    ys: &[f64],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<Vec<u8>, String> {
+    let opt = with_render_defaults(opt);
+    check_render_options(&opt)?;
+    encode_png(&opt, (opt.width, opt.height), |root| {
+        draw_scatter(root, xs, ys, &opt, XLabels::Numeric).map(|_| ())
+    })
+}
+
+/// Internal implementation of PDF rendering.
+///
+/// Fails unless the crate is built with the `pdf` feature.
+#[doc(hidden)]
+pub fn plot_scatter_pdf_impl<'a>(
+    path: &str,
+    xs: &[f64],
+    ys: &[f64],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    let opt = with_render_defaults(opt);
+    check_render_options(&opt)?;
+    check_output_dir(path)?;
+    let (rgb, size) = render_rgb(&opt, (opt.width, opt.height), |root| {
+        draw_scatter(root, xs, ys, &opt, XLabels::Numeric).map(|_| ())
+    })?;
+
+    #[cfg(feature = "pdf")]
+    {
+        pdf::write_pdf(path, rgb, size, opt.dpi)
+    }
+    #[cfg(not(feature = "pdf"))]
+    {
+        let _ = (rgb, size);
+        Err("PDF output is not available: plotffi was built without the `pdf` feature".to_string())
+    }
+}
+
+/// Internal implementation of rendering into an OpenGL texture.
+///
+/// Fails unless the crate is built with the `opengl` feature.
+///
+/// # Safety
+/// With the `opengl` feature, a GL context must be current on the calling thread.
+#[doc(hidden)]
+pub unsafe fn plot_render_to_texture_gl_impl<'a>(
+    gl_texture_id: u32,
+    xs: &[f64],
+    ys: &[f64],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    #[cfg(feature = "opengl")]
+    {
+        let opt = with_render_defaults(opt);
+        let rgba = plot_scatter_rgba_impl(xs, ys, opt)?;
+        // SAFETY: the caller guarantees a current GL context
+        unsafe { gl::upload_texture(gl_texture_id, &rgba, (opt.width, opt.height)) }
+    }
+    #[cfg(not(feature = "opengl"))]
+    {
+        let _ = (gl_texture_id, xs, ys, opt);
+        Err("OpenGL output is not available: plotffi was built without the `opengl` feature".to_string())
+    }
+}
+
+/// Internal implementation of SVG rendering.
+#[doc(hidden)]
+pub fn plot_scatter_svg_impl<'a>(
+    path: &str,
+    xs: &[f64],
+    ys: &[f64],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    let opt = with_render_defaults(opt);
+    check_render_options(&opt)?;
+
+    check_output_dir(path)?;
+
+    let root = SVGBackend::new(path, (opt.width, opt.height)).into_drawing_area();
+    draw_scatter(&root, xs, ys, &opt, XLabels::Numeric)?;
+    root.present().map_err(|e| match e {
+        // The SVG backend only writes the file here
+        DrawingAreaErrorKind::BackendError(DrawingErrorKind::DrawingError(e)) => io_error(e),
+        e => drawing_error("Failed to write SVG", e),
+    })?;
+
+    Ok(())
+}
+
+/// Default label format for time series X axes.
+const DEFAULT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Internal implementation of time series rendering.
+///
+/// `timestamps` are Unix seconds; X tick labels are formatted with `opt.x_label_format`.
+#[doc(hidden)]
+pub fn plot_timeseries_png_impl<'a>(
+    path: &str,
+    timestamps: &[f64],
+    ys: &[f64],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    let opt = with_render_defaults(opt);
+    if let Some(i) = timestamps.iter().position(|t| !t.is_finite()) {
+        return Err(format!("Invalid timestamp at index {}: must be finite", i));
+    }
+
+    let format = match fixed_str(&opt.x_label_format)? {
+        "" => DEFAULT_TIME_FORMAT,
+        format => format,
+    };
+    let items = chrono::format::StrftimeItems::new(format)
+        .parse()
+        .map_err(|_| format!("Invalid X label format: {}", format))?;
+
+    render_png(path, &opt, (opt.width, opt.height), |root| {
+        draw_scatter(root, timestamps, ys, &opt, XLabels::Timestamp(&items)).map(|_| ())
+    })
+}
+
+/// Internal implementation of line chart rendering.
+#[doc(hidden)]
+pub fn plot_line_png_impl<'a>(
+    path: &str,
+    xs: &[f64],
+    ys: &[f64],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    let opt = with_render_defaults(opt);
+    render_png(path, &opt, (opt.width, opt.height), |root| {
+        draw_line(root, xs, ys, &opt, None)
+    })
+}
+
+/// Internal implementation of empirical CDF rendering.
+#[doc(hidden)]
+pub fn plot_cdf_png_impl<'a>(path: &str, data: &[f64], opt: impl Into<RenderOptions<'a>>) -> Result<(), String> {
+    let opt = with_render_defaults(opt);
+    let (xs, ys) = ecdf_points(data, &opt)?;
+    let opt = opt.with_options(PlotOptions {
+        line_mode: PLOT_LINE_STEP_RIGHT,
+        ..opt.opt
+    });
+    render_png(path, &opt, (opt.width, opt.height), |root| {
+        draw_line(root, &xs, &ys, &opt, Some((0.0, 1.0)))
+    })
+}
+
+/// Computes the points of the empirical CDF of `data`, starting at probability zero.
+///
+/// Sorts a copy of the data after applying the NaN policy.
+fn ecdf_points(data: &[f64], opt: &PlotOptions) -> Result<(Vec<f64>, Vec<f64>), String> {
+    let mut sorted: Vec<f64> = if !skips_non_finite(opt)? {
+        if let Some(i) = data.iter().position(|v| !v.is_finite()) {
+            return Err(non_finite_error(&[data[i]], "value", i));
+        }
+        data.to_vec()
+    } else {
+        data.iter().copied().filter(|v| v.is_finite()).collect()
+    };
+    if sorted.is_empty() {
+        return Err("No valid (non-NaN, finite) data points after filtering".to_string());
+    }
+    sorted.sort_by(f64::total_cmp);
+
+    let n = sorted.len() as f64;
+    let mut xs = Vec::with_capacity(sorted.len() + 1);
+    let mut ys = Vec::with_capacity(sorted.len() + 1);
+    xs.push(sorted[0]);
+    ys.push(0.0);
+    for (i, &x) in sorted.iter().enumerate() {
+        xs.push(x);
+        ys.push((i + 1) as f64 / n);
+    }
+    Ok((xs, ys))
+}
+
+/// Internal implementation of polar scatter rendering.
+#[doc(hidden)]
+pub fn plot_polar_scatter_png_impl(path: &str, angles: &[f64], radii: &[f64], opt: PlotOptions) -> Result<(), String> {
+    let opt = with_global_defaults(opt);
+    render_png(path, &opt, (opt.width, opt.height), |root| {
+        draw_polar(root, angles, radii, &opt)
+    })
+}
+
+/// Internal implementation of scatter plus regression line rendering.
+#[doc(hidden)]
+pub fn plot_scatter_with_regression_png_impl<'a>(
+    path: &str,
+    xs: &[f64],
+    ys: &[f64],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    let opt = with_render_defaults(opt);
+    render_png(path, &opt, (opt.width, opt.height), |root| {
+        draw_scatter_with_regression(root, xs, ys, &opt)
+    })
+}
+
+/// Internal implementation of quantile band rendering.
+///
+/// The band is drawn only when both `p_low` and `p_high` are given.
+#[doc(hidden)]
+pub fn plot_quantile_band_png_impl<'a>(
+    path: &str,
+    xs: &[f64],
+    p_low: Option<&[f64]>,
+    p_mid: &[f64],
+    p_high: Option<&[f64]>,
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    let opt = with_render_defaults(opt);
+    let band = p_low.zip(p_high);
+    render_png(path, &opt, (opt.width, opt.height), |root| {
+        draw_quantile_band(root, xs, band, p_mid, &opt)
+    })
+}
+
+/// Internal implementation of appending a series to an existing PNG.
+///
+/// `opt` must describe the base chart: same size, padding and explicit ranges.
+#[doc(hidden)]
+pub fn plot_scatter_png_append_impl(
+    path: &str,
+    xs: &[f64],
+    ys: &[f64],
+    color: u32,
+    opt: PlotOptions,
+) -> Result<(), String> {
+    let opt = with_global_defaults(opt);
+    ensure_font_registered(opt.use_system_font_fallback != 0)?;
+
+    let image = image::open(path)
+        .map_err(|e| format!("Failed to load PNG: {}", e))?
+        .to_rgb8();
+    let (width, height) = image.dimensions();
+    // The base image includes the outer padding, if any
+    let padding = 2 * opt.outer_padding as u64;
+    let expected = (opt.width as u64 + padding, opt.height as u64 + padding);
+    if (width as u64, height as u64) != expected {
+        return Err(format!(
+            "Image size {}x{} does not match the options ({}x{})",
+            width, height, expected.0, expected.1
+        ));
+    }
+
+    // Ranges must come from the options so the new points line up with the base chart
+    let opt = PlotOptions { auto_range: 0, ..opt };
+    let (xs, ys) = prepare_data(xs, ys, &opt)?;
+    let ranges = compute_ranges(&xs, &ys, &opt)?;
+    let (x_min, x_max, y_min, y_max) = ranges;
+    check_plot_area_fraction(opt.plot_area_fraction)?;
+    let layout = compute_layout(&opt, x_max - x_min, y_max - y_min);
+
+    let mut rgb = image.into_raw();
+    {
+        let root = BitMapBackend::with_buffer(&mut rgb, (width, height)).into_drawing_area();
+        let padding = opt.outer_padding as i32;
+        let area = root.margin(padding, padding, padding, padding);
+        let mut chart = build_chart(&area, ranges, &opt, &layout)?;
+
+        let marker_radius = opt.marker_radius as i32;
+        let marker_color = color_from_argb(color);
+        chart
+            .draw_series(
+                xs.iter()
+                    .zip(ys.iter())
+                    .map(|(&x, &y)| Circle::new((x, y), marker_radius, marker_color.filled())),
+            )
+            .map_err(|e| drawing_error("Failed to draw points", e))?;
+        root.present().map_err(|e| drawing_error("Failed to render image", e))?;
+    }
+
+    image::save_buffer(path, &rgb, width, height, image::ColorType::Rgb8)
+        .map_err(|e| format!("Failed to write PNG: {}", e))?;
+    let chunks = metadata_chunks(&opt)?;
+    if !chunks.is_empty() {
+        insert_png_chunks(path, &chunks)?;
+    }
+
+    Ok(())
+}
+
+/// Internal implementation of band chart rendering.
+#[doc(hidden)]
+pub fn plot_band_png_impl<'a>(
+    path: &str,
+    xs: &[f64],
+    y_lower: &[f64],
+    y_upper: &[f64],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    let opt = with_render_defaults(opt);
+    render_png(path, &opt, (opt.width, opt.height), |root| {
+        draw_band(root, xs, y_lower, y_upper, &opt)
+    })
+}
+
+/// Internal implementation of vector field rendering.
+#[doc(hidden)]
+pub fn plot_quiver_png_impl<'a>(
+    path: &str,
+    xs: &[f64],
+    ys: &[f64],
+    us: &[f64],
+    vs: &[f64],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    let opt = with_render_defaults(opt);
+    render_png(path, &opt, (opt.width, opt.height), |root| {
+        draw_quiver(root, xs, ys, us, vs, &opt)
+    })
+}
+
+/// Internal implementation of 2D histogram rendering.
+///
+/// Bin counts of 0 default to `ceil(sqrt(n))` bins on that axis.
+#[doc(hidden)]
+pub fn plot_histogram2d_png_impl<'a>(
+    path: &str,
+    xs: &[f64],
+    ys: &[f64],
+    n_bins_x: u32,
+    n_bins_y: u32,
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    let opt = with_render_defaults(opt);
+    render_png(path, &opt, (opt.width, opt.height), |root| {
+        draw_histogram2d(root, xs, ys, (n_bins_x, n_bins_y), &opt)
+    })
+}
+
+/// Internal implementation of error bar rendering.
+///
+/// The error arrays replace `style.x_err`/`style.y_err`, which are not read.
+#[doc(hidden)]
+pub fn plot_scatter_errorbars_impl<'a>(
+    path: &str,
+    xs: &[f64],
+    ys: &[f64],
+    x_err: Option<&[f64]>,
+    y_err: Option<&[f64]>,
+    style: ErrorBarOptions,
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    plot_scatter_asymmetric_errorbars_impl(path, xs, ys, (x_err, x_err), (y_err, y_err), style, opt)
+}
+
+/// Internal implementation of asymmetric error bar rendering.
+///
+/// Each error is a `(lower, upper)` pair of magnitude arrays, and a missing side has
+/// zero length. The error arrays replace `style.x_err`/`style.y_err`, which are not read.
+#[doc(hidden)]
+pub fn plot_scatter_asymmetric_errorbars_impl<'a>(
+    path: &str,
+    xs: &[f64],
+    ys: &[f64],
+    x_err: ErrorBounds<'_>,
+    y_err: ErrorBounds<'_>,
+    style: ErrorBarOptions,
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    let opt = with_render_defaults(opt);
+    render_png(path, &opt, (opt.width, opt.height), |root| {
+        draw_errorbars(root, xs, ys, (x_err, y_err), &style, &opt)
+    })
+}
+
+/// Internal implementation of grid rendering.
+///
+/// Each cell is `opt.width` x `opt.height` pixels and is drawn with its own options,
+/// except for the size. Cells are filled row by row; unused cells stay white.
+#[doc(hidden)]
+pub fn plot_grid_png_impl(
+    path: &str,
+    cells: &[(&[f64], &[f64], RenderOptions<'_>)],
+    grid_cols: u32,
+    opt: PlotOptions,
+) -> Result<(), String> {
+    let opt = with_global_defaults(opt);
+    if grid_cols == 0 {
+        return Err("Grid column count must be greater than zero".to_string());
+    }
+    if cells.is_empty() {
+        return Err("Spec count (n_specs) must be greater than zero".to_string());
+    }
+
+    let grid_rows = cells.len().div_ceil(grid_cols as usize) as u32;
+    let total_width = grid_cols
+        .checked_mul(opt.width)
+        .ok_or_else(|| "Grid width overflows".to_string())?;
+    let total_height = grid_rows
+        .checked_mul(opt.height)
+        .ok_or_else(|| "Grid height overflows".to_string())?;
+
+    render_png(path, &opt, (total_width, total_height), |root| {
+        root.fill(&background_color(&opt))
+            .map_err(|e| drawing_error("Failed to fill background", e))?;
+
+        let areas = root.split_evenly((grid_rows as usize, grid_cols as usize));
+        for (i, (area, (xs, ys, cell_opt))) in areas.iter().zip(cells).enumerate() {
+            let cell_opt = with_render_defaults(*cell_opt);
+            let cell_opt = cell_opt.with_options(PlotOptions {
+                width: opt.width,
+                height: opt.height,
+                ..cell_opt.opt
+            });
+            draw_scatter(area, xs, ys, &cell_opt, XLabels::Numeric).map_err(|e| format!("Grid cell {}: {}", i, e))?;
+        }
+        Ok(())
+    })
+}
+
+/// Internal implementation of animated GIF rendering.
+///
+/// With `opt.auto_range` the axis ranges are computed over all frames, so the axes
+/// stay fixed while the points move.
+#[doc(hidden)]
+pub fn plot_scatter_gif_impl<'a>(
+    path: &str,
+    frames: &[(&[f64], &[f64])],
+    frame_delay_ms: u32,
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    let opt = with_render_defaults(opt);
+    check_render_options(&opt)?;
+    if frames.is_empty() {
+        return Err("Frame count (n_frames) must be greater than zero".to_string());
+    }
+    check_output_dir(path)?;
+
+    let mut frame_opt = opt;
+    if opt.auto_range != 0 {
+        let (mut all_xs, mut all_ys) = (Vec::new(), Vec::new());
+        for (i, (xs, ys)) in frames.iter().enumerate() {
+            let (xs, ys) = prepare_data(xs, ys, &opt).map_err(|e| format!("Frame {}: {}", i, e))?;
+            all_xs.extend_from_slice(&xs);
+            all_ys.extend_from_slice(&ys);
+        }
+        (frame_opt.x_min, frame_opt.x_max, frame_opt.y_min, frame_opt.y_max) = compute_ranges(&all_xs, &all_ys, &opt)?;
+        frame_opt.auto_range = 0;
+    }
+
+    let size = (opt.width, opt.height);
+    let padded_size = (size.0 + 2 * opt.outer_padding, size.1 + 2 * opt.outer_padding);
+    let (width, height) = match (u16::try_from(padded_size.0), u16::try_from(padded_size.1)) {
+        (Ok(width), Ok(height)) => (width, height),
+        _ => return Err("GIF images are limited to 65535 x 65535 pixels".to_string()),
+    };
+
+    let file = std::fs::File::create(path).map_err(io_error)?;
+    let mut encoder = gif::Encoder::new(std::io::BufWriter::new(file), width, height, &[])
+        .map_err(|e| format!("Failed to write GIF: {}", e))?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(|e| format!("Failed to write GIF: {}", e))?;
+
+    // GIF delays are in hundredths of a second
+    let delay = u16::try_from(frame_delay_ms.div_ceil(10)).unwrap_or(u16::MAX);
+    for (i, (xs, ys)) in frames.iter().enumerate() {
+        let mut rgb = vec![0u8; size.0 as usize * size.1 as usize * 3];
+        {
+            let root = BitMapBackend::with_buffer(&mut rgb, size).into_drawing_area();
+            draw_scatter(&root, xs, ys, &frame_opt, XLabels::Numeric).map_err(|e| format!("Frame {}: {}", i, e))?;
+            root.present().map_err(|e| drawing_error("Failed to render image", e))?;
+        }
+        if opt.outer_padding != 0 {
+            rgb = pad_rgb(&rgb, size, opt.outer_padding)?.0;
+        }
+
+        let mut frame = gif::Frame::from_rgb_speed(width, height, &rgb, 10);
+        frame.delay = delay;
+        encoder
+            .write_frame(&frame)
+            .map_err(|e| format!("Failed to write GIF: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Internal implementation of multi-series rendering.
+///
+/// Each series is drawn with its own style; the data pointers in the `ScatterSeries` are ignored.
+#[doc(hidden)]
+pub fn plot_scatter_series_png_impl<'a>(
+    path: &str,
+    series: &[(&[f64], &[f64], ScatterSeries)],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    let opt = with_render_defaults(opt);
+    if series.is_empty() {
+        return Err("At least one series required".to_string());
+    }
+    render_png(path, &opt, (opt.width, opt.height), |root| {
+        draw_series_chart(root, series, &opt)
+    })
+}
+
+/// The data of one composite chart layer, with the point arrays as slices.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub enum Layer<'a> {
+    /// Scatter points
+    Scatter(&'a [f64], &'a [f64], ScatterStyle),
+    /// Density heatmap
+    Heatmap(&'a [f64], &'a [f64], HeatmapStyle),
+    /// Reference line
+    ReferenceLine(ReferenceLine),
+}
+
+/// Marker style of a scatter `Layer`.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScatterStyle {
+    /// Marker color as packed ARGB (0 = blue with `PlotOptions::marker_alpha`)
+    pub color: u32,
+    /// Marker radius in pixels (0 = `PlotOptions::marker_radius`)
+    pub marker_radius: u32,
+}
+
+impl From<ScatterLayer> for ScatterStyle {
+    fn from(layer: ScatterLayer) -> Self {
+        Self {
+            color: layer.color,
+            marker_radius: layer.marker_radius,
+        }
+    }
+}
+
+/// Binning and opacity of a heatmap `Layer`.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeatmapStyle {
+    /// Bins along X (0 = `ceil(sqrt(n))`)
+    pub n_bins_x: u32,
+    /// Bins along Y (0 = `ceil(sqrt(n))`)
+    pub n_bins_y: u32,
+    /// Opacity of the bins in (0, 1] (0 = 0.5)
+    pub opacity: f64,
+}
+
+impl From<HeatmapLayer> for HeatmapStyle {
+    fn from(layer: HeatmapLayer) -> Self {
+        Self {
+            n_bins_x: layer.n_bins_x,
+            n_bins_y: layer.n_bins_y,
+            opacity: layer.opacity,
+        }
+    }
+}
+
+/// Internal implementation of dual-axis line chart rendering.
+#[doc(hidden)]
+pub fn plot_dual_axis_png_impl<'a>(
+    path: &str,
+    xs: &[f64],
+    (ys_left, ys_right): (&[f64], &[f64]),
+    opt: impl Into<RenderOptions<'a>>,
+    right_axis: AxisOptions,
+) -> Result<(), String> {
+    let opt = with_render_defaults(opt);
+    render_png(path, &opt, (opt.width, opt.height), |root| {
+        draw_dual_axis(root, xs, (ys_left, ys_right), &opt, &right_axis)
+    })
+}
+
+/// Internal implementation of composite chart rendering.
+#[doc(hidden)]
+pub fn plot_composite_png_impl<'a>(
+    path: &str,
+    layers: &[Layer<'_>],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    let opt = with_render_defaults(opt);
+    if layers.is_empty() {
+        return Err("At least one layer required".to_string());
+    }
+    render_png(path, &opt, (opt.width, opt.height), |root| {
+        draw_composite(root, layers, &opt)
+    })
+}
+
+/// Internal implementation of violin plot rendering.
+///
+/// Each group is a slice of samples and its label; group `i` is centred on `x = i`.
+#[doc(hidden)]
+pub fn plot_violin_png_impl<'a>(
+    path: &str,
+    groups: &[(&[f64], &str)],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    let opt = with_render_defaults(opt);
+    if groups.is_empty() {
+        return Err("Group count (n_groups) must be greater than zero".to_string());
+    }
+    render_png(path, &opt, (opt.width, opt.height), |root| {
+        draw_violin(root, groups, &opt)
+    })
+}
+
+/// Draws a complete scatter chart (background, mesh, labels and points) onto `root`.
+///
+/// `opt.width` and `opt.height` must match the size of `root`. Returns the point counts;
+/// the render time is left for the caller to fill in.
+fn draw_scatter<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    xs: &[f64],
+    ys: &[f64],
+    opt: &RenderOptions<'_>,
+    x_labels: XLabels,
+) -> Result<PlotStats, String> {
+    let total = xs.len();
+    let (xs, ys) = prepare_data(xs, ys, opt)?;
+    let (xs, ys) = (xs.as_ref(), ys.as_ref());
+
+    // Compute axis ranges
+    let ranges = compute_ranges(xs, ys, opt)?;
+    let (x_min, x_max, y_min, y_max) = ranges;
+    let (cx_min, cx_max, cy_min, cy_max) = clip_box(opt)?;
+    let in_clip_box = |x: f64, y: f64| (cx_min..=cx_max).contains(&x) && (cy_min..=cy_max).contains(&y);
+    // Points outside the axis ranges are still drawn, so markers on the edge stay partly visible
+    let visible = |x: f64, y: f64| in_clip_box(x, y) && (x_min..=x_max).contains(&x) && (y_min..=y_max).contains(&y);
+    let points_clipped = xs.iter().zip(ys).filter(|&(&x, &y)| !visible(x, y)).count();
+    let stats = PlotStats {
+        points_rendered: xs.len() - points_clipped,
+        points_skipped_nan: total - xs.len(),
+        points_clipped,
+        render_time_us: 0,
+    };
+
+    let mut chart = draw_frame(root, ranges, opt, x_labels)?;
+
+    if opt.density_overlay != 0 {
+        let bins = if opt.density_bins == 0 {
+            DEFAULT_DENSITY_BINS
+        } else {
+            opt.density_bins
+        } as usize;
+        if bins.checked_mul(bins).is_none_or(|total| total > MAX_HISTOGRAM2D_BINS) {
+            return Err(format!(
+                "Too many density bins: {} x {} exceeds {}",
+                bins, bins, MAX_HISTOGRAM2D_BINS
+            ));
+        }
+        draw_density_bins(
+            &mut chart,
+            xs,
+            ys,
+            ranges,
+            (bins, bins),
+            BinStyle {
+                colormap: plasma,
+                opacity: DENSITY_OVERLAY_OPACITY,
+                draw_empty: false,
+            },
+        )?;
+    }
+
+    // Jitter only moves the markers; the ranges above use the original coordinates
+    let offsets = jitter_offsets(xs.len(), opt.jitter_amount, render_rng(opt, opt.jitter_seed));
+
+    // Draw scatter points as filled circles
+    let marker_color = BLUE.mix(opt.marker_alpha as f64 / 255.0);
+    let points = xs
+        .iter()
+        .zip(ys.iter())
+        .zip(offsets)
+        .filter(|&((&x, &y), _)| in_clip_box(x, y))
+        .map(|((&x, &y), dx)| (x + dx, y));
+    if opt.antialias != 0 {
+        draw_antialiased_circles(root, &chart, points, opt.marker_radius, marker_color)?;
+    } else {
+        draw_circle_markers(root, &chart, points, opt.marker_radius, marker_color)?;
+    }
+
+    draw_moving_average(&mut chart, xs, ys, opt)?;
+
+    Ok(stats)
+}
+
+/// Draws filled circles, skipping markers that cannot change the image.
+///
+/// Points are mapped to pixels before anything is drawn. Markers entirely outside `root`
+/// are dropped, and for opaque colors so are repeats on an already drawn centre pixel,
+/// which would paint exactly the same pixels. Dense charts with millions of points
+/// therefore draw only a fraction of their markers.
+fn draw_circle_markers<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    chart: &Chart<'_, DB>,
+    points: impl Iterator<Item = (f64, f64)>,
+    radius: u32,
+    color: RGBAColor,
+) -> Result<(), String> {
+    let (width, height) = root.dim_in_pixel();
+    let (width, height) = (width as i32, height as i32);
+    let (base_x, base_y) = root.get_base_pixel();
+    let r = radius as i32;
+    let style = color.filled();
+    let opaque = color.3 >= 1.0;
+    let mut drawn = vec![false; if opaque { (width * height) as usize } else { 0 }];
+
+    for point in points {
+        let (px, py) = chart.backend_coord(&point);
+        let (x, y) = (px - base_x, py - base_y);
+        if x < -r || y < -r || x >= width + r || y >= height + r {
+            continue;
+        }
+        if opaque && (0..width).contains(&x) && (0..height).contains(&y) {
+            let index = (y * width + x) as usize;
+            if drawn[index] {
+                continue;
+            }
+            drawn[index] = true;
+        }
+        root.draw(&Circle::new((x, y), r, style))
+            .map_err(|e| drawing_error("Failed to draw points", e))?;
+    }
+    Ok(())
+}
+
+/// Draws filled circles whose edge pixels are blended by coverage.
+///
+/// Each pixel within one pixel of the radius gets the marker color with its alpha scaled
+/// by how much of the pixel the circle covers, which smooths the outline. Pixels are
+/// drawn one by one, so this is noticeably slower than Plotters' hard-edged circles.
+fn draw_antialiased_circles<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    chart: &Chart<'_, DB>,
+    points: impl Iterator<Item = (f64, f64)>,
+    radius: u32,
+    color: RGBAColor,
+) -> Result<(), String> {
+    // Backend coordinates are absolute, while `root` may be a sub-area of the image
+    let (x_pixels, y_pixels) = chart.plotting_area().get_pixel_range();
+    let (base_x, base_y) = root.get_base_pixel();
+    let reach = radius as i32 + 1;
+    let edge = radius as f64 + 0.5;
+
+    for point in points {
+        let (cx, cy) = chart.backend_coord(&point);
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let (px, py) = (cx + dx, cy + dy);
+                if !x_pixels.contains(&px) || !y_pixels.contains(&py) {
+                    continue;
+                }
+                let coverage = (edge - (dx as f64).hypot(dy as f64)).clamp(0.0, 1.0);
+                if coverage > 0.0 {
+                    root.draw_pixel((px - base_x, py - base_y), &color.mix(coverage))
+                        .map_err(|e| drawing_error("Failed to draw points", e))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the clip box `(x_min, x_max, y_min, y_max)`, which is unbounded unless `clip_enabled` is set.
+fn clip_box(opt: &PlotOptions) -> Result<(f64, f64, f64, f64), String> {
+    if opt.clip_enabled == 0 {
+        return Ok((f64::NEG_INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::INFINITY));
+    }
+    // Written so that NaN bounds are rejected too
+    if !(opt.clip_x_min <= opt.clip_x_max && opt.clip_y_min <= opt.clip_y_max) {
+        return Err("Invalid clip box: minimum must not exceed maximum".to_string());
+    }
+    Ok((opt.clip_x_min, opt.clip_x_max, opt.clip_y_min, opt.clip_y_max))
+}
+
+/// Draws several series onto one chart, with ranges covering all of them.
+///
+/// Series are drawn in `z_order`, then array order, so later series appear on top. Default
+/// colors and error messages refer to the array index.
+fn draw_series_chart<'a, DB: DrawingBackend>(
+    root: &'a DrawingArea<DB, Shift>,
+    series: &[(&[f64], &[f64], ScatterSeries)],
+    opt: &RenderOptions<'_>,
+) -> Result<(), String> {
+    let mut prepared = Vec::with_capacity(series.len());
+    for (i, (xs, ys, style)) in series.iter().enumerate() {
+        let (xs, ys) = prepare_data(xs, ys, opt).map_err(|e| format!("Series {}: {}", i, e))?;
+        prepared.push((i, xs, ys, style));
+    }
+    // Stable, so equal z-orders keep the array order
+    prepared.sort_by_key(|(_, _, _, series)| series.z_order);
+
+    let all_xs: Vec<f64> = prepared.iter().flat_map(|(_, xs, _, _)| xs.iter().copied()).collect();
+    let all_ys: Vec<f64> = prepared.iter().flat_map(|(_, _, ys, _)| ys.iter().copied()).collect();
+    let ranges = compute_ranges(&all_xs, &all_ys, opt)?;
+
+    let palette = palette(opt.color_scheme)?;
+    let mut chart = draw_frame(root, ranges, opt, XLabels::Numeric)?;
+    let mut has_legend = false;
+
+    for (i, xs, ys, series) in prepared {
+        let style = &series.style;
+        let name = fixed_str(&style.name).map_err(|e| format!("Series {}: {}", i, e))?;
+        let dashes = dash_pattern(style.line_dash).map_err(|e| format!("Series {}: {}", i, e))?;
+        if style.marker_shape > PLOT_MARKER_CROSS {
+            return Err(format!(
+                "Series {}: Unsupported marker shape: {}",
+                i, style.marker_shape
+            ));
+        }
+        let color = if style.color == 0 {
+            color_from_argb(palette[i % palette.len()])
+        } else {
+            color_from_argb(style.color)
+        };
+        let line_style = color.stroke_width(style.line_width.max(1));
+        let outline_style = color.stroke_width(1);
+        let filled = style.marker_filled != 0;
+        let marker_style = if filled { color.filled() } else { outline_style };
+        let points: Vec<(f64, f64)> = xs.iter().copied().zip(ys.iter().copied()).collect();
+
+        // One legend entry per named series, attached to whatever is drawn last
+        let (draw_line, shape, radius) = (style.draw_line != 0, style.marker_shape, style.marker_radius as i32);
+        let legend = move |(x, y): (i32, i32)| {
+            let line = if draw_line { vec![(0, 0), (20, 0)] } else { Vec::new() };
+            let mut outline: Vec<(i32, i32)> = if radius != 0 {
+                marker_outline(shape, radius.min(5))
+                    .into_iter()
+                    .map(|(dx, dy)| (dx + 10, dy))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            let fill = if filled && shape != PLOT_MARKER_CROSS {
+                outline.clone()
+            } else {
+                Vec::new()
+            };
+            if shape != PLOT_MARKER_CROSS && !outline.is_empty() {
+                outline.push(outline[0]);
+            }
+            EmptyElement::at((x, y))
+                + PathElement::new(line, line_style)
+                + Polygon::new(fill, marker_style)
+                + PathElement::new(outline, outline_style)
+        };
+        let label = |anno: &mut SeriesAnno<'a, DB>| {
+            if !name.is_empty() {
+                anno.label(name).legend(legend);
+            }
+        };
+
+        if draw_line {
+            let anno = if dashes.is_empty() {
+                chart.draw_series(LineSeries::new(points.iter().copied(), line_style))
+            } else {
+                let to_pixel = |p: &(f64, f64)| {
+                    let (px, py) = chart.backend_coord(p);
+                    (px as f64, py as f64)
+                };
+                let paths = dash_polyline(&points, to_pixel, dashes);
+                chart.draw_series(paths.into_iter().map(|path| PathElement::new(path, line_style)))
+            }
+            .map_err(|e| drawing_error("Failed to draw line", e))?;
+            if radius == 0 {
+                label(anno);
+            }
+        }
+        if radius != 0 {
+            let outline = marker_outline(shape, radius);
+            let mut closed = outline.clone();
+            closed.push(outline[0]);
+            let markers = points.iter().copied();
+            let anno = match shape {
+                PLOT_MARKER_CIRCLE => chart.draw_series(markers.map(|p| Circle::new(p, radius, marker_style))),
+                PLOT_MARKER_CROSS => chart.draw_series(markers.map(|p| Cross::new(p, radius, marker_style))),
+                _ if filled => chart
+                    .draw_series(markers.map(|p| EmptyElement::at(p) + Polygon::new(outline.clone(), marker_style))),
+                _ => chart
+                    .draw_series(markers.map(|p| EmptyElement::at(p) + PathElement::new(closed.clone(), marker_style))),
+            }
+            .map_err(|e| drawing_error("Failed to draw points", e))?;
+            label(anno);
+        }
+        has_legend |= !name.is_empty() && (draw_line || radius != 0);
+    }
+    // A single series needs no legend to tell it apart
+    has_legend &= series.len() > 1 || opt.force_legend != 0;
+
+    let position = legend_position(opt.legend_position, &all_xs, &all_ys, ranges, opt)?;
+    if let (true, Some(position)) = (has_legend, position) {
+        let text = text_color(opt);
+        chart
+            .configure_series_labels()
+            .position(position)
+            .label_font(TextStyle::from(resolve_font(&opt.label_font_name, label_font_size(opt))?).color(&text))
+            .background_style(background_color(opt).mix(0.8))
+            .border_style(text)
+            .draw()
+            .map_err(|e| drawing_error("Failed to draw legend", e))?;
+    }
+
+    Ok(())
+}
+
+/// Maps a `PLOT_LEGEND_*` constant to a legend position, or `None` to suppress the legend.
+///
+/// `PLOT_LEGEND_AUTO` counts the points in each quadrant of the plot area and picks the
+/// emptiest corner; ties go to the upper right, then upper left, lower right, lower left.
+fn legend_position(
+    mode: u8,
+    xs: &[f64],
+    ys: &[f64],
+    (x_min, x_max, y_min, y_max): (f64, f64, f64, f64),
+    opt: &PlotOptions,
+) -> Result<Option<SeriesLabelPosition>, String> {
+    let corner = match mode {
+        PLOT_LEGEND_NONE => return Ok(None),
+        PLOT_LEGEND_UPPER_RIGHT..=PLOT_LEGEND_LOWER_LEFT => mode,
+        PLOT_LEGEND_AUTO => {
+            let (x_mid, y_mid) = ((x_min + x_max) / 2.0, (y_min + y_max) / 2.0);
+            // Indexed by corner constant minus one
+            let mut counts = [0usize; 4];
+            for (&x, &y) in xs.iter().zip(ys) {
+                // Quadrants are on screen, so inverted axes swap sides
+                let right = (x > x_mid) != (opt.invert_x != 0);
+                let upper = (y > y_mid) != (opt.invert_y != 0);
+                let corner = match (upper, right) {
+                    (true, true) => PLOT_LEGEND_UPPER_RIGHT,
+                    (true, false) => PLOT_LEGEND_UPPER_LEFT,
+                    (false, true) => PLOT_LEGEND_LOWER_RIGHT,
+                    (false, false) => PLOT_LEGEND_LOWER_LEFT,
+                };
+                counts[corner as usize - 1] += 1;
+            }
+            // min_by_key keeps the first minimum, which gives the documented tie order
+            (0..4).min_by_key(|&i| counts[i]).unwrap() as u8 + 1
+        },
+        _ => return Err(format!("Unsupported legend position: {}", mode)),
+    };
+    Ok(Some(match corner {
+        PLOT_LEGEND_UPPER_RIGHT => SeriesLabelPosition::UpperRight,
+        PLOT_LEGEND_UPPER_LEFT => SeriesLabelPosition::UpperLeft,
+        PLOT_LEGEND_LOWER_RIGHT => SeriesLabelPosition::LowerRight,
+        _ => SeriesLabelPosition::LowerLeft,
+    }))
+}
+
+/// Returns the outline of a `PLOT_MARKER_*` shape as pixel offsets from its centre.
+///
+/// Circles are approximated by a 16-gon; the cross is an open path through the centre.
+fn marker_outline(shape: u8, radius: i32) -> Vec<(i32, i32)> {
+    let r = radius as f64;
+    let polygon = |corners: usize, start_deg: f64| {
+        (0..corners)
+            .map(|i| {
+                let angle = (start_deg + 360.0 * i as f64 / corners as f64).to_radians();
+                ((r * angle.cos()).round() as i32, (r * angle.sin()).round() as i32)
+            })
+            .collect()
+    };
+    match shape {
+        PLOT_MARKER_SQUARE => vec![
+            (-radius, -radius),
+            (radius, -radius),
+            (radius, radius),
+            (-radius, radius),
+        ],
+        PLOT_MARKER_TRIANGLE => polygon(3, -90.0),
+        PLOT_MARKER_CROSS => vec![
+            (-radius, -radius),
+            (radius, radius),
+            (0, 0),
+            (-radius, radius),
+            (radius, -radius),
+        ],
+        _ => polygon(16, 0.0),
+    }
+}
+
+// Violin plots: KDE grid size and the share of the column taken by the widest violin
+const VIOLIN_GRID_POINTS: usize = 100;
+const VIOLIN_WIDTH: f64 = 0.8;
+
+/// Returns the `q` quantile (0..=1) of sorted values, interpolating linearly between ranks.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let rank = q * (sorted.len() - 1) as f64;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+}
+
+/// Gaussian kernel bandwidth by Silverman's rule of thumb: `0.9 * min(sd, IQR / 1.34) * n^(-1/5)`.
+///
+/// Falls back to the standard deviation alone when the IQR is zero.
+fn silverman_bandwidth(sorted: &[f64]) -> f64 {
+    let n = sorted.len() as f64;
+    let mean = sorted.iter().sum::<f64>() / n;
+    let sd = (sorted.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / (n - 1.0)).sqrt();
+    let iqr = quantile(sorted, 0.75) - quantile(sorted, 0.25);
+    let spread = if iqr > 0.0 { sd.min(iqr / 1.34) } else { sd };
+    0.9 * spread * n.powf(-0.2)
+}
+
+/// Evaluates the Gaussian kernel density estimate of `values` at `y`, up to a constant factor.
+fn kernel_density(values: &[f64], bandwidth: f64, y: f64) -> f64 {
+    values
+        .iter()
+        .map(|v| {
+            let z = (y - v) / bandwidth;
+            (-0.5 * z * z).exp()
+        })
+        .sum()
+}
+
+/// Draws a complete violin chart onto `root`.
+fn draw_violin<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    groups: &[(&[f64], &str)],
+    opt: &RenderOptions<'_>,
+) -> Result<(), String> {
+    let mut samples = Vec::with_capacity(groups.len());
+    for (i, (values, _)) in groups.iter().enumerate() {
+        let mut sorted: Vec<f64> = values.to_vec();
+        if sorted.iter().any(|v| !v.is_finite()) {
+            return Err(format!("Group {}: values must be finite", i));
+        }
+        sorted.sort_by(f64::total_cmp);
+        if sorted.len() < 2 || sorted[0] == sorted[sorted.len() - 1] {
+            return Err(format!("Group {}: at least two distinct values are required", i));
+        }
+        samples.push(sorted);
+    }
+
+    // Groups sit at 0, 1, 2, ... in columns of width 1
+    let positions: Vec<f64> = (0..groups.len()).map(|i| i as f64).collect();
+    let all_values: Vec<f64> = samples.iter().flatten().copied().collect();
+    let (_, _, y_min, y_max) = compute_ranges(&positions, &all_values, opt)?;
+    let x_range = (-0.5, groups.len() as f64 - 0.5);
+    let names: Vec<&str> = groups.iter().map(|(_, name)| *name).collect();
+    let frame_opt = opt.with_x_ticks(&positions);
+    let mut chart = draw_frame(
+        root,
+        (x_range.0, x_range.1, y_min, y_max),
+        &frame_opt,
+        XLabels::Categories(&names),
+    )?;
+
+    // Densities on a grid spanning each group's data range
+    let densities: Vec<Vec<(f64, f64)>> = samples
+        .iter()
+        .map(|sorted| {
+            let bandwidth = silverman_bandwidth(sorted);
+            let (lo, hi) = (sorted[0], sorted[sorted.len() - 1]);
+            (0..VIOLIN_GRID_POINTS)
+                .map(|k| {
+                    let y = lo + (hi - lo) * k as f64 / (VIOLIN_GRID_POINTS - 1) as f64;
+                    (y, kernel_density(sorted, bandwidth, y))
+                })
+                .collect()
+        })
+        .collect();
+    let max_density = densities.iter().flatten().map(|&(_, d)| d).fold(0.0, f64::max);
+    let scale = VIOLIN_WIDTH / 2.0 / max_density;
+
+    for ((center, sorted), density) in positions.iter().zip(&samples).zip(&densities) {
+        let right = density.iter().map(|&(y, d)| (center + d * scale, y));
+        let left = density.iter().rev().map(|&(y, d)| (center - d * scale, y));
+        let outline: Vec<(f64, f64)> = right.chain(left).collect();
+        chart
+            .draw_series(std::iter::once(Polygon::new(outline.clone(), BLUE.mix(0.3).filled())))
+            .map_err(|e| drawing_error("Failed to draw violin", e))?;
+        chart
+            .draw_series(std::iter::once(PathElement::new(outline, BLUE.stroke_width(1))))
+            .map_err(|e| drawing_error("Failed to draw violin", e))?;
+
+        // Median across the violin, IQR along its centre
+        let median = quantile(sorted, 0.5);
+        let half_width = kernel_density(sorted, silverman_bandwidth(sorted), median) * scale;
+        let (q1, q3) = (quantile(sorted, 0.25), quantile(sorted, 0.75));
+        chart
+            .draw_series([
+                PathElement::new(
+                    vec![(center - half_width, median), (center + half_width, median)],
+                    BLACK.stroke_width(1),
+                ),
+                PathElement::new(vec![(*center, q1), (*center, q3)], BLACK.stroke_width(1)),
+            ])
+            .map_err(|e| drawing_error("Failed to draw violin", e))?;
+    }
+
+    Ok(())
+}
+
+/// Least-squares fit of a straight line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LinearFit {
+    slope: f64,
+    intercept: f64,
+    r_squared: f64,
+}
+
+/// Computes the ordinary least-squares regression line through the points.
+fn linear_regression(xs: &[f64], ys: &[f64]) -> Result<LinearFit, String> {
+    if xs.len() < 2 {
+        return Err("Regression requires at least two points".to_string());
+    }
+
+    let n = xs.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = ys.iter().sum::<f64>() / n;
+    let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+    for (&x, &y) in xs.iter().zip(ys) {
+        sxx += (x - mean_x) * (x - mean_x);
+        sxy += (x - mean_x) * (y - mean_y);
+        syy += (y - mean_y) * (y - mean_y);
+    }
+    if sxx == 0.0 {
+        return Err("Regression requires at least two distinct X values".to_string());
+    }
+
+    let slope = sxy / sxx;
+    // A constant Y is fitted exactly by the horizontal line
+    let r_squared = if syy == 0.0 { 1.0 } else { sxy * sxy / (sxx * syy) };
+    Ok(LinearFit {
+        slope,
+        intercept: mean_y - slope * mean_x,
+        r_squared,
+    })
+}
+
+/// Draws a scatter chart with its least-squares regression line onto `root`.
+fn draw_scatter_with_regression<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    xs: &[f64],
+    ys: &[f64],
+    opt: &RenderOptions<'_>,
+) -> Result<(), String> {
+    let (xs, ys) = prepare_data(xs, ys, opt)?;
+    let (xs, ys) = (xs.as_ref(), ys.as_ref());
+    let fit = linear_regression(xs, ys)?;
+
+    let mut opt = *opt;
+    if opt.show_r_squared != 0 {
+        let r_squared = format!("R² = {:.4}", fit.r_squared);
+        let title = match fixed_str(&opt.title)? {
+            "" => r_squared,
+            title => format!("{} ({})", title, r_squared),
+        };
+        write_fixed_str(&mut opt.title, &title);
+    }
+
+    let ranges = compute_ranges(xs, ys, &opt)?;
+    let (x_min, x_max, _, _) = ranges;
+    let mut chart = draw_frame(root, ranges, &opt, XLabels::Numeric)?;
+
+    let marker_radius = opt.marker_radius as i32;
+    let marker_color = BLUE.mix(opt.marker_alpha as f64 / 255.0);
+    chart
+        .draw_series(
+            xs.iter()
+                .zip(ys.iter())
+                .map(|(&x, &y)| Circle::new((x, y), marker_radius, marker_color.filled())),
+        )
+        .map_err(|e| drawing_error("Failed to draw points", e))?;
+
+    // The fit spans the full X range of the chart
+    let line = [x_min, x_max].map(|x| (x, fit.slope * x + fit.intercept));
+    chart
+        .draw_series(LineSeries::new(line, RED.stroke_width(2)))
+        .map_err(|e| drawing_error("Failed to draw regression line", e))?;
+
+    Ok(())
+}
+
+// Seed used when both `render_seed` and the operation's own seed are zero
+const DEFAULT_RENDER_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+/// Small deterministic generator for the random draws of a render call.
+struct RenderRng {
+    state: u64,
+}
+
+impl RenderRng {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns a uniform value in `[0, 1)`.
+    fn next_unit(&mut self) -> f64 {
+        // 64-bit LCG (Knuth's MMIX constants); the high 53 bits give a uniform value in [0, 1)
+        self.state = self
+            .state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Returns `n` deterministic pseudo-random points in the unit square.
+///
+/// Used by the benchmarks, so that they draw their data from `RenderRng`.
+#[doc(hidden)]
+pub fn random_unit_points(n: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut rng = RenderRng::new(DEFAULT_RENDER_SEED);
+    (0..n).map(|_| (rng.next_unit(), rng.next_unit())).unzip()
+}
+
+/// Returns the generator for one operation: its own `seed` if nonzero, else `opt.render_seed`.
+fn render_rng(opt: &PlotOptions, seed: u64) -> RenderRng {
+    let seed = match (seed, opt.render_seed) {
+        (0, 0) => DEFAULT_RENDER_SEED,
+        (0, render_seed) => render_seed,
+        (seed, _) => seed,
+    };
+    RenderRng::new(seed)
+}
+
+/// Returns `n` deterministic X offsets in `[-amount/2, amount/2]`, or zeros if `amount <= 0`.
+fn jitter_offsets(n: usize, amount: f64, mut rng: RenderRng) -> impl Iterator<Item = f64> {
+    let amount = if amount > 0.0 { amount } else { 0.0 };
+    (0..n).map(move |_| {
+        if amount == 0.0 {
+            return 0.0;
+        }
+        (rng.next_unit() - 0.5) * amount
+    })
+}
+
+/// Applies the NaN policy and ensures at least one valid point remains.
+fn prepare_data<'a>(xs: &'a [f64], ys: &'a [f64], opt: &PlotOptions) -> Result<Coords<'a>, String> {
+    // Filter or reject NaN points
+    let (xs, ys) = apply_nan_policy(xs, ys, opt)?;
+    if xs.is_empty() {
+        return Err("No valid (non-NaN, finite) data points after filtering".to_string());
+    }
+    Ok((xs, ys))
+}
+
+/// Draws a complete line chart onto `root`, connecting points in array order.
+///
+/// When `y_range` is given it replaces the Y axis range from the options.
+fn draw_line<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    xs: &[f64],
+    ys: &[f64],
+    opt: &RenderOptions<'_>,
+    y_range: Option<(f64, f64)>,
+) -> Result<(), String> {
+    let (xs, ys) = prepare_data(xs, ys, opt)?;
+    let points: Vec<(f64, f64)> = xs.iter().copied().zip(ys.iter().copied()).collect();
+
+    let (x_min, x_max, mut y_min, mut y_max) = compute_ranges(&xs, &ys, opt)?;
+    if let Some((lo, hi)) = y_range {
+        (y_min, y_max) = (lo, hi);
+    }
+    // Half-pixel spacing for interpolated curves
+    let step = (x_max - x_min) / (2.0 * opt.width.max(1) as f64);
+    let vertices = line_vertices(&points, opt.line_mode, step)?;
+
+    let dashes = dash_pattern(opt.line_dash_pattern)?;
+    let style = BLUE.stroke_width(opt.line_stroke_width.max(1));
+
+    let mut chart = draw_frame(root, (x_min, x_max, y_min, y_max), opt, XLabels::Numeric)?;
+    if dashes.is_empty() {
+        chart
+            .draw_series(LineSeries::new(vertices, style))
+            .map_err(|e| drawing_error("Failed to draw line", e))?;
+    } else {
+        let to_pixel = |p: &(f64, f64)| {
+            let (px, py) = chart.backend_coord(p);
+            (px as f64, py as f64)
+        };
+        let paths = dash_polyline(&vertices, to_pixel, dashes);
+        chart
+            .draw_series(paths.into_iter().map(|path| PathElement::new(path, style)))
+            .map_err(|e| drawing_error("Failed to draw line", e))?;
+    }
+
+    draw_moving_average(&mut chart, &xs, &ys, opt)?;
+
+    Ok(())
+}
+
+/// Draws a median line with an optional shaded band between the low and high quantiles.
+fn draw_quantile_band<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    xs: &[f64],
+    band: Option<(&[f64], &[f64])>,
+    p_mid: &[f64],
+    opt: &RenderOptions<'_>,
+) -> Result<(), String> {
+    if xs.is_empty() {
+        return Err("Point count must be greater than zero".to_string());
+    }
+    if p_mid.len() != xs.len() || band.is_some_and(|(lo, hi)| lo.len() != xs.len() || hi.len() != xs.len()) {
+        return Err("X and quantile arrays must have the same length".to_string());
+    }
+    for (i, (&x, &mid)) in xs.iter().zip(p_mid).enumerate() {
+        let (lo, hi) = band.map_or((mid, mid), |(lo, hi)| (lo[i], hi[i]));
+        if ![x, lo, mid, hi].iter().all(|v| v.is_finite()) {
+            return Err(non_finite_error(&[x, lo, mid, hi], "value", i));
+        }
+        if lo > mid || mid > hi {
+            return Err(format!(
+                "Quantiles out of order at index {}: expected p_low <= p_mid <= p_high",
+                i
+            ));
+        }
+    }
+
+    // The band bounds the median, so they determine the Y range when present
+    let ys: Vec<f64> = match band {
+        Some((lo, hi)) => lo.iter().chain(hi).copied().collect(),
+        None => p_mid.to_vec(),
+    };
+    let range_xs: Vec<f64> = xs.iter().cycle().take(ys.len()).copied().collect();
+    let ranges = compute_ranges(&range_xs, &ys, opt)?;
+
+    let mut chart = draw_frame(root, ranges, opt, XLabels::Numeric)?;
+
+    if let Some((lo, hi)) = band {
+        // Walk along the lower quantile, then back along the upper one
+        let outline: Vec<(f64, f64)> = xs
+            .iter()
+            .copied()
+            .zip(lo.iter().copied())
+            .chain(xs.iter().copied().zip(hi.iter().copied()).rev())
+            .collect();
+        chart
+            .draw_series(std::iter::once(Polygon::new(outline, BLUE.mix(0.3).filled())))
+            .map_err(|e| drawing_error("Failed to draw band", e))?;
+    }
+
+    chart
+        .draw_series(LineSeries::new(
+            xs.iter().copied().zip(p_mid.iter().copied()),
+            BLUE.stroke_width(2),
+        ))
+        .map_err(|e| drawing_error("Failed to draw line", e))?;
+
+    Ok(())
+}
+
+/// Draws the area between two Y arrays as a shaded band outlined by both bounds.
+fn draw_band<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    xs: &[f64],
+    y_lower: &[f64],
+    y_upper: &[f64],
+    opt: &RenderOptions<'_>,
+) -> Result<(), String> {
+    if xs.is_empty() {
+        return Err("Point count must be greater than zero".to_string());
+    }
+    if y_lower.len() != xs.len() || y_upper.len() != xs.len() {
+        return Err("X and bound arrays must have the same length".to_string());
+    }
+    for (i, ((&x, &lo), &hi)) in xs.iter().zip(y_lower).zip(y_upper).enumerate() {
+        if ![x, lo, hi].iter().all(|v| v.is_finite()) {
+            return Err(non_finite_error(&[x, lo, hi], "value", i));
+        }
+        if lo > hi {
+            return Err(format!(
+                "Band bounds out of order at index {}: y_lower ({}) exceeds y_upper ({})",
+                i, lo, hi
+            ));
+        }
+    }
+
+    let range_xs: Vec<f64> = xs.iter().chain(xs).copied().collect();
+    let range_ys: Vec<f64> = y_lower.iter().chain(y_upper).copied().collect();
+    let ranges = compute_ranges(&range_xs, &range_ys, opt)?;
+
+    let mut chart = draw_frame(root, ranges, opt, XLabels::Numeric)?;
+
+    let upper = || xs.iter().copied().zip(y_upper.iter().copied());
+    let lower = || xs.iter().copied().zip(y_lower.iter().copied());
+    // Along the upper bound left to right, then back along the lower bound
+    let outline: Vec<(f64, f64)> = upper().chain(lower().rev()).collect();
+    chart
+        .draw_series(std::iter::once(Polygon::new(outline, BLUE.mix(80.0 / 255.0).filled())))
+        .map_err(|e| drawing_error("Failed to draw band", e))?;
+    for bound in [upper().collect::<Vec<_>>(), lower().collect()] {
+        chart
+            .draw_series(LineSeries::new(bound, BLUE.stroke_width(1)))
+            .map_err(|e| drawing_error("Failed to draw line", e))?;
+    }
+
+    Ok(())
+}
+
+// Quiver plots: auto-scaled length of the longest arrow as a fraction of the axis range,
+// and the arrowhead length in pixels
+const QUIVER_AUTO_LENGTH: f64 = 0.05;
+const ARROWHEAD_SIZE: f64 = 8.0;
+
+// Upper bound on the number of 2D histogram bins, which are drawn one rectangle each
+const MAX_HISTOGRAM2D_BINS: usize = 1 << 20;
+
+// Bins per axis and opacity of the `density_overlay` heatmap
+const DEFAULT_DENSITY_BINS: u32 = 50;
+const DENSITY_OVERLAY_OPACITY: f64 = 0.7;
+
+/// Draws a vector field: an arrow from each `(x, y)` to `(x + scale * u, y + scale * v)`.
+fn draw_quiver<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    xs: &[f64],
+    ys: &[f64],
+    us: &[f64],
+    vs: &[f64],
+    opt: &RenderOptions<'_>,
+) -> Result<(), String> {
+    if xs.is_empty() {
+        return Err("Point count must be greater than zero".to_string());
+    }
+    if ys.len() != xs.len() || us.len() != xs.len() || vs.len() != xs.len() {
+        return Err("Position and vector arrays must have the same length".to_string());
+    }
+    for (i, (((&x, &y), &u), &v)) in xs.iter().zip(ys).zip(us).zip(vs).enumerate() {
+        if ![x, y, u, v].iter().all(|c| c.is_finite()) {
+            return Err(non_finite_error(&[x, y, u, v], "value", i));
+        }
+    }
+
+    let scale = if opt.arrow_scale != 0.0 {
+        opt.arrow_scale
+    } else {
+        // Longest component relative to its axis range, measured on the arrow bases
+        let (x_min, x_max, y_min, y_max) = compute_ranges(xs, ys, opt)?;
+        let longest = us
+            .iter()
+            .zip(vs)
+            .map(|(u, v)| (u.abs() / (x_max - x_min)).max(v.abs() / (y_max - y_min)))
+            .fold(0.0, f64::max);
+        if longest == 0.0 {
+            1.0
+        } else {
+            QUIVER_AUTO_LENGTH / longest
+        }
+    };
+    let tips: Vec<(f64, f64)> = xs
+        .iter()
+        .zip(ys)
+        .zip(us.iter().zip(vs))
+        .map(|((x, y), (u, v))| (x + scale * u, y + scale * v))
+        .collect();
+
+    // Auto ranges include the arrow tips so every arrow stays visible
+    let range_xs: Vec<f64> = xs.iter().copied().chain(tips.iter().map(|t| t.0)).collect();
+    let range_ys: Vec<f64> = ys.iter().copied().chain(tips.iter().map(|t| t.1)).collect();
+    let ranges = compute_ranges(&range_xs, &range_ys, opt)?;
+
+    let chart = draw_frame(root, ranges, opt, XLabels::Numeric)?;
+    let (base_x, base_y) = root.get_base_pixel();
+    let style = BLUE.stroke_width(1);
+    for ((&x, &y), &tip) in xs.iter().zip(ys).zip(&tips) {
+        chart
+            .plotting_area()
+            .draw(&PathElement::new(vec![(x, y), tip], style))
+            .map_err(|e| drawing_error("Failed to draw arrow", e))?;
+
+        // Arrowhead in pixel space: a fixed-size triangle pointing along the arrow
+        let (bx, by) = chart.backend_coord(&(x, y));
+        let (tx, ty) = chart.backend_coord(&tip);
+        let (dx, dy) = ((tx - bx) as f64, (ty - by) as f64);
+        let length = dx.hypot(dy);
+        if length == 0.0 {
+            continue;
+        }
+        let (ux, uy) = (dx / length, dy / length);
+        let back = (tx as f64 - ux * ARROWHEAD_SIZE, ty as f64 - uy * ARROWHEAD_SIZE);
+        let half = ARROWHEAD_SIZE / 2.0;
+        let head = [
+            (tx as f64, ty as f64),
+            (back.0 - uy * half, back.1 + ux * half),
+            (back.0 + uy * half, back.1 - ux * half),
+        ]
+        .map(|(px, py)| (px.round() as i32 - base_x, py.round() as i32 - base_y));
+        root.draw(&Polygon::new(head.to_vec(), BLUE.filled()))
+            .map_err(|e| drawing_error("Failed to draw arrow", e))?;
+    }
+
+    Ok(())
+}
+
+// Width in pixels of the colorbar strip to the right of heatmaps, and of the bar itself
+const COLORBAR_AREA_SIZE: u32 = 70;
+const COLORBAR_WIDTH: i32 = 16;
+// Width in pixels of the strip to the right of dual-axis charts with the right axis labels
+const RIGHT_AXIS_AREA_SIZE: u32 = 70;
+// Number of bands the colorbar gradient is drawn with
+const COLORBAR_STEPS: usize = 64;
+
+// Viridis anchor colors at evenly spaced positions in [0, 1], as packed RGB
+const VIRIDIS: [u32; 9] = [
+    0x440154, 0x472D7B, 0x3B528B, 0x2C728E, 0x21918C, 0x28AE80, 0x5EC962, 0xADDC30, 0xFDE725,
+];
+
+// Plasma anchor colors at evenly spaced positions in [0, 1], as packed RGB
+const PLASMA: [u32; 9] = [
+    0x0D0887, 0x4C02A1, 0x7E03A8, 0xA92395, 0xCC4778, 0xE56B5D, 0xF89441, 0xFDC328, 0xF0F921,
+];
+
+/// Maps `t` in [0, 1] to a color on the viridis scale, interpolating between anchors.
+fn viridis(t: f64) -> RGBColor {
+    colormap(&VIRIDIS, t)
+}
+
+/// Maps `t` in [0, 1] to a color on the plasma scale, interpolating between anchors.
+fn plasma(t: f64) -> RGBColor {
+    colormap(&PLASMA, t)
+}
+
+/// Maps `t` in [0, 1] to a color between evenly spaced `anchors`.
+fn colormap(anchors: &[u32], t: f64) -> RGBColor {
+    let scaled = t.clamp(0.0, 1.0) * (anchors.len() - 1) as f64;
+    let i = (scaled.floor() as usize).min(anchors.len() - 2);
+    let f = scaled - i as f64;
+    let channel = |c: u32, shift: u32| ((c >> shift) & 0xFF) as f64;
+    let mix = |shift: u32| {
+        let (a, b) = (channel(anchors[i], shift), channel(anchors[i + 1], shift));
+        (a + (b - a) * f).round() as u8
+    };
+    RGBColor(mix(16), mix(8), mix(0))
+}
+
+/// Counts points per bin of an `nx` x `ny` grid over the given ranges, row-major from the
+/// bottom-left bin. Points outside the ranges are ignored; the upper edges belong to the last bins.
+fn histogram2d(
+    xs: &[f64],
+    ys: &[f64],
+    (x_min, x_max, y_min, y_max): (f64, f64, f64, f64),
+    (nx, ny): (usize, usize),
+) -> Vec<u32> {
+    let mut counts = vec![0u32; nx * ny];
+    let bin = |v: f64, min: f64, max: f64, n: usize| {
+        if v < min || v > max {
+            return None;
+        }
+        Some((((v - min) / (max - min) * n as f64) as usize).min(n - 1))
+    };
+    for (&x, &y) in xs.iter().zip(ys) {
+        if let (Some(bx), Some(by)) = (bin(x, x_min, x_max, nx), bin(y, y_min, y_max, ny)) {
+            counts[by * nx + bx] += 1;
+        }
+    }
+    counts
+}
+
+/// Draws a 2D histogram: the points binned over the plot area, each bin colored by its
+/// count relative to the fullest bin, with a colorbar on the right.
+fn draw_histogram2d<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    xs: &[f64],
+    ys: &[f64],
+    (n_bins_x, n_bins_y): (u32, u32),
+    opt: &RenderOptions<'_>,
+) -> Result<(), String> {
+    let (xs, ys) = prepare_data(xs, ys, opt)?;
+    let default_bins = (xs.len() as f64).sqrt().ceil() as usize;
+    let bins_or_default = |n: u32| if n == 0 { default_bins } else { n as usize };
+    let (nx, ny) = (bins_or_default(n_bins_x), bins_or_default(n_bins_y));
+    if nx.checked_mul(ny).is_none_or(|total| total > MAX_HISTOGRAM2D_BINS) {
+        return Err(format!(
+            "Too many bins: {} x {} exceeds {}",
+            nx, ny, MAX_HISTOGRAM2D_BINS
+        ));
+    }
+
+    let ranges = compute_ranges(&xs, &ys, opt)?;
+
+    root.fill(&background_color(opt))
+        .map_err(|e| drawing_error("Failed to fill background", e))?;
+    let (width, _) = root.dim_in_pixel();
+    let plot_width = width.saturating_sub(COLORBAR_AREA_SIZE);
+    if plot_width == 0 {
+        return Err(format!("Width must exceed the {}-pixel colorbar", COLORBAR_AREA_SIZE));
+    }
+    let (plot_area, bar_area) = root.split_horizontally(plot_width);
+    let plot_opt = opt.with_options(PlotOptions {
+        width: plot_width,
+        ..opt.opt
+    });
+
+    let mut chart = draw_frame(&plot_area, ranges, &plot_opt, XLabels::Numeric)?;
+    let style = BinStyle {
+        colormap: viridis,
+        opacity: 1.0,
+        draw_empty: true,
+    };
+    draw_density_bins(&mut chart, &xs, &ys, ranges, (nx, ny), style)?;
+
+    // Colorbar spanning the height of the plotting area, labelled with relative density
+    let (_, y_pixels) = chart.plotting_area().get_pixel_range();
+    let base_y = root.get_base_pixel().1;
+    let (top, bottom) = (y_pixels.start - base_y, y_pixels.end - base_y);
+    let bar_height = (bottom - top).max(1);
+    let left = 10;
+    let steps = COLORBAR_STEPS as i32;
+    for step in 0..steps {
+        let y0 = top + bar_height * step / steps;
+        let y1 = top + bar_height * (step + 1) / steps;
+        let color = viridis(1.0 - (step as f64 + 0.5) / steps as f64);
+        bar_area
+            .draw(&Rectangle::new(
+                [(left, y0), (left + COLORBAR_WIDTH, y1)],
+                color.filled(),
+            ))
+            .map_err(|e| drawing_error("Failed to draw colorbar", e))?;
+    }
+    bar_area
+        .draw(&Rectangle::new(
+            [(left, top), (left + COLORBAR_WIDTH, bottom)],
+            text_color(opt).stroke_width(1),
+        ))
+        .map_err(|e| drawing_error("Failed to draw colorbar", e))?;
+    let text = text_color(opt);
+    let label_style = TextStyle::from(resolve_font(&opt.label_font_name, label_font_size(opt))?)
+        .color(&text)
+        .pos(Pos::new(HPos::Left, VPos::Center));
+    for (value, y) in [(1.0, top), (0.5, (top + bottom) / 2), (0.0, bottom)] {
+        bar_area
+            .draw(&Text::new(
+                format!("{:.1}", value),
+                (left + COLORBAR_WIDTH + 4, y),
+                label_style.clone(),
+            ))
+            .map_err(|e| drawing_error("Failed to draw colorbar", e))?;
+    }
+    Ok(())
+}
+
+/// Coloring of the bins drawn by `draw_density_bins`.
+struct BinStyle {
+    /// Color of a bin at its count relative to the fullest bin
+    colormap: fn(f64) -> RGBColor,
+    /// Opacity of the bins in (0, 1]
+    opacity: f64,
+    /// Whether empty bins are drawn; otherwise what lies below them stays visible
+    draw_empty: bool,
+}
+
+/// Draws the bins of an `nx` x `ny` 2D histogram of the points over `ranges`.
+fn draw_density_bins<DB: DrawingBackend>(
+    chart: &mut Chart<'_, DB>,
+    xs: &[f64],
+    ys: &[f64],
+    ranges: (f64, f64, f64, f64),
+    (nx, ny): (usize, usize),
+    style: BinStyle,
+) -> Result<(), String> {
+    let (x_min, x_max, y_min, y_max) = ranges;
+    let counts = histogram2d(xs, ys, ranges, (nx, ny));
+    let max_count = counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+    let (bin_w, bin_h) = ((x_max - x_min) / nx as f64, (y_max - y_min) / ny as f64);
+    chart
+        .draw_series(
+            counts
+                .iter()
+                .enumerate()
+                .filter(|&(_, &count)| style.draw_empty || count > 0)
+                .map(|(i, &count)| {
+                    let (bx, by) = ((i % nx) as f64, (i / nx) as f64);
+                    let corner = (x_min + bx * bin_w, y_min + by * bin_h);
+                    let color = (style.colormap)(count as f64 / max_count).mix(style.opacity);
+                    Rectangle::new([corner, (corner.0 + bin_w, corner.1 + bin_h)], color.filled())
+                }),
+        )
+        .map_err(|e| drawing_error("Failed to draw bins", e))?;
+    Ok(())
+}
+
+/// Computes the right axis range of a dual-axis chart, in log10 units for log axes.
+fn right_axis_range(ys: &[f64], axis: &AxisOptions) -> Result<(f64, f64), String> {
+    let log = axis.log_scale != 0;
+    let to_axis = |v: f64| if log { v.log10() } else { v };
+    if axis.auto_range == 0 {
+        if axis.y_min >= axis.y_max {
+            return Err(format!(
+                "Invalid right axis range: y_min ({}) must be less than y_max ({})",
+                axis.y_min, axis.y_max
+            ));
+        }
+        if log && axis.y_min <= 0.0 {
+            return Err("Right axis log scale requires a positive y_min".to_string());
+        }
+        return Ok((to_axis(axis.y_min), to_axis(axis.y_max)));
+    }
+
+    if !(axis.auto_range_padding.is_finite() && axis.auto_range_padding >= 0.0) {
+        return Err(format!(
+            "Right axis padding must be non-negative, got {}",
+            axis.auto_range_padding
+        ));
+    }
+    if log && ys.iter().any(|&y| y <= 0.0) {
+        return Err("Right axis log scale requires positive values".to_string());
+    }
+    let data_min = ys.iter().copied().map(to_axis).fold(f64::INFINITY, f64::min);
+    let data_max = ys.iter().copied().map(to_axis).fold(f64::NEG_INFINITY, f64::max);
+    // Handle case where all points have the same value
+    if (data_max - data_min).abs() < f64::EPSILON {
+        return Ok((data_min - 1.0, data_max + 1.0));
+    }
+    let padding = (data_max - data_min) * axis.auto_range_padding;
+    Ok((data_min - padding, data_max + padding))
+}
+
+/// Draws a line chart of two series sharing the X axis, `ys_left` against the left Y axis
+/// and `ys_right` against a right Y axis set up by `axis`.
+///
+/// The right series is mapped onto the left axis range for drawing, and the right axis
+/// ticks, labels and name are drawn in a strip to the right of the chart.
+fn draw_dual_axis<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    xs: &[f64],
+    (ys_left, ys_right): (&[f64], &[f64]),
+    opt: &RenderOptions<'_>,
+    axis: &AxisOptions,
+) -> Result<(), String> {
+    // Either series fails if the NaN policy leaves no points in it
+    let (left_xs, left_ys) = prepare_data(xs, ys_left, opt).map_err(|e| format!("Left series: {}", e))?;
+    let (right_xs, right_ys) = prepare_data(xs, ys_right, opt).map_err(|e| format!("Right series: {}", e))?;
+    let name = fixed_str(&axis.series_name)?;
+
+    // The X range covers both series, the left Y range only the left one
+    let range_xs: Vec<f64> = left_xs.iter().chain(right_xs.iter()).copied().collect();
+    let ranges = compute_ranges(&range_xs, &left_ys, opt)?;
+    let (x_min, _, y_min, y_max) = ranges;
+    let (r_min, r_max) = right_axis_range(&right_ys, axis)?;
+    let log = axis.log_scale != 0;
+    // Left axis value at the height of right axis value `t` (in log10 units for log axes)
+    let to_left = |t: f64| y_min + (t - r_min) / (r_max - r_min) * (y_max - y_min);
+
+    let colors = palette(opt.color_scheme)?;
+    let left_color = color_from_argb(colors[0]);
+    let right_color = if axis.series_color == 0 {
+        color_from_argb(colors[1 % colors.len()])
+    } else {
+        color_from_argb(axis.series_color)
+    };
+
+    root.fill(&background_color(opt))
+        .map_err(|e| drawing_error("Failed to fill background", e))?;
+    let (width, _) = root.dim_in_pixel();
+    let plot_width = width.saturating_sub(RIGHT_AXIS_AREA_SIZE);
+    if plot_width == 0 {
+        return Err(format!(
+            "Width must exceed the {}-pixel right axis",
+            RIGHT_AXIS_AREA_SIZE
+        ));
+    }
+    let (plot_area, _) = root.split_horizontally(plot_width);
+    let plot_opt = opt.with_options(PlotOptions {
+        width: plot_width,
+        ..opt.opt
+    });
+    let mut chart = draw_frame(&plot_area, ranges, &plot_opt, XLabels::Numeric)?;
+
+    let stroke = opt.line_stroke_width.max(1);
+    let left_points = left_xs.iter().copied().zip(left_ys.iter().copied());
+    chart
+        .draw_series(LineSeries::new(left_points, left_color.stroke_width(stroke)))
+        .map_err(|e| drawing_error("Failed to draw line", e))?;
+    let right_points = right_xs
+        .iter()
+        .copied()
+        .zip(right_ys.iter().map(|&y| to_left(if log { y.log10() } else { y })));
+    chart
+        .draw_series(LineSeries::new(right_points, right_color.stroke_width(stroke)))
+        .map_err(|e| drawing_error("Failed to draw line", e))?;
+
+    // Right axis line with ticks and labels at Plotters' tick positions, or at whole
+    // decades if the axis is logarithmic
+    let keep_trailing_zeros = opt.keep_trailing_zeros != 0;
+    let r_spec = RangedCoordf64::from(r_min..r_max);
+    let mut ticks = r_spec.key_points(tick_count(opt.y_tick_count));
+    if log {
+        let decades: Vec<f64> = (r_min.ceil() as i64..=r_max.floor() as i64).map(|e| e as f64).collect();
+        if !decades.is_empty() {
+            ticks = decades;
+        }
+    }
+    format_tick(0.0, opt.y_tick_format)?;
+    let format = |t: f64| {
+        if log {
+            format_scientific(10f64.powf(t), keep_trailing_zeros)
+        } else if opt.y_scientific != 0 {
+            format_scientific(t, keep_trailing_zeros)
+        } else {
+            format_tick(t, opt.y_tick_format)
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| r_spec.format_ext(&t))
+        }
+    };
+
+    let (x_pixels, y_pixels) = chart.plotting_area().get_pixel_range();
+    let (base_x, base_y) = root.get_base_pixel();
+    let x = x_pixels.end - base_x;
+    let tick_style = axis_line_style(opt).unwrap_or_else(|| BLACK.stroke_width(1));
+    root.draw(&PathElement::new(
+        [(x, y_pixels.start - base_y), (x, y_pixels.end - base_y)],
+        tick_style,
+    ))
+    .map_err(|e| drawing_error("Failed to draw right axis", e))?;
+    let label_style = TextStyle::from(resolve_font(&opt.label_font_name, label_font_size(opt))?)
+        .color(&right_color)
+        .pos(Pos::new(HPos::Left, VPos::Center));
+    for t in ticks {
+        let (_, py) = chart.backend_coord(&(x_min, to_left(t)));
+        let y = py - base_y;
+        root.draw(&PathElement::new([(x, y), (x + TICK_MARK_SIZE, y)], tick_style))
+            .map_err(|e| drawing_error("Failed to draw right axis", e))?;
+        root.draw(&Text::new(format(t), (x + LABEL_GAP, y), label_style.clone()))
+            .map_err(|e| drawing_error("Failed to draw right axis", e))?;
+    }
+    if !name.is_empty() {
+        let name_style = TextStyle::from(resolve_styled_font(
+            &opt.label_font_name,
+            label_font_size(opt) + 2.0,
+            opt.axis_desc_font_style,
+        )?)
+        .color(&right_color)
+        .transform(FontTransform::Rotate90)
+        .pos(Pos::new(HPos::Center, VPos::Center));
+        let anchor = (width as i32 - LABEL_GAP, (y_pixels.start + y_pixels.end) / 2 - base_y);
+        root.draw(&Text::new(name, anchor, name_style))
+            .map_err(|e| drawing_error("Failed to draw right axis", e))?;
+    }
+    Ok(())
+}
+
+/// Draws the layers of a composite chart in order onto one set of axes.
+///
+/// Auto ranges cover the points of all scatter layers. Heatmap bins without points are
+/// left out so the layers below stay visible, and no colorbar is drawn.
+fn draw_composite<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    layers: &[Layer<'_>],
+    opt: &RenderOptions<'_>,
+) -> Result<(), String> {
+    let mut prepared = Vec::with_capacity(layers.len());
+    for (i, layer) in layers.iter().enumerate() {
+        let data = match layer {
+            Layer::Scatter(xs, ys, _) | Layer::Heatmap(xs, ys, _) => {
+                Some(prepare_data(xs, ys, opt).map_err(|e| format!("Layer {}: {}", i, e))?)
+            },
+            Layer::ReferenceLine(_) => None,
+        };
+        prepared.push(data);
+    }
+
+    let scatter_points = || {
+        layers
+            .iter()
+            .zip(&prepared)
+            .filter(|(layer, _)| matches!(layer, Layer::Scatter(..)))
+            .filter_map(|(_, data)| data.as_ref())
+    };
+    let all_xs: Vec<f64> = scatter_points().flat_map(|(xs, _)| xs.iter().copied()).collect();
+    let all_ys: Vec<f64> = scatter_points().flat_map(|(_, ys)| ys.iter().copied()).collect();
+    if opt.auto_range != 0 && all_xs.is_empty() {
+        return Err("Auto range requires a scatter layer with at least one point".to_string());
+    }
+    let ranges = compute_ranges(&all_xs, &all_ys, opt)?;
+    let (x_min, x_max, y_min, y_max) = ranges;
+
+    let mut chart = draw_frame(root, ranges, opt, XLabels::Numeric)?;
+    for (i, (layer, data)) in layers.iter().zip(&prepared).enumerate() {
+        match (layer, data) {
+            (Layer::Scatter(_, _, style), Some((xs, ys))) => {
+                let color = if style.color == 0 {
+                    BLUE.mix(opt.marker_alpha as f64 / 255.0)
+                } else {
+                    color_from_argb(style.color)
+                };
+                let radius = if style.marker_radius == 0 {
+                    opt.marker_radius
+                } else {
+                    style.marker_radius
+                };
+                let points = xs.iter().copied().zip(ys.iter().copied());
+                if opt.antialias != 0 {
+                    draw_antialiased_circles(root, &chart, points, radius, color)?;
+                } else {
+                    draw_circle_markers(root, &chart, points, radius, color)?;
+                }
+            },
+            (Layer::Heatmap(_, _, style), Some((xs, ys))) => {
+                let opacity = if style.opacity == 0.0 { 0.5 } else { style.opacity };
+                if !(opacity > 0.0 && opacity <= 1.0) {
+                    return Err(format!(
+                        "Layer {}: Heatmap opacity must be in (0, 1], got {}",
+                        i, style.opacity
+                    ));
+                }
+                let default_bins = (xs.len() as f64).sqrt().ceil().max(1.0) as usize;
+                let bins_or_default = |n: u32| if n == 0 { default_bins } else { n as usize };
+                let (nx, ny) = (bins_or_default(style.n_bins_x), bins_or_default(style.n_bins_y));
+                if nx.checked_mul(ny).is_none_or(|total| total > MAX_HISTOGRAM2D_BINS) {
+                    return Err(format!(
+                        "Layer {}: Too many bins: {} x {} exceeds {}",
+                        i, nx, ny, MAX_HISTOGRAM2D_BINS
+                    ));
+                }
+                let style = BinStyle {
+                    colormap: viridis,
+                    opacity,
+                    draw_empty: false,
+                };
+                draw_density_bins(&mut chart, xs, ys, ranges, (nx, ny), style)?;
+            },
+            (Layer::ReferenceLine(line), _) => {
+                if !line.value.is_finite() {
+                    return Err(format!("Layer {}: Reference line value must be finite", i));
+                }
+                let ends = match line.orientation {
+                    PLOT_REFERENCE_HORIZONTAL => [(x_min, line.value), (x_max, line.value)],
+                    PLOT_REFERENCE_VERTICAL => [(line.value, y_min), (line.value, y_max)],
+                    other => {
+                        return Err(format!(
+                            "Layer {}: Unsupported reference line orientation: {}",
+                            i, other
+                        ));
+                    },
+                };
+                let color = if line.color == 0 {
+                    BLACK.to_rgba()
+                } else {
+                    color_from_argb(line.color)
+                };
+                chart
+                    .draw_series(std::iter::once(PathElement::new(
+                        ends,
+                        color.stroke_width(line.line_width.max(1)),
+                    )))
+                    .map_err(|e| drawing_error("Failed to draw reference line", e))?;
+            },
+            _ => unreachable!("point layers are always prepared"),
+        }
+    }
+    Ok(())
+}
+
+/// Lower and upper error magnitudes, one per point; `None` sides have zero length.
+#[doc(hidden)]
+pub type ErrorBounds<'a> = (Option<&'a [f64]>, Option<&'a [f64]>);
+
+/// Draws a scatter chart with error bars around each point.
+///
+/// Bars reach from the point minus the lower error to the point plus the upper error.
+/// Points with a non-finite coordinate or error follow `opt.nan_policy`. Auto ranges
+/// cover the bar ends.
+fn draw_errorbars<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    xs: &[f64],
+    ys: &[f64],
+    (x_err, y_err): (ErrorBounds<'_>, ErrorBounds<'_>),
+    style: &ErrorBarOptions,
+    opt: &RenderOptions<'_>,
+) -> Result<(), String> {
+    if xs.is_empty() {
+        return Err("Point count must be greater than zero".to_string());
+    }
+    let (has_x, has_y) = (x_err != (None, None), y_err != (None, None));
+    if !has_x && !has_y {
+        return Err("At least one of x_err and y_err must be given".to_string());
+    }
+    let err_at = |errors: Option<&[f64]>, i: usize| errors.map_or(0.0, |e| e[i]);
+
+    // (x, y, [x lower, x upper, y lower, y upper] errors) of every point to draw
+    let skip = skips_non_finite(opt)?;
+    let mut points = Vec::with_capacity(xs.len());
+    for (i, (&x, &y)) in xs.iter().zip(ys).enumerate() {
+        let errors = [x_err.0, x_err.1, y_err.0, y_err.1].map(|e| err_at(e, i));
+        if errors.iter().any(|&e| e < 0.0) {
+            return Err(format!("Negative error at index {}", i));
+        }
+        if [x, y].iter().chain(&errors).all(|v| v.is_finite()) {
+            points.push((x, y, errors));
+        } else if !skip {
+            let values: Vec<f64> = [x, y].into_iter().chain(errors).collect();
+            return Err(non_finite_error(&values, "value", i));
+        }
+    }
+    if points.is_empty() {
+        return Err("No valid (non-NaN, finite) data points after filtering".to_string());
+    }
+
+    let range_xs: Vec<f64> = points.iter().flat_map(|&(x, _, e)| [x - e[0], x + e[1]]).collect();
+    let range_ys: Vec<f64> = points.iter().flat_map(|&(_, y, e)| [y - e[2], y + e[3]]).collect();
+    let ranges = compute_ranges(&range_xs, &range_ys, opt)?;
+    let chart = draw_frame(root, ranges, opt, XLabels::Numeric)?;
+
+    let marker_color = BLUE.mix(opt.marker_alpha as f64 / 255.0);
+    let bar_color = if style.color == 0 {
+        marker_color
+    } else {
+        color_from_argb(style.color)
+    };
+    let bar_style = bar_color.stroke_width(style.line_width.max(1));
+    let (base_x, base_y) = root.get_base_pixel();
+    let to_pixel = |p: (f64, f64)| {
+        let (px, py) = chart.backend_coord(&p);
+        (px - base_x, py - base_y)
+    };
+    let half_cap = style.cap_size_px as i32 / 2;
+    let draw_bar = |from: (f64, f64), to: (f64, f64), vertical: bool| -> Result<(), String> {
+        let (a, b) = (to_pixel(from), to_pixel(to));
+        root.draw(&PathElement::new([a, b], bar_style))
+            .map_err(|e| drawing_error("Failed to draw error bars", e))?;
+        if half_cap == 0 {
+            return Ok(());
+        }
+        for (x, y) in [a, b] {
+            let cap = if vertical {
+                [(x - half_cap, y), (x + half_cap, y)]
+            } else {
+                [(x, y - half_cap), (x, y + half_cap)]
+            };
+            root.draw(&PathElement::new(cap, bar_style))
+                .map_err(|e| drawing_error("Failed to draw error bars", e))?;
+        }
+        Ok(())
+    };
+    for &(x, y, e) in &points {
+        if has_y {
+            draw_bar((x, y - e[2]), (x, y + e[3]), true)?;
+        }
+        if has_x {
+            draw_bar((x - e[0], y), (x + e[1], y), false)?;
+        }
+    }
+
+    let centres = points.iter().map(|&(x, y, _)| (x, y));
+    draw_circle_markers(root, &chart, centres, opt.marker_radius, marker_color)
+}
+
+/// Draws the moving average of the points as an orange line, if `opt.moving_avg_window` is set.
+fn draw_moving_average<DB: DrawingBackend>(
+    chart: &mut Chart<'_, DB>,
+    xs: &[f64],
+    ys: &[f64],
+    opt: &PlotOptions,
+) -> Result<(), String> {
+    if opt.moving_avg_window == 0 {
+        return Ok(());
+    }
+    let smoothed = moving_average(xs, ys, opt.moving_avg_window as usize)?;
+    chart
+        .draw_series(LineSeries::new(smoothed, RGBColor(255, 165, 0).stroke_width(2)))
+        .map_err(|e| drawing_error("Failed to draw moving average", e))?;
+    Ok(())
+}
+
+/// Computes a centered moving average of Y over the points sorted by X.
+///
+/// The window narrows at both ends instead of padding with zeros.
+fn moving_average(xs: &[f64], ys: &[f64], window: usize) -> Result<Vec<(f64, f64)>, String> {
+    if window > xs.len() {
+        return Err(format!(
+            "Moving average window ({}) exceeds point count ({})",
+            window,
+            xs.len()
+        ));
+    }
+
+    let mut points: Vec<(f64, f64)> = xs.iter().copied().zip(ys.iter().copied()).collect();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // Prefix sums make each window average O(1)
+    let mut prefix = Vec::with_capacity(points.len() + 1);
+    prefix.push(0.0);
+    for &(_, y) in &points {
+        prefix.push(prefix[prefix.len() - 1] + y);
+    }
+
+    let before = (window - 1) / 2;
+    let after = window / 2;
+    Ok(points
+        .iter()
+        .enumerate()
+        .map(|(i, &(x, _))| {
+            let start = i.saturating_sub(before);
+            let end = (i + after + 1).min(points.len());
+            (x, (prefix[end] - prefix[start]) / (end - start) as f64)
+        })
+        .collect())
+}
+
+/// Returns the alternating on/off lengths in pixels for a dash pattern; empty means solid.
+fn dash_pattern(pattern: u8) -> Result<&'static [f64], String> {
+    match pattern {
+        PLOT_DASH_SOLID => Ok(&[]),
+        PLOT_DASH_DASHED => Ok(&[8.0, 4.0]),
+        PLOT_DASH_DOTTED => Ok(&[2.0, 4.0]),
+        PLOT_DASH_DASH_DOT => Ok(&[8.0, 4.0, 2.0, 4.0]),
+        _ => Err(format!("Unsupported line dash pattern: {}", pattern)),
+    }
+}
+
+/// Splits a polyline into the visible pieces of a dash pattern.
+///
+/// The pattern is measured in pixels along the line (via `to_pixel`) and continues across
+/// vertices, so dashes keep their length however densely the data is sampled. Because
+/// the data-to-pixel mapping is affine, the returned pieces stay in data coordinates.
+fn dash_polyline<F>(vertices: &[(f64, f64)], to_pixel: F, pattern: &[f64]) -> Vec<Vec<(f64, f64)>>
+where
+    F: Fn(&(f64, f64)) -> (f64, f64),
+{
+    let mut paths = Vec::new();
+    let mut current: Vec<(f64, f64)> = Vec::new();
+    let mut index = 0;
+    let mut remaining = pattern[0];
+
+    if let Some(first) = vertices.first() {
+        current.push(*first);
+    }
+    for pair in vertices.windows(2) {
+        let (start, end) = (pair[0], pair[1]);
+        let (p0, p1) = (to_pixel(&start), to_pixel(&end));
+        let length = (p1.0 - p0.0).hypot(p1.1 - p0.1);
+        let lerp = |t: f64| (start.0 + (end.0 - start.0) * t, start.1 + (end.1 - start.1) * t);
+
+        let mut travelled = 0.0;
+        while length - travelled > remaining {
+            travelled += remaining;
+            // Even entries are dashes, odd entries gaps: a dash ends here or a new one starts
+            current.push(lerp(travelled / length));
+            if index % 2 == 0 {
+                paths.push(std::mem::take(&mut current));
+            }
+            index = (index + 1) % pattern.len();
+            remaining = pattern[index];
+        }
+        remaining -= length - travelled;
+        if index % 2 == 0 {
+            current.push(end);
+        }
+    }
+    if current.len() > 1 {
+        paths.push(current);
+    }
+    paths
+}
+
+/// Expands points into line vertices according to the line mode.
+///
+/// Interpolated modes sample the curve every `step` along X.
+fn line_vertices(points: &[(f64, f64)], mode: u8, step: f64) -> Result<Vec<(f64, f64)>, String> {
+    match mode {
+        PLOT_LINE_LINEAR => Ok(points.to_vec()),
+        PLOT_LINE_MONOTONE_CUBIC => interpolation::monotone_cubic(points, step),
+        PLOT_LINE_STEP_RIGHT | PLOT_LINE_STEP_LEFT => {
+            let mut vertices = Vec::with_capacity(points.len() * 2);
+            for (i, &(x, y)) in points.iter().enumerate() {
+                if i > 0 {
+                    let (prev_x, prev_y) = points[i - 1];
+                    // Corner between the previous point and this one
+                    if mode == PLOT_LINE_STEP_RIGHT {
+                        vertices.push((x, prev_y));
+                    } else {
+                        vertices.push((prev_x, y));
+                    }
+                }
+                vertices.push((x, y));
+            }
+            Ok(vertices)
+        },
+        _ => Err(format!("Unsupported line mode: {}", mode)),
+    }
+}
+
+/// Draws a complete polar scatter chart onto `root`.
+///
+/// Points are given as angles in radians and radii and are converted to Cartesian
+/// coordinates. The plotting area is square and centred, with rings at evenly spaced
+/// radii, radial lines every 45° and angle labels around the outside.
+fn draw_polar<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    angles: &[f64],
+    radii: &[f64],
+    opt: &PlotOptions,
+) -> Result<(), String> {
+    let (angles, radii) = prepare_data(angles, radii, opt)?;
+    let points: Vec<(f64, f64)> = angles
+        .iter()
+        .zip(radii.iter())
+        .map(|(&theta, &r)| (r * theta.cos(), r * theta.sin()))
+        .collect();
+
+    let r_max = radii.iter().fold(0.0f64, |acc, r| acc.max(r.abs()));
+    let r_max = if r_max > 0.0 { r_max } else { 1.0 };
+    let extent = r_max * 1.05;
+
+    // Square plotting area centred in the image, leaving room for the angle labels
+    let (width, height) = root.dim_in_pixel();
+    let side = width.min(height).saturating_sub(2 * POLAR_LABEL_MARGIN);
+    let margin_x = (width - side) / 2;
+    let margin_y = (height - side) / 2;
+
+    root.fill(&background_color(opt))
+        .map_err(|e| drawing_error("Failed to fill background", e))?;
+
+    let mut chart = ChartBuilder::on(root)
+        .margin_left(margin_x)
+        .margin_right(width - side - margin_x)
+        .margin_top(margin_y)
+        .margin_bottom(height - side - margin_y)
+        .build_cartesian_2d(-extent..extent, -extent..extent)
+        .map_err(|e| drawing_error("Failed to build chart", e))?;
+
+    // Concentric rings
+    let grid_style = RGBColor(200, 200, 200).stroke_width(1);
+    let rings = if opt.polar_grid_rings == 0 {
+        DEFAULT_POLAR_GRID_RINGS
+    } else {
+        opt.polar_grid_rings
+    };
+    for k in 1..=rings {
+        let r = r_max * k as f64 / rings as f64;
+        let circle = (0..=360).map(|deg| {
+            let theta = (deg as f64).to_radians();
+            (r * theta.cos(), r * theta.sin())
+        });
+        chart
+            .draw_series(LineSeries::new(circle, grid_style))
+            .map_err(|e| drawing_error("Failed to draw grid", e))?;
+    }
+
+    // Radial lines with angle labels just outside the outer ring
+    let text = text_color(opt);
+    let label_style = TextStyle::from(resolve_font(&opt.label_font_name, label_font_size(opt))?)
+        .color(&text)
+        .pos(Pos::new(HPos::Center, VPos::Center));
+    let (base_x, base_y) = root.get_base_pixel();
+    let (center_x, center_y) = chart.backend_coord(&(0.0, 0.0));
+    let (edge_x, _) = chart.backend_coord(&(r_max, 0.0));
+    let label_radius = (edge_x - center_x + 2 * LABEL_GAP) as f64;
+    for deg in (0..360).step_by(45) {
+        let theta = (deg as f64).to_radians();
+        chart
+            .draw_series(LineSeries::new(
+                [(0.0, 0.0), (r_max * theta.cos(), r_max * theta.sin())],
+                grid_style,
+            ))
+            .map_err(|e| drawing_error("Failed to draw grid", e))?;
+
+        let anchor = (
+            center_x - base_x + (label_radius * theta.cos()).round() as i32,
+            center_y - base_y - (label_radius * theta.sin()).round() as i32,
+        );
+        root.draw(&Text::new(format!("{}°", deg), anchor, label_style.clone()))
+            .map_err(|e| drawing_error("Failed to draw labels", e))?;
+    }
+
+    let marker_radius = opt.marker_radius as i32;
+    let marker_color = BLUE.mix(opt.marker_alpha as f64 / 255.0);
+    chart
+        .draw_series(
+            points
+                .into_iter()
+                .map(|p| Circle::new(p, marker_radius, marker_color.filled())),
+        )
+        .map_err(|e| drawing_error("Failed to draw points", e))?;
+
+    Ok(())
+}
+
+/// Chart context with the f64 Cartesian coordinates used by all charts.
+type Chart<'a, DB> = ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>;
+
+/// Number of ticks to place on an axis, given its `*_tick_count` option.
+fn tick_count(option: u32) -> usize {
+    if option == 0 {
+        DEFAULT_LABEL_COUNT
+    } else {
+        option as usize
+    }
+}
+
+/// Returns how many automatic X labels fit on an axis `axis_width` pixels wide.
+///
+/// Starts at `DEFAULT_LABEL_COUNT` and halves the count, down to `MIN_LABEL_COUNT`,
+/// while the estimated label widths (`LABEL_CHAR_WIDTH` font sizes per character)
+/// add up to more than the axis length.
+fn fit_label_count(x_spec: &RangedCoordf64, format: &dyn Fn(&f64) -> String, font_size: f64, axis_width: u32) -> usize {
+    let mut count = DEFAULT_LABEL_COUNT;
+    while count > MIN_LABEL_COUNT {
+        let width: f64 = x_spec
+            .key_points(count)
+            .iter()
+            .map(|v| format(v).chars().count() as f64 * font_size * LABEL_CHAR_WIDTH)
+            .sum();
+        if width <= axis_width as f64 {
+            break;
+        }
+        count = (count / 2).max(MIN_LABEL_COUNT);
+    }
+    count
+}
+
+/// Shades the bands of `opt.h_bands` and `opt.v_bands`, clipped to the axis ranges.
+fn draw_bands<DB: DrawingBackend>(
+    chart: &mut Chart<'_, DB>,
+    (x_min, x_max, y_min, y_max): (f64, f64, f64, f64),
+    opt: &RenderOptions<'_>,
+) -> Result<(), String> {
+    let (h_bands, v_bands) = (opt.h_bands, opt.v_bands);
+    // Edges in either order, clipped to the axis range; None when nothing is left
+    let clip = |kind: &str, i: usize, (lo, hi): (f64, f64), (a, b): (f64, f64)| {
+        if !lo.is_finite() || !hi.is_finite() {
+            return Err(format!("{} band {}: edges must be finite", kind, i));
+        }
+        let (lo, hi) = (lo.min(hi).max(a.min(b)), lo.max(hi).min(a.max(b)));
+        Ok((lo < hi).then_some((lo, hi)))
+    };
+
+    let mut rects = Vec::with_capacity(h_bands.len() + v_bands.len());
+    for (i, band) in h_bands.iter().enumerate() {
+        if let Some((lo, hi)) = clip("Horizontal", i, (band.lo, band.hi), (y_min, y_max))? {
+            rects.push(Rectangle::new(
+                [(x_min, lo), (x_max, hi)],
+                color_from_argb(band.color).filled(),
+            ));
+        }
+    }
+    for (i, band) in v_bands.iter().enumerate() {
+        if let Some((lo, hi)) = clip("Vertical", i, (band.lo, band.hi), (x_min, x_max))? {
+            rects.push(Rectangle::new(
+                [(lo, y_min), (hi, y_max)],
+                color_from_argb(band.color).filled(),
+            ));
+        }
+    }
+    chart
+        .draw_series(rects)
+        .map_err(|e| drawing_error("Failed to draw band", e))?;
+    Ok(())
+}
+
+/// Views a pointer and count from `PlotOptions` as a slice, empty when the pointer is null.
+///
+/// # Safety
+/// A non-null `ptr` must point to `n` values that outlive the returned slice.
+unsafe fn option_slice<'a, T>(ptr: *const T, n: u32) -> &'a [T] {
+    if ptr.is_null() || n == 0 {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(ptr, n as usize) }
+    }
+}
+
+/// Returns the explicit tick positions inside `a..=b`, or `None` when automatic ticks are used.
+fn custom_ticks(ticks: &[f64], a: f64, b: f64) -> Option<Vec<f64>> {
+    if ticks.is_empty() {
+        return None;
+    }
+    let (lo, hi) = (a.min(b), a.max(b));
+    Some(ticks.iter().copied().filter(|v| (lo..=hi).contains(v)).collect())
+}
+
+/// Builds the chart coordinate system for the given ranges and layout without drawing anything.
+fn build_chart<'a, DB: DrawingBackend>(
+    root: &'a DrawingArea<DB, Shift>,
+    (x_min, x_max, y_min, y_max): (f64, f64, f64, f64),
+    opt: &PlotOptions,
+    layout: &ChartLayout,
+) -> Result<Chart<'a, DB>, String> {
+    // Inverted axes are built from reversed ranges
+    let x_axis = if opt.invert_x != 0 { x_max..x_min } else { x_min..x_max };
+    let y_axis = if opt.invert_y != 0 { y_max..y_min } else { y_min..y_max };
+
+    // Build chart with label areas
+    ChartBuilder::on(root)
+        .margin_top(layout.margin_top)
+        .margin_bottom(layout.margin_bottom)
+        .margin_left(layout.margin_left)
+        .margin_right(layout.margin_right)
+        .x_label_area_size(layout.x_label_area_size)
+        .y_label_area_size(layout.y_label_area_size)
+        .build_cartesian_2d(x_axis, y_axis)
+        .map_err(|e| drawing_error("Failed to build chart", e))
+}
+
+/// Builds the coordinate system `plot_scatter_png()` would draw into and passes it to `f`,
+/// without rendering anything.
+///
+/// Only explicit ranges are supported, since auto-ranging depends on the data. Backend
+/// coordinates of the chart exclude the outer padding.
+fn with_chart_coords<R>(opt: PlotOptions, f: impl FnOnce(&Chart<'_, SVGBackend<'_>>) -> R) -> Result<R, String> {
+    let opt = with_global_defaults(opt);
+    if opt.auto_range != 0 {
+        return Err("Pixel mapping requires explicit axis ranges (auto_range = 0)".to_string());
+    }
+    if opt.width == 0 || opt.height == 0 {
+        return Err("Width and height must be greater than zero".to_string());
+    }
+    check_plot_area_fraction(opt.plot_area_fraction)?;
+    let ranges = compute_ranges(&[], &[], &opt)?;
+    let (x_min, x_max, y_min, y_max) = ranges;
+    let layout = compute_layout(&opt, x_max - x_min, y_max - y_min);
+
+    // Nothing is drawn, so the SVG output stays empty
+    let mut svg = String::new();
+    let root = SVGBackend::with_string(&mut svg, (opt.width, opt.height)).into_drawing_area();
+    let chart = build_chart(&root, ranges, &opt, &layout)?;
+    Ok(f(&chart))
+}
+
+/// Maps a data point to the pixel it is drawn at in a scatter plot rendered with `opt`.
+fn data_to_pixel(opt: PlotOptions, x: f64, y: f64) -> Result<(u32, u32), String> {
+    let padding = with_global_defaults(opt).outer_padding;
+    let inside = (opt.x_min..=opt.x_max).contains(&x) && (opt.y_min..=opt.y_max).contains(&y);
+    let (px, py) = with_chart_coords(opt, |chart| chart.backend_coord(&(x, y)))?;
+    if !inside {
+        return Err("Point outside axis range".to_string());
+    }
+    Ok((px as u32 + padding, py as u32 + padding))
+}
+
+/// Maps a pixel of a scatter plot rendered with `opt` back to data coordinates.
+///
+/// The inverse of `data_to_pixel`: mapping the result back gives the same pixel.
+fn pixel_to_data(opt: PlotOptions, px: u32, py: u32) -> Result<(f64, f64), String> {
+    let padding = with_global_defaults(opt).outer_padding;
+    let backend = (px.checked_sub(padding), py.checked_sub(padding));
+    with_chart_coords(opt, |chart| match backend {
+        (Some(x), Some(y)) => {
+            let (x, y) = (i32::try_from(x).ok()?, i32::try_from(y).ok()?);
+            chart.as_coord_spec().reverse_translate((x, y))
+        },
+        _ => None,
+    })?
+    .ok_or_else(|| "Pixel is in margin area".to_string())
+}
+
+/// Finds the point nearest to pixel `(px, py)` of a scatter plot rendered with `opt`.
+///
+/// The pixel is converted to data coordinates, and the points are ranked by their
+/// distance to it in data space, so an axis with a larger range counts for less per
+/// pixel. Points with a non-finite coordinate are skipped. Returns the index of the
+/// nearest point and its distance from the pixel in pixels; ties go to the first point.
+fn nearest_point(opt: PlotOptions, xs: &[f64], ys: &[f64], px: u32, py: u32) -> Result<(usize, f64), String> {
+    if xs.is_empty() {
+        return Err("Point count (n) must be greater than zero".to_string());
+    }
+    let (qx, qy) = pixel_to_data(opt, px, py)?;
+    let mut nearest: Option<(usize, f64)> = None;
+    for (i, (&x, &y)) in xs.iter().zip(ys).enumerate() {
+        if !x.is_finite() || !y.is_finite() {
+            continue;
+        }
+        let dist = (x - qx).hypot(y - qy);
+        if nearest.is_none_or(|(_, best)| dist < best) {
+            nearest = Some((i, dist));
+        }
+    }
+    let (index, _) = nearest.ok_or_else(|| "No point has finite coordinates".to_string())?;
+
+    let padding = with_global_defaults(opt).outer_padding as f64;
+    let (bx, by) = with_chart_coords(opt, |chart| chart.backend_coord(&(xs[index], ys[index])))?;
+    let dist_px = (bx as f64 + padding - px as f64).hypot(by as f64 + padding - py as f64);
+    Ok((index, dist_px))
+}
+
+/// Finds the points drawn within `radius` pixels of pixel `(px, py)` of a scatter plot
+/// rendered with `opt`.
+///
+/// Distances are measured between pixels, so both axes count equally whatever their
+/// ranges, and points exactly `radius` away are included. Returns the indices ordered by
+/// ascending distance; ties keep index order.
+fn points_in_radius(
+    opt: PlotOptions,
+    xs: &[f64],
+    ys: &[f64],
+    px: u32,
+    py: u32,
+    radius: f64,
+) -> Result<Vec<usize>, String> {
+    if radius.is_nan() || radius < 0.0 {
+        return Err(format!("Radius must be non-negative, got {}", radius));
+    }
+    let padding = with_global_defaults(opt).outer_padding as f64;
+    let (qx, qy) = (px as f64 - padding, py as f64 - padding);
+    let mut hits = with_chart_coords(opt, |chart| {
+        xs.iter()
+            .zip(ys)
+            .enumerate()
+            .filter(|(_, (x, y))| x.is_finite() && y.is_finite())
+            .filter_map(|(i, (&x, &y))| {
+                let (bx, by) = chart.backend_coord(&(x, y));
+                let dist = (bx as f64 - qx).hypot(by as f64 - qy);
+                (dist <= radius).then_some((i, dist))
+            })
+            .collect::<Vec<_>>()
+    })?;
+    hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+    Ok(hits.into_iter().map(|(i, _)| i).collect())
+}
+
+/// Fills the background and draws the axes, mesh and tick labels for the given ranges.
+///
+/// Returns the chart so the caller can draw its series on top.
+fn draw_frame<'a, DB: DrawingBackend>(
+    root: &'a DrawingArea<DB, Shift>,
+    (x_min, x_max, y_min, y_max): (f64, f64, f64, f64),
+    opt: &RenderOptions<'_>,
+    x_labels: XLabels,
+) -> Result<Chart<'a, DB>, String> {
+    // Validate label rotations
+    let x_rotation = parse_label_rotation(opt.x_label_rotation)?;
+    let y_rotation = parse_label_rotation(opt.y_label_rotation)?;
+
+    let title = fixed_str(&opt.title)?;
+    check_plot_area_fraction(opt.plot_area_fraction)?;
+    let layout = compute_layout(opt, x_max - x_min, y_max - y_min);
+
+    root.fill(&background_color(opt))
+        .map_err(|e| drawing_error("Failed to fill background", e))?;
+
+    // Title centred in the space reserved above the plotting area
+    if !title.is_empty() {
+        let (width, _) = root.dim_in_pixel();
+        let text = text_color(opt);
+        let title_style = TextStyle::from(resolve_styled_font(&opt.title_font_name, 20.0, opt.title_font_style)?)
+            .color(&text)
+            .pos(Pos::new(HPos::Center, VPos::Center));
+        let anchor = ((width / 2) as i32, (layout.margin_top - TITLE_AREA_SIZE / 2) as i32);
+        root.draw(&Text::new(title, anchor, title_style))
+            .map_err(|e| drawing_error("Failed to draw title", e))?;
+    }
+
+    let mut chart = build_chart(root, (x_min, x_max, y_min, y_max), opt, &layout)?;
+
+    // Tick label formatters
+    let keep_trailing_zeros = opt.keep_trailing_zeros != 0;
+    let x_spec = chart.as_coord_spec().x_spec().clone();
+    let y_spec = chart.as_coord_spec().y_spec().clone();
+    // Validate the tick formats up front so the formatters below cannot fail
+    format_tick(0.0, opt.x_tick_format)?;
+    format_tick(0.0, opt.y_tick_format)?;
+    let format_x = |v: &f64| match x_labels {
+        XLabels::Timestamp(items) => format_timestamp(*v, items),
+        XLabels::Categories(names) => {
+            let index = v.round();
+            if (v - index).abs() < 1e-9 && index >= 0.0 && (index as usize) < names.len() {
+                names[index as usize].to_string()
+            } else {
+                String::new()
+            }
+        },
+        XLabels::Numeric if opt.x_scientific != 0 => format_scientific(*v, keep_trailing_zeros),
+        XLabels::Numeric => format_tick(*v, opt.x_tick_format)
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| x_spec.format_ext(v)),
+    };
+    let format_y = |v: &f64| {
+        if opt.y_scientific != 0 {
+            format_scientific(*v, keep_trailing_zeros)
+        } else {
+            format_tick(*v, opt.y_tick_format)
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| y_spec.format_ext(v))
+        }
+    };
+    let hide_label = |_: &f64| String::new();
+    let x_custom = custom_ticks(opt.x_ticks, x_min, x_max);
+    let y_custom = custom_ticks(opt.y_ticks, y_min, y_max);
+
+    // Configure and draw mesh (ticks/grid) with bundled font
+    let label_size = label_font_size(opt);
+    let label_font = resolve_font(&opt.label_font_name, label_size)?;
+    let axis_desc_font = resolve_styled_font(&opt.label_font_name, label_size + 2.0, opt.axis_desc_font_style)?;
+    let text = text_color(opt);
+    let label_style = TextStyle::from(label_font.clone()).color(&text);
+    let x_label_count = if opt.x_tick_count != 0 {
+        opt.x_tick_count as usize
+    } else if opt.disable_label_skip == 0 && opt.x_label_rotation == 0 {
+        let axis_width = chart.plotting_area().dim_in_pixel().0;
+        fit_label_count(&x_spec, &format_x, label_size, axis_width)
+    } else {
+        DEFAULT_LABEL_COUNT
+    };
+    let mut mesh = chart.configure_mesh();
+    mesh.label_style(label_style.clone())
+        .axis_desc_style(TextStyle::from(axis_desc_font).color(&text));
+    if let Some(style) = axis_line_style(opt) {
+        mesh.axis_style(style);
+    }
+    // Explicit ticks are drawn after the mesh, so Plotters draws none of its own
+    if x_custom.is_some() {
+        mesh.x_labels(0);
+    } else {
+        mesh.x_labels(x_label_count);
+    }
+    if y_custom.is_some() {
+        mesh.y_labels(0);
+    } else if opt.y_tick_count != 0 {
+        mesh.y_labels(opt.y_tick_count as usize);
+    }
+    if opt.x_minor_tick_count != 0 {
+        mesh.x_max_light_lines(opt.x_minor_tick_count as usize);
+    }
+    if opt.y_minor_tick_count != 0 {
+        mesh.y_max_light_lines(opt.y_minor_tick_count as usize);
+    }
+    if opt.grid_color != 0 {
+        let bold = color_from_argb(opt.grid_color);
+        let light = RGBAColor(bold.0, bold.1, bold.2, bold.3 / 2.0);
+        mesh.bold_line_style(bold).light_line_style(light);
+    }
+    if opt.hide_grid != 0 {
+        mesh.disable_mesh();
+    }
+    match &x_rotation {
+        LabelRotation::Native(transform) => mesh
+            .x_label_style(label_style.clone().transform(transform.clone()))
+            .x_label_formatter(&format_x),
+        LabelRotation::Diagonal(_) => mesh.x_label_formatter(&hide_label),
+    };
+    match &y_rotation {
+        LabelRotation::Native(transform) => mesh
+            .y_label_style(label_style.clone().transform(transform.clone()))
+            .y_label_formatter(&format_y),
+        LabelRotation::Diagonal(_) => mesh.y_label_formatter(&hide_label),
+    };
+    mesh.draw().map_err(|e| drawing_error("Failed to draw mesh", e))?;
+    draw_bands(&mut chart, (x_min, x_max, y_min, y_max), opt)?;
+
+    // Backend coordinates are absolute, while `root` may be a sub-area of the image
+    let (x_pixels, y_pixels) = chart.plotting_area().get_pixel_range();
+    let (base_x, base_y) = root.get_base_pixel();
+
+    // Explicit ticks: grid line, tick mark and (unless diagonal) label for each value
+    let grid_style = if opt.grid_color != 0 {
+        color_from_argb(opt.grid_color).stroke_width(1)
+    } else {
+        BLACK.mix(0.2).stroke_width(1)
+    };
+    let tick_style = axis_line_style(opt).unwrap_or_else(|| BLACK.stroke_width(1));
+    for &value in x_custom.iter().flatten() {
+        if opt.hide_grid == 0 {
+            chart
+                .draw_series(LineSeries::new([(value, y_min), (value, y_max)], grid_style))
+                .map_err(|e| drawing_error("Failed to draw ticks", e))?;
+        }
+        let (px, _) = chart.backend_coord(&(value, y_min));
+        let (x, y) = (px - base_x, y_pixels.end - base_y);
+        root.draw(&PathElement::new([(x, y), (x, y + TICK_MARK_SIZE)], tick_style))
+            .map_err(|e| drawing_error("Failed to draw ticks", e))?;
+        if let LabelRotation::Native(transform) = &x_rotation {
+            let style = label_style
+                .transform(transform.clone())
+                .pos(Pos::new(HPos::Center, VPos::Top));
+            root.draw(&Text::new(format_x(&value), (x, y + LABEL_GAP), style))
+                .map_err(|e| drawing_error("Failed to draw ticks", e))?;
+        }
+    }
+    for &value in y_custom.iter().flatten() {
+        if opt.hide_grid == 0 {
+            chart
+                .draw_series(LineSeries::new([(x_min, value), (x_max, value)], grid_style))
+                .map_err(|e| drawing_error("Failed to draw ticks", e))?;
+        }
+        let (_, py) = chart.backend_coord(&(x_min, value));
+        let (x, y) = (x_pixels.start - base_x, py - base_y);
+        root.draw(&PathElement::new([(x - TICK_MARK_SIZE, y), (x, y)], tick_style))
+            .map_err(|e| drawing_error("Failed to draw ticks", e))?;
+        if let LabelRotation::Native(transform) = &y_rotation {
+            let style = label_style
+                .transform(transform.clone())
+                .pos(Pos::new(HPos::Right, VPos::Center));
+            root.draw(&Text::new(format_y(&value), (x - LABEL_GAP, y), style))
+                .map_err(|e| drawing_error("Failed to draw ticks", e))?;
+        }
+    }
+
+    // Zero lines go over the grid; data drawn after the frame covers them
+    let zero_style = if opt.zero_line_color == 0 {
+        BLACK.stroke_width(1)
+    } else {
+        color_from_argb(opt.zero_line_color).stroke_width(1)
+    };
+    let contains_zero = |a: f64, b: f64| a.min(b) <= 0.0 && 0.0 <= a.max(b);
+    if opt.draw_zero_x_line != 0 && contains_zero(x_min, x_max) {
+        chart
+            .draw_series(std::iter::once(PathElement::new(
+                [(0.0, y_min), (0.0, y_max)],
+                zero_style,
+            )))
+            .map_err(|e| drawing_error("Failed to draw zero line", e))?;
+    }
+    if opt.draw_zero_y_line != 0 && contains_zero(y_min, y_max) {
+        chart
+            .draw_series(std::iter::once(PathElement::new(
+                [(x_min, 0.0), (x_max, 0.0)],
+                zero_style,
+            )))
+            .map_err(|e| drawing_error("Failed to draw zero line", e))?;
+    }
+
+    if let LabelRotation::Diagonal(angle) = x_rotation {
+        let values = x_custom.unwrap_or_else(|| x_spec.key_points(x_label_count));
+        for value in values {
+            let (px, _) = chart.backend_coord(&(value, y_min));
+            // Hang the label below the axis: clockwise labels start at the tick, others end there
+            let anchor = (px - base_x, y_pixels.end + LABEL_GAP - base_y);
+            draw_rotated_text(root, &format_x(&value), &label_style, anchor, angle, angle < 0.0)?;
+        }
+    }
+    if let LabelRotation::Diagonal(angle) = y_rotation {
+        let values = y_custom.unwrap_or_else(|| y_spec.key_points(tick_count(opt.y_tick_count)));
+        for value in values {
+            let (_, py) = chart.backend_coord(&(x_min, value));
+            let anchor = (x_pixels.start - LABEL_GAP - base_x, py - base_y);
+            draw_rotated_text(root, &format_y(&value), &label_style, anchor, angle, true)?;
+        }
+    }
+
+    Ok(chart)
+}
+
+// PNG files start with an 8-byte signature followed by the 25-byte IHDR chunk
+const PNG_SIGNATURE_LEN: usize = 8;
+const PNG_IHDR_CHUNK_LEN: usize = 25;
+
+/// Encodes a PNG chunk: length, type, data and CRC.
+fn png_chunk(kind: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut chunk = Vec::with_capacity(data.len() + 12);
+    chunk.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    chunk.extend_from_slice(kind);
+    chunk.extend_from_slice(data);
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(kind);
+    hasher.update(data);
+    chunk.extend_from_slice(&hasher.finalize().to_be_bytes());
+    chunk
+}
+
+/// Builds a pHYs chunk for the given resolution in dots per inch.
+fn phys_chunk(dpi: u32) -> Vec<u8> {
+    let dots_per_metre = ((dpi as u64 * 10000 + 127) / 254) as u32;
+    let mut data = Vec::with_capacity(9);
+    data.extend_from_slice(&dots_per_metre.to_be_bytes());
+    data.extend_from_slice(&dots_per_metre.to_be_bytes());
+    // Unit specifier: metre
+    data.push(1);
+    png_chunk(b"pHYs", &data)
+}
+
+/// Builds a text chunk: tEXt for ASCII text, otherwise an uncompressed UTF-8 iTXt chunk.
+fn text_chunk(keyword: &str, text: &str) -> Vec<u8> {
+    let mut data = Vec::with_capacity(keyword.len() + text.len() + 5);
+    data.extend_from_slice(keyword.as_bytes());
+    data.push(0);
+    if text.is_ascii() {
+        data.extend_from_slice(text.as_bytes());
+        return png_chunk(b"tEXt", &data);
+    }
+    // Not compressed, no language tag and no translated keyword
+    data.extend_from_slice(&[0, 0, 0, 0]);
+    data.extend_from_slice(text.as_bytes());
+    png_chunk(b"iTXt", &data)
+}
+
+/// Returns the chunks the options add to PNG output: pHYs for `dpi`, which BitMapBackend
+/// does not expose, and the `png_title`, `png_author` and `png_description` text.
+fn metadata_chunks(opt: &PlotOptions) -> Result<Vec<Vec<u8>>, String> {
+    let mut chunks = Vec::new();
+    if opt.dpi != 0 {
+        chunks.push(phys_chunk(opt.dpi));
+    }
+    for (keyword, text) in [
+        ("Title", &opt.png_title[..]),
+        ("Author", &opt.png_author[..]),
+        ("Description", &opt.png_description[..]),
+    ] {
+        let text = fixed_str(text)?;
+        if !text.is_empty() {
+            chunks.push(text_chunk(keyword, text));
+        }
+    }
+    Ok(chunks)
+}
+
+/// Rewrites the PNG file at `path` with the given chunks inserted right after IHDR.
+fn insert_png_chunks(path: &str, chunks: &[Vec<u8>]) -> Result<(), String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read PNG for metadata: {}", e))?;
+    let output = with_png_chunks(&bytes, chunks)?;
+    std::fs::write(path, output).map_err(|e| format!("Failed to write PNG metadata: {}", e))
+}
+
+/// Returns a copy of the encoded PNG `bytes` with extra chunks inserted after IHDR.
+fn with_png_chunks(bytes: &[u8], chunks: &[Vec<u8>]) -> Result<Vec<u8>, String> {
+    let insert_at = PNG_SIGNATURE_LEN + PNG_IHDR_CHUNK_LEN;
+    if bytes.len() < insert_at || &bytes[PNG_SIGNATURE_LEN + 4..PNG_SIGNATURE_LEN + 8] != b"IHDR" {
+        return Err("Failed to add PNG metadata: unexpected PNG layout".to_string());
+    }
+
+    let extra: usize = chunks.iter().map(Vec::len).sum();
+    let mut output = Vec::with_capacity(bytes.len() + extra);
+    output.extend_from_slice(&bytes[..insert_at]);
+    for chunk in chunks {
+        output.extend_from_slice(chunk);
+    }
+    output.extend_from_slice(&bytes[insert_at..]);
+    Ok(output)
+}
+
+/// Runs an FFI entry point, converting errors and panics into a status code.
+///
+/// Clears any previous error first. Returns 0 on success and 1 on failure, in which case
+/// the message is available via `plot_take_error()`.
+fn run_ffi<F: FnOnce() -> Result<(), String>>(f: F) -> i32 {
+    // Clear any previous error and warning
+    clear_error();
+    clear_warning();
+    ERROR_KIND.set(ErrorKind::Other);
+
+    // Wrap everything in catch_unwind to prevent panics crossing FFI boundary
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => 0,
+        Ok(Err(msg)) => {
+            set_error(msg, ERROR_KIND.get());
+            1
+        },
+        Err(panic_info) => {
+            set_error(panic_message(panic_info.as_ref()), ErrorKind::InternalPanic);
+            1
+        },
+    }
+}
+
+/// Describes a caught panic as an error message.
+fn panic_message(panic_info: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = panic_info.downcast_ref::<&str>() {
+        format!("{}: {}", PANIC_ERROR_PREFIX, s)
+    } else if let Some(s) = panic_info.downcast_ref::<String>() {
+        format!("{}: {}", PANIC_ERROR_PREFIX, s)
+    } else {
+        format!("{} (unknown cause)", PANIC_ERROR_PREFIX)
+    }
+}
+
+/// Converts a C path pointer into a Rust string.
+///
+/// # Safety
+/// `path` must be NULL or a valid NUL-terminated string.
+unsafe fn path_from_ptr<'a>(path: *const c_char) -> Result<Cow<'a, str>, String> {
+    // Validate path pointer
+    if path.is_null() {
+        return Err("Path pointer is NULL".to_string());
+    }
+
+    // Convert path to Rust string
+    let path_cstr = unsafe { CStr::from_ptr(path) };
+    let path = path_cstr.to_str().map_err(|_| "Path is not valid UTF-8".to_string())?;
+    resolve_path(path)
+}
+
+/// Resolves a relative path against the base directory set with `plot_set_base_directory()`.
+///
+/// Absolute paths, and all paths while no base directory is set, are returned unchanged.
+fn resolve_path(path: &str) -> Result<Cow<'_, str>, String> {
+    let base = lock_base_directory();
+    match &*base {
+        Some(dir) if std::path::Path::new(path).is_relative() => dir
+            .join(path)
+            .into_os_string()
+            .into_string()
+            .map(Cow::Owned)
+            .map_err(|_| "Path is not valid UTF-8".to_string()),
+        _ => Ok(Cow::Borrowed(path)),
+    }
+}
+
+/// Wraps the `post_render_fn` hook of options passed through the C API as a drawing step.
+///
+/// # Safety
+/// A non-null `opt.post_render_fn` must be safe to call with `opt.post_render_ctx` for as
+/// long as the returned closure is alive.
+unsafe fn ffi_post_render(
+    opt: PlotOptions,
+) -> Option<impl Fn(&DrawingArea<BitMapBackend, Shift>) -> Result<(), String>> {
+    let (hook, ctx) = (opt.post_render_fn?, opt.post_render_ctx);
+    Some(move |_: &DrawingArea<BitMapBackend, Shift>| {
+        // SAFETY: the caller of `ffi_post_render` vouches for the hook and its context
+        unsafe { hook(ctx) };
+        Ok(())
+    })
+}
+
+/// Runs `render` with options passed through the C API, including their `post_render_fn` hook.
+///
+/// # Safety
+/// The requirements of `RenderOptions::from_ffi` and `ffi_post_render` for the duration
+/// of the call.
+unsafe fn render_with_ffi_hook<T>(opt: PlotOptions, render: impl FnOnce(RenderOptions<'_>) -> T) -> T {
+    let post_render = unsafe { ffi_post_render(opt) };
+    render(unsafe { RenderOptions::from_ffi(opt) }.with_post_render(post_render.as_ref()))
+}
+
+/// Validates coordinate pointers and the point count, then creates slices from them.
+///
+/// # Safety
+/// Non-NULL `xs` and `ys` must point to arrays of at least `n` elements.
+unsafe fn data_slices<'a, T>(xs: *const T, ys: *const T, n: u64) -> Result<(&'a [T], &'a [T]), String> {
+    // Validate data pointers
+    if xs.is_null() {
+        return Err("X data pointer is NULL".to_string());
+    }
+    if ys.is_null() {
+        return Err("Y data pointer is NULL".to_string());
+    }
+
+    let n = nonzero_point_count::<T>(n)?;
+
+    // Create slices from raw pointers
+    let xs_slice = unsafe { std::slice::from_raw_parts(xs, n) };
+    let ys_slice = unsafe { std::slice::from_raw_parts(ys, n) };
+    Ok((xs_slice, ys_slice))
+}
+
+/// Validates a value pointer and its count, then creates a slice from them.
+///
+/// # Safety
+/// A non-NULL `data` must point to an array of at least `n` elements.
+unsafe fn value_slice<'a, T>(data: *const T, n: u64) -> Result<&'a [T], String> {
+    if data.is_null() {
+        return Err("Data pointer is NULL".to_string());
+    }
+    let n = nonzero_point_count::<T>(n)?;
+    Ok(unsafe { std::slice::from_raw_parts(data, n) })
+}
+
+/// Like `point_count`, also rejecting a count of zero.
+fn nonzero_point_count<T>(n: u64) -> Result<usize, String> {
+    if n == 0 {
+        return Err("Point count (n) must be greater than zero".to_string());
+    }
+    point_count::<T>(n)
+}
+
+// Point counts cross the C ABI as `u64`, which must be able to hold any `usize`
+const _: () = assert!(usize::BITS <= u64::BITS);
+
+/// Converts a point count from the C ABI, rejecting counts no array of `T` can have.
+fn point_count<T>(n: u64) -> Result<usize, String> {
+    // Slices must not span more than `isize::MAX` bytes
+    let max = isize::MAX as usize / std::mem::size_of::<T>().max(1);
+    usize::try_from(n)
+        .ok()
+        .filter(|&n| n <= max)
+        .ok_or_else(|| "Point count (n) is too large".to_string())
+}
+
+/// Converts single-precision coordinates to double precision.
+fn widen(values: &[f32]) -> Vec<f64> {
+    values.iter().map(|&v| v as f64).collect()
+}
+
+/// Splits an interleaved `[x0, y0, x1, y1, ...]` array of `n` points into X and Y vectors.
+///
+/// # Safety
+/// `xy` must point to at least `2 * n` elements if non-null.
+unsafe fn deinterleave(xy: *const c_double, n: u64) -> Result<(Vec<f64>, Vec<f64>), String> {
+    if xy.is_null() {
+        return Err("XY data pointer is NULL".to_string());
+    }
+    if n == 0 {
+        return Err("Point count (n) must be greater than zero".to_string());
+    }
+    let len = point_count::<[c_double; 2]>(n)? * 2;
+
+    let values = unsafe { std::slice::from_raw_parts(xy, len) };
+    Ok(values.chunks_exact(2).map(|pair| (pair[0], pair[1])).unzip())
+}
+
+/// Gathers `n` values spaced `stride` elements apart, starting at `ptr`.
+///
+/// `axis` names the coordinate in error messages.
+///
+/// # Safety
+/// A non-null `ptr` must point into an allocation that contains `ptr.add((n - 1) * stride)`.
+unsafe fn strided_values(ptr: *const c_double, stride: usize, n: u64, axis: &str) -> Result<Vec<f64>, String> {
+    if ptr.is_null() {
+        return Err(format!("{} data pointer is NULL", axis));
+    }
+    if stride == 0 {
+        return Err(format!("{} stride must be greater than zero", axis));
+    }
+    if n == 0 {
+        return Err("Point count (n) must be greater than zero".to_string());
+    }
+    // The last element must be addressable within one allocation
+    let n = point_count::<c_double>(n)?;
+    (n - 1)
+        .checked_mul(stride)
+        .filter(|&last| last < isize::MAX as usize / std::mem::size_of::<c_double>())
+        .ok_or_else(|| format!("{} stride is too large for {} points", axis, n))?;
+    Ok((0..n).map(|i| unsafe { *ptr.add(i * stride) }).collect())
+}
+
+/// Hands a buffer over to the caller through output pointers.
+///
+/// # Safety
+/// Non-NULL `out_ptr` and `out_len` must be valid for writes.
+unsafe fn write_buffer<T>(buffer: Vec<T>, out_ptr: *mut *mut T, out_len: *mut usize) -> Result<(), String> {
+    if out_ptr.is_null() || out_len.is_null() {
+        return Err("Output pointer is NULL".to_string());
+    }
+    let len = buffer.len();
+    let ptr = Box::into_raw(buffer.into_boxed_slice()) as *mut T;
+    unsafe {
+        *out_ptr = ptr;
+        *out_len = len;
+    }
+    Ok(())
+}
+
+/// Renders a scatter plot to a PNG file.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output PNG file
+/// - `xs`: Pointer to array of X coordinates
+/// - `ys`: Pointer to array of Y coordinates
+/// - `n`: Number of points (length of xs and ys arrays)
+/// - `opt`: Plot configuration options
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+/// - `n` must be greater than 0
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_png(
+    path: *const c_char,
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+
+        // Call implementation
+        unsafe { render_with_ffi_hook(opt, |opt| plot_scatter_png_impl(&path_str, xs_slice, ys_slice, opt)) }
+    })
+}
+
+/// Renders a time series scatter plot to a PNG file.
+///
+/// X values are Unix timestamps in seconds and are labelled as UTC dates using
+/// `opt.x_label_format` (default `"%Y-%m-%d %H:%M"`). The axis itself stays numeric.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output PNG file
+/// - `timestamps`: Pointer to array of Unix timestamps in seconds
+/// - `ys`: Pointer to array of Y coordinates
+/// - `n`: Number of points (length of timestamps and ys arrays)
+/// - `opt`: Plot configuration options
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure, including non-finite timestamps (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `timestamps` and `ys` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_timeseries_png(
+    path: *const c_char,
+    timestamps: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (ts_slice, ys_slice) = unsafe { data_slices(timestamps, ys, n) }?;
+        plot_timeseries_png_impl(&path_str, ts_slice, ys_slice, unsafe { RenderOptions::from_ffi(opt) })
+    })
+}
+
+/// Renders a line chart to a PNG file.
+///
+/// Points are connected in array order using `opt.line_mode`.
+/// Takes the same parameters as `plot_scatter_png()`.
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_line_png(
+    path: *const c_char,
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        plot_line_png_impl(&path_str, xs_slice, ys_slice, unsafe { RenderOptions::from_ffi(opt) })
+    })
+}
+
+/// Renders the empirical cumulative distribution function of `data` to a PNG file.
+///
+/// The CDF is drawn as a right-step line. The Y axis always spans 0 to 1, while the
+/// X axis follows `auto_range` or the explicit range. The caller's array is not modified.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output PNG file
+/// - `data`: Pointer to array of samples
+/// - `n`: Number of samples
+/// - `opt`: Plot configuration options
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `data` must point to an array of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_cdf_png(path: *const c_char, data: *const c_double, n: u64, opt: PlotOptions) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let data_slice = unsafe { value_slice(data, n) }?;
+        plot_cdf_png_impl(&path_str, data_slice, unsafe { RenderOptions::from_ffi(opt) })
+    })
+}
+
+/// Renders a polar scatter plot to a PNG file.
+///
+/// Each point is given by an angle in radians (counter-clockwise from the positive X axis)
+/// and a radius. The chart is square, with `opt.polar_grid_rings` concentric rings and
+/// radial lines every 45°. `auto_range` and the explicit ranges are ignored.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output PNG file
+/// - `angles_rad`: Pointer to array of angles in radians
+/// - `radii`: Pointer to array of radii
+/// - `n`: Number of points
+/// - `opt`: Plot configuration options
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `angles_rad` and `radii` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_polar_scatter_png(
+    path: *const c_char,
+    angles_rad: *const c_double,
+    radii: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (angles_slice, radii_slice) = unsafe { data_slices(angles_rad, radii, n) }?;
+        plot_polar_scatter_png_impl(&path_str, angles_slice, radii_slice, opt)
+    })
+}
+
+/// Renders a scatter plot with its ordinary least-squares regression line to a PNG file.
+///
+/// The red regression line spans the full X range of the chart. When
+/// `opt.show_r_squared` is set, `"R² = 0.9876"` is shown as the title, or appended
+/// to `opt.title` if one is given. Takes the same parameters as `plot_scatter_png()`.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure, including fewer than two points or all X values equal
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_with_regression_png(
+    path: *const c_char,
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        plot_scatter_with_regression_png_impl(&path_str, xs_slice, ys_slice, unsafe { RenderOptions::from_ffi(opt) })
+    })
+}
+
+/// Renders a median line with a shaded quantile band to a PNG file.
+///
+/// The area between `p_low` and `p_high` is filled in translucent blue and `p_mid`
+/// is drawn as a solid blue line on top. Points are connected in array order, so
+/// `xs` should be sorted.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output PNG file
+/// - `xs`: Pointer to array of X coordinates
+/// - `p_low`: Pointer to array of lower quantiles, or NULL to omit the band
+/// - `p_mid`: Pointer to array of median values
+/// - `p_high`: Pointer to array of upper quantiles, or NULL to omit the band
+/// - `n`: Number of points in each array
+/// - `opt`: Plot configuration options
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure, including `p_low[i] <= p_mid[i] <= p_high[i]` not holding for some `i`
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs`, `p_mid` and the non-NULL quantile pointers must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_quantile_band_png(
+    path: *const c_char,
+    xs: *const c_double,
+    p_low: *const c_double,
+    p_mid: *const c_double,
+    p_high: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs_slice, mid_slice) = unsafe { data_slices(xs, p_mid, n) }?;
+        // SAFETY: caller guarantees non-NULL quantile pointers cover n elements
+        let optional =
+            |ptr: *const c_double| (!ptr.is_null()).then(|| unsafe { std::slice::from_raw_parts(ptr, xs_slice.len()) });
+        plot_quantile_band_png_impl(
+            &path_str,
+            xs_slice,
+            optional(p_low),
+            mid_slice,
+            optional(p_high),
+            unsafe { RenderOptions::from_ffi(opt) },
+        )
+    })
+}
+
+/// Renders the area between two Y arrays as a shaded band to a PNG file.
+///
+/// The band is filled in translucent blue and both bounds are drawn as solid lines.
+/// Points are connected in array order, so `xs` should be sorted.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output PNG file
+/// - `xs`: Pointer to array of X coordinates
+/// - `y_lower`: Pointer to array of lower bounds
+/// - `y_upper`: Pointer to array of upper bounds
+/// - `n`: Number of points in each array
+/// - `opt`: Plot configuration options
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure, including `y_lower[i] > y_upper[i]` for some `i`
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs`, `y_lower` and `y_upper` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_band_png(
+    path: *const c_char,
+    xs: *const c_double,
+    y_lower: *const c_double,
+    y_upper: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs_slice, lower_slice) = unsafe { data_slices(xs, y_lower, n) }?;
+        let (_, upper_slice) = unsafe { data_slices(xs, y_upper, n) }?;
+        plot_band_png_impl(&path_str, xs_slice, lower_slice, upper_slice, unsafe {
+            RenderOptions::from_ffi(opt)
+        })
+    })
+}
+
+/// Renders a vector field as arrows to a PNG file.
+///
+/// Each arrow runs from `(xs[i], ys[i])` to `(xs[i] + s * us[i], ys[i] + s * vs[i])`, where
+/// `s` is `opt.arrow_scale` or, if that is 0, chosen so the longest arrow spans 5% of the
+/// axis range. Arrowheads are 8-pixel triangles regardless of the arrow length.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output PNG file
+/// - `xs`, `ys`: Pointers to arrays of arrow base coordinates
+/// - `us`, `vs`: Pointers to arrays of vector components
+/// - `n`: Number of arrows
+/// - `opt`: Plot configuration options
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs`, `ys`, `us` and `vs` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_quiver_png(
+    path: *const c_char,
+    xs: *const c_double,
+    ys: *const c_double,
+    us: *const c_double,
+    vs: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        let (us_slice, vs_slice) = unsafe { data_slices(us, vs, n) }?;
+        plot_quiver_png_impl(&path_str, xs_slice, ys_slice, us_slice, vs_slice, unsafe {
+            RenderOptions::from_ffi(opt)
+        })
+    })
+}
+
+/// Renders a 2D histogram (density heatmap) of points to a PNG file.
+///
+/// The plot area is divided into `n_bins_x` x `n_bins_y` bins over the axis ranges
+/// (auto-ranged from the data or taken from the options). Each bin is colored on the
+/// viridis scale by its count divided by the largest count, and a colorbar shows the scale.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output PNG file
+/// - `xs`, `ys`: Pointers to arrays of point coordinates
+/// - `n`: Number of points
+/// - `n_bins_x`, `n_bins_y`: Bins per axis (0 = `ceil(sqrt(n))`)
+/// - `opt`: Plot configuration options
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_histogram2d_png(
+    path: *const c_char,
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    n_bins_x: u32,
+    n_bins_y: u32,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        plot_histogram2d_png_impl(&path_str, xs_slice, ys_slice, n_bins_x, n_bins_y, unsafe {
+            RenderOptions::from_ffi(opt)
+        })
+    })
+}
+
+/// Renders a scatter plot with symmetric error bars to a PNG file.
+///
+/// Each point gets a vertical bar from `y - y_err[i]` to `y + y_err[i]` and/or a
+/// horizontal bar from `x - x_err[i]` to `x + x_err[i]`, depending on which arrays in
+/// `ebo` are non-NULL. Bar ends get caps of `ebo.cap_size_px` pixels.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output PNG file
+/// - `xs`, `ys`: Pointers to arrays of point coordinates
+/// - `n`: Number of points
+/// - `ebo`: Error values and bar style; at least one error array must be non-NULL
+/// - `opt`: Plot configuration options
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs`, `ys` and the non-NULL error arrays must point to at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_errorbars_ex(
+    path: *const c_char,
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    ebo: ErrorBarOptions,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        let len = xs_slice.len();
+        // SAFETY: caller guarantees non-NULL error arrays hold n elements
+        let errors = |ptr: *const c_double| (!ptr.is_null()).then(|| unsafe { std::slice::from_raw_parts(ptr, len) });
+        plot_scatter_errorbars_impl(
+            &path_str,
+            xs_slice,
+            ys_slice,
+            errors(ebo.x_err),
+            errors(ebo.y_err),
+            ebo,
+            unsafe { RenderOptions::from_ffi(opt) },
+        )
+    })
+}
+
+/// Renders a scatter plot with asymmetric error bars to a PNG file.
+///
+/// Each point gets a vertical bar from `y - y_err_lo[i]` to `y + y_err_hi[i]` and/or a
+/// horizontal bar from `x - x_err_lo[i]` to `x + x_err_hi[i]`. Any error array may be
+/// NULL, which gives that side of the bars zero length; an axis gets no bars when both
+/// of its arrays are NULL. Bars are drawn in the marker color with the default caps of
+/// `ErrorBarOptions`.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output PNG file
+/// - `xs`, `ys`: Pointers to arrays of point coordinates
+/// - `x_err_lo`, `x_err_hi`: Non-negative lower and upper X error magnitudes (or NULL)
+/// - `y_err_lo`, `y_err_hi`: Non-negative lower and upper Y error magnitudes (or NULL)
+/// - `n`: Number of points
+/// - `opt`: Plot configuration options
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure, including negative errors or all four arrays NULL (call
+///   `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs`, `ys` and the non-NULL error arrays must point to at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_asymmetric_errorbars_png(
+    path: *const c_char,
+    xs: *const c_double,
+    ys: *const c_double,
+    x_err_lo: *const c_double,
+    x_err_hi: *const c_double,
+    y_err_lo: *const c_double,
+    y_err_hi: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        let len = xs_slice.len();
+        // SAFETY: caller guarantees non-NULL error arrays hold n elements
+        let errors = |ptr: *const c_double| (!ptr.is_null()).then(|| unsafe { std::slice::from_raw_parts(ptr, len) });
+        plot_scatter_asymmetric_errorbars_impl(
+            &path_str,
+            xs_slice,
+            ys_slice,
+            (errors(x_err_lo), errors(x_err_hi)),
+            (errors(y_err_lo), errors(y_err_hi)),
+            ErrorBarOptions::default(),
+            unsafe { RenderOptions::from_ffi(opt) },
+        )
+    })
+}
+
+/// Registers a TrueType/OpenType font file for use in `title_font_name` and `label_font_name`.
+///
+/// The bundled font is always available as `"app-font"`, and its bold face as
+/// `"app-font-bold"`. Registering the same name and style again replaces the
+/// earlier font.
+///
+/// # Parameters
+/// - `name`: NUL-terminated UTF-8 family name to register the font under
+/// - `path`: NUL-terminated UTF-8 path to the font file
+/// - `style`: 0 = normal, 1 = bold, 2 = italic, 3 = oblique
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `name` and `path` must be valid NUL-terminated UTF-8 strings
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_register_font(name: *const c_char, path: *const c_char, style: u8) -> i32 {
+    run_ffi(|| {
+        if name.is_null() {
+            return Err("Font name pointer is null".to_string());
+        }
+        // SAFETY: caller guarantees name is a valid NUL-terminated string
+        let name_str = unsafe { CStr::from_ptr(name) }
+            .to_str()
+            .map_err(|_| "Font name is not valid UTF-8".to_string())?;
+        let path_str = unsafe { path_from_ptr(path) }?;
+        register_font_file(name_str, &path_str, style)
+    })
+}
+
+/// Registers a TrueType/OpenType font from memory, for hosts without a filesystem.
+///
+/// Behaves like `plot_register_font()`: registering a name that is already registered
+/// replaces the earlier font and returns 0. The bytes are copied, so `data` only needs to
+/// be valid during the call; the copy is kept for the rest of the process.
+///
+/// # Parameters
+/// - `name`: NUL-terminated UTF-8 family name to register the font under
+/// - `style`: 0 = normal, 1 = bold, 2 = italic, 3 = oblique
+/// - `data`: Pointer to the font file contents
+/// - `data_len`: Length of `data` in bytes
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure, including data that is not a font (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `name` must be a valid NUL-terminated UTF-8 string
+/// - `data` must point to at least `data_len` bytes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_register_font_bytes(
+    name: *const c_char,
+    style: u8,
+    data: *const u8,
+    data_len: usize,
+) -> i32 {
+    run_ffi(|| {
+        if name.is_null() {
+            return Err("Font name pointer is null".to_string());
+        }
+        if data.is_null() || data_len == 0 {
+            return Err("Font data is empty".to_string());
+        }
+        // SAFETY: caller guarantees name is a valid NUL-terminated string
+        let name_str = unsafe { CStr::from_ptr(name) }
+            .to_str()
+            .map_err(|_| "Font name is not valid UTF-8".to_string())?;
+        // SAFETY: caller guarantees data points to data_len bytes
+        let bytes = unsafe { std::slice::from_raw_parts(data, data_len) }.to_vec();
+        register_font_bytes(name_str, bytes, style)
+    })
+}
+
+/// Renders a scatter plot into a newly allocated RGBA pixel buffer.
+///
+/// # Parameters
+/// - `xs`: Pointer to array of X coordinates
+/// - `ys`: Pointer to array of Y coordinates
+/// - `n`: Number of points (length of xs and ys arrays)
+/// - `opt`: Plot configuration options
+/// - `out_ptr`: Receives a pointer to `width * height * 4` bytes of RGBA pixels, row-major
+/// - `out_len`: Receives the buffer length in bytes
+///
+/// The buffer must be released with `plot_free_buffer()`.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+/// - `out_ptr` and `out_len` must be valid for writes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_rgba(
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+    out_ptr: *mut *mut u8,
+    out_len: *mut usize,
+) -> i32 {
+    run_ffi(|| {
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        let pixels = plot_scatter_rgba_impl(xs_slice, ys_slice, unsafe { RenderOptions::from_ffi(opt) })?;
+        unsafe { write_buffer(pixels, out_ptr, out_len) }
+    })
+}
+
+/// Renders a scatter plot into a newly allocated RGB565 pixel buffer.
+///
+/// Each pixel is a 16-bit word with 5 red bits at the top, then 6 green and 5 blue bits.
+/// The words are stored little-endian, or big-endian with `opt.pixel_endian = 1`.
+///
+/// # Parameters
+/// - `xs`: Pointer to array of X coordinates
+/// - `ys`: Pointer to array of Y coordinates
+/// - `n`: Number of points (length of xs and ys arrays)
+/// - `opt`: Plot configuration options
+/// - `out_ptr`: Receives a pointer to `width * height` pixels, row-major
+/// - `out_len`: Receives the buffer length in pixels (16-bit words)
+///
+/// The buffer must be released with `plot_free_u16_buffer()`.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+/// - `out_ptr` and `out_len` must be valid for writes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_rgb565(
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+    out_ptr: *mut *mut u16,
+    out_len: *mut usize,
+) -> i32 {
+    run_ffi(|| {
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        let pixels = plot_scatter_rgb565_impl(xs_slice, ys_slice, unsafe { RenderOptions::from_ffi(opt) })?;
+        unsafe { write_buffer(pixels, out_ptr, out_len) }
+    })
+}
+
+/// Renders a scatter plot to an SVG file.
+///
+/// Takes the same parameters as `plot_scatter_png()`. Text refers to the font by name,
+/// so the viewer must have a matching font installed.
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_svg(
+    path: *const c_char,
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        plot_scatter_svg_impl(&path_str, xs_slice, ys_slice, unsafe { RenderOptions::from_ffi(opt) })
+    })
+}
+
+/// Renders a scatter plot from single-precision coordinates to a PNG file.
+///
+/// Behaves like `plot_scatter_png()`; coordinates are widened to double precision.
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_f32_png(
+    path: *const c_char,
+    xs: *const f32,
+    ys: *const f32,
+    n: u64,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        let (xs, ys) = (widen(xs_slice), widen(ys_slice));
+        unsafe { render_with_ffi_hook(opt, |opt| plot_scatter_png_impl(&path_str, &xs, &ys, opt)) }
+    })
+}
+
+/// Renders a scatter plot from single-precision coordinates into an RGBA buffer.
+///
+/// Behaves like `plot_scatter_rgba()`; release the buffer with `plot_free_buffer()`.
+///
+/// # Safety
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+/// - `out_ptr` and `out_len` must be valid for writes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_f32_rgba(
+    xs: *const f32,
+    ys: *const f32,
+    n: u64,
+    opt: PlotOptions,
+    out_ptr: *mut *mut u8,
+    out_len: *mut usize,
+) -> i32 {
+    run_ffi(|| {
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        let pixels = plot_scatter_rgba_impl(&widen(xs_slice), &widen(ys_slice), unsafe {
+            RenderOptions::from_ffi(opt)
+        })?;
+        unsafe { write_buffer(pixels, out_ptr, out_len) }
+    })
+}
+
+/// Renders a scatter plot from single-precision coordinates to an SVG file.
+///
+/// Behaves like `plot_scatter_svg()`.
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_f32_svg(
+    path: *const c_char,
+    xs: *const f32,
+    ys: *const f32,
+    n: u64,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        plot_scatter_svg_impl(&path_str, &widen(xs_slice), &widen(ys_slice), unsafe {
+            RenderOptions::from_ffi(opt)
+        })
+    })
+}
+
+/// Draws an additional series onto an existing scatter plot PNG and saves it in place.
+///
+/// The image at `path` must have been rendered with the same `width`, `height`,
+/// `outer_padding`, label and title options as `opt`, and with explicit ranges:
+/// `opt.auto_range` is ignored and `x_min`..`y_max` must match the base chart.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the PNG file to update
+/// - `xs`: Pointer to array of X coordinates
+/// - `ys`: Pointer to array of Y coordinates
+/// - `n`: Number of points
+/// - `color`: Marker color as packed ARGB (`0xAARRGGBB`)
+/// - `opt`: Options of the base chart
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure, including a size mismatch between `opt` and the image
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_png_append(
+    path: *const c_char,
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    color: u32,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        plot_scatter_png_append_impl(&path_str, xs_slice, ys_slice, color, opt)
+    })
+}
+
+/// Renders a scatter plot to a PNG file and reports statistics about the render.
+///
+/// Identical to `plot_scatter_png()`, except that on success `*stats` receives the number
+/// of drawn, NaN-skipped and clipped points and the elapsed time, and `*info` the pixel
+/// rectangle and axis ranges of the data area, for example to place interactive elements
+/// over the image. `stats` and `info` may be NULL.
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+/// - `stats` and `info` must each be NULL or valid for writes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_png_ex(
+    path: *const c_char,
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+    stats: *mut PlotStats,
+    info: *mut PlotAreaInfo,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        // Computed before rendering, so that a failure leaves no image behind
+        let area = if info.is_null() {
+            None
+        } else {
+            Some(plot_area_info(xs_slice, ys_slice, opt)?)
+        };
+        let result =
+            unsafe { render_with_ffi_hook(opt, |opt| plot_scatter_png_ex_impl(&path_str, xs_slice, ys_slice, opt)) }?;
+        if !stats.is_null() {
+            // SAFETY: caller guarantees a non-NULL stats pointer is valid for writes
+            unsafe { *stats = result };
+        }
+        if let Some(area) = area {
+            // SAFETY: caller guarantees a non-NULL info pointer is valid for writes
+            unsafe { *info = area };
+        }
+        Ok(())
+    })
+}
+
+/// Computes the pixel at which a data point is drawn, without rendering.
+///
+/// Mirrors the mapping of `plot_scatter_png()` for the same options, including margins,
+/// label areas, the title, `lock_aspect_ratio`, `plot_area_fraction`, inverted axes and
+/// `outer_padding`. `(0, 0)` is the top-left pixel of the image. The options must use
+/// explicit ranges (`auto_range = 0`, see `plot_compute_range()`) and a nonzero height.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure, including "Point outside axis range" when the point is not within
+///   the axis ranges
+///
+/// # Safety
+/// `pixel_x` and `pixel_y` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_data_to_pixel(
+    opt: PlotOptions,
+    data_x: c_double,
+    data_y: c_double,
+    pixel_x: *mut u32,
+    pixel_y: *mut u32,
+) -> i32 {
+    run_ffi(|| {
+        if pixel_x.is_null() || pixel_y.is_null() {
+            return Err("Output pointer is NULL".to_string());
+        }
+        let (px, py) = data_to_pixel(opt, data_x, data_y)?;
+        // SAFETY: caller guarantees both pointers are valid for writes
+        unsafe {
+            *pixel_x = px;
+            *pixel_y = py;
+        }
+        Ok(())
+    })
+}
+
+/// Finds the point nearest to a pixel, without rendering, for example to show a
+/// tooltip for the point under the mouse.
+///
+/// The query pixel is converted to data coordinates with the same layout and option
+/// requirements as `plot_pixel_to_data()`, and the point nearest to them in data space is
+/// chosen. Points with a non-finite coordinate are skipped; ties go to the lowest index.
+///
+/// # Parameters
+/// - `opt`: Options the chart was rendered with (`auto_range` must be 0)
+/// - `xs`, `ys`: Pointers to arrays of point coordinates
+/// - `n`: Number of points
+/// - `query_pixel_x`, `query_pixel_y`: Pixel to search from, including any outer padding
+/// - `out_index`: Receives the index of the nearest point
+/// - `out_dist_px`: Receives its distance from the query pixel in pixels
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure, including when `n` is 0 or the query pixel is outside the plot area
+///   (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+/// - `out_index` and `out_dist_px` must be valid for writes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_find_nearest_point(
+    opt: PlotOptions,
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    query_pixel_x: u32,
+    query_pixel_y: u32,
+    out_index: *mut usize,
+    out_dist_px: *mut c_double,
+) -> i32 {
+    run_ffi(|| {
+        if out_index.is_null() || out_dist_px.is_null() {
+            return Err("Output pointer is NULL".to_string());
+        }
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        let (index, dist) = nearest_point(opt, xs_slice, ys_slice, query_pixel_x, query_pixel_y)?;
+        // SAFETY: caller guarantees both pointers are valid for writes
+        unsafe {
+            *out_index = index;
+            *out_dist_px = dist;
+        }
+        Ok(())
+    })
+}
+
+/// Finds all points drawn within a radius of a pixel, without rendering, for example to
+/// hit-test a mouse click.
+///
+/// Points are mapped to pixels as in `plot_data_to_pixel()`, and the distance to the
+/// query pixel is measured in pixels; points exactly
+/// `radius_px` away count as inside. The indices of up to `out_capacity` points are
+/// written to `out_indices`, nearest first, with ties in index order. With an
+/// `out_capacity` of 0 nothing is written and `out_indices` may be NULL, which is enough
+/// to check whether any point is near the query pixel.
+///
+/// # Parameters
+/// - `opt`: Options the chart was rendered with (`auto_range` must be 0)
+/// - `xs`, `ys`: Pointers to arrays of point coordinates
+/// - `n`: Number of points
+/// - `query_pixel_x`, `query_pixel_y`: Pixel to search from, including any outer padding
+/// - `radius_px`: Search radius in pixels
+/// - `out_indices`: Receives the indices of the points found
+/// - `out_capacity`: Number of indices `out_indices` can hold
+///
+/// # Returns
+/// The number of points within the radius, which may exceed `out_capacity`. Returns 0 on
+/// failure, with the reason available from `plot_take_error()`.
+///
+/// # Safety
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+/// - `out_indices` must be valid for writing `out_capacity` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_points_in_radius(
+    opt: PlotOptions,
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    query_pixel_x: u32,
+    query_pixel_y: u32,
+    radius_px: c_double,
+    out_indices: *mut usize,
+    out_capacity: usize,
+) -> usize {
+    let mut count = 0;
+    run_ffi(|| {
+        if out_indices.is_null() && out_capacity > 0 {
+            return Err("Output pointer is NULL".to_string());
+        }
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        let indices = points_in_radius(opt, xs_slice, ys_slice, query_pixel_x, query_pixel_y, radius_px)?;
+        let written = indices.len().min(out_capacity);
+        if written > 0 {
+            // SAFETY: caller guarantees room for `out_capacity` indices
+            unsafe { std::ptr::copy_nonoverlapping(indices.as_ptr(), out_indices, written) };
+        }
+        count = indices.len();
+        Ok(())
+    });
+    count
+}
+
+/// Computes the data coordinates of a pixel, without rendering.
+///
+/// The inverse of `plot_data_to_pixel()`, with the same layout and option requirements.
+/// Useful for mapping mouse clicks on a rendered chart back to data space.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure, including "Pixel is in margin area" when the pixel is outside the
+///   plotting area
+///
+/// # Safety
+/// `data_x` and `data_y` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_pixel_to_data(
+    opt: PlotOptions,
+    pixel_x: u32,
+    pixel_y: u32,
+    data_x: *mut c_double,
+    data_y: *mut c_double,
+) -> i32 {
+    run_ffi(|| {
+        if data_x.is_null() || data_y.is_null() {
+            return Err("Output pointer is NULL".to_string());
+        }
+        let (x, y) = pixel_to_data(opt, pixel_x, pixel_y)?;
+        // SAFETY: caller guarantees both pointers are valid for writes
+        unsafe {
+            *data_x = x;
+            *data_y = y;
+        }
+        Ok(())
+    })
+}
+
+/// Computes axis ranges from data and stores them as explicit ranges in `*out`.
+///
+/// Sets `x_min`, `x_max`, `y_min` and `y_max` and clears `auto_range`; all other fields
+/// of `*out` are left as they are, so the struct can be passed straight to
+/// `plot_scatter_png()`. This makes it easy to give several plots the same axes.
+///
+/// # Parameters
+/// - `xs`, `ys`: Pointers to arrays of `n` coordinates
+/// - `n`: Number of points
+/// - `padding`: Margin added on both ends of each axis, as a fraction of its extent
+///   (0.02 matches `PLOT_RANGE_PADDED`)
+/// - `out`: Options to update
+///
+/// Points with a NaN or infinite coordinate are skipped; it is an error if none are left.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+/// - `out` must point to a valid `PlotOptions`
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_compute_range(
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    padding: c_double,
+    out: *mut PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        if out.is_null() {
+            return Err("Output pointer is NULL".to_string());
+        }
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        let (x_min, x_max, y_min, y_max) = padded_data_range(xs_slice, ys_slice, padding)?;
+        // SAFETY: caller guarantees out points to a valid PlotOptions
+        let opt = unsafe { &mut *out };
+        opt.x_min = x_min;
+        opt.x_max = x_max;
+        opt.y_min = y_min;
+        opt.y_max = y_max;
+        opt.auto_range = 0;
+        Ok(())
+    })
+}
+
+/// Renders a scatter plot to a PNG file and reports how long the rendering took.
+///
+/// Behaves exactly like `plot_scatter_png()`. On success, the microseconds spent drawing,
+/// encoding and writing the image (after validation) are stored in `*elapsed_us`.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+/// - `elapsed_us` must be NULL or valid for writes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_png_timed(
+    path: *const c_char,
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+    elapsed_us: *mut u64,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        let elapsed = unsafe {
+            render_with_ffi_hook(opt, |opt| {
+                plot_scatter_png_timed_impl(&path_str, xs_slice, ys_slice, opt)
+            })
+        }?;
+        if !elapsed_us.is_null() {
+            // SAFETY: caller guarantees a non-NULL elapsed_us pointer is valid for writes
+            unsafe { *elapsed_us = elapsed };
+        }
+        Ok(())
+    })
+}
+
+/// Renders a scatter plot into an existing OpenGL texture.
+///
+/// The chart is rendered as with `plot_scatter_rgba()` and uploaded as level 0 of the
+/// `GL_TEXTURE_2D` texture `gl_texture_id` (`GL_RGBA8` storage, `GL_RGBA`/`GL_UNSIGNED_BYTE`
+/// data), replacing its size and contents. The first texture row is the top row of the
+/// chart, so flip the V coordinate when sampling with OpenGL's bottom-left origin. The
+/// texture binding and unpack alignment of the context are left unchanged.
+///
+/// Must be called from a thread on which the GL context owning the texture is current;
+/// without a current context, GL calls have no effect or undefined behavior depending on
+/// the driver. Requires the library to be built with the `opengl` Cargo feature, which
+/// links the system OpenGL library; otherwise the call always fails.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure, including GL errors (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - A GL context must be current on the calling thread
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_render_to_texture_gl(
+    gl_texture_id: u32,
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        // SAFETY: the caller guarantees a current GL context
+        unsafe { plot_render_to_texture_gl_impl(gl_texture_id, xs_slice, ys_slice, RenderOptions::from_ffi(opt)) }
+    })
+}
+
+/// Renders a scatter plot to a single-page PDF file.
+///
+/// The chart is rendered as a bitmap and embedded as an image filling the page. The
+/// page size is `opt.width` x `opt.height` pixels at `opt.dpi` (0 = 72 DPI, where one
+/// pixel is one point).
+///
+/// Requires the library to be built with the `pdf` Cargo feature; otherwise the call
+/// always fails.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_pdf(
+    path: *const c_char,
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        plot_scatter_pdf_impl(&path_str, xs_slice, ys_slice, unsafe { RenderOptions::from_ffi(opt) })
+    })
+}
+
+/// Renders a scatter plot as a base64-encoded PNG string.
+///
+/// With `opt.base64_data_uri` set, the string is prefixed with `"data:image/png;base64,"`
+/// so it can be used directly as an image source.
+///
+/// # Parameters
+/// - `xs`: Pointer to array of X coordinates
+/// - `ys`: Pointer to array of Y coordinates
+/// - `n`: Number of points
+/// - `opt`: Plot configuration options
+/// - `out_str`: Receives a NUL-terminated ASCII string, to be released with `plot_free_string()`
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+/// - `out_str` must be valid for writes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_png_base64(
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+    out_str: *mut *mut c_char,
+) -> i32 {
+    run_ffi(|| {
+        if out_str.is_null() {
+            return Err("Output pointer is NULL".to_string());
+        }
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        let png = plot_scatter_png_bytes_impl(xs_slice, ys_slice, unsafe { RenderOptions::from_ffi(opt) })?;
+
+        let mut encoded = if opt.base64_data_uri != 0 {
+            "data:image/png;base64,".to_string()
+        } else {
+            String::new()
+        };
+        base64::engine::general_purpose::STANDARD.encode_string(&png, &mut encoded);
+        // Base64 output never contains NUL bytes
+        let encoded = CString::new(encoded).map_err(|_| "Encoded string contains NUL".to_string())?;
+        unsafe { *out_str = encoded.into_raw() };
+        Ok(())
+    })
+}
+
+/// Frees a string returned by `plot_scatter_png_base64()`, `plot_options_to_json()` or
+/// `plot_take_error()`.
+///
+/// Passing NULL is a no-op.
+///
+/// # Safety
+/// `ptr` must be NULL or a string returned by this library that has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_free_string(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        drop(unsafe { CString::from_raw(ptr) });
+    }
+}
+
+/// Serializes plot options to a JSON object string.
+///
+/// String options such as `title` become JSON strings. The result must be released
+/// with `plot_free_string()`.
+///
+/// # Returns
+/// - A NUL-terminated UTF-8 string on success
+/// - NULL on failure (call `plot_take_error()` for details)
+#[unsafe(no_mangle)]
+pub extern "C" fn plot_options_to_json(opt: PlotOptions) -> *mut c_char {
+    let mut out = std::ptr::null_mut();
+    run_ffi(|| {
+        let json = json::options_to_json(&opt)?;
+        let json = CString::new(json).map_err(|_| "Serialized options contain NUL".to_string())?;
+        out = json.into_raw();
+        Ok(())
+    });
+    out
+}
+
+/// Parses plot options from a JSON object string.
+///
+/// Fields missing from the JSON keep their default values; unknown fields are an error.
+///
+/// # Parameters
+/// - `json`: NUL-terminated UTF-8 JSON text, as produced by `plot_options_to_json()`
+/// - `out`: Receives the parsed options
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `json` must be a valid NUL-terminated string
+/// - `out` must be valid for writes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_options_from_json(json: *const c_char, out: *mut PlotOptions) -> i32 {
+    run_ffi(|| {
+        if json.is_null() {
+            return Err("JSON pointer is NULL".to_string());
+        }
+        if out.is_null() {
+            return Err("Output pointer is NULL".to_string());
+        }
+        // SAFETY: caller guarantees json is a valid NUL-terminated string
+        let text = unsafe { CStr::from_ptr(json) }
+            .to_str()
+            .map_err(|_| "JSON is not valid UTF-8".to_string())?;
+        let opt = json::options_from_json(text)?;
+        unsafe { *out = opt };
+        Ok(())
+    })
+}
+
+/// Reads a NUL-terminated UTF-8 global option argument.
+///
+/// # Safety
+/// Non-NULL `ptr` must point to a valid NUL-terminated string.
+unsafe fn option_arg<'a>(ptr: *const c_char, what: &str) -> Result<&'a str, String> {
+    if ptr.is_null() {
+        return Err(format!("Option {} pointer is NULL", what));
+    }
+    // SAFETY: caller guarantees ptr is a valid NUL-terminated string
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map_err(|_| format!("Option {} is not valid UTF-8", what))
+}
+
+/// Parses the string form of a global option value.
+///
+/// Every option accepts a decimal integer; `color_scheme` also accepts a scheme name.
+fn parse_global_option(key: &str, value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    if key == "color_scheme" {
+        let scheme = match value.to_ascii_lowercase().as_str() {
+            "tableau10" => Some(PLOT_SCHEME_TABLEAU10),
+            "set1" => Some(PLOT_SCHEME_SET1),
+            "pastel" => Some(PLOT_SCHEME_PASTEL),
+            "monochrome" => Some(PLOT_SCHEME_MONOCHROME),
+            _ => None,
+        };
+        if let Some(scheme) = scheme {
+            return Ok(scheme as u64);
+        }
+    }
+    value
+        .parse::<u64>()
+        .map_err(|_| format!("Invalid value for global option {}: {:?}", key, value))
+}
+
+/// Sets a process-wide default from a string value.
+///
+/// Whenever a rendering call receives a `PlotOptions` field that is 0, the global
+/// default for that field is used instead. Valid keys are `"width"`, `"height"`,
+/// `"marker_radius"`, `"dpi"`, `"label_font_size"` and `"color_scheme"`. Values are
+/// decimal integers; `"color_scheme"` also accepts `"tableau10"`, `"set1"`, `"pastel"`
+/// and `"monochrome"`.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `key` and `value` must be valid NUL-terminated UTF-8 strings
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_set_global_option(key: *const c_char, value: *const c_char) -> i32 {
+    run_ffi(|| {
+        let key = unsafe { option_arg(key, "key") }?;
+        let value = unsafe { option_arg(value, "value") }?;
+        set_global_option(key, parse_global_option(key, value)?)
+    })
+}
+
+/// Sets a process-wide default from an integer value.
+///
+/// Same keys and semantics as `plot_set_global_option()`.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `key` must be a valid NUL-terminated UTF-8 string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_set_global_option_u32(key: *const c_char, value: u32) -> i32 {
+    run_ffi(|| {
+        let key = unsafe { option_arg(key, "key") }?;
+        set_global_option(key, value as u64)
+    })
+}
+
+/// Sets the directory that relative paths are resolved against.
+///
+/// By default, relative paths passed to this library are relative to the process's
+/// current working directory, which is often unclear in sandboxed or embedded hosts.
+/// Once a base directory is set, every relative path argument (output files, and font
+/// files in `plot_register_font()`) is joined to it; absolute paths are unaffected. The
+/// setting is process-wide.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 if `dir` is not an existing directory (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `dir` must be a valid NUL-terminated UTF-8 string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_set_base_directory(dir: *const c_char) -> i32 {
+    run_ffi(|| {
+        if dir.is_null() {
+            return Err("Directory pointer is NULL".to_string());
+        }
+        // SAFETY: caller guarantees dir is a valid NUL-terminated string
+        let dir = unsafe { CStr::from_ptr(dir) }
+            .to_str()
+            .map_err(|_| "Directory is not valid UTF-8".to_string())?;
+        let path = PathBuf::from(dir);
+        if !path.is_dir() {
+            return Err(format!("Base directory does not exist or is not a directory: {}", dir));
+        }
+        *lock_base_directory() = Some(path);
+        Ok(())
+    })
+}
+
+/// Resets relative paths to resolve against the current working directory.
+#[unsafe(no_mangle)]
+pub extern "C" fn plot_clear_base_directory() {
+    *lock_base_directory() = None;
+}
+
+/// Removes all process-wide defaults set with `plot_set_global_option()`.
+#[unsafe(no_mangle)]
+pub extern "C" fn plot_reset_global_options() {
+    lock_global_options().clear();
+}
+
+/// Returns the default options, with any process-wide defaults taking precedence.
+#[unsafe(no_mangle)]
+pub extern "C" fn plot_options_default() -> PlotOptions {
+    let defaults = PlotOptions::default();
+    let opt = with_global_defaults(PlotOptions {
+        width: 0,
+        height: 0,
+        marker_radius: 0,
+        dpi: 0,
+        label_font_size: 0,
+        color_scheme: 0,
+        ..defaults
+    });
+    let or_default = |value: u32, default: u32| if value == 0 { default } else { value };
+    let mut opt = PlotOptions {
+        width: or_default(opt.width, defaults.width),
+        height: or_default(opt.height, defaults.height),
+        marker_radius: or_default(opt.marker_radius, defaults.marker_radius),
+        ..opt
+    };
+    if let Some(theme) = *lock_theme() {
+        theme.apply(&mut opt);
+    }
+    opt
+}
+
+/// Selects the theme `plot_options_default()` starts from.
+///
+/// Themes set the background, text, grid and axis colors, the tick label font size and
+/// whether grid lines are drawn; any field can still be changed on the returned options.
+/// Available themes:
+/// - `"default"`: the built-in look
+/// - `"dark"`: dark gray background, white text and axes, no grid
+/// - `"minimal"`: gray text and axes, no grid
+/// - `"scientific"`: faint grid, black axes and larger tick labels
+/// - `"seaborn"`: light blue-gray background with a white grid and no axis lines
+///
+/// The theme is process-wide and only affects options from `plot_options_default()`.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 if the name is unknown (call `plot_take_error()` for details)
+///
+/// # Safety
+/// `theme` must be a valid NUL-terminated UTF-8 string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_set_theme(theme: *const c_char) -> i32 {
+    run_ffi(|| {
+        if theme.is_null() {
+            return Err("Theme name is NULL".to_string());
+        }
+        let name = unsafe { CStr::from_ptr(theme) }
+            .to_str()
+            .map_err(|_| "Theme name is not valid UTF-8".to_string())?;
+        *lock_theme() = Theme::named(name)?;
+        Ok(())
+    })
+}
+
+/// Renders a scatter plot of interleaved `[x0, y0, x1, y1, ...]` data to a PNG file.
+///
+/// Otherwise identical to `plot_scatter_png()`.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output PNG file
+/// - `xy`: Pointer to `2 * n` interleaved coordinates
+/// - `n`: Number of points
+/// - `opt`: Plot configuration options
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xy` must point to an array of at least `2 * n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_interleaved_png(
+    path: *const c_char,
+    xy: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs, ys) = unsafe { deinterleave(xy, n) }?;
+        unsafe { render_with_ffi_hook(opt, |opt| plot_scatter_png_impl(&path_str, &xs, &ys, opt)) }
+    })
+}
+
+/// Renders a scatter plot of coordinates spread out in memory to a PNG file.
+///
+/// Point `i` is `(xs[i * x_stride], ys[i * y_stride])`, so rows or columns of a row-major
+/// matrix can be plotted without copying them out first. Strides count elements, not
+/// bytes. Otherwise identical to `plot_scatter_png()`.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output PNG file
+/// - `xs`, `x_stride`: Pointer to the first X coordinate and the element distance between
+///   X coordinates (at least 1)
+/// - `ys`, `y_stride`: Pointer to the first Y coordinate and the element distance between
+///   Y coordinates (at least 1)
+/// - `n`: Number of points
+/// - `opt`: Plot configuration options
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs.add((n - 1) * x_stride)` and `ys.add((n - 1) * y_stride)` must be within the same
+///   allocations as `xs` and `ys`
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_strided_png(
+    path: *const c_char,
+    xs: *const c_double,
+    x_stride: usize,
+    ys: *const c_double,
+    y_stride: usize,
+    n: u64,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let xs = unsafe { strided_values(xs, x_stride, n, "X") }?;
+        let ys = unsafe { strided_values(ys, y_stride, n, "Y") }?;
+        unsafe { render_with_ffi_hook(opt, |opt| plot_scatter_png_impl(&path_str, &xs, &ys, opt)) }
+    })
+}
+
+/// Renders a scatter plot of interleaved `[x0, y0, x1, y1, ...]` data into a newly allocated RGBA buffer.
+///
+/// Otherwise identical to `plot_scatter_rgba()`; release the buffer with `plot_free_buffer()`.
+///
+/// # Safety
+/// - `xy` must point to an array of at least `2 * n` elements
+/// - `out_ptr` and `out_len` must be valid for writes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_interleaved_rgba(
+    xy: *const c_double,
+    n: u64,
+    opt: PlotOptions,
+    out_ptr: *mut *mut u8,
+    out_len: *mut usize,
+) -> i32 {
+    run_ffi(|| {
+        let (xs, ys) = unsafe { deinterleave(xy, n) }?;
+        let pixels = plot_scatter_rgba_impl(&xs, &ys, unsafe { RenderOptions::from_ffi(opt) })?;
+        unsafe { write_buffer(pixels, out_ptr, out_len) }
+    })
+}
+
+/// Frees a buffer returned by `plot_scatter_rgba()` or `plot_scatter_f32_rgba()`.
+///
+/// Passing NULL is a no-op.
+///
+/// # Safety
+/// `ptr` and `len` must be exactly the values returned by the rendering call,
+/// and the buffer must not be freed twice.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_free_buffer(ptr: *mut u8, len: usize) {
+    if !ptr.is_null() {
+        drop(unsafe { Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)) });
+    }
+}
+
+/// Frees a buffer returned by `plot_scatter_rgb565()`.
+///
+/// Passing NULL is a no-op.
+///
+/// # Safety
+/// `ptr` and `len` must be exactly the values returned by the rendering call,
+/// and the buffer must not be freed twice.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_free_u16_buffer(ptr: *mut u16, len: usize) {
+    if !ptr.is_null() {
+        drop(unsafe { Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)) });
+    }
+}
+
+/// Starts a staged scatter plot whose points are supplied by `plot_append_points()`.
+///
+/// The tick and band arrays in `opt` are copied, so they only need to be valid during this call.
+/// Nothing is drawn until `plot_finish()`.
+///
+/// # Returns
+/// A context to pass to the other `plot_*` context functions; release it with
+/// `plot_context_free()`.
+///
+/// # Safety
+/// Non-null `opt.x_ticks`/`opt.y_ticks` must point to `x_n_ticks`/`y_n_ticks` values,
+/// and non-null `opt.h_bands`/`opt.v_bands` to `n_h_bands`/`n_v_bands` values.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_begin(opt: PlotOptions) -> *mut PlotContext {
+    Box::into_raw(Box::new(PlotContext::new(unsafe { RenderOptions::from_ffi(opt) })))
+}
+
+/// Adds `n` points to a context created by `plot_begin()`.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `ctx` must be a live context returned by `plot_begin()`
+/// - `xs` and `ys` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_append_points(
+    ctx: *mut PlotContext,
+    xs: *const c_double,
+    ys: *const c_double,
+    n: u64,
+) -> i32 {
+    run_ffi(|| {
+        let ctx = unsafe { ctx.as_mut() }.ok_or("Context pointer is NULL")?;
+        let (xs_slice, ys_slice) = unsafe { data_slices(xs, ys, n) }?;
+        ctx.append(xs_slice, ys_slice);
+        Ok(())
+    })
+}
+
+/// Renders all points appended to a context to a PNG file.
+///
+/// The output is identical to a single `plot_scatter_png()` call with all points and the
+/// options given to `plot_begin()`. The context stays valid and can receive more points,
+/// and keeps the rendered pixels for `plot_context_get_pixel()`.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure, including a context without points
+///
+/// # Safety
+/// - `ctx` must be a live context returned by `plot_begin()`
+/// - `path` must be a valid NUL-terminated UTF-8 string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_finish(ctx: *mut PlotContext, path: *const c_char) -> i32 {
+    run_ffi(|| {
+        let ctx = unsafe { ctx.as_mut() }.ok_or("Context pointer is NULL")?;
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let post_render = unsafe { ffi_post_render(*ctx.options()) };
+        ctx.finish(&path_str, post_render.as_ref())
+    })
+}
+
+/// Reads the RGBA color of one pixel of the image last rendered by `plot_finish()`.
+///
+/// `(0, 0)` is the top-left corner; coordinates include any outer padding. Rendered
+/// images are opaque, so alpha is always 255. NULL output pointers are skipped.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure: no image rendered yet, or `x`/`y` outside the image
+///
+/// # Safety
+/// - `ctx` must be a live context returned by `plot_begin()`
+/// - `r`, `g`, `b` and `a` must each be NULL or valid for writes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_context_get_pixel(
+    ctx: *const PlotContext,
+    x: u32,
+    y: u32,
+    r: *mut u8,
+    g: *mut u8,
+    b: *mut u8,
+    a: *mut u8,
+) -> i32 {
+    run_ffi(|| {
+        let ctx = unsafe { ctx.as_ref() }.ok_or("Context pointer is NULL")?;
+        let pixel = ctx.pixel(x, y)?;
+        for (out, value) in [r, g, b, a].into_iter().zip(pixel) {
+            // SAFETY: caller guarantees each non-NULL pointer is valid for writes
+            if let Some(out) = unsafe { out.as_mut() } {
+                *out = value;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Returns the number of points appended to a context so far.
+///
+/// Returns 0 if `ctx` is NULL.
+///
+/// # Safety
+/// `ctx` must be NULL or a live context returned by `plot_begin()`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_context_point_count(ctx: *const PlotContext) -> usize {
+    unsafe { ctx.as_ref() }.map_or(0, PlotContext::point_count)
+}
+
+/// Writes the axis ranges `plot_finish()` would use for the points appended so far.
+///
+/// Sets `x_min`, `x_max`, `y_min` and `y_max` of `out_opt` and clears its `auto_range`;
+/// other fields are left untouched. With auto-ranging enabled in the context's options
+/// these are the padded data bounds, otherwise the explicit ranges.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure, including a context without points
+///
+/// # Safety
+/// - `ctx` must be a live context returned by `plot_begin()`
+/// - `out_opt` must be valid for reads and writes of a `PlotOptions`
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_context_bounds(ctx: *const PlotContext, out_opt: *mut PlotOptions) -> i32 {
+    run_ffi(|| {
+        let ctx = unsafe { ctx.as_ref() }.ok_or("Context pointer is NULL")?;
+        let out = unsafe { out_opt.as_mut() }.ok_or("Output options pointer is NULL")?;
+        let (x_min, x_max, y_min, y_max) = ctx.bounds()?;
+        out.auto_range = 0;
+        out.x_min = x_min;
+        out.x_max = x_max;
+        out.y_min = y_min;
+        out.y_max = y_max;
+        Ok(())
+    })
+}
+
+/// Frees a context returned by `plot_begin()`.
+///
+/// Passing NULL is a no-op.
+///
+/// # Safety
+/// `ctx` must have been returned by `plot_begin()` and must not be freed twice.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_context_free(ctx: *mut PlotContext) {
+    if !ctx.is_null() {
+        drop(unsafe { Box::from_raw(ctx) });
+    }
+}
+
+/// Renders several data series into one chart in a single PNG file.
+///
+/// Each series is drawn with its own `SeriesStyle`; `opt.marker_radius` is not used.
+/// The axis ranges cover all series, and named series are listed in a legend. With a
+/// single series the legend is left out unless `opt.force_legend` is set.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output PNG file
+/// - `series`: Pointer to array of series
+/// - `n_series`: Number of series (at least one)
+/// - `opt`: Plot configuration options
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `series` must point to an array of at least `n_series` elements
+/// - each series' `xs` and `ys` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_series_png(
+    path: *const c_char,
+    series: *const ScatterSeries,
+    n_series: usize,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        if series.is_null() {
+            return Err("Series pointer is NULL".to_string());
+        }
+        let series = unsafe { std::slice::from_raw_parts(series, n_series) };
+
+        let mut data = Vec::with_capacity(series.len());
+        for (i, s) in series.iter().enumerate() {
+            let (xs, ys) = unsafe { data_slices(s.xs, s.ys, s.n) }.map_err(|e| format!("Series {}: {}", i, e))?;
+            data.push((xs, ys, *s));
+        }
+
+        plot_scatter_series_png_impl(&path_str, &data, unsafe { RenderOptions::from_ffi(opt) })
+    })
+}
+
+/// Renders several overlapping layers into one chart in a single PNG file.
+///
+/// Layers are drawn in array order, so later layers appear on top: scatter points,
+/// point density heatmaps (see `HeatmapLayer`) and reference lines. All layers share the
+/// axes; with `opt.auto_range` the ranges cover the points of all scatter layers, so at
+/// least one scatter layer is then required. Heatmap bins without points are left
+/// transparent and no colorbar is drawn.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output PNG file
+/// - `layers`: Pointer to array of layers
+/// - `n_layers`: Number of layers (at least one)
+/// - `opt`: Plot configuration options
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `layers` must point to an array of at least `n_layers` elements
+/// - each layer's `data` must point to the struct selected by its `layer_type`, whose
+///   `xs` and `ys` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_composite_png(
+    path: *const c_char,
+    layers: *const CompositeLayer,
+    n_layers: usize,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        if layers.is_null() {
+            return Err("Layers pointer is NULL".to_string());
+        }
+        let layers = unsafe { std::slice::from_raw_parts(layers, n_layers) };
+
+        let mut data = Vec::with_capacity(layers.len());
+        for (i, layer) in layers.iter().enumerate() {
+            if layer.data.is_null() {
+                return Err(format!("Layer {}: Data pointer is NULL", i));
+            }
+            // SAFETY: the caller guarantees data points to the struct selected by layer_type
+            let layer = match layer.layer_type {
+                PLOT_LAYER_SCATTER => {
+                    let scatter = unsafe { *layer.data.cast::<ScatterLayer>() };
+                    let (xs, ys) = unsafe { data_slices(scatter.xs, scatter.ys, scatter.n) }
+                        .map_err(|e| format!("Layer {}: {}", i, e))?;
+                    Layer::Scatter(xs, ys, scatter.into())
+                },
+                PLOT_LAYER_HEATMAP => {
+                    let heatmap = unsafe { *layer.data.cast::<HeatmapLayer>() };
+                    let (xs, ys) = unsafe { data_slices(heatmap.xs, heatmap.ys, heatmap.n) }
+                        .map_err(|e| format!("Layer {}: {}", i, e))?;
+                    Layer::Heatmap(xs, ys, heatmap.into())
+                },
+                PLOT_LAYER_REFERENCE_LINE => Layer::ReferenceLine(unsafe { *layer.data.cast::<ReferenceLine>() }),
+                other => return Err(format!("Layer {}: Unsupported layer type: {}", i, other)),
+            };
+            data.push(layer);
+        }
+
+        plot_composite_png_impl(&path_str, &data, unsafe { RenderOptions::from_ffi(opt) })
+    })
+}
+
+/// Renders a line chart with two Y axes to a PNG file.
+///
+/// Both series share `xs` and are drawn as lines connecting the points in array order:
+/// `ys_left` against the left axis, set up by `main_opt` like any other chart, and
+/// `ys_right` against a right axis set up by `right_axis`. `main_opt` controls all
+/// image-level settings. The right series, tick labels and name use
+/// `right_axis.series_color`; the left series uses the first color of
+/// `main_opt.color_scheme`.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output PNG file
+/// - `xs`: Pointer to array of X coordinates
+/// - `ys_left`, `ys_right`: Pointers to arrays of Y coordinates for each axis
+/// - `n`: Number of points (length of all three arrays)
+/// - `main_opt`: Plot configuration options
+/// - `right_axis`: Range and style of the right axis
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `xs`, `ys_left` and `ys_right` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_dual_axis_png(
+    path: *const c_char,
+    xs: *const c_double,
+    ys_left: *const c_double,
+    ys_right: *const c_double,
+    n: u64,
+    main_opt: PlotOptions,
+    right_axis: AxisOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        let (xs_slice, left_slice) = unsafe { data_slices(xs, ys_left, n) }?;
+        let (_, right_slice) = unsafe { data_slices(xs, ys_right, n) }?;
+        plot_dual_axis_png_impl(
+            &path_str,
+            xs_slice,
+            (left_slice, right_slice),
+            unsafe { RenderOptions::from_ffi(main_opt) },
+            right_axis,
+        )
+    })
+}
+
+/// Renders one violin per group of samples to a PNG file.
+///
+/// Each violin mirrors a Gaussian kernel density estimate (bandwidth by Silverman's rule)
+/// over its group's data range, scaled so the widest violin fills 80% of its column.
+/// Thin lines mark the median and the interquartile range. Groups are placed left to
+/// right and labelled with their names; `opt.x_min`, `opt.x_max` and X tick options are not used.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output PNG file
+/// - `groups`: Pointer to array of groups, each with at least two distinct finite values
+/// - `n_groups`: Number of groups
+/// - `opt`: Plot configuration options
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `groups` must point to an array of at least `n_groups` elements
+/// - each group's `values` must point to an array of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_violin_png(
+    path: *const c_char,
+    groups: *const BoxGroup,
+    n_groups: usize,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        if groups.is_null() {
+            return Err("Groups pointer is NULL".to_string());
+        }
+        let groups = unsafe { std::slice::from_raw_parts(groups, n_groups) };
+
+        let mut data = Vec::with_capacity(groups.len());
+        for (i, group) in groups.iter().enumerate() {
+            if group.values.is_null() {
+                return Err(format!("Group {}: values pointer is NULL", i));
+            }
+            let n = point_count::<c_double>(group.n).map_err(|e| format!("Group {}: {}", i, e))?;
+            let values = unsafe { std::slice::from_raw_parts(group.values, n) };
+            let label = fixed_str(&group.label).map_err(|e| format!("Group {}: {}", i, e))?;
+            data.push((values, label));
+        }
+
+        plot_violin_png_impl(&path_str, &data, unsafe { RenderOptions::from_ffi(opt) })
+    })
+}
+
+/// Renders several scatter plots into a grid in a single PNG file.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output PNG file
+/// - `specs`: Pointer to array of chart specs (their `path` fields are ignored)
+/// - `n_specs`: Number of specs
+/// - `grid_cols`: Number of grid columns
+/// - `opt`: Grid options; `width` and `height` give the size of each cell
+///
+/// The image is `grid_cols * width` pixels wide and `ceil(n_specs / grid_cols) * height`
+/// pixels high. Cells without a spec are left white.
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `specs` must point to an array of at least `n_specs` elements
+/// - each spec's `xs` and `ys` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_grid_png(
+    path: *const c_char,
+    specs: *const BatchSpec,
+    n_specs: usize,
+    grid_cols: u32,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        if specs.is_null() {
+            return Err("Specs pointer is NULL".to_string());
+        }
+        let specs = unsafe { std::slice::from_raw_parts(specs, n_specs) };
+
+        let mut cells = Vec::with_capacity(specs.len());
+        for (i, spec) in specs.iter().enumerate() {
+            let (xs, ys) =
+                unsafe { data_slices(spec.xs, spec.ys, spec.n) }.map_err(|e| format!("Spec {}: {}", i, e))?;
+            cells.push((xs, ys, unsafe { RenderOptions::from_ffi(spec.opt) }));
+        }
+
+        plot_grid_png_impl(&path_str, &cells, grid_cols, opt)
+    })
+}
+
+/// Renders each spec to its own PNG file, spreading the work over threads.
+///
+/// Every spec is rendered as by `plot_scatter_png()`, and a failing spec does not stop
+/// the others. With the `parallel` Cargo feature the specs are shared out among one
+/// thread per CPU core; otherwise they are rendered one after another on the calling
+/// thread. `post_render_fn` hooks may therefore run on worker threads.
+///
+/// # Returns
+/// - the number of specs that failed to render (0 = all succeeded); read their errors with
+///   `plot_batch_errors()`
+/// - -1 if `specs` is NULL (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `specs` must point to an array of at least `n_specs` elements
+/// - each spec's `path` must be a valid NUL-terminated UTF-8 string, and its `xs` and
+///   `ys` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_png_batch_parallel(specs: *const BatchSpec, n_specs: usize) -> i32 {
+    lock_batch_errors().clear();
+    clear_error();
+    clear_warning();
+    if specs.is_null() {
+        set_error("Specs pointer is NULL".to_string(), ErrorKind::Other);
+        return -1;
+    }
+    // SAFETY: caller guarantees specs points to n_specs elements
+    let specs = unsafe { std::slice::from_raw_parts(specs, n_specs) };
+
+    let mut errors = render_batch(specs);
+    errors.sort_by_key(|&(index, _)| index);
+    let failed = errors.len();
+    if let Some((index, msg)) = errors.first() {
+        set_error(
+            format!(
+                "{} of {} batch renders failed; spec {}: {}",
+                failed, n_specs, index, msg
+            ),
+            ErrorKind::Other,
+        );
+    }
+    *lock_batch_errors() = errors;
+    i32::try_from(failed).unwrap_or(i32::MAX)
+}
+
+/// Copies the failures of the last `plot_scatter_png_batch_parallel()` call into `out_buf`.
+///
+/// Failures are ordered by spec index. At most `buf_capacity` entries are written; NULL
+/// `out_buf` only counts them.
+///
+/// # Returns
+/// The total number of failures, which may exceed `buf_capacity`.
+///
+/// # Safety
+/// A non-NULL `out_buf` must be valid for writes of `buf_capacity` elements.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_batch_errors(out_buf: *mut BatchError, buf_capacity: usize) -> usize {
+    let errors = lock_batch_errors();
+    if !out_buf.is_null() {
+        for (i, (index, msg)) in errors.iter().take(buf_capacity).enumerate() {
+            let mut error = BatchError {
+                index: *index,
+                message: [0; 256],
+            };
+            write_fixed_str(&mut error.message, msg);
+            // SAFETY: caller guarantees out_buf holds buf_capacity elements
+            unsafe { out_buf.add(i).write(error) };
+        }
+    }
+    errors.len()
+}
+
+/// Locks the batch failures, recovering from a poisoned mutex.
+fn lock_batch_errors() -> MutexGuard<'static, Vec<(usize, String)>> {
+    BATCH_ERRORS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Renders one batch spec, converting errors and panics into a message.
+///
+/// # Safety
+/// The spec's pointers must be valid as documented on `plot_scatter_png_batch_parallel()`.
+unsafe fn render_batch_spec(spec: &BatchSpec) -> Result<(), String> {
+    catch_unwind(AssertUnwindSafe(|| {
+        let path_str = unsafe { path_from_ptr(spec.path) }?;
+        let (xs_slice, ys_slice) = unsafe { data_slices(spec.xs, spec.ys, spec.n) }?;
+        unsafe {
+            render_with_ffi_hook(spec.opt, |opt| {
+                plot_scatter_png_impl(&path_str, xs_slice, ys_slice, opt)
+            })
+        }
+    }))
+    .unwrap_or_else(|panic_info| Err(panic_message(panic_info.as_ref())))
+}
+
+/// Renders all specs on the calling thread, returning the failures.
+#[cfg(not(feature = "parallel"))]
+fn render_batch(specs: &[BatchSpec]) -> Vec<(usize, String)> {
+    specs
+        .iter()
+        .enumerate()
+        // SAFETY: plot_scatter_png_batch_parallel's caller guarantees valid specs
+        .filter_map(|(i, spec)| unsafe { render_batch_spec(spec) }.err().map(|e| (i, e)))
+        .collect()
+}
+
+/// Renders the specs on one scoped thread per core, returning the failures.
+#[cfg(feature = "parallel")]
+fn render_batch(specs: &[BatchSpec]) -> Vec<(usize, String)> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// The specs, shared with the worker threads.
+    struct SharedSpecs<'a>(&'a [BatchSpec]);
+    // SAFETY: the caller keeps all data behind the spec pointers valid and unchanged for
+    // the duration of the batch, and renders only read it
+    unsafe impl Sync for SharedSpecs<'_> {}
+
+    let shared = SharedSpecs(specs);
+    let next = AtomicUsize::new(0);
+    let errors = Mutex::new(Vec::new());
+    let threads = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(specs.len());
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                let specs = &shared;
+                // Each thread takes the next unrendered spec until none are left
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(spec) = specs.0.get(i) else {
+                        break;
+                    };
+                    // SAFETY: plot_scatter_png_batch_parallel's caller guarantees valid specs
+                    if let Err(e) = unsafe { render_batch_spec(spec) } {
+                        errors.lock().unwrap_or_else(|e| e.into_inner()).push((i, e));
+                    }
+                }
+            });
+        }
+    });
+    errors.into_inner().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Renders an animated GIF with one scatter plot per frame.
+///
+/// With `opt.auto_range` the axis ranges cover the points of all frames, so the axes do
+/// not jump between frames. The animation loops forever. `opt.dpi` is ignored.
+///
+/// # Parameters
+/// - `path`: NUL-terminated UTF-8 path to the output GIF file
+/// - `frames`: Pointer to array of frames
+/// - `n_frames`: Number of frames
+/// - `frame_delay_ms`: Display time of each frame in milliseconds, rounded up to 10 ms
+/// - `opt`: Plot configuration options
+///
+/// # Returns
+/// - 0 on success
+/// - 1 on failure (call `plot_take_error()` for details)
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated UTF-8 string
+/// - `frames` must point to an array of at least `n_frames` elements
+/// - each frame's `xs` and `ys` must point to arrays of at least `n` elements
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_scatter_gif(
+    path: *const c_char,
+    frames: *const GifFrame,
+    n_frames: usize,
+    frame_delay_ms: u32,
+    opt: PlotOptions,
+) -> i32 {
+    run_ffi(|| {
+        let path_str = unsafe { path_from_ptr(path) }?;
+        if frames.is_null() {
+            return Err("Frames pointer is NULL".to_string());
+        }
+        let frames = unsafe { std::slice::from_raw_parts(frames, n_frames) };
+
+        let mut data = Vec::with_capacity(frames.len());
+        for (i, frame) in frames.iter().enumerate() {
+            let slices =
+                unsafe { data_slices(frame.xs, frame.ys, frame.n) }.map_err(|e| format!("Frame {}: {}", i, e))?;
+            data.push(slices);
+        }
+
+        plot_scatter_gif_impl(&path_str, &data, frame_delay_ms, unsafe {
+            RenderOptions::from_ffi(opt)
+        })
+    })
+}
+
+/// Returns the last error message, or NULL if no error has occurred.
+///
+/// The returned pointer is valid until the next call to `plot_scatter_png()`.
+/// The string is NUL-terminated UTF-8.
+///
+/// Deprecated: another thread's rendering call can free the string while it is being
+/// read. Use `plot_take_error()` or `plot_copy_last_error()` instead.
+///
+/// # Safety
+/// The returned pointer must not be freed by the caller.
+#[unsafe(no_mangle)]
+pub extern "C" fn plot_last_error_message() -> *const c_char {
+    match &*lock_last_error() {
+        Some((cstring, _)) => cstring.as_ptr(),
+        None => std::ptr::null(),
+    }
+}
+
+/// Copies `text` into a caller buffer of `buf_len` bytes, truncating at a character boundary.
+///
+/// Returns the number of bytes written, excluding the NUL terminator.
+///
+/// # Safety
+/// Non-NULL `buf` must be valid for writes of `buf_len` bytes.
+unsafe fn copy_to_buffer(text: &CStr, buf: *mut c_char, buf_len: usize) -> usize {
+    if buf.is_null() || buf_len == 0 {
+        return 0;
+    }
+    let bytes = text.to_bytes();
+    let mut len = bytes.len().min(buf_len - 1);
+    // Error messages are UTF-8; do not cut a multi-byte character in half
+    while len > 0 && len < bytes.len() && (bytes[len] & 0xC0) == 0x80 {
+        len -= 1;
+    }
+    unsafe {
+        std::ptr::copy_nonoverlapping(bytes.as_ptr() as *const c_char, buf, len);
+        *buf.add(len) = 0;
+    }
+    len
+}
+
+/// Copies the last error message into a caller-provided buffer.
+///
+/// Unlike `plot_last_error_message()`, the copy stays valid regardless of later calls.
+/// A message longer than `buf_len - 1` bytes is truncated (at a UTF-8 character
+/// boundary). The buffer is always NUL-terminated when `buf_len > 0`.
+///
+/// # Returns
+/// The number of bytes written excluding the NUL terminator, or 0 if no error is stored
+/// (the buffer then holds an empty string).
+///
+/// # Safety
+/// `buf` must be NULL or valid for writes of `buf_len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plot_copy_last_error(buf: *mut c_char, buf_len: usize) -> usize {
+    match &*lock_last_error() {
+        Some((cstring, _)) => unsafe { copy_to_buffer(cstring, buf, buf_len) },
+        None => unsafe { copy_to_buffer(c"", buf, buf_len) },
+    }
+}
+
+/// Removes the last error message and returns it as a newly allocated string.
+///
+/// This is the recommended way to read errors: the caller owns the returned string, so
+/// it stays valid across later calls and other threads. Release it with
+/// `plot_free_string()`. Afterwards no error is stored, as after `plot_clear_last_error()`.
+///
+/// # Returns
+/// The NUL-terminated UTF-8 message, or NULL if no error is stored.
+#[unsafe(no_mangle)]
+pub extern "C" fn plot_take_error() -> *mut c_char {
+    match lock_last_error().take() {
+        Some((cstring, _)) => cstring.into_raw(),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Returns the last warning message, or NULL if the last call produced no warning.
+///
+/// Warnings report non-fatal substitutions, such as rendering with a system font
+/// because the bundled font could not be loaded. Each rendering call clears the
+/// previous warning. The string is NUL-terminated UTF-8.
+///
+/// # Safety
+/// The returned pointer must not be freed by the caller.
+#[unsafe(no_mangle)]
+pub extern "C" fn plot_last_warning_message() -> *const c_char {
+    match &*lock_last_warning() {
+        Some(cstring) => cstring.as_ptr(),
+        None => std::ptr::null(),
+    }
+}
+
+/// Clears the last error message.
+///
+/// Calling this is optional: `plot_scatter_png()` already clears the error at its start.
+/// It is useful to reset the error state before a series of operations.
+#[unsafe(no_mangle)]
+pub extern "C" fn plot_clear_last_error() {
+    clear_error();
+}
+
+/// Returns 1 if an error message is currently stored, 0 otherwise.
+///
+/// This allows checking for an error without reading the message.
+#[unsafe(no_mangle)]
+pub extern "C" fn plot_has_error() -> u8 {
+    lock_last_error().is_some() as u8
+}
+
+/// Returns the category of the stored error as a `PLOT_ERR_*` code.
+///
+/// `PLOT_ERR_NONE` if no error is stored. Like the message, the code describes the last
+/// failed call and is cleared by the next call or by `plot_take_error()`.
+#[unsafe(no_mangle)]
+pub extern "C" fn plot_last_error_code() -> i32 {
+    lock_last_error()
+        .as_ref()
+        .map_or(PLOT_ERR_NONE, |&(_, kind)| kind.code())
+}
+
+/// Returns the `PLOTFFI_ABI_VERSION` the library was built with.
+///
+/// Compare it with the `PLOTFFI_ABI_VERSION` macro from the header to detect a
+/// mismatched shared library at runtime.
+#[unsafe(no_mangle)]
+pub extern "C" fn plot_abi_version() -> u32 {
+    PLOTFFI_ABI_VERSION
+}
+
+/// Returns `sizeof(PlotOptions)` as compiled into the library.
+///
+/// A caller built against a different header passes a struct of a different size by
+/// value, which corrupts every argument after it. Compare the result with the header's
+/// `PLOTFFI_OPTIONS_SIZE` at startup and refuse to render on a mismatch.
+#[unsafe(no_mangle)]
+pub extern "C" fn plot_options_size() -> usize {
+    std::mem::size_of::<PlotOptions>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+    use std::fs;
+
+    #[test]
+    fn test_basic_plot() {
+        let path = CString::new("/tmp/test_scatter.png").unwrap();
+        let xs: Vec<f64> = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        let ys: Vec<f64> = vec![1.0, 4.0, 2.0, 3.0, 5.0];
+        let opt = PlotOptions::default();
+
+        let result = unsafe { plot_scatter_png(path.as_ptr(), xs.as_ptr(), ys.as_ptr(), xs.len() as u64, opt) };
+
+        assert_eq!(result, 0, "Expected success");
+        assert!(fs::metadata("/tmp/test_scatter.png").is_ok());
+        fs::remove_file("/tmp/test_scatter.png").ok();
+    }
+
+    #[test]
+    fn test_null_path() {
+        let xs: Vec<f64> = vec![1.0, 2.0];
+        let ys: Vec<f64> = vec![1.0, 2.0];
+        let opt = PlotOptions::default();
+
+        let result = unsafe { plot_scatter_png(std::ptr::null(), xs.as_ptr(), ys.as_ptr(), xs.len() as u64, opt) };
+
+        assert_eq!(result, 1, "Expected failure for NULL path");
+        let err = plot_last_error_message();
+        assert!(!err.is_null());
+    }
+
+    #[test]
+    fn test_zero_count() {
+        let path = CString::new("/tmp/test_zero.png").unwrap();
+        let xs: Vec<f64> = vec![];
+        let ys: Vec<f64> = vec![];
+        let opt = PlotOptions::default();
+
+        let result = unsafe { plot_scatter_png(path.as_ptr(), xs.as_ptr(), ys.as_ptr(), 0, opt) };
+
+        assert_eq!(result, 1, "Expected failure for zero count");
+    }
+
+    #[test]
+    fn test_explicit_range() {
+        let path = CString::new("/tmp/test_explicit.png").unwrap();
+        let xs: Vec<f64> = vec![1.0, 2.0, 3.0];
+        let ys: Vec<f64> = vec![1.0, 2.0, 3.0];
+        let opt = PlotOptions {
+            auto_range: 0,
+            x_min: 0.0,
+            x_max: 10.0,
+            y_min: 0.0,
+            y_max: 10.0,
+            ..Default::default()
+        };
+
+        let result = unsafe { plot_scatter_png(path.as_ptr(), xs.as_ptr(), ys.as_ptr(), xs.len() as u64, opt) };
+
+        assert_eq!(result, 0, "Expected success with explicit range");
+        fs::remove_file("/tmp/test_explicit.png").ok();
+    }
+
+    #[test]
+    fn test_lock_aspect_ratio() {
+        let opt = PlotOptions {
+            auto_range: 0,
+            x_min: 0.0,
+            x_max: 10.0,
+            y_min: 0.0,
+            y_max: 10.0,
+            lock_aspect_ratio: 1,
+            ..Default::default()
+        };
+
+        let layout = compute_layout(&opt, 10.0, 10.0);
+        assert_eq!(
+            layout.plot_width(opt.width),
+            layout.plot_height(opt.height),
+            "Expected a square plot area"
+        );
+
+        let path = CString::new("/tmp/test_lock_aspect.png").unwrap();
+        let xs: Vec<f64> = vec![1.0, 5.0, 9.0];
+        let ys: Vec<f64> = vec![1.0, 5.0, 9.0];
+        let result = unsafe { plot_scatter_png(path.as_ptr(), xs.as_ptr(), ys.as_ptr(), xs.len() as u64, opt) };
+
+        assert_eq!(result, 0, "Expected success with locked aspect ratio");
+        fs::remove_file("/tmp/test_lock_aspect.png").ok();
+    }
+
+    #[test]
+    fn test_plot_area_fraction() {
+        let opt = PlotOptions {
+            width: 800,
+            height: 600,
+            plot_area_fraction: 0.5,
+            ..Default::default()
+        };
+        let layout = compute_layout(&opt, 1.0, 1.0);
+        assert_eq!((layout.plot_width(800), layout.plot_height(600)), (400, 300));
+        // The label areas keep their default share of the axis sides
+        assert_eq!((layout.y_label_area_size, layout.margin_left), (166, 34));
+        assert_eq!((layout.x_label_area_size, layout.margin_bottom), (120, 30));
+        plot_scatter_png_impl("/tmp/test_plot_area_fraction.png", &[1.0, 2.0], &[2.0, 1.0], opt).unwrap();
+        fs::remove_file("/tmp/test_plot_area_fraction.png").ok();
+
+        for fraction in [-0.5, 1.5, f64::NAN] {
+            let opt = PlotOptions {
+                plot_area_fraction: fraction,
+                ..Default::default()
+            };
+            let err = plot_scatter_png_impl("/tmp/test_plot_area_fraction.png", &[1.0], &[1.0], opt).unwrap_err();
+            assert!(err.starts_with("Plot area fraction"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_default_label_area_sizes() {
+        let xs: Vec<f64> = vec![1.0, 2.0, 3.0];
+        let ys: Vec<f64> = vec![3.0, 1.0, 2.0];
+        let default_opt = PlotOptions::default();
+        let explicit_opt = PlotOptions {
+            x_label_area_size: 40,
+            y_label_area_size: 50,
+            ..Default::default()
+        };
+
+        plot_scatter_png_impl("/tmp/test_label_area_default.png", &xs, &ys, default_opt).unwrap();
+        plot_scatter_png_impl("/tmp/test_label_area_explicit.png", &xs, &ys, explicit_opt).unwrap();
+
+        let default_bytes = fs::read("/tmp/test_label_area_default.png").unwrap();
+        let explicit_bytes = fs::read("/tmp/test_label_area_explicit.png").unwrap();
+        assert_eq!(
+            default_bytes, explicit_bytes,
+            "Zero label area sizes should use the defaults"
+        );
+        fs::remove_file("/tmp/test_label_area_default.png").ok();
+        fs::remove_file("/tmp/test_label_area_explicit.png").ok();
+    }
+
+    #[test]
+    fn test_x_label_rotation() {
+        let xs: Vec<f64> = vec![1000.0, 2000.0, 3000.0];
+        let ys: Vec<f64> = vec![1.0, 2.0, 3.0];
+        // Rows below the X axis with any ink between the left and right plot edges
+        let label_rows = |x_label_rotation: i32| {
+            let opt = PlotOptions {
+                x_label_rotation,
+                ..Default::default()
+            };
+            let info = plot_area_info(&xs, &ys, opt).unwrap();
+            let rgba = plot_scatter_rgba_impl(&xs, &ys, opt).unwrap();
+            let inked = |x: u32, y: u32| rgba[((y * opt.width + x) * 4) as usize..][..3] != [255, 255, 255];
+            let rows = (info.chart_y + info.chart_height + 1..opt.height)
+                .filter(|&y| (info.chart_x..info.chart_x + info.chart_width).any(|x| inked(x, y)))
+                .count();
+            (rgba, rows)
+        };
+
+        let (upright, upright_rows) = label_rows(0);
+        let (rotated, rotated_rows) = label_rows(45);
+        assert_ne!(upright, rotated);
+        assert!(
+            rotated_rows > upright_rows,
+            "Expected rotated labels to be taller: {} vs {} rows",
+            rotated_rows,
+            upright_rows
+        );
+    }
+
+    #[test]
+    fn test_unsupported_label_rotation() {
+        let opt = PlotOptions {
+            y_label_rotation: 30,
+            ..Default::default()
+        };
+
+        let err = plot_scatter_png_impl("/tmp/test_bad_rotation.png", &[1.0], &[1.0], opt).unwrap_err();
+        assert_eq!(err, "Unsupported label rotation: must be 0, ±45, or ±90");
+    }
+
+    #[test]
+    fn test_dpi_metadata() {
+        let xs: Vec<f64> = vec![1.0, 2.0, 3.0];
+        let ys: Vec<f64> = vec![1.0, 2.0, 3.0];
+        let opt = PlotOptions {
+            dpi: 150,
+            ..Default::default()
+        };
+
+        plot_scatter_png_impl("/tmp/test_dpi.png", &xs, &ys, opt).unwrap();
+
+        let bytes = fs::read("/tmp/test_dpi.png").unwrap();
+        let pos = bytes
+            .windows(4)
+            .position(|w| w == b"pHYs")
+            .expect("Expected a pHYs chunk");
+        let dots_per_metre = u32::from_be_bytes(bytes[pos + 4..pos + 8].try_into().unwrap());
+        assert_eq!(dots_per_metre, 5906);
+        fs::remove_file("/tmp/test_dpi.png").ok();
+    }
+
+    #[test]
+    fn test_png_text_metadata() {
+        let mut opt = PlotOptions::default();
+        write_fixed_str(&mut opt.png_title, "Quarterly report");
+        write_fixed_str(&mut opt.png_description, "Größe über Zeit");
+        plot_scatter_png_impl("/tmp/test_png_text.png", &[1.0, 2.0], &[2.0, 1.0], opt).unwrap();
+
+        let bytes = fs::read("/tmp/test_png_text.png").unwrap();
+        let chunk = |kind: &[u8]| {
+            let pos = bytes
+                .windows(4)
+                .position(|w| w == kind)
+                .unwrap_or_else(|| panic!("no {:?} chunk", kind));
+            let len = u32::from_be_bytes(bytes[pos - 4..pos].try_into().unwrap()) as usize;
+            bytes[pos + 4..pos + 4 + len].to_vec()
+        };
+        assert_eq!(chunk(b"tEXt"), b"Title\0Quarterly report");
+        // Text that is not ASCII is stored as UTF-8 in iTXt
+        assert_eq!(chunk(b"iTXt"), "Description\0\0\0\0\0Größe über Zeit".as_bytes());
+        assert!(!bytes.windows(6).any(|w| w == b"Author"));
+        // The file is still a valid PNG
+        image::open("/tmp/test_png_text.png").unwrap();
+        fs::remove_file("/tmp/test_png_text.png").ok();
+    }
+
+    #[test]
+    fn test_bands() {
+        let xs: Vec<f64> = vec![1.0, 2.0, 3.0, 4.0];
+        let ys: Vec<f64> = vec![60.0, 90.0, 110.0, 140.0];
+        // Green "normal range", and a vertical band reaching past the X axis
+        let h_bands = [HBand {
+            lo: 80.0,
+            hi: 120.0,
+            color: 0x8000FF00,
+        }];
+        let v_bands = [VBand {
+            lo: 3.5,
+            hi: 100.0,
+            color: 0x80FF0000,
+        }];
+        let base = PlotOptions {
+            width: 400,
+            height: 300,
+            auto_range: 0,
+            x_min: 0.0,
+            x_max: 5.0,
+            y_min: 50.0,
+            y_max: 150.0,
+            marker_radius: 2,
+            ..Default::default()
+        };
+        let opt = RenderOptions::from(base).with_h_bands(&h_bands).with_v_bands(&v_bands);
+        let plain = plot_scatter_rgba_impl(&xs, &ys, base).unwrap();
+        let banded = plot_scatter_rgba_impl(&xs, &ys, opt).unwrap();
+        // The C API reads the same bands from the pointer fields
+        let ffi_opt = PlotOptions {
+            h_bands: h_bands.as_ptr(),
+            n_h_bands: 1,
+            v_bands: v_bands.as_ptr(),
+            n_v_bands: 1,
+            ..base
+        };
+        assert_eq!(
+            plot_scatter_rgba_impl(&xs, &ys, unsafe { RenderOptions::from_ffi(ffi_opt) }).unwrap(),
+            banded
+        );
+        let pixel = |rgba: &[u8], x: f64, y: f64| {
+            let (px, py) = data_to_pixel(base, x, y).unwrap();
+            let i = (py as usize * 400 + px as usize) * 4;
+            [rgba[i], rgba[i + 1], rgba[i + 2]]
+        };
+
+        // Half of the band color is mixed into the background and grid
+        let mixed = |x: f64, y: f64, color: [u8; 3]| {
+            let (under, over) = (pixel(&plain, x, y), pixel(&banded, x, y));
+            (0..3).all(|c| (over[c] as i32 - (under[c] as i32 + color[c] as i32) / 2).abs() <= 2)
+        };
+        assert!(mixed(0.7, 103.0, [0, 255, 0]));
+        assert!(mixed(2.5, 85.0, [0, 255, 0]));
+        assert_eq!(pixel(&banded, 0.7, 67.0), pixel(&plain, 0.7, 67.0));
+        // Outside the Y band but inside the clipped X band
+        assert!(mixed(4.7, 67.0, [255, 0, 0]));
+        assert!(!mixed(4.7, 67.0, [0, 255, 0]));
+        // Markers stay on top of the bands
+        assert_eq!(pixel(&banded, 2.0, 90.0), pixel(&plain, 2.0, 90.0));
+
+        let bad = [HBand {
+            lo: f64::NAN,
+            hi: 1.0,
+            color: 0,
+        }];
+        let opt = RenderOptions::from(base).with_h_bands(&bad);
+        assert_eq!(
+            plot_scatter_rgba_impl(&xs, &ys, opt).unwrap_err(),
+            "Horizontal band 0: edges must be finite"
+        );
+    }
+
+    #[test]
+    fn test_grid_png() {
+        let path = CString::new("/tmp/test_grid.png").unwrap();
+        let xs: Vec<f64> = vec![1.0, 2.0, 3.0];
+        let ys: Vec<f64> = vec![3.0, 1.0, 2.0];
+        let spec = BatchSpec {
+            path: std::ptr::null(),
+            xs: xs.as_ptr(),
+            ys: ys.as_ptr(),
+            n: xs.len() as u64,
+            opt: PlotOptions::default(),
+        };
+        let specs = [spec, spec, spec];
+        let opt = PlotOptions {
+            width: 300,
+            height: 200,
+            ..Default::default()
+        };
+
+        let result = unsafe { plot_grid_png(path.as_ptr(), specs.as_ptr(), specs.len(), 2, opt) };
+
+        assert_eq!(result, 0, "Expected success for 2x2 grid with 3 specs");
+        let bytes = fs::read("/tmp/test_grid.png").unwrap();
+        let width = u32::from_be_bytes(bytes[16..20].try_into().unwrap());
+        let height = u32::from_be_bytes(bytes[20..24].try_into().unwrap());
+        assert_eq!((width, height), (600, 400));
+        fs::remove_file("/tmp/test_grid.png").ok();
+    }
+
+    #[test]
+    fn test_format_scientific() {
+        assert_eq!(format_scientific(1e-7, true), "1.00e-7");
+        assert_eq!(format_scientific(1e-7, false), "1e-7");
+        assert_eq!(format_scientific(2.5e6, false), "2.5e6");
+        assert_eq!(format_scientific(0.0, false), "0e0");
+    }
+
+    #[test]
+    fn test_y_scientific() {
+        let xs: Vec<f64> = vec![1.0, 2.0, 3.0];
+        let ys: Vec<f64> = vec![2e-7, 5e-7, 8e-7];
+        let opt = PlotOptions {
+            auto_range: 0,
+            x_min: 0.0,
+            x_max: 4.0,
+            y_min: 1e-7,
+            y_max: 9e-7,
+            y_scientific: 1,
+            keep_trailing_zeros: 1,
+            ..Default::default()
+        };
+
+        assert_eq!(format_scientific(1.5e-7, false), "1.5e-7");
+        assert_eq!(format_scientific(1e-7, false), "1e-7");
+        assert_eq!(format_scientific(1e-7, true), "1.00e-7");
+        // The Y key points that get labelled are all written in scientific notation
+        let key_points = RangedCoordf64::from(opt.y_min..opt.y_max).key_points(tick_count(opt.y_tick_count));
+        let labels: Vec<String> = key_points.iter().map(|&v| format_scientific(v, true)).collect();
+        assert!(labels.contains(&"1.00e-7".to_string()), "{:?}", labels);
+        assert!(labels.iter().all(|l| l.ends_with("e-7")), "{:?}", labels);
+
+        let scientific = plot_scatter_rgba_impl(&xs, &ys, opt).unwrap();
+        let plain = plot_scatter_rgba_impl(&xs, &ys, PlotOptions { y_scientific: 0, ..opt }).unwrap();
+        assert_ne!(scientific, plain);
+        let trimmed = plot_scatter_rgba_impl(
+            &xs,
+            &ys,
+            PlotOptions {
+                keep_trailing_zeros: 0,
+                ..opt
+            },
+        )
+        .unwrap();
+        assert_ne!(scientific, trimmed);
+    }
+
+    #[test]
+    fn test_all_nan_skipped() {
+        let path = CString::new("/tmp/test_all_nan.png").unwrap();
+        let xs: Vec<f64> = vec![f64::NAN; 5];
+        let ys: Vec<f64> = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        let opt = PlotOptions {
+            nan_policy: 1,
+            ..Default::default()
+        };
+
+        let result = unsafe { plot_scatter_png(path.as_ptr(), xs.as_ptr(), ys.as_ptr(), xs.len() as u64, opt) };
+        assert_eq!(result, 1, "Expected failure when every point is NaN");
+
+        let err = plot_scatter_png_impl("/tmp/test_all_nan.png", &xs, &ys, opt).unwrap_err();
+        assert!(err.contains("No valid"), "Unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_nan_rejected_by_default() {
+        let xs: Vec<f64> = vec![1.0, f64::NAN, 3.0];
+        let ys: Vec<f64> = vec![1.0, 2.0, 3.0];
+
+        let err = plot_scatter_png_impl("/tmp/test_nan_rejected.png", &xs, &ys, PlotOptions::default()).unwrap_err();
+        assert_eq!(err, "NaN coordinate at index 1");
+
+        let opt = PlotOptions {
+            nan_policy: 2,
+            ..Default::default()
+        };
+        let err = plot_scatter_png_impl("/tmp/test_nan_rejected.png", &xs, &ys, opt).unwrap_err();
+        assert_eq!(err, "Unsupported NaN policy: 2");
+        let err = plot_cdf_png_impl("/tmp/test_nan_rejected.png", &xs, opt).unwrap_err();
+        assert_eq!(err, "Unsupported NaN policy: 2");
+    }
+
+    #[test]
+    fn test_border_style() {
+        let opt = PlotOptions {
+            auto_range: 0,
+            x_max: 10.0,
+            y_max: 10.0,
+            border_width: 3,
+            border_color: 0xFF_FF_00_00,
+            ..Default::default()
+        };
+        let info = plot_area_info(&[1.0], &[1.0], opt).unwrap();
+        // Pixels of `color` across the left axis at mid-height and across the bottom axis
+        // at mid-width, away from the ticks
+        let axis_pixels = |opt: PlotOptions, color: [u8; 3]| {
+            let rgba = plot_scatter_rgba_impl(&[1.0], &[1.0], opt).unwrap();
+            let is_color = |x: u32, y: u32| rgba[((y * opt.width + x) * 4) as usize..][..3] == color;
+            let (mid_x, mid_y) = (
+                info.chart_x + info.chart_width / 2 + 7,
+                info.chart_y + info.chart_height / 2 + 7,
+            );
+            let bottom = info.chart_y + info.chart_height;
+            let left = (info.chart_x - 8..info.chart_x + 8)
+                .filter(|&x| is_color(x, mid_y))
+                .count();
+            let below = (bottom - 8..bottom + 8).filter(|&y| is_color(mid_x, y)).count();
+            (left, below)
+        };
+
+        // Thick lines may cover one extra pixel
+        let (left, below) = axis_pixels(opt, [255, 0, 0]);
+        assert!(
+            (3..=4).contains(&left) && (3..=4).contains(&below),
+            "{} {}",
+            left,
+            below
+        );
+        let thin = PlotOptions { border_width: 1, ..opt };
+        assert_eq!(axis_pixels(thin, [255, 0, 0]), (1, 1));
+
+        // The default border is black, and PLOT_BORDER_NONE removes it
+        let default = PlotOptions {
+            border_width: 0,
+            border_color: 0,
+            ..opt
+        };
+        assert_ne!(axis_pixels(default, [0, 0, 0]), (0, 0));
+        let hidden = PlotOptions {
+            border_color: PLOT_BORDER_NONE,
+            ..opt
+        };
+        assert_eq!(axis_pixels(hidden, [0, 0, 0]), (0, 0));
+        assert_eq!(axis_pixels(hidden, [255, 0, 0]), (0, 0));
+    }
+
+    #[test]
+    fn test_axis_color() {
+        let xs: Vec<f64> = vec![1.0, 2.0, 3.0];
+        let ys: Vec<f64> = vec![1.0, 2.0, 3.0];
+        let opt = PlotOptions {
+            axis_color: 0xFF_00_00_FF,
+            ..Default::default()
+        };
+
+        let style = axis_line_style(&opt).unwrap();
+        assert_eq!(style.color.rgb(), (0, 0, 255));
+
+        plot_scatter_png_impl("/tmp/test_axis_color.png", &xs, &ys, opt).unwrap();
+        assert!(fs::metadata("/tmp/test_axis_color.png").is_ok());
+        fs::remove_file("/tmp/test_axis_color.png").ok();
+    }
+
+    #[test]
+    fn test_f32_matches_f64() {
+        let path64 = CString::new("/tmp/test_scatter_f64.png").unwrap();
+        let path32 = CString::new("/tmp/test_scatter_f32.png").unwrap();
+        let xs32: Vec<f32> = vec![1.0, 2.5, 3.0, 4.5];
+        let ys32: Vec<f32> = vec![2.0, 1.5, 4.0, 3.5];
+        let xs64: Vec<f64> = xs32.iter().map(|&v| v as f64).collect();
+        let ys64: Vec<f64> = ys32.iter().map(|&v| v as f64).collect();
+        let opt = PlotOptions::default();
+
+        let result64 =
+            unsafe { plot_scatter_png(path64.as_ptr(), xs64.as_ptr(), ys64.as_ptr(), xs64.len() as u64, opt) };
+        let result32 =
+            unsafe { plot_scatter_f32_png(path32.as_ptr(), xs32.as_ptr(), ys32.as_ptr(), xs32.len() as u64, opt) };
+        assert_eq!((result64, result32), (0, 0));
+
+        let bytes64 = fs::read("/tmp/test_scatter_f64.png").unwrap();
+        let bytes32 = fs::read("/tmp/test_scatter_f32.png").unwrap();
+        assert_eq!(bytes64, bytes32, "Expected identical PNG output");
+        fs::remove_file("/tmp/test_scatter_f64.png").ok();
+        fs::remove_file("/tmp/test_scatter_f32.png").ok();
+    }
+
+    #[test]
+    fn test_rgba_buffer() {
+        let xs: Vec<f32> = vec![1.0, 2.0, 3.0];
+        let ys: Vec<f32> = vec![1.0, 2.0, 3.0];
+        let opt = PlotOptions {
+            width: 200,
+            height: 100,
+            ..Default::default()
+        };
+        let mut ptr: *mut u8 = std::ptr::null_mut();
+        let mut len: usize = 0;
+
+        let result =
+            unsafe { plot_scatter_f32_rgba(xs.as_ptr(), ys.as_ptr(), xs.len() as u64, opt, &mut ptr, &mut len) };
+
+        assert_eq!(result, 0, "Expected success");
+        assert_eq!(len, 200 * 100 * 4);
+        let pixels = unsafe { std::slice::from_raw_parts(ptr, len) };
+        assert_eq!(&pixels[..4], &[255, 255, 255, 255], "Expected white top-left pixel");
+        unsafe { plot_free_buffer(ptr, len) };
+    }
+
+    #[test]
+    fn test_svg_output() {
+        let path = CString::new("/tmp/test_scatter.svg").unwrap();
+        let xs: Vec<f32> = vec![1.0, 2.0, 3.0];
+        let ys: Vec<f32> = vec![1.0, 2.0, 3.0];
+
+        let result = unsafe {
+            plot_scatter_f32_svg(
+                path.as_ptr(),
+                xs.as_ptr(),
+                ys.as_ptr(),
+                xs.len() as u64,
+                PlotOptions::default(),
+            )
+        };
+
+        assert_eq!(result, 0, "Expected success");
+        let svg = fs::read_to_string("/tmp/test_scatter.svg").unwrap();
+        assert!(svg.starts_with("<svg"));
+        fs::remove_file("/tmp/test_scatter.svg").ok();
+    }
+
+    /// Returns the (x, y) pixel positions of all pure blue pixels in an RGBA buffer.
+    fn blue_pixels(rgba: &[u8], width: u32) -> Vec<(u32, u32)> {
+        rgba.chunks_exact(4)
+            .enumerate()
+            .filter(|(_, p)| p[..3] == [0, 0, 255])
+            .map(|(i, _)| (i as u32 % width, i as u32 / width))
+            .collect()
+    }
+
+    #[test]
+    fn test_invert_y() {
+        let xs: Vec<f64> = vec![0.0, 1.0];
+        let ys: Vec<f64> = vec![1.0, 2.0];
+        let opt = PlotOptions {
+            invert_y: 1,
+            ..Default::default()
+        };
+
+        let rgba = plot_scatter_rgba_impl(&xs, &ys, opt).unwrap();
+
+        // With Y inverted, the point with the smaller X and Y is drawn at the top left
+        let blue = blue_pixels(&rgba, opt.width);
+        let leftmost = blue.iter().min_by_key(|(x, _)| *x).unwrap();
+        let rightmost = blue.iter().max_by_key(|(x, _)| *x).unwrap();
+        assert!(leftmost.1 < rightmost.1, "Expected Y to increase downward");
+    }
+
+    #[test]
+    fn test_invert_x_labels() {
+        let opt = PlotOptions {
+            invert_x: 1,
+            auto_range: 0,
+            x_min: 0.0,
+            x_max: 10.0,
+            y_min: 0.0,
+            y_max: 10.0,
+            ..Default::default()
+        };
+
+        // A point at the X minimum is drawn at the right edge of the plot area
+        let rgba = plot_scatter_rgba_impl(&[0.5], &[5.0], opt).unwrap();
+        let blue = blue_pixels(&rgba, opt.width);
+        assert!(
+            blue.iter().all(|(x, _)| *x > opt.width / 2),
+            "Expected the X axis to run right to left"
+        );
+
+        // The tick labels run from "10" on the left to "0" on the right
+        let labelled = PlotOptions {
+            x_tick_format: PLOT_TICK_INTEGER,
+            ..opt
+        };
+        let ticks = [0.0, 5.0, 10.0];
+        let rgba = plot_scatter_rgba_impl(&[0.5], &[5.0], RenderOptions::from(labelled).with_x_ticks(&ticks)).unwrap();
+        let info = plot_area_info(&[0.5], &[5.0], labelled).unwrap();
+        let inked = |x: u32, y: u32| rgba[((y * opt.width + x) * 4) as usize..][..3] != [255, 255, 255];
+        // The edge labels are centred on the plot edges, while the Y labels end further left
+        let columns = info.chart_x - 12..info.chart_x + info.chart_width + 12;
+        let top = info.chart_y + info.chart_height + 1;
+        let bottom = (top..opt.height)
+            .find(|&y| columns.clone().all(|x| !inked(x, y)))
+            .unwrap();
+
+        // Columns of ink (start, end) below the axis, merging the small gaps between glyphs
+        let mut labels: Vec<(u32, u32)> = Vec::new();
+        for x in columns.filter(|&x| (top..bottom).any(|y| inked(x, y))) {
+            match labels.last_mut() {
+                Some((_, end)) if x - *end <= 4 => *end = x,
+                _ => labels.push((x, x)),
+            }
+        }
+        let widths: Vec<u32> = labels.iter().map(|(start, end)| end - start + 1).collect();
+        assert_eq!(widths.len(), 3, "Expected three tick labels: {:?}", labels);
+        assert!(widths[0] > widths[2], "Expected \"10\" left of \"0\": {:?}", labels);
+        assert!(labels[0].0 < info.chart_x && labels[2].1 > info.chart_x + info.chart_width - 1);
+    }
+
+    #[test]
+    fn test_timeseries_png() {
+        let path = CString::new("/tmp/test_timeseries.png").unwrap();
+        let start = 1_705_287_600.0; // 2024-01-15 03:00 UTC
+        let ts: Vec<f64> = (0..5).map(|i| start + i as f64 * 3600.0).collect();
+        let ys: Vec<f64> = vec![1.0, 3.0, 2.0, 5.0, 4.0];
+
+        let result = unsafe {
+            plot_timeseries_png(
+                path.as_ptr(),
+                ts.as_ptr(),
+                ys.as_ptr(),
+                ts.len() as u64,
+                PlotOptions::default(),
+            )
+        };
+
+        assert_eq!(result, 0, "Expected success for time series");
+        assert!(fs::metadata("/tmp/test_timeseries.png").is_ok());
+        fs::remove_file("/tmp/test_timeseries.png").ok();
+
+        let items = chrono::format::StrftimeItems::new(DEFAULT_TIME_FORMAT).parse().unwrap();
+        assert_eq!(format_timestamp(start, &items), "2024-01-15 03:00");
+    }
+
+    #[test]
+    fn test_timeseries_rejects_nan() {
+        let ts: Vec<f64> = vec![0.0, f64::NAN];
+        let ys: Vec<f64> = vec![1.0, 2.0];
+
+        let err =
+            plot_timeseries_png_impl("/tmp/test_timeseries_nan.png", &ts, &ys, PlotOptions::default()).unwrap_err();
+        assert_eq!(err, "Invalid timestamp at index 1: must be finite");
+    }
+
+    #[test]
+    fn test_marker_alpha() {
+        let opt = PlotOptions {
+            marker_alpha: 128,
+            auto_range: 0,
+            x_max: 10.0,
+            y_max: 10.0,
+            ..Default::default()
+        };
+        // A lone marker and two overlapping ones, away from the grid lines
+        let xs = [2.3, 6.3, 6.35];
+        let ys = [5.3, 5.3, 5.3];
+        let rgba = plot_scatter_rgba_impl(&xs, &ys, opt).unwrap();
+        let pixel = |x: f64, y: f64| {
+            let (px, py) = data_to_pixel(opt, x, y).unwrap();
+            let i = (py * opt.width + px) as usize * 4;
+            [rgba[i], rgba[i + 1], rgba[i + 2]]
+        };
+
+        let single = pixel(2.3, 5.3);
+        assert!(single != [0, 0, 255] && single != [255, 255, 255], "{:?}", single);
+        assert!(single[2] > single[0], "Expected a blue tint: {:?}", single);
+        // Where the markers overlap, the second one blends over the first
+        let overlap = pixel(6.3, 5.3);
+        assert!(
+            overlap != [0, 0, 255] && overlap[0] < single[0],
+            "{:?} {:?}",
+            overlap,
+            single
+        );
+    }
+
+    #[test]
+    fn test_line_modes() {
+        let xs: Vec<f64> = vec![1.0, 2.0, 3.0, 4.0];
+        let ys: Vec<f64> = vec![1.0, 3.0, 2.0, 4.0];
+        for mode in [
+            PLOT_LINE_LINEAR,
+            PLOT_LINE_STEP_RIGHT,
+            PLOT_LINE_STEP_LEFT,
+            PLOT_LINE_MONOTONE_CUBIC,
+        ] {
+            let opt = PlotOptions {
+                line_mode: mode,
+                ..Default::default()
+            };
+            plot_line_png_impl("/tmp/test_line_modes.png", &xs, &ys, opt).unwrap();
+        }
+        fs::remove_file("/tmp/test_line_modes.png").ok();
+
+        let step = line_vertices(&[(0.0, 0.0), (1.0, 1.0)], PLOT_LINE_STEP_RIGHT, 0.1).unwrap();
+        assert_eq!(step, vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]);
+
+        let opt = PlotOptions {
+            line_mode: PLOT_LINE_MONOTONE_CUBIC,
+            ..Default::default()
+        };
+        let err = plot_line_png_impl("/tmp/test_line_modes.png", &[2.0, 1.0], &[1.0, 2.0], opt).unwrap_err();
+        assert!(err.contains("strictly increasing"), "{}", err);
+    }
+
+    #[test]
+    fn test_cdf_png() {
+        // Deterministic uniform samples in [0, 1)
+        let mut rng = RenderRng::new(42);
+        let data: Vec<f64> = (0..1000).map(|_| rng.next_unit()).collect();
+        let original = data.clone();
+
+        let path = CString::new("/tmp/test_cdf.png").unwrap();
+        let result = unsafe { plot_cdf_png(path.as_ptr(), data.as_ptr(), data.len() as u64, PlotOptions::default()) };
+        assert_eq!(result, 0, "Expected success for CDF");
+        assert_eq!(data, original, "Caller data must not be modified");
+        fs::remove_file("/tmp/test_cdf.png").ok();
+
+        let (xs, ys) = ecdf_points(&data, &PlotOptions::default()).unwrap();
+        let mid = xs.iter().position(|&x| x >= 0.5).unwrap();
+        assert!((ys[mid] - 0.5).abs() < 0.05, "CDF at 0.5 was {}", ys[mid]);
+
+        let result = unsafe { plot_cdf_png(path.as_ptr(), std::ptr::null(), 10, PlotOptions::default()) };
+        assert_eq!(result, 1);
+        assert_eq!(
+            unsafe { value_slice::<f64>(std::ptr::null(), 10) }.unwrap_err(),
+            "Data pointer is NULL"
+        );
+        assert!(unsafe { value_slice(data.as_ptr(), 0) }.is_err());
+    }
+
+    #[test]
+    fn test_polar_scatter() {
+        let angles: Vec<f64> = (0..360).map(|deg| (deg as f64).to_radians()).collect();
+        let radii: Vec<f64> = (0..360).map(|deg| 1.0 + deg as f64 / 360.0).collect();
+        let opt = PlotOptions {
+            polar_grid_rings: 4,
+            ..Default::default()
+        };
+
+        let path = CString::new("/tmp/test_polar.png").unwrap();
+        let result = unsafe { plot_polar_scatter_png(path.as_ptr(), angles.as_ptr(), radii.as_ptr(), 360, opt) };
+        assert_eq!(result, 0, "Expected success for polar scatter");
+
+        assert!(fs::metadata("/tmp/test_polar.png").unwrap().len() > 0);
+        fs::remove_file("/tmp/test_polar.png").ok();
+        // A lone point at r = 1, θ = 45°: the chart is a square of `side`
+        // pixels centred in the image spanning ±1.05 (the 5% padding).
+        let opt = PlotOptions::default();
+        let path = "/tmp/test_polar_pixel.png";
+        plot_polar_scatter_png_impl(path, &[45f64.to_radians()], &[1.0], opt).unwrap();
+        let pixels = image::open(path).unwrap().to_rgb8();
+        fs::remove_file(path).ok();
+
+        let side = opt.width.min(opt.height) - 2 * POLAR_LABEL_MARGIN;
+        let scale = side as f64 / 2.0 / 1.05;
+        let offset = 1.0 / 2f64.sqrt() * scale;
+        let expected_x = (opt.width - side) / 2 + side / 2 + offset.round() as u32;
+        let expected_y = (opt.height - side) / 2 + side / 2 - offset.round() as u32;
+        let blue: Vec<(u32, u32)> = pixels
+            .enumerate_pixels()
+            .filter(|(_, _, p)| p.0 == [0, 0, 255])
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert!(!blue.is_empty(), "Expected a marker to be drawn");
+        for &(x, y) in &blue {
+            assert!(
+                x.abs_diff(expected_x) <= opt.marker_radius + 2 && y.abs_diff(expected_y) <= opt.marker_radius + 2,
+                "Marker pixel ({}, {}) is not at ({}, {})",
+                x,
+                y,
+                expected_x,
+                expected_y
+            );
+        }
+
+        let err = plot_polar_scatter_png_impl(path, &[f64::NAN], &[1.0], opt).unwrap_err();
+        assert!(err.contains("NaN coordinate at index 0"), "{}", err);
+        let path = CString::new(path).unwrap();
+        let result = unsafe { plot_polar_scatter_png(path.as_ptr(), angles.as_ptr(), radii.as_ptr(), 0, opt) };
+        assert_eq!(result, 1, "Expected failure for an empty series");
+        let result = unsafe { plot_polar_scatter_png(path.as_ptr(), std::ptr::null(), radii.as_ptr(), 360, opt) };
+        assert_eq!(result, 1, "Expected failure for a NULL angle pointer");
+    }
+
+    #[test]
+    fn test_regression() {
+        let xs: Vec<f64> = (0..50).map(|i| i as f64).collect();
+        let fit = linear_regression(&xs, &xs).unwrap();
+        assert!((fit.slope - 1.0).abs() < 1e-12);
+        assert!(fit.intercept.abs() < 1e-12);
+        assert!((fit.r_squared - 1.0).abs() < 1e-12);
+
+        let mut opt = PlotOptions {
+            show_r_squared: 1,
+            ..Default::default()
+        };
+        write_fixed_str(&mut opt.title, "Identity");
+        plot_scatter_with_regression_png_impl("/tmp/test_regression.png", &xs, &xs, opt).unwrap();
+        fs::remove_file("/tmp/test_regression.png").ok();
+
+        let err = plot_scatter_with_regression_png_impl("/tmp/test_regression.png", &[1.0], &[1.0], opt).unwrap_err();
+        assert_eq!(err, "Regression requires at least two points");
+    }
+
+    #[test]
+    fn test_jitter() {
+        let xs = vec![5.0; 100];
+        let ys: Vec<f64> = (0..100).map(|i| i as f64).collect();
+        let opt = PlotOptions {
+            auto_range: 0,
+            x_min: 4.0,
+            x_max: 6.0,
+            y_min: -1.0,
+            y_max: 100.0,
+            marker_radius: 2,
+            jitter_amount: 0.5,
+            ..Default::default()
+        };
+
+        let offsets: Vec<f64> = jitter_offsets(100, 0.5, render_rng(&opt, 0)).collect();
+        assert!(offsets.iter().all(|d| d.abs() <= 0.25));
+        assert_eq!(
+            offsets,
+            jitter_offsets(100, 0.5, render_rng(&opt, 0)).collect::<Vec<_>>(),
+            "Jitter must be deterministic"
+        );
+
+        // Without jitter all markers share one column; with it they spread around x=5
+        let spread = |opt: PlotOptions| {
+            let rgba = plot_scatter_rgba_impl(&xs, &ys, opt).unwrap();
+            let columns: std::collections::BTreeSet<u32> = blue_pixels(&rgba, opt.width).iter().map(|p| p.0).collect();
+            columns.len()
+        };
+        let plain = spread(PlotOptions {
+            jitter_amount: 0.0,
+            ..opt
+        });
+        assert!(
+            spread(opt) > plain * 5,
+            "Expected jittered points to spread horizontally"
+        );
+    }
+
+    #[test]
+    fn test_register_font() {
+        let name = CString::new("second-font").unwrap();
+        let font_path = CString::new(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/fonts/Inter-Regular.ttf")).unwrap();
+        let result = unsafe { plot_register_font(name.as_ptr(), font_path.as_ptr(), 1) };
+        assert_eq!(result, 0, "Expected font registration to succeed");
+
+        let mut opt = PlotOptions::default();
+        write_fixed_str(&mut opt.title, "Second font");
+        write_fixed_str(&mut opt.title_font_name, "second-font");
+        plot_scatter_png_impl("/tmp/test_register_font.png", &[1.0, 2.0], &[1.0, 2.0], opt).unwrap();
+        fs::remove_file("/tmp/test_register_font.png").ok();
+
+        write_fixed_str(&mut opt.label_font_name, "missing-font");
+        let err = plot_scatter_png_impl("/tmp/test_register_font.png", &[1.0, 2.0], &[1.0, 2.0], opt).unwrap_err();
+        assert_eq!(err, "Font not registered: missing-font");
+    }
+
+    #[test]
+    #[cfg(feature = "embedded-font")]
+    fn test_bold_font_style() {
+        let mut opt = PlotOptions {
+            axis_desc_font_style: PLOT_FONT_BOLD,
+            title_font_style: PLOT_FONT_BOLD,
+            ..Default::default()
+        };
+        write_fixed_str(&mut opt.title, "Bold title");
+        plot_scatter_png_impl("/tmp/test_bold_font_style.png", &[1.0, 2.0], &[1.0, 2.0], opt).unwrap();
+        fs::remove_file("/tmp/test_bold_font_style.png").ok();
+
+        let font = resolve_styled_font(&opt.title_font_name, 20.0, PLOT_FONT_BOLD).unwrap();
+        assert_eq!(font.get_name(), DEFAULT_BOLD_FONT);
+        assert!(matches!(font.get_style(), FontStyle::Bold));
+        assert_eq!(
+            resolve_font(&opt.title_font_name, 20.0).unwrap().get_name(),
+            DEFAULT_FONT
+        );
+
+        // The bold face draws heavier strokes than the regular face
+        let ink = |style| {
+            let opt = PlotOptions {
+                title_font_style: style,
+                ..opt
+            };
+            let rgba = plot_scatter_rgba_impl(&[1.0, 2.0], &[1.0, 2.0], opt).unwrap();
+            rgba.chunks_exact(4).filter(|p| p[..3] != [255, 255, 255]).count()
+        };
+        assert!(ink(PLOT_FONT_BOLD) > ink(PLOT_FONT_NORMAL));
+
+        opt.title_font_style = 4;
+        let err = plot_scatter_png_impl("/tmp/test_bold_font_style.png", &[1.0, 2.0], &[1.0, 2.0], opt).unwrap_err();
+        assert_eq!(err, "Unsupported font style: 4");
+    }
+
+    #[test]
+    #[cfg(feature = "embedded-font")]
+    fn test_register_font_bytes() {
+        let name = c"font-from-bytes";
+        let data = FONT_BYTES.to_vec();
+        let result = unsafe { plot_register_font_bytes(name.as_ptr(), 0, data.as_ptr(), data.len()) };
+        assert_eq!(result, 0);
+        drop(data);
+        // Registering the name again replaces the font
+        let result = unsafe { plot_register_font_bytes(name.as_ptr(), 0, FONT_BYTES.as_ptr(), FONT_BYTES.len()) };
+        assert_eq!(result, 0);
+
+        let mut opt = PlotOptions::default();
+        write_fixed_str(&mut opt.label_font_name, "font-from-bytes");
+        plot_scatter_png_impl("/tmp/test_register_font_bytes.png", &[1.0, 2.0], &[1.0, 2.0], opt).unwrap();
+        fs::remove_file("/tmp/test_register_font_bytes.png").ok();
+
+        let junk = [0u8; 16];
+        assert_eq!(
+            register_font_bytes("junk-font", junk.to_vec(), 0).unwrap_err(),
+            "Failed to register font: invalid font data"
+        );
+        let result = unsafe { plot_register_font_bytes(name.as_ptr(), 0, std::ptr::null(), 0) };
+        assert_eq!(result, 1);
+    }
+
+    #[test]
+    fn test_tick_format() {
+        assert_eq!(format_tick(2.6, PLOT_TICK_INTEGER).unwrap().unwrap(), "3");
+        assert_eq!(format_tick(2.5, PLOT_TICK_FIXED2).unwrap().unwrap(), "2.50");
+        assert_eq!(format_tick(2.5, PLOT_TICK_FIXED4).unwrap().unwrap(), "2.5000");
+        assert_eq!(format_tick(1.0, PLOT_TICK_DEFAULT).unwrap(), None);
+        assert_eq!(format_si(1200.0), "1.2k");
+        assert_eq!(format_si(-3.5e6), "-3.5M");
+        assert_eq!(format_si(1e12), "1T");
+        assert_eq!(format_si(0.002), "2m");
+        assert_eq!(format_si(4.7e-6), "4.7µ");
+        assert_eq!(format_si(1e-9), "1n");
+        assert_eq!(format_si(42.0), "42");
+
+        // Every Y tick between 1000 and 5000 gets a kilo suffix
+        let opt = PlotOptions {
+            auto_range: 0,
+            y_min: 1000.0,
+            y_max: 5000.0,
+            y_tick_format: PLOT_TICK_SI,
+            ..Default::default()
+        };
+        let y_spec = RangedCoordf64::from(1000.0..5000.0);
+        for value in y_spec.key_points(DEFAULT_LABEL_COUNT) {
+            assert!(format_tick(value, opt.y_tick_format).unwrap().unwrap().ends_with('k'));
+        }
+        plot_scatter_png_impl("/tmp/test_tick_format.png", &[0.5], &[2500.0], opt).unwrap();
+        fs::remove_file("/tmp/test_tick_format.png").ok();
+
+        let opt = PlotOptions {
+            x_tick_format: 9,
+            ..Default::default()
+        };
+        let err = plot_scatter_png_impl("/tmp/test_tick_format.png", &[0.5], &[0.5], opt).unwrap_err();
+        assert_eq!(err, "Unsupported tick format: 9");
+    }
+
+    #[test]
+    fn test_moving_average() {
+        let smoothed = moving_average(&[2.0, 0.0, 1.0], &[20.0, 0.0, 10.0], 3).unwrap();
+        assert_eq!(smoothed, vec![(0.0, 5.0), (1.0, 10.0), (2.0, 15.0)]);
+
+        // Deterministic noise around y = x
+        let xs: Vec<f64> = (0..100).map(|i| i as f64).collect();
+        let ys: Vec<f64> = xs.iter().map(|x| x + ((x * 7.3).sin() * 5.0)).collect();
+        let opt = PlotOptions {
+            moving_avg_window: 10,
+            ..Default::default()
+        };
+        plot_scatter_png_impl("/tmp/test_moving_avg.png", &xs, &ys, opt).unwrap();
+        plot_line_png_impl("/tmp/test_moving_avg.png", &xs, &ys, opt).unwrap();
+        fs::remove_file("/tmp/test_moving_avg.png").ok();
+
+        let opt = PlotOptions {
+            moving_avg_window: 101,
+            ..opt
+        };
+        let err = plot_scatter_png_impl("/tmp/test_moving_avg.png", &xs, &ys, opt).unwrap_err();
+        assert_eq!(err, "Moving average window (101) exceeds point count (100)");
+    }
+
+    #[test]
+    fn test_quantile_band() {
+        let xs: Vec<f64> = (0..10).map(|i| i as f64).collect();
+        let mid: Vec<f64> = xs.iter().map(|x| x * 2.0).collect();
+        let low: Vec<f64> = mid.iter().map(|m| m - 1.5).collect();
+        let high: Vec<f64> = mid.iter().map(|m| m + 1.5).collect();
+
+        let path = CString::new("/tmp/test_quantile_band.png").unwrap();
+        let opt = PlotOptions::default();
+        let result = unsafe {
+            plot_quantile_band_png(
+                path.as_ptr(),
+                xs.as_ptr(),
+                low.as_ptr(),
+                mid.as_ptr(),
+                high.as_ptr(),
+                10,
+                opt,
+            )
+        };
+        assert_eq!(result, 0, "Expected success with band");
+
+        let result = unsafe {
+            plot_quantile_band_png(
+                path.as_ptr(),
+                xs.as_ptr(),
+                std::ptr::null(),
+                mid.as_ptr(),
+                high.as_ptr(),
+                10,
+                opt,
+            )
+        };
+        assert_eq!(result, 0, "Expected success without band");
+        fs::remove_file("/tmp/test_quantile_band.png").ok();
+
+        let mut bad_high = high.clone();
+        bad_high[4] = mid[4] - 0.5;
+        let err = plot_quantile_band_png_impl(
+            "/tmp/test_quantile_band.png",
+            &xs,
+            Some(&low),
+            &mid,
+            Some(&bad_high),
+            opt,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            "Quantiles out of order at index 4: expected p_low <= p_mid <= p_high"
+        );
+    }
+
+    #[test]
+    fn test_outer_padding() {
+        let opt = PlotOptions {
+            width: 300,
+            height: 200,
+            outer_padding: 20,
+            ..Default::default()
+        };
+        plot_scatter_png_impl("/tmp/test_outer_padding.png", &[1.0, 2.0], &[1.0, 2.0], opt).unwrap();
+
+        // IHDR stores the width and height as big-endian u32 at bytes 16..24
+        let bytes = fs::read("/tmp/test_outer_padding.png").unwrap();
+        let width = u32::from_be_bytes(bytes[16..20].try_into().unwrap());
+        let height = u32::from_be_bytes(bytes[20..24].try_into().unwrap());
+        assert_eq!((width, height), (340, 240));
+        fs::remove_file("/tmp/test_outer_padding.png").ok();
+
+        let (padded, size) = pad_rgb(&[0, 0, 0], (1, 1), 1).unwrap();
+        assert_eq!(size, (3, 3));
+        assert_eq!(&padded[12..15], &[0, 0, 0], "Expected the source pixel in the centre");
+        assert!(padded[..12].iter().all(|&b| b == 255));
+    }
+
+    #[test]
+    fn test_missing_output_dir() {
+        let path = CString::new("/nonexistent_dir/out.png").unwrap();
+        let xs: Vec<f64> = vec![1.0, 2.0];
+        let ys: Vec<f64> = vec![1.0, 2.0];
+        let result = unsafe { plot_scatter_png(path.as_ptr(), xs.as_ptr(), ys.as_ptr(), 2, PlotOptions::default()) };
+        assert_eq!(result, 1, "Expected failure for missing directory");
+
+        let err = plot_scatter_png_impl("/nonexistent_dir/out.png", &xs, &ys, PlotOptions::default()).unwrap_err();
+        assert_eq!(
+            err,
+            "Output directory does not exist or is not writable: /nonexistent_dir"
+        );
+    }
+
+    #[test]
+    fn test_strided() {
+        // Interleaved XYXY layout: both strides are 2, Y starts one element in
+        let xy = [1.0, 2.0, 2.5, 1.0, 3.0, 4.0, 4.5, 3.5];
+        let xs = unsafe { strided_values(xy.as_ptr(), 2, 4, "X") }.unwrap();
+        let ys = unsafe { strided_values(xy.as_ptr().add(1), 2, 4, "Y") }.unwrap();
+        assert_eq!(xs, [1.0, 2.5, 3.0, 4.5]);
+        assert_eq!(ys, [2.0, 1.0, 4.0, 3.5]);
+
+        let opt = PlotOptions {
+            width: 200,
+            height: 150,
+            ..Default::default()
+        };
+        let strided = CString::new("/tmp/test_strided.png").unwrap();
+        let separate = "/tmp/test_strided_separate.png";
+        let result =
+            unsafe { plot_scatter_strided_png(strided.as_ptr(), xy.as_ptr(), 2, xy.as_ptr().add(1), 2, 4, opt) };
+        assert_eq!(result, 0);
+        plot_scatter_png_impl(separate, &xs, &ys, opt).unwrap();
+        assert_eq!(fs::read("/tmp/test_strided.png").unwrap(), fs::read(separate).unwrap());
+
+        let result = unsafe { plot_scatter_strided_png(strided.as_ptr(), xy.as_ptr(), 0, xy.as_ptr(), 1, 4, opt) };
+        assert_eq!(result, 1);
+        let err = unsafe { strided_values(xy.as_ptr(), 0, 4, "X") }.unwrap_err();
+        assert_eq!(err, "X stride must be greater than zero");
+        assert!(unsafe { strided_values(xy.as_ptr(), usize::MAX, 2, "Y") }.is_err());
+        fs::remove_file("/tmp/test_strided.png").ok();
+        fs::remove_file(separate).ok();
+    }
+
+    #[test]
+    fn test_interleaved_matches_separate() {
+        let xs: Vec<f64> = vec![1.0, 2.5, 3.0, 4.5];
+        let ys: Vec<f64> = vec![2.0, 1.0, 4.0, 3.5];
+        let xy: Vec<f64> = xs.iter().zip(&ys).flat_map(|(&x, &y)| [x, y]).collect();
+        let opt = PlotOptions {
+            width: 200,
+            height: 150,
+            ..Default::default()
+        };
+
+        let (mut a_ptr, mut a_len) = (std::ptr::null_mut(), 0usize);
+        let (mut b_ptr, mut b_len) = (std::ptr::null_mut(), 0usize);
+        unsafe {
+            assert_eq!(
+                plot_scatter_rgba(xs.as_ptr(), ys.as_ptr(), 4, opt, &mut a_ptr, &mut a_len),
+                0
+            );
+            assert_eq!(
+                plot_scatter_interleaved_rgba(xy.as_ptr(), 4, opt, &mut b_ptr, &mut b_len),
+                0
+            );
+            assert_eq!(
+                std::slice::from_raw_parts(a_ptr, a_len),
+                std::slice::from_raw_parts(b_ptr, b_len)
+            );
+            plot_free_buffer(a_ptr, a_len);
+            plot_free_buffer(b_ptr, b_len);
+        }
+
+        let path = CString::new("/tmp/test_interleaved.png").unwrap();
+        let result = unsafe { plot_scatter_interleaved_png(path.as_ptr(), std::ptr::null(), 4, opt) };
+        assert_eq!(result, 1, "Expected failure for NULL xy");
+        let result = unsafe { plot_scatter_interleaved_png(path.as_ptr(), xy.as_ptr(), 4, opt) };
+        assert_eq!(result, 0, "Expected success");
+        fs::remove_file("/tmp/test_interleaved.png").ok();
+    }
+
+    #[test]
+    fn test_png_append() {
+        let opt = PlotOptions {
+            width: 300,
+            height: 200,
+            auto_range: 0,
+            x_min: 0.0,
+            x_max: 10.0,
+            y_min: 0.0,
+            y_max: 10.0,
+            ..Default::default()
+        };
+        let path = "/tmp/test_png_append.png";
+        plot_scatter_png_impl(path, &[2.0, 4.0], &[2.0, 4.0], opt).unwrap();
+
+        plot_scatter_png_append_impl(path, &[6.0, 8.0], &[6.0, 8.0], 0xFFFF0000, opt).unwrap();
+        assert!(fs::metadata(path).unwrap().len() > 0);
+        let pixels = image::open(path).unwrap().to_rgb8();
+        assert_eq!(pixels.dimensions(), (300, 200));
+        assert!(
+            pixels.pixels().any(|p| p.0 == [255, 0, 0]),
+            "Expected appended red markers"
+        );
+
+        let err = plot_scatter_png_append_impl(path, &[1.0], &[1.0], 0xFFFF0000, PlotOptions { width: 301, ..opt })
+            .unwrap_err();
+        assert_eq!(err, "Image size 300x200 does not match the options (301x200)");
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_line_dash_patterns() {
+        let xs: Vec<f64> = (0..50).map(|i| i as f64).collect();
+        let ys: Vec<f64> = xs.iter().map(|x| (x / 5.0).sin()).collect();
+        for pattern in [PLOT_DASH_SOLID, PLOT_DASH_DASHED, PLOT_DASH_DOTTED, PLOT_DASH_DASH_DOT] {
+            let opt = PlotOptions {
+                line_stroke_width: 3,
+                line_dash_pattern: pattern,
+                ..Default::default()
+            };
+            plot_line_png_impl("/tmp/test_line_dash.png", &xs, &ys, opt).unwrap();
+        }
+        fs::remove_file("/tmp/test_line_dash.png").ok();
+
+        // A 24 pixel line with 8 on / 4 off gives two dashes, the second ending at 20
+        let paths = dash_polyline(&[(0.0, 0.0), (24.0, 0.0)], |p| *p, &[8.0, 4.0]);
+        assert_eq!(
+            paths,
+            vec![vec![(0.0, 0.0), (8.0, 0.0)], vec![(12.0, 0.0), (20.0, 0.0)]]
+        );
+    }
+
+    #[test]
+    fn test_auto_range_modes() {
+        let xs = [1.3, 8.7];
+        let ys = [-0.03, 0.42];
+        let range = |mode| {
+            let opt = PlotOptions {
+                auto_range_mode: mode,
+                ..Default::default()
+            };
+            compute_ranges(&xs, &ys, &opt).unwrap()
+        };
+
+        assert_eq!(range(PLOT_RANGE_EXACT), (1.3, 8.7, -0.03, 0.42));
+        let (x_min, x_max, y_min, y_max) = range(PLOT_RANGE_NICE);
+        assert_eq!((x_min, x_max), (0.0, 10.0));
+        assert!(
+            (y_min + 0.1).abs() < 1e-12 && (y_max - 0.5).abs() < 1e-12,
+            "{} {}",
+            y_min,
+            y_max
+        );
+        let (x_min, x_max, _, _) = range(PLOT_RANGE_PADDED);
+        assert!(x_min < 1.3 && x_max > 8.7);
+
+        let opt = PlotOptions {
+            auto_range_mode: 7,
+            ..Default::default()
+        };
+        assert_eq!(
+            compute_ranges(&xs, &ys, &opt).unwrap_err(),
+            "Unsupported auto range mode: 7"
+        );
+    }
+
+    #[test]
+    fn test_png_stats() {
+        let mut xs: Vec<f64> = (0..10).map(|i| i as f64).collect();
+        let ys: Vec<f64> = (0..10).map(|i| i as f64).collect();
+        xs[3] = f64::NAN;
+        xs[7] = f64::NAN;
+        let opt = PlotOptions {
+            nan_policy: 1,
+            ..Default::default()
+        };
+
+        let path = CString::new("/tmp/test_png_stats.png").unwrap();
+        let mut stats = PlotStats::default();
+        let mut info = PlotAreaInfo::default();
+        let result =
+            unsafe { plot_scatter_png_ex(path.as_ptr(), xs.as_ptr(), ys.as_ptr(), 10, opt, &mut stats, &mut info) };
+        assert_eq!(result, 0, "Expected success");
+        assert_eq!(stats.points_skipped_nan, 2);
+        assert_eq!(stats.points_rendered, 8);
+        assert_eq!(stats.points_clipped, 0);
+
+        let result = unsafe {
+            plot_scatter_png_ex(
+                path.as_ptr(),
+                xs.as_ptr(),
+                ys.as_ptr(),
+                10,
+                opt,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        };
+        assert_eq!(result, 0, "Expected success with NULL stats");
+
+        // The default layout puts the data area at (60, 10), 730 x 540 pixels
+        assert_eq!(
+            (info.chart_x, info.chart_y, info.chart_width, info.chart_height),
+            (60, 10, 730, 540)
+        );
+        assert!(info.chart_x + info.chart_width <= opt.width);
+        assert!(info.chart_y + info.chart_height <= opt.height);
+        let (x_min, x_max, y_min, y_max) = compute_ranges(&[0.0, 9.0], &[0.0, 9.0], &opt).unwrap();
+        assert_eq!(
+            (info.x_min, info.x_max, info.y_min, info.y_max),
+            (x_min, x_max, y_min, y_max)
+        );
+
+        let clipped = PlotOptions {
+            auto_range: 0,
+            x_min: 0.0,
+            x_max: 4.5,
+            y_min: 0.0,
+            y_max: 10.0,
+            ..opt
+        };
+        let stats = plot_scatter_png_ex_impl("/tmp/test_png_stats.png", &xs, &ys, clipped).unwrap();
+        assert_eq!((stats.points_rendered, stats.points_clipped), (4, 4));
+        // The top-left corner of the data area is where the range minimum and maximum meet
+        let padded = PlotOptions {
+            outer_padding: 5,
+            title: opt.title.map(|_| b'T' as c_char),
+            ..clipped
+        };
+        let info = plot_area_info(&xs, &ys, padded).unwrap();
+        assert_eq!(data_to_pixel(padded, 0.0, 10.0), Ok((info.chart_x, info.chart_y)));
+        assert!(info.chart_y > 10 + 5, "the title moves the data area down");
+        fs::remove_file("/tmp/test_png_stats.png").ok();
+    }
+
+    #[test]
+    fn test_minor_ticks() {
+        let opt = PlotOptions {
+            x_tick_count: 5,
+            x_minor_tick_count: 4,
+            y_minor_tick_count: 2,
+            grid_color: 0xFF808080,
+            ..Default::default()
+        };
+        let path = CString::new("/tmp/test_minor_ticks.png").unwrap();
+        let xs: Vec<f64> = vec![0.0, 10.0];
+        let ys: Vec<f64> = vec![0.0, 10.0];
+        let result = unsafe { plot_scatter_png(path.as_ptr(), xs.as_ptr(), ys.as_ptr(), 2, opt) };
+        assert_eq!(result, 0, "Expected success with minor ticks");
+        fs::remove_file("/tmp/test_minor_ticks.png").ok();
+    }
+
+    #[test]
+    fn test_clip_box() {
+        let xs: Vec<f64> = (0..10).map(|i| i as f64).collect();
+        let ys = xs.clone();
+        let opt = PlotOptions {
+            auto_range: 0,
+            x_min: 0.0,
+            x_max: 10.0,
+            y_min: 0.0,
+            y_max: 10.0,
+            clip_enabled: 1,
+            clip_x_min: 3.0,
+            clip_x_max: 7.0,
+            clip_y_min: 0.0,
+            clip_y_max: 10.0,
+            ..Default::default()
+        };
+        let stats = plot_scatter_png_ex_impl("/tmp/test_clip_box.png", &xs, &ys, opt).unwrap();
+        assert_eq!(stats.points_rendered, 5);
+        assert_eq!(stats.points_clipped, 5);
+        fs::remove_file("/tmp/test_clip_box.png").ok();
+
+        let bad = PlotOptions { clip_x_min: 8.0, ..opt };
+        let err = plot_scatter_png_impl("/tmp/test_clip_box.png", &xs, &ys, bad).unwrap_err();
+        assert_eq!(err, "Invalid clip box: minimum must not exceed maximum");
+    }
+
+    #[test]
+    fn test_series_styles() {
+        let xs: Vec<f64> = (0..20).map(|i| i as f64).collect();
+        let ys_a: Vec<f64> = xs.iter().map(|x| x * 0.5).collect();
+        let ys_b: Vec<f64> = xs.iter().map(|x| 10.0 - x * 0.3).collect();
+        let series = [
+            ScatterSeries {
+                xs: xs.as_ptr(),
+                ys: ys_a.as_ptr(),
+                n: xs.len() as u64,
+                style: SeriesStyle {
+                    color: 0xFF0000FF,
+                    marker_radius: 6,
+                    marker_filled: 1,
+                    ..Default::default()
+                },
+                z_order: 0,
+            },
+            ScatterSeries {
+                xs: xs.as_ptr(),
+                ys: ys_b.as_ptr(),
+                n: xs.len() as u64,
+                style: SeriesStyle {
+                    color: 0xFFFF0000,
+                    draw_line: 1,
+                    line_width: 1,
+                    ..Default::default()
+                },
+                z_order: 0,
+            },
+        ];
+
+        let path = CString::new("/tmp/test_series_styles.png").unwrap();
+        let result = unsafe { plot_scatter_series_png(path.as_ptr(), series.as_ptr(), 2, PlotOptions::default()) };
+        assert_eq!(result, 0, "Expected success for two series");
+        fs::remove_file("/tmp/test_series_styles.png").ok();
+
+        let result = unsafe { plot_scatter_series_png(path.as_ptr(), series.as_ptr(), 0, PlotOptions::default()) };
+        assert_eq!(result, 1, "Expected failure for zero series");
+    }
+
+    #[test]
+    fn test_abi_version() {
+        assert_eq!(plot_abi_version(), PLOTFFI_ABI_VERSION);
+        const OPTIONS_SIZE: usize = std::mem::size_of::<PlotOptions>();
+        assert_eq!(plot_options_size(), OPTIONS_SIZE);
+    }
+
+    #[test]
+    fn test_band_chart() {
+        let xs: Vec<f64> = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        let lower: Vec<f64> = vec![0.5, 1.0, 1.8, 2.5, 3.1];
+        let upper: Vec<f64> = vec![1.5, 2.4, 3.0, 4.2, 5.0];
+
+        let path = CString::new("/tmp/test_band.png").unwrap();
+        let result = unsafe {
+            plot_band_png(
+                path.as_ptr(),
+                xs.as_ptr(),
+                lower.as_ptr(),
+                upper.as_ptr(),
+                5,
+                PlotOptions::default(),
+            )
+        };
+        assert_eq!(result, 0, "Expected success for band chart");
+        fs::remove_file("/tmp/test_band.png").ok();
+
+        let err = plot_band_png_impl("/tmp/test_band.png", &xs, &upper, &lower, PlotOptions::default()).unwrap_err();
+        assert_eq!(
+            err,
+            "Band bounds out of order at index 0: y_lower (1.5) exceeds y_upper (0.5)"
+        );
+    }
+
+    #[test]
+    fn test_png_base64() {
+        let xs: Vec<f64> = vec![1.0, 2.0, 3.0];
+        let ys: Vec<f64> = vec![3.0, 1.0, 2.0];
+        let opt = PlotOptions {
+            width: 200,
+            height: 100,
+            base64_data_uri: 1,
+            ..Default::default()
+        };
+
+        let mut out: *mut c_char = std::ptr::null_mut();
+        let result = unsafe { plot_scatter_png_base64(xs.as_ptr(), ys.as_ptr(), 3, opt, &mut out) };
+        assert_eq!(result, 0, "Expected success");
+        let text = unsafe { CStr::from_ptr(out) }.to_str().unwrap().to_string();
+        unsafe { plot_free_string(out) };
+
+        let payload = text
+            .strip_prefix("data:image/png;base64,")
+            .expect("Expected data URI prefix");
+        let png = base64::engine::general_purpose::STANDARD.decode(payload).unwrap();
+        let decoded = image::load_from_memory_with_format(&png, image::ImageFormat::Png).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (200, 100));
+    }
+
+    #[test]
+    fn test_options_json_ffi() {
+        let mut opt = PlotOptions {
+            height: 480,
+            ..Default::default()
+        };
+        write_fixed_str(&mut opt.title, "FFI");
+        let json = plot_options_to_json(opt);
+        assert!(!json.is_null());
+
+        let mut parsed = PlotOptions::default();
+        let result = unsafe { plot_options_from_json(json, &mut parsed) };
+        unsafe { plot_free_string(json) };
+        assert_eq!(result, 0, "Expected success");
+        assert_eq!(parsed.height, 480);
+        assert_eq!(parsed.title, opt.title);
+
+        let bad = CString::new("not json").unwrap();
+        assert_eq!(unsafe { plot_options_from_json(bad.as_ptr(), &mut parsed) }, 1);
+    }
+
+    #[test]
+    fn test_antialias() {
+        let xs: Vec<f64> = vec![0.2, 0.5, 0.8];
+        let ys: Vec<f64> = vec![0.3, 0.6, 0.4];
+        for antialias in [0, 1] {
+            let opt = PlotOptions {
+                width: 100,
+                height: 75,
+                antialias,
+                ..Default::default()
+            };
+            let path = format!("/tmp/test_antialias_{}.png", antialias);
+            plot_scatter_png_impl(&path, &xs, &ys, opt).unwrap();
+            assert!(fs::metadata(&path).unwrap().len() > 0);
+            fs::remove_file(&path).ok();
+        }
+
+        // Smoothed edges produce partially blended pixels that hard circles never have
+        let opt = PlotOptions {
+            width: 200,
+            height: 150,
+            marker_radius: 8,
+            antialias: 1,
+            ..Default::default()
+        };
+        let rgba = plot_scatter_rgba_impl(&xs, &ys, opt).unwrap();
+        assert!(
+            rgba.chunks_exact(4)
+                .any(|p| p[2] == 255 && p[0] > 0 && p[0] < 255 && p[0] == p[1])
+        );
+    }
+
+    #[test]
+    fn test_custom_ticks() {
+        let xs: Vec<f64> = vec![0.0, 10.0];
+        let ys: Vec<f64> = vec![0.0, 10.0];
+        let ticks = [2.5, 7.5, 12.0];
+        let base = PlotOptions {
+            width: 300,
+            height: 200,
+            auto_range: 0,
+            x_min: 0.0,
+            x_max: 10.0,
+            y_min: 0.0,
+            y_max: 10.0,
+            ..Default::default()
+        };
+        let vertical_lines = |opt: RenderOptions<'_>| {
+            let rgba = plot_scatter_rgba_impl(&xs, &ys, opt).unwrap();
+            let width = opt.width as usize;
+            let rows = 40..120;
+            let row_count = rows.len();
+            let grid_columns: Vec<usize> = (60..280)
+                .filter(|&col| {
+                    rows.clone().filter(|&row| rgba[(row * width + col) * 4] < 255).count() > row_count * 3 / 4
+                })
+                .collect();
+            grid_columns
+        };
+
+        // 12.0 lies outside the X range and is ignored; explicit values beat x_tick_count
+        let opt = PlotOptions {
+            x_tick_count: 10,
+            ..base
+        };
+        let columns = vertical_lines(RenderOptions::from(opt).with_x_ticks(&ticks));
+        assert_eq!(columns.len(), 2, "columns: {:?}", columns);
+        assert!(vertical_lines(base.into()).len() > 2);
+
+        // The C API reads the same ticks from the pointer fields
+        let ffi_opt = PlotOptions {
+            x_ticks: ticks.as_ptr(),
+            x_n_ticks: ticks.len() as u32,
+            ..opt
+        };
+        assert_eq!(vertical_lines(unsafe { RenderOptions::from_ffi(ffi_opt) }), columns);
+        // The Rust API ignores them
+        assert!(vertical_lines(ffi_opt.into()).len() > 2);
+
+        assert_eq!(custom_ticks(&ticks, 10.0, 0.0), Some(vec![2.5, 7.5]));
+        assert_eq!(custom_ticks(&[], 0.0, 10.0), None);
+    }
+
+    #[test]
+    fn test_plot_context() {
+        let xs: Vec<f64> = (0..10).map(|i| i as f64).collect();
+        let ys: Vec<f64> = xs.iter().map(|x| x * x).collect();
+        let opt = PlotOptions {
+            width: 200,
+            height: 150,
+            ..Default::default()
+        };
+        plot_scatter_png_impl("/tmp/test_context_single.png", &xs, &ys, opt).unwrap();
+
+        let path = CString::new("/tmp/test_context_staged.png").unwrap();
+        unsafe {
+            let ctx = plot_begin(opt);
+            assert_eq!(plot_finish(ctx, path.as_ptr()), 1);
+            let mut bounds = PlotOptions::default();
+            assert_eq!(plot_context_point_count(ctx), 0);
+            assert_eq!(plot_context_bounds(ctx, &mut bounds), 1);
+            assert_eq!(plot_append_points(ctx, xs.as_ptr(), ys.as_ptr(), 4), 0);
+            assert_eq!(plot_context_point_count(ctx), 4);
+            assert_eq!(plot_context_bounds(ctx, &mut bounds), 0);
+            assert_eq!(bounds.auto_range, 0);
+            // 2% padding of the 0..3 and 0..9 extents
+            assert!((bounds.x_min + 0.06).abs() < 1e-12 && (bounds.x_max - 3.06).abs() < 1e-12);
+            assert!((bounds.y_min + 0.18).abs() < 1e-12 && (bounds.y_max - 9.18).abs() < 1e-12);
+            assert_eq!(plot_append_points(ctx, xs[4..].as_ptr(), ys[4..].as_ptr(), 6), 0);
+            assert_eq!(plot_context_point_count(ctx), 10);
+            assert_eq!(plot_finish(ctx, path.as_ptr()), 0);
+
+            // The white background is readable after rendering
+            let (mut r, mut g, mut b, mut a) = (0u8, 0u8, 0u8, 0u8);
+            assert_eq!(plot_context_get_pixel(ctx, 0, 0, &mut r, &mut g, &mut b, &mut a), 0);
+            assert_eq!([r, g, b, a], [255, 255, 255, 255]);
+            let null = std::ptr::null_mut();
+            assert_eq!(plot_context_get_pixel(ctx, 199, 149, null, null, null, &mut a), 0);
+            assert_eq!(plot_context_get_pixel(ctx, 200, 0, &mut r, &mut g, &mut b, &mut a), 1);
+            assert_eq!(plot_context_get_pixel(ctx, 0, 150, &mut r, &mut g, &mut b, &mut a), 1);
+            plot_context_free(ctx);
+            plot_context_free(std::ptr::null_mut());
+        }
+        let ctx = PlotContext::new(opt.into());
+        assert_eq!(
+            ctx.pixel(0, 0).unwrap_err(),
+            "No image has been rendered with this context"
+        );
+
+        assert_eq!(
+            fs::read("/tmp/test_context_single.png").unwrap(),
+            fs::read("/tmp/test_context_staged.png").unwrap()
+        );
+        fs::remove_file("/tmp/test_context_single.png").ok();
+        fs::remove_file("/tmp/test_context_staged.png").ok();
+    }
+
+    #[test]
+    fn test_render_seed() {
+        let xs: Vec<f64> = (0..50).map(|i| (i % 5) as f64).collect();
+        let ys: Vec<f64> = (0..50).map(|i| i as f64).collect();
+        let render = |render_seed: u64| {
+            let opt = PlotOptions {
+                width: 200,
+                height: 150,
+                jitter_amount: 0.5,
+                render_seed,
+                ..Default::default()
+            };
+            plot_scatter_png_bytes_impl(&xs, &ys, opt).unwrap()
+        };
+
+        assert_eq!(render(42), render(42));
+        assert_ne!(render(42), render(43));
+        // Zero uses the canonical seed
+        assert_eq!(render(0), render(DEFAULT_RENDER_SEED));
+    }
+
+    #[test]
+    fn test_series_style_combinations() {
+        let xs: Vec<f64> = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        let ys: Vec<f64> = vec![2.0, 4.0, 3.0, 5.0, 1.0];
+        let path = "/tmp/test_series_style_combinations.png";
+        let red_pixels = |style: SeriesStyle| {
+            let series = ScatterSeries {
+                xs: xs.as_ptr(),
+                ys: ys.as_ptr(),
+                n: xs.len() as u64,
+                style,
+                z_order: 0,
+            };
+            // The data stays in the lower half, clear of the legend in the upper right
+            let opt = PlotOptions {
+                width: 240,
+                height: 180,
+                auto_range: 0,
+                x_min: 0.0,
+                x_max: 6.0,
+                y_min: 0.0,
+                y_max: 12.0,
+                force_legend: 1,
+                ..Default::default()
+            };
+            plot_scatter_series_png_impl(path, &[(&xs, &ys, series)], opt)?;
+            let image = image::open(path).unwrap().to_rgb8();
+            Ok::<_, String>(image.pixels().filter(|p| p.0 == [255, 0, 0]).count())
+        };
+
+        for marker_shape in [
+            PLOT_MARKER_CIRCLE,
+            PLOT_MARKER_SQUARE,
+            PLOT_MARKER_TRIANGLE,
+            PLOT_MARKER_CROSS,
+        ] {
+            let marker = SeriesStyle {
+                color: 0xFFFF0000,
+                marker_shape,
+                marker_radius: 6,
+                ..Default::default()
+            };
+            let outline = red_pixels(marker).unwrap();
+            let filled = red_pixels(SeriesStyle {
+                marker_filled: 1,
+                ..marker
+            })
+            .unwrap();
+            assert!(outline > 0, "shape {} drew nothing", marker_shape);
+            if marker_shape == PLOT_MARKER_CROSS {
+                assert_eq!(filled, outline, "crosses have no interior");
+            } else {
+                assert!(filled > outline, "shape {}: {} <= {}", marker_shape, filled, outline);
+            }
+
+            for line_dash in [PLOT_DASH_SOLID, PLOT_DASH_DASHED, PLOT_DASH_DOTTED, PLOT_DASH_DASH_DOT] {
+                let mut style = SeriesStyle {
+                    draw_line: 1,
+                    line_width: 2,
+                    line_dash,
+                    marker_filled: 1,
+                    ..marker
+                };
+                let with_markers = red_pixels(style).unwrap();
+                style.marker_radius = 0;
+                let line_only = red_pixels(style).unwrap();
+                assert!(line_only > 0, "dash {} drew nothing", line_dash);
+                assert!(with_markers > line_only);
+                // Named series get a legend entry with the line and marker
+                write_fixed_str(&mut style.name, "series");
+                assert!(red_pixels(style).unwrap() > line_only);
+            }
+        }
+
+        let bad_shape = SeriesStyle {
+            marker_shape: 4,
+            marker_radius: 3,
+            ..Default::default()
+        };
+        assert_eq!(
+            red_pixels(bad_shape).unwrap_err(),
+            "Series 0: Unsupported marker shape: 4"
+        );
+        let bad_dash = SeriesStyle {
+            draw_line: 1,
+            line_dash: 9,
+            ..Default::default()
+        };
+        assert_eq!(
+            red_pixels(bad_dash).unwrap_err(),
+            "Series 0: Unsupported line dash pattern: 9"
+        );
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_series_z_order() {
+        let (xs, ys) = ([5.0], [50.0]);
+        let series = |color: u32, z_order: i32| ScatterSeries {
+            xs: xs.as_ptr(),
+            ys: ys.as_ptr(),
+            n: 1,
+            style: SeriesStyle {
+                color,
+                marker_radius: 8,
+                marker_filled: 1,
+                ..Default::default()
+            },
+            z_order,
+        };
+        let opt = PlotOptions {
+            auto_range: 0,
+            x_min: 0.0,
+            x_max: 10.0,
+            y_min: 0.0,
+            y_max: 100.0,
+            ..Default::default()
+        };
+        let (px, py) = data_to_pixel(opt, 5.0, 50.0).unwrap();
+        let path = "/tmp/test_series_z_order.png";
+        let centre = |z_red: i32| {
+            let data = [
+                (&xs[..], &ys[..], series(0xFFFF0000, z_red)),
+                (&xs[..], &ys[..], series(0xFF0000FF, 0)),
+            ];
+            plot_scatter_series_png_impl(path, &data, opt).unwrap();
+            image::open(path).unwrap().to_rgb8().get_pixel(px, py).0
+        };
+        assert_eq!(centre(0), [0, 0, 255], "array order by default");
+        assert_eq!(centre(1), [255, 0, 0], "higher z-order on top");
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_legend_position() {
+        // All points in the lower-left quadrant of the 0..10 plot
+        let xs: Vec<f64> = vec![0.5, 1.0, 2.0, 3.0, 4.0];
+        let ys: Vec<f64> = vec![1.0, 0.5, 3.0, 2.0, 4.0];
+        let mut style = SeriesStyle {
+            color: 0xFFFF0000,
+            marker_radius: 4,
+            ..Default::default()
+        };
+        write_fixed_str(&mut style.name, "series");
+        let series = ScatterSeries {
+            xs: xs.as_ptr(),
+            ys: ys.as_ptr(),
+            n: xs.len() as u64,
+            style,
+            z_order: 0,
+        };
+        let path = "/tmp/test_legend_position.png";
+        let render = |legend_position: u8| {
+            let opt = PlotOptions {
+                width: 240,
+                height: 180,
+                auto_range: 0,
+                x_min: 0.0,
+                x_max: 10.0,
+                y_min: 0.0,
+                y_max: 10.0,
+                legend_position,
+                force_legend: 1,
+                ..Default::default()
+            };
+            plot_scatter_series_png_impl(path, &[(&xs, &ys, series)], opt)?;
+            Ok::<_, String>(image::open(path).unwrap().to_rgb8().into_raw())
+        };
+
+        let auto = render(PLOT_LEGEND_AUTO).unwrap();
+        assert_eq!(auto, render(PLOT_LEGEND_UPPER_RIGHT).unwrap());
+        assert_ne!(auto, render(PLOT_LEGEND_LOWER_LEFT).unwrap());
+        assert_ne!(auto, render(PLOT_LEGEND_NONE).unwrap());
+        assert_eq!(render(6).unwrap_err(), "Unsupported legend position: 6");
+        fs::remove_file(path).ok();
+
+        // Upper-right data moves the legend to the next emptiest corner, and inverted
+        // axes mirror the quadrants
+        let ranges = (0.0, 10.0, 0.0, 10.0);
+        let opt = PlotOptions::default();
+        let upper_right = legend_position(PLOT_LEGEND_AUTO, &[8.0, 9.0], &[8.0, 9.0], ranges, &opt).unwrap();
+        assert!(matches!(upper_right, Some(SeriesLabelPosition::UpperLeft)));
+        let inverted = PlotOptions { invert_x: 1, ..opt };
+        let mirrored = legend_position(PLOT_LEGEND_AUTO, &[1.0], &[9.0], ranges, &inverted).unwrap();
+        assert!(matches!(mirrored, Some(SeriesLabelPosition::UpperLeft)));
+    }
+
+    #[test]
+    fn test_single_series_legend() {
         let xs: Vec<f64> = vec![1.0, 2.0, 3.0];
-        let ys: Vec<f64> = vec![1.0, 2.0, 3.0];
+        let ys: Vec<f64> = vec![3.0, 1.0, 2.0];
+        let series = |name: &str, color: u32| {
+            let mut style = SeriesStyle {
+                color,
+                marker_radius: 3,
+                ..Default::default()
+            };
+            write_fixed_str(&mut style.name, name);
+            ScatterSeries {
+                xs: xs.as_ptr(),
+                ys: ys.as_ptr(),
+                n: xs.len() as u64,
+                style,
+                z_order: 0,
+            }
+        };
+        let (a, b) = (series("a", 0xFFFF0000), series("b", 0xFF0000FF));
+        let path = "/tmp/test_single_series_legend.png";
+        let render = |series: &[(&[f64], &[f64], ScatterSeries)], force_legend: u8| {
+            let opt = PlotOptions {
+                width: 240,
+                height: 180,
+                force_legend,
+                ..Default::default()
+            };
+            plot_scatter_series_png_impl(path, series, opt).unwrap();
+            image::open(path).unwrap().to_rgb8()
+        };
+        // The legend's black border sits in the upper-right corner of the plot area
+        let has_legend =
+            |image: &image::RgbImage| (120..230).any(|x| (10..60).any(|y| image.get_pixel(x, y).0 == [0, 0, 0]));
+
+        assert!(!has_legend(&render(&[(&xs, &ys, a)], 0)));
+        assert!(has_legend(&render(&[(&xs, &ys, a), (&xs, &ys, b)], 0)));
+        assert!(has_legend(&render(&[(&xs, &ys, a)], 1)));
+        assert_eq!(
+            plot_scatter_series_png_impl(path, &[], PlotOptions::default()).unwrap_err(),
+            "At least one series required"
+        );
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_post_render_callback() {
+        unsafe extern "C" fn count_calls(ctx: *mut c_void) {
+            unsafe { *(ctx as *mut u32) += 1 };
+        }
+
+        let path = "/tmp/test_post_render.png";
+        let c_path = CString::new(path).unwrap();
+        let (xs, ys) = ([1.0, 2.0], [2.0, 1.0]);
+        let mut calls = 0u32;
+        let opt = PlotOptions {
+            post_render_fn: Some(count_calls),
+            post_render_ctx: &mut calls as *mut u32 as *mut c_void,
+            ..Default::default()
+        };
+        assert_eq!(
+            unsafe { plot_scatter_png(c_path.as_ptr(), xs.as_ptr(), ys.as_ptr(), 2, opt) },
+            0
+        );
+        assert_eq!(calls, 1);
+        // Renderers other than the scatter PNG ones ignore the hook, as does the Rust API
+        let (mut buf, mut len) = (std::ptr::null_mut(), 0usize);
+        assert_eq!(
+            unsafe { plot_scatter_rgba(xs.as_ptr(), ys.as_ptr(), 2, opt, &mut buf, &mut len) },
+            0
+        );
+        unsafe { plot_free_buffer(buf, len) };
+        plot_scatter_png_impl(path, &xs, &ys, opt).unwrap();
+        assert_eq!(calls, 1);
+
+        // The hook runs on the finished chart: nothing is drawn over what it paints
+        let paint = |root: &DrawingArea<BitMapBackend, Shift>| {
+            root.fill(&MAGENTA).map_err(|e| drawing_error("Failed to paint", e))
+        };
+        let opt = RenderOptions::from(PlotOptions::default()).with_post_render(Some(&paint));
+        plot_scatter_png_impl(path, &xs, &ys, opt).unwrap();
+        let image = image::open(path).unwrap().to_rgb8();
+        fs::remove_file(path).ok();
+        assert!(image.pixels().all(|p| p.0 == [255, 0, 255]));
+    }
+
+    #[test]
+    fn test_scatter_png_timed() {
+        let path = CString::new("/tmp/test_scatter_timed.png").unwrap();
+        let xs: Vec<f64> = vec![1.0, 2.0, 3.0];
+        let ys: Vec<f64> = vec![2.0, 1.0, 3.0];
+
+        let mut elapsed = 0u64;
+        let result = unsafe {
+            plot_scatter_png_timed(
+                path.as_ptr(),
+                xs.as_ptr(),
+                ys.as_ptr(),
+                3,
+                PlotOptions::default(),
+                &mut elapsed,
+            )
+        };
+        assert_eq!(result, 0);
+        assert!(elapsed > 0);
+
+        let result = unsafe {
+            plot_scatter_png_timed(
+                path.as_ptr(),
+                xs.as_ptr(),
+                ys.as_ptr(),
+                3,
+                PlotOptions::default(),
+                std::ptr::null_mut(),
+            )
+        };
+        assert_eq!(result, 0);
+        fs::remove_file("/tmp/test_scatter_timed.png").ok();
+    }
+
+    #[test]
+    fn test_find_nearest_point() {
+        let opt = PlotOptions {
+            auto_range: 0,
+            x_min: 0.0,
+            x_max: 10.0,
+            y_min: 0.0,
+            y_max: 100.0,
+            ..Default::default()
+        };
+        let xs = [1.0, 3.0, 5.0, 7.0, f64::NAN];
+        let ys = [10.0, 90.0, 50.0, 52.0, 50.0];
+        // Point 2 is drawn at (424, 280)
+        let (px, py) = data_to_pixel(opt, 5.0, 50.0).unwrap();
+        let (mut index, mut dist) = (0usize, 0.0);
+        let result =
+            unsafe { plot_find_nearest_point(opt, xs.as_ptr(), ys.as_ptr(), 5, px + 3, py + 4, &mut index, &mut dist) };
+        assert_eq!(result, 0);
+        assert_eq!((index, dist), (2, 5.0));
+        assert_eq!(
+            nearest_point(opt, &xs, &ys, 0, 0).unwrap_err(),
+            "Pixel is in margin area"
+        );
+
+        // Ranking is in data space: (5, 58) is 8 units but about 43 pixels away, and
+        // (6, 50) 1 unit but 73 pixels
+        let near_in_pixels = data_to_pixel(opt, 5.0, 58.0).unwrap();
+        let near_in_data = data_to_pixel(opt, 6.0, 50.0).unwrap();
+        let pixel_dist = |(x, y): (u32, u32)| (x as f64 - px as f64).hypot(y as f64 - py as f64);
+        assert!(pixel_dist(near_in_pixels) < pixel_dist(near_in_data));
+        assert_eq!(
+            nearest_point(opt, &[5.0, 6.0], &[58.0, 50.0], px, py).unwrap(),
+            (1, pixel_dist(near_in_data))
+        );
+
+        let result =
+            unsafe { plot_find_nearest_point(opt, xs.as_ptr(), ys.as_ptr(), 0, px, py, &mut index, &mut dist) };
+        assert_eq!(result, 1);
+        assert!(nearest_point(opt, &xs[4..], &ys[4..], px, py).is_err());
+    }
+
+    #[test]
+    fn test_points_in_radius() {
+        let opt = PlotOptions {
+            auto_range: 0,
+            x_min: 0.0,
+            x_max: 10.0,
+            y_min: 0.0,
+            y_max: 100.0,
+            ..Default::default()
+        };
+        // Points 1 and 3 are drawn 29px and 38px from point 0 at (424, 280); the rest are far
+        let xs = [5.0, 5.4, 1.0, 4.9, 9.0];
+        let ys = [50.0, 50.0, 10.0, 43.0, 90.0];
+        let (px, py) = data_to_pixel(opt, 5.0, 50.0).unwrap();
+        let mut indices = [usize::MAX; 5];
+        let count =
+            unsafe { plot_points_in_radius(opt, xs.as_ptr(), ys.as_ptr(), 5, px, py, 50.0, indices.as_mut_ptr(), 5) };
+        assert_eq!(count, 3);
+        assert_eq!(indices[..3], [0, 1, 3]);
+        assert_eq!(indices[3], usize::MAX);
+
+        let count =
+            unsafe { plot_points_in_radius(opt, xs.as_ptr(), ys.as_ptr(), 5, px, py, 50.0, std::ptr::null_mut(), 0) };
+        assert_eq!(count, 3);
+        let mut first = [usize::MAX; 1];
+        let count =
+            unsafe { plot_points_in_radius(opt, xs.as_ptr(), ys.as_ptr(), 5, px, py, 50.0, first.as_mut_ptr(), 1) };
+        assert_eq!((count, first[0]), (3, 0));
+        assert!(points_in_radius(opt, &xs, &ys, px, py, -1.0).is_err());
+    }
+
+    #[test]
+    fn test_snap_range_to_ticks() {
+        let xs = [1.05, 2.0, 3.5, 4.95];
+        let ys = [0.12, 0.5, 0.31, 0.87];
+        let opt = PlotOptions {
+            x_tick_count: 5,
+            snap_range_to_ticks: 1,
+            ..Default::default()
+        };
+        let (x_min, x_max, y_min, y_max) = compute_ranges(&xs, &ys, &opt).unwrap();
+        assert_eq!((x_min, x_max), (1.0, 5.0));
+        // The default tick count spaces the Y ticks 0.1 apart
+        assert!(
+            (y_min - 0.1).abs() < 1e-12 && (y_max - 0.9).abs() < 1e-12,
+            "{} {}",
+            y_min,
+            y_max
+        );
+
+        let unsnapped = compute_ranges(
+            &xs,
+            &ys,
+            &PlotOptions {
+                snap_range_to_ticks: 0,
+                ..opt
+            },
+        )
+        .unwrap();
+        assert!(unsnapped.0 < 1.0 && unsnapped.1 > 5.0);
+        // A single value on a tick keeps the unsnapped range
+        let single = compute_ranges(&[2.0], &[2.0], &opt).unwrap();
+        assert_eq!((single.0, single.1), (1.0, 3.0));
+    }
+
+    #[test]
+    fn test_write_metadata() {
+        let path = "/tmp/test_write_metadata.png";
+        let xs = [1.0, 2.0, f64::NAN, 4.0];
+        let ys = [3.0, 1.0, 2.0, 2.5];
+        let opt = PlotOptions {
+            write_metadata: 1,
+            nan_policy: 1,
+            outer_padding: 10,
+            ..Default::default()
+        };
+        plot_scatter_png_impl(path, &xs, &ys, opt).unwrap();
+        let json = fs::read_to_string("/tmp/test_write_metadata.png.json").unwrap();
+        let metadata: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let (x_min, x_max, y_min, y_max) = compute_ranges(&[1.0, 2.0, 4.0], &[3.0, 1.0, 2.5], &opt).unwrap();
+        assert_eq!(
+            metadata,
+            serde_json::json!({
+                "width": 820,
+                "height": 620,
+                "x_min": x_min,
+                "x_max": x_max,
+                "y_min": y_min,
+                "y_max": y_max,
+                "n_points": 3,
+            })
+        );
+        fs::remove_file("/tmp/test_write_metadata.png.json").ok();
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_density_overlay() {
+        let path = "/tmp/test_density_overlay.png";
+        let mut rng = RenderRng::new(DEFAULT_RENDER_SEED);
+        let mut next = || rng.next_unit();
+        // Points concentrated towards the lower left
+        let (xs, ys): (Vec<f64>, Vec<f64>) = (0..10_000).map(|_| (next() * next(), next() * next())).unzip();
+        let opt = PlotOptions {
+            density_overlay: 1,
+            marker_radius: 1,
+            ..Default::default()
+        };
+        plot_scatter_png_impl(path, &xs, &ys, opt).unwrap();
+        let image = image::open(path).unwrap().to_rgb8();
+        assert_eq!(image.dimensions(), (800, 600));
+        // The fullest bin is yellow, blended over the white background
+        assert!(image.pixels().any(|p| p.0[2] < 0x80 && p.0[0] > 0xE0 && p.0[1] > 0xC0));
+
+        let opt = PlotOptions {
+            density_bins: 2000,
+            ..opt
+        };
+        let err = plot_scatter_png_impl(path, &xs, &ys, opt).unwrap_err();
+        assert!(err.starts_with("Too many density bins"), "{}", err);
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_dual_axis() {
+        let path = "/tmp/test_dual_axis.png";
+        let xs: Vec<f64> = (0..=10).map(f64::from).collect();
+        let ys_left = xs.clone();
+        let ys_right: Vec<f64> = xs.iter().map(|x| 100.0 - 10.0 * x).collect();
+        let opt = PlotOptions {
+            auto_range: 0,
+            x_min: 0.0,
+            x_max: 10.0,
+            y_min: 0.0,
+            y_max: 10.0,
+            line_stroke_width: 2,
+            ..Default::default()
+        };
+        let mut right = AxisOptions {
+            auto_range: 0,
+            y_min: 0.0,
+            y_max: 100.0,
+            series_color: 0xFFFF0000,
+            ..Default::default()
+        };
+        write_fixed_str(&mut right.series_name, "Percent");
+        plot_dual_axis_png_impl(path, &xs, (&ys_left, &ys_right), opt, right).unwrap();
+        let image = image::open(path).unwrap().to_rgb8();
+        assert_eq!(image.dimensions(), (800, 600));
+        // The lines cross in the middle of the plot area; the right one goes from top left
+        // to bottom right, and the labels in the strip on the right are red too
+        let [r, g, b] = color_from_argb(PALETTE_TABLEAU10[0]).rgb().into();
+        assert!(image.pixels().any(|p| p.0 == [r, g, b]));
+        assert!(image.get_pixel(70, 20).0 == [255, 0, 0] || image.get_pixel(70, 21).0 == [255, 0, 0]);
+        let reddish = |p: &image::Rgb<u8>| p.0[0] > 0xC0 && p.0[1] < 0x80 && p.0[2] < 0x80;
+        assert!((740..800).any(|x| (0..600).any(|y| reddish(image.get_pixel(x, y)))));
+
+        right.log_scale = 1;
+        let err = plot_dual_axis_png_impl(path, &xs, (&ys_left, &ys_right), opt, right).unwrap_err();
+        assert!(err.contains("positive y_min"), "{}", err);
+        right.auto_range = 1;
+        let err = plot_dual_axis_png_impl(path, &xs, (&ys_left, &ys_right), opt, right).unwrap_err();
+        assert!(err.contains("positive values"), "{}", err);
+        let ys_right: Vec<f64> = xs.iter().map(|x| 10f64.powf(*x / 2.0)).collect();
+        plot_dual_axis_png_impl(path, &xs, (&ys_left, &ys_right), opt, right).unwrap();
+
+        // Skipping NaN points shortens a series, and a series without points fails
+        let skip = PlotOptions { nan_policy: 1, ..opt };
+        let mut ys_gap = ys_left.clone();
+        ys_gap[3] = f64::NAN;
+        plot_dual_axis_png_impl(path, &xs, (&ys_gap, &ys_right), skip, right).unwrap();
+        let err = plot_dual_axis_png_impl(path, &xs, (&ys_left, &[f64::NAN; 11]), skip, right).unwrap_err();
+        assert_eq!(
+            err,
+            "Right series: No valid (non-NaN, finite) data points after filtering"
+        );
+        let err = plot_dual_axis_png_impl(path, &xs, (&[f64::NAN; 11], &ys_right), skip, right).unwrap_err();
+        assert!(err.starts_with("Left series: "), "{}", err);
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_io_error_message() {
+        // A directory cannot be opened as a file, even with root permissions
+        let path = "/tmp/test_io_error_message.png";
+        fs::create_dir_all(path).unwrap();
+        let err = plot_scatter_png_impl(path, &[1.0, 2.0], &[1.0, 2.0], PlotOptions::default()).unwrap_err();
+        fs::remove_dir(path).ok();
+        assert!(err.starts_with("IO error writing chart: "), "{}", err);
+        assert!(err.contains("os error"), "{}", err);
+
+        let err = drawing_error(
+            "Failed to draw points",
+            DrawingAreaErrorKind::<std::io::Error>::LayoutError,
+        );
+        assert_eq!(err, "Failed to draw points: the chart layout does not fit the image");
+        let e = DrawingAreaErrorKind::BackendError(DrawingErrorKind::DrawingError(std::io::Error::other("disk full")));
+        assert_eq!(
+            drawing_error("Failed to draw points", e),
+            "Failed to draw points: backend error: disk full"
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "embedded-font"))]
+    fn test_system_font_without_embedded_font() {
+        let path = "/tmp/test_system_font.png";
+        let result = plot_scatter_png_impl(path, &[1.0, 2.0], &[1.0, 2.0], PlotOptions::default());
+        fs::remove_file(path).ok();
+        if SYSTEM_FONT_PATHS.iter().any(|p| std::path::Path::new(p).is_file()) {
+            result.unwrap();
+            assert!(FONT_FALLBACK_WARNING.lock().unwrap().is_none());
+        } else {
+            assert!(result.unwrap_err().contains("embedded-font"));
+        }
+    }
+
+    #[test]
+    fn test_pixel_mapping() {
+        // Default layout: the plot area starts at (60, 10) and is 730 x 540 pixels
         let opt = PlotOptions {
             width: 800,
             height: 600,
-            marker_radius: 5,
             auto_range: 0,
             x_min: 0.0,
             x_max: 10.0,
             y_min: 0.0,
+            y_max: 100.0,
+            ..Default::default()
+        };
+        assert_eq!(data_to_pixel(opt, 0.0, 100.0), Ok((60, 10)));
+        assert_eq!(data_to_pixel(opt, 5.0, 50.0), Ok((424, 280)));
+        let inverted = PlotOptions {
+            invert_y: 1,
+            outer_padding: 5,
+            ..opt
+        };
+        assert_eq!(data_to_pixel(inverted, 0.0, 0.0), Ok((65, 15)));
+
+        let (mut px, mut py) = (0u32, 0u32);
+        assert_eq!(unsafe { plot_data_to_pixel(opt, 5.0, 50.0, &mut px, &mut py) }, 0);
+        assert_eq!((px, py), (424, 280));
+        assert_eq!(unsafe { plot_data_to_pixel(opt, 10.5, 50.0, &mut px, &mut py) }, 1);
+        assert_eq!(data_to_pixel(opt, 10.5, 50.0).unwrap_err(), "Point outside axis range");
+        let auto = PlotOptions::default();
+        assert!(
+            data_to_pixel(auto, 0.5, 0.5)
+                .unwrap_err()
+                .contains("explicit axis ranges")
+        );
+
+        // Pixels map back to data that maps to the same pixel
+        for (px, py) in [(60, 10), (424, 280), (789, 549), (100, 500)] {
+            let (x, y) = pixel_to_data(opt, px, py).unwrap();
+            assert_eq!(data_to_pixel(opt, x, y), Ok((px, py)));
+        }
+        for (x, y) in [(0.0, 0.0), (2.5, 75.0), (10.0, 100.0)] {
+            let (px, py) = data_to_pixel(inverted, x, y).unwrap();
+            let (rx, ry) = pixel_to_data(inverted, px, py).unwrap();
+            // Within one pixel of the plot area, 730 x 540 pixels
+            assert!((rx - x).abs() <= 10.0 / 730.0 && (ry - y).abs() <= 100.0 / 540.0);
+        }
+        let (mut x, mut y) = (0.0, 0.0);
+        assert_eq!(unsafe { plot_pixel_to_data(opt, 60, 549, &mut x, &mut y) }, 0);
+        assert_eq!((x, 0.0), (0.0, y));
+        assert_eq!(unsafe { plot_pixel_to_data(opt, 30, 300, &mut x, &mut y) }, 1);
+        assert_eq!(pixel_to_data(opt, 400, 580).unwrap_err(), "Pixel is in margin area");
+        assert_eq!(pixel_to_data(inverted, 2, 300).unwrap_err(), "Pixel is in margin area");
+
+        // The mapping matches where a marker is drawn
+        let marker = PlotOptions {
+            marker_radius: 0,
+            ..opt
+        };
+        let rgba = plot_scatter_rgba_impl(&[7.0], &[30.0], marker).unwrap();
+        let (px, py) = data_to_pixel(marker, 7.0, 30.0).unwrap();
+        let i = (py as usize * 800 + px as usize) * 4;
+        assert_ne!(&rgba[i..i + 3], &[255, 255, 255]);
+    }
+
+    #[test]
+    fn test_compute_range() {
+        let xs: Vec<f64> = vec![1.0, 4.0, f64::NAN, 2.0, 3.0, 5.0];
+        let ys: Vec<f64> = vec![10.0, 30.0, 99.0, 20.0, f64::INFINITY, 40.0];
+        let mut opt = PlotOptions::default();
+        let result = unsafe { plot_compute_range(xs.as_ptr(), ys.as_ptr(), xs.len() as u64, 0.05, &mut opt) };
+        assert_eq!(result, 0);
+        assert_eq!(opt.auto_range, 0);
+        assert!(
+            xs.iter()
+                .filter(|x| x.is_finite())
+                .all(|&x| opt.x_min < x && x < opt.x_max)
+        );
+        assert!((opt.x_min - 0.8).abs() < 1e-9 && (opt.x_max - 5.2).abs() < 1e-9);
+        // The skipped points do not widen the Y range
+        assert!((opt.y_min - 8.5).abs() < 1e-9 && (opt.y_max - 41.5).abs() < 1e-9);
+
+        let nan = [f64::NAN, f64::NAN];
+        assert_eq!(
+            padded_data_range(&nan, &nan, 0.05).unwrap_err(),
+            "No finite points to compute a range from"
+        );
+        assert!(padded_data_range(&xs, &ys, -1.0).is_err());
+    }
+
+    #[test]
+    fn test_scatter_pdf() {
+        let path = "/tmp/test_scatter.pdf";
+        let result = plot_scatter_pdf_impl(path, &[1.0, 2.0, 3.0], &[3.0, 1.0, 2.0], PlotOptions::default());
+        if cfg!(feature = "pdf") {
+            result.unwrap();
+            assert!(fs::read(path).unwrap().starts_with(b"%PDF-"));
+            fs::remove_file(path).ok();
+        } else {
+            assert!(result.unwrap_err().contains("`pdf` feature"));
+        }
+    }
+
+    #[test]
+    fn test_render_to_texture_gl() {
+        let (xs, ys) = ([1.0, 2.0], [2.0, 1.0]);
+        // Invalid input fails before any GL call, so no context is needed
+        let result = unsafe { plot_render_to_texture_gl(1, std::ptr::null(), ys.as_ptr(), 2, PlotOptions::default()) };
+        assert_eq!(result, 1);
+        let zero_width = PlotOptions {
+            width: 0,
+            ..Default::default()
+        };
+        let result = unsafe { plot_render_to_texture_gl_impl(1, &xs, &ys, zero_width) };
+        if cfg!(feature = "opengl") {
+            assert!(result.unwrap_err().contains("Width and height"));
+        } else {
+            assert!(result.unwrap_err().contains("`opengl` feature"));
+        }
+    }
+
+    #[test]
+    fn test_composite_scatter_heatmap() {
+        let mut rng = RenderRng::new(5);
+        let (xs, ys): (Vec<f64>, Vec<f64>) = (0..500).map(|_| (rng.next_unit(), rng.next_unit())).unzip();
+        let scatter = ScatterStyle {
+            color: 0xFFFF0000,
+            marker_radius: 2,
+        };
+        let heatmap = HeatmapStyle {
+            n_bins_x: 4,
+            n_bins_y: 4,
+            opacity: 0.0,
+        };
+        let line = ReferenceLine {
+            orientation: PLOT_REFERENCE_VERTICAL,
+            value: 0.5,
+            ..Default::default()
+        };
+        let layers = [
+            Layer::Heatmap(&xs, &ys, heatmap),
+            Layer::Scatter(&xs, &ys, scatter),
+            Layer::ReferenceLine(line),
+        ];
+        let path = "/tmp/test_composite.png";
+        plot_composite_png_impl(path, &layers, PlotOptions::default()).unwrap();
+        let image = image::open(path).unwrap().to_rgb8();
+        assert_eq!(image.dimensions(), (800, 600));
+        // Red markers on top, and the half-transparent bins blend with the white background
+        assert!(image.pixels().any(|p| p.0 == [255, 0, 0]));
+        let bin = image.get_pixel(200, 450).0;
+        assert!(bin != [255, 255, 255] && bin.iter().all(|&c| c > 0x20), "{:?}", bin);
+
+        let bad_line = ReferenceLine { orientation: 7, ..line };
+        let err = plot_composite_png_impl(path, &[Layer::ReferenceLine(bad_line)], PlotOptions::default()).unwrap_err();
+        assert!(err.contains("scatter layer"), "{}", err);
+        let fixed = PlotOptions {
+            auto_range: 0,
+            ..Default::default()
+        };
+        let err = plot_composite_png_impl(path, &[Layer::ReferenceLine(bad_line)], fixed).unwrap_err();
+        assert!(err.contains("orientation"), "{}", err);
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_histogram2d() {
+        // 1000 points clustered around the origin of a symmetric 9 x 9 grid
+        let mut rng = RenderRng::new(11);
+        let mut sample = || (0..4).map(|_| rng.next_unit() - 0.5).sum::<f64>();
+        let points: Vec<(f64, f64)> = (0..1000).map(|_| (sample(), sample())).collect();
+        let (xs, ys): (Vec<f64>, Vec<f64>) = points.into_iter().unzip();
+        let opt = PlotOptions {
+            auto_range: 0,
+            x_min: -2.0,
+            x_max: 2.0,
+            y_min: -2.0,
+            y_max: 2.0,
+            ..Default::default()
+        };
+
+        let counts = histogram2d(&xs, &ys, (-2.0, 2.0, -2.0, 2.0), (9, 9));
+        assert_eq!(counts.iter().sum::<u32>(), 1000);
+        let max = *counts.iter().max().unwrap();
+        assert_eq!(counts[4 * 9 + 4], max, "central bin holds the most points");
+
+        let path = "/tmp/test_histogram2d.png";
+        plot_histogram2d_png_impl(path, &xs, &ys, 9, 9, opt).unwrap();
+        let image = image::open(path).unwrap().to_rgb8();
+        // The fullest bin has the top color of the scale; apart from the colorbar, it
+        // sits in the middle of the plot
+        let top = viridis(1.0);
+        let plot_width = opt.width - COLORBAR_AREA_SIZE;
+        let hot: Vec<(u32, u32)> = image
+            .enumerate_pixels()
+            .filter(|(x, _, p)| *x < plot_width && p.0 == [top.0, top.1, top.2])
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert!(!hot.is_empty());
+        let cx = hot.iter().map(|p| p.0 as f64).sum::<f64>() / hot.len() as f64;
+        let cy = hot.iter().map(|p| p.1 as f64).sum::<f64>() / hot.len() as f64;
+        assert!((plot_width as f64 / 3.0..plot_width as f64 * 2.0 / 3.0).contains(&cx));
+        assert!((opt.height as f64 / 3.0..opt.height as f64 * 2.0 / 3.0).contains(&cy));
+
+        // Default bins: ceil(sqrt(1000)) = 32 per axis
+        plot_histogram2d_png_impl(path, &xs, &ys, 0, 0, PlotOptions::default()).unwrap();
+        fs::remove_file(path).ok();
+        assert!(plot_histogram2d_png_impl(path, &xs, &ys, 1 << 16, 1 << 16, opt).is_err());
+    }
+
+    #[test]
+    fn test_label_skip() {
+        // 11 labels such as "1000.0" need about 11 * 6 * 8.4 = 554 px
+        let spec: RangedCoordf64 = (0.0..1000.0).into();
+        let format = |v: &f64| format!("{:.1}", v);
+        let count = fit_label_count(&spec, &format, 14.0, 150);
+        assert!(count < DEFAULT_LABEL_COUNT);
+        let width: f64 = spec
+            .key_points(count)
+            .iter()
+            .map(|v| format(v).len() as f64 * 14.0 * 0.6)
+            .sum();
+        assert!(width <= 150.0, "{} labels need {} px", count, width);
+        assert_eq!(fit_label_count(&spec, &format, 14.0, 2000), DEFAULT_LABEL_COUNT);
+        assert_eq!(fit_label_count(&spec, &format, 14.0, 1), MIN_LABEL_COUNT);
+
+        let xs: Vec<f64> = vec![1000.0, 2500.0, 4000.0];
+        let ys: Vec<f64> = vec![1.0, 3.0, 2.0];
+        let render = |disable_label_skip: u8| {
+            let opt = PlotOptions {
+                width: 200,
+                height: 150,
+                disable_label_skip,
+                ..Default::default()
+            };
+            plot_scatter_png_bytes_impl(&xs, &ys, opt).unwrap()
+        };
+        assert_ne!(render(0), render(1));
+    }
+
+    #[test]
+    fn test_errorbars() {
+        let xs: Vec<f64> = vec![1.0, 2.0, 3.0, 4.0];
+        let ys: Vec<f64> = vec![2.0, 3.0, 2.5, 4.0];
+        let y_err: Vec<f64> = vec![0.5, 0.3, 0.8, 0.2];
+        let path = CString::new("/tmp/test_errorbars.png").unwrap();
+        let ebo = ErrorBarOptions {
+            y_err: y_err.as_ptr(),
+            color: 0xFFFF0000,
+            line_width: 2,
+            ..Default::default()
+        };
+        let red_pixels = |ebo: ErrorBarOptions| {
+            let result = unsafe {
+                plot_scatter_errorbars_ex(path.as_ptr(), xs.as_ptr(), ys.as_ptr(), 4, ebo, PlotOptions::default())
+            };
+            assert_eq!(result, 0);
+            let image = image::open("/tmp/test_errorbars.png").unwrap().to_rgb8();
+            image.pixels().filter(|p| p.0 == [255, 0, 0]).count()
+        };
+
+        // Only Y errors: the X array stays NULL
+        let with_caps = red_pixels(ebo);
+        let without_caps = red_pixels(ErrorBarOptions { cap_size_px: 0, ..ebo });
+        assert!(without_caps > 0);
+        assert!(with_caps > without_caps);
+        assert!(
+            red_pixels(ErrorBarOptions {
+                x_err: y_err.as_ptr(),
+                ..ebo
+            }) > with_caps
+        );
+        fs::remove_file("/tmp/test_errorbars.png").ok();
+
+        let neither = ErrorBarOptions::default();
+        let result = unsafe {
+            plot_scatter_errorbars_ex(
+                path.as_ptr(),
+                xs.as_ptr(),
+                ys.as_ptr(),
+                4,
+                neither,
+                PlotOptions::default(),
+            )
+        };
+        assert_eq!(result, 1);
+        let negative = [0.5, -0.1, 0.0, 0.0];
+        let err = plot_scatter_errorbars_impl(
+            "/tmp/test_errorbars.png",
+            &xs,
+            &ys,
+            None,
+            Some(&negative),
+            ebo,
+            PlotOptions::default(),
+        )
+        .unwrap_err();
+        assert_eq!(err, "Negative error at index 1");
+    }
+
+    #[test]
+    fn test_asymmetric_errorbars() {
+        let path = c"/tmp/test_asymmetric_errorbars.png";
+        let xs = [1.0, 2.0, 3.0, 4.0];
+        let ys = [2.0, 4.0, 3.0, 5.0];
+        let lo = [0.2, 0.5, 0.1, 1.0];
+        let hi = [1.0, 0.5, 2.0, 0.1];
+        let render = |y_lo: *const f64, y_hi: *const f64| unsafe {
+            plot_scatter_asymmetric_errorbars_png(
+                path.as_ptr(),
+                xs.as_ptr(),
+                ys.as_ptr(),
+                std::ptr::null(),
+                std::ptr::null(),
+                y_lo,
+                y_hi,
+                4,
+                PlotOptions::default(),
+            )
+        };
+        assert_eq!(render(lo.as_ptr(), hi.as_ptr()), 0);
+        assert_eq!(render(std::ptr::null(), hi.as_ptr()), 0);
+        assert_eq!(render(std::ptr::null(), std::ptr::null()), 1);
+
+        // The bar reaches 10 below and 30 above the point
+        let opt = PlotOptions {
+            auto_range: 0,
+            x_min: 0.0,
+            x_max: 10.0,
+            y_min: 0.0,
+            y_max: 100.0,
+            ..Default::default()
+        };
+        let style = ErrorBarOptions {
+            cap_size_px: 0,
+            color: 0xFFFF0000,
+            ..Default::default()
+        };
+        let path = "/tmp/test_asymmetric_errorbars.png";
+        plot_scatter_asymmetric_errorbars_impl(
+            path,
+            &[5.0],
+            &[50.0],
+            (None, None),
+            (Some(&[10.0]), Some(&[30.0])),
+            style,
+            opt,
+        )
+        .unwrap();
+        let image = image::open(path).unwrap().to_rgb8();
+        let red_rows: Vec<u32> = image
+            .enumerate_pixels()
+            .filter(|(_, _, p)| p.0 == [255, 0, 0])
+            .map(|(_, y, _)| y)
+            .collect();
+        let (_, top) = data_to_pixel(opt, 5.0, 80.0).unwrap();
+        let (_, bottom) = data_to_pixel(opt, 5.0, 40.0).unwrap();
+        assert_eq!(red_rows.iter().min(), Some(&top));
+        assert_eq!(red_rows.iter().max(), Some(&bottom));
+
+        let negative = [0.1, 0.1, -0.5, 0.1];
+        let err = plot_scatter_asymmetric_errorbars_impl(
+            path,
+            &xs,
+            &ys,
+            (None, None),
+            (Some(&lo), Some(&negative)),
+            style,
+            opt,
+        )
+        .unwrap_err();
+        assert_eq!(err, "Negative error at index 2");
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    // Binary literals are grouped by channel
+    #[allow(clippy::unusual_byte_groupings)]
+    fn test_rgb565() {
+        assert_eq!(rgb565(255, 0, 0), 0b11111_000000_00000);
+        assert_eq!(rgb565(0, 255, 0), 0b00000_111111_00000);
+        assert_eq!(rgb565(0, 0, 255), 0b00000_000000_11111);
+
+        let xs: Vec<f64> = vec![1.0, 2.0, 3.0];
+        let ys: Vec<f64> = vec![3.0, 1.0, 2.0];
+        let opt = PlotOptions {
+            width: 64,
+            height: 48,
+            ..Default::default()
+        };
+        let (mut ptr, mut len) = (std::ptr::null_mut(), 0usize);
+        let result = unsafe { plot_scatter_rgb565(xs.as_ptr(), ys.as_ptr(), 3, opt, &mut ptr, &mut len) };
+        assert_eq!(result, 0);
+        assert_eq!(len, 64 * 48);
+        // The top-left corner is white background
+        let first = unsafe { *ptr };
+        assert_eq!(u16::from_le(first), 0xFFFF);
+        unsafe { plot_free_u16_buffer(ptr, len) };
+
+        let little = plot_scatter_rgb565_impl(&xs, &ys, opt).unwrap();
+        let big = plot_scatter_rgb565_impl(&xs, &ys, PlotOptions { pixel_endian: 1, ..opt }).unwrap();
+        assert!(little.iter().zip(&big).all(|(l, b)| *l == b.swap_bytes()));
+        assert!(plot_scatter_rgb565_impl(&xs, &ys, PlotOptions { pixel_endian: 2, ..opt }).is_err());
+    }
+
+    #[test]
+    fn test_zero_lines() {
+        let xs: Vec<f64> = vec![-4.0, -1.0, 2.0, 4.0];
+        let ys: Vec<f64> = vec![1.0, 3.0, 2.0, 4.0];
+        let render = |x_min: f64, x_max: f64, draw_zero_x_line: u8| {
+            let opt = PlotOptions {
+                width: 200,
+                height: 150,
+                auto_range: 0,
+                x_min,
+                x_max,
+                y_min: 0.5,
+                y_max: 5.0,
+                draw_zero_x_line,
+                draw_zero_y_line: 1,
+                zero_line_color: 0xFFFF0000,
+                ..Default::default()
+            };
+            let png = plot_scatter_png_bytes_impl(&xs, &ys, opt).unwrap();
+            let image = image::load_from_memory(&png).unwrap().to_rgb8();
+            image.pixels().filter(|p| p.0 == [255, 0, 0]).count()
+        };
+
+        // Only the X line is inside the ranges; y = 0 is below the Y range
+        assert!(render(-5.0, 5.0, 1) > 0);
+        assert_eq!(render(-5.0, 5.0, 0), 0);
+        assert_eq!(render(1.0, 5.0, 1), 0);
+    }
+
+    #[test]
+    fn test_auto_height() {
+        let xs: Vec<f64> = vec![0.0, 10.0, 5.0];
+        let ys: Vec<f64> = vec![0.0, 10.0, 2.0];
+        let path = "/tmp/test_auto_height.png";
+        let opt = PlotOptions {
+            width: 800,
+            height: 0,
+            ..Default::default()
+        };
+        plot_scatter_png_impl(path, &xs, &ys, opt).unwrap();
+        assert_eq!(image::image_dimensions(path).unwrap(), (800, 800));
+
+        let wide = PlotOptions {
+            auto_range: 0,
+            x_min: 0.0,
+            x_max: 20.0,
+            y_min: 0.0,
             y_max: 10.0,
+            ..opt
         };
+        plot_scatter_png_impl(path, &xs, &ys, wide).unwrap();
+        assert_eq!(image::image_dimensions(path).unwrap(), (800, 400));
+        fs::remove_file(path).ok();
+
+        let both_zero = PlotOptions { width: 0, ..opt };
+        assert_eq!(
+            plot_scatter_png_impl(path, &xs, &ys, both_zero).unwrap_err(),
+            "Width and height must not both be zero"
+        );
+        let flat = PlotOptions { y_max: 0.0001, ..wide };
+        assert!(
+            plot_scatter_png_impl(path, &xs, &ys, flat)
+                .unwrap_err()
+                .starts_with("Automatic height is zero")
+        );
+    }
 
-        let result = unsafe { plot_scatter_png(path.as_ptr(), xs.as_ptr(), ys.as_ptr(), xs.len(), opt) };
+    #[test]
+    fn test_violin() {
+        // Deterministic pseudo-normal samples: sums of uniform draws
+        let mut rng = RenderRng::new(7);
+        let mut sample = |center: f64| center + (0..4).map(|_| rng.next_unit() - 0.5).sum::<f64>();
+        let a: Vec<f64> = (0..50).map(|_| sample(2.0)).collect();
+        let b: Vec<f64> = (0..50).map(|_| sample(4.0)).collect();
 
-        assert_eq!(result, 0, "Expected success with explicit range");
-        fs::remove_file("/tmp/test_explicit.png").ok();
+        let mut groups = [
+            BoxGroup {
+                values: a.as_ptr(),
+                n: a.len() as u64,
+                label: [0; 64],
+            },
+            BoxGroup {
+                values: b.as_ptr(),
+                n: b.len() as u64,
+                label: [0; 64],
+            },
+        ];
+        write_fixed_str(&mut groups[0].label, "control");
+        write_fixed_str(&mut groups[1].label, "treated");
+
+        let path = CString::new("/tmp/test_violin.png").unwrap();
+        let result = unsafe { plot_violin_png(path.as_ptr(), groups.as_ptr(), 2, PlotOptions::default()) };
+        assert_eq!(result, 0, "Expected success for two groups");
+        let image = image::open("/tmp/test_violin.png").unwrap();
+        assert_eq!((image.width(), image.height()), (800, 600));
+        fs::remove_file("/tmp/test_violin.png").ok();
+
+        let constant = [1.0, 1.0, 1.0];
+        groups[1].values = constant.as_ptr();
+        groups[1].n = constant.len() as u64;
+        let result = unsafe { plot_violin_png(path.as_ptr(), groups.as_ptr(), 2, PlotOptions::default()) };
+        assert_eq!(result, 1, "Expected failure for a constant group");
+
+        let mut sorted = a.clone();
+        sorted.sort_by(f64::total_cmp);
+        assert_eq!(quantile(&[1.0, 2.0, 3.0, 4.0], 0.5), 2.5);
+        assert!(silverman_bandwidth(&sorted) > 0.0);
+    }
+
+    #[test]
+    fn test_color_scheme_cycles() {
+        let xs: Vec<f64> = (0..5).map(|i| i as f64).collect();
+        let ys: Vec<Vec<f64>> = (0..12).map(|k| xs.iter().map(|x| x + k as f64).collect()).collect();
+        let style = SeriesStyle {
+            draw_line: 1,
+            line_width: 3,
+            ..Default::default()
+        };
+        let series: Vec<(&[f64], &[f64], ScatterSeries)> = ys
+            .iter()
+            .map(|ys| {
+                let s = ScatterSeries {
+                    xs: xs.as_ptr(),
+                    ys: ys.as_ptr(),
+                    n: xs.len() as u64,
+                    style,
+                    z_order: 0,
+                };
+                (&xs[..], &ys[..], s)
+            })
+            .collect();
+
+        let path = "/tmp/test_color_scheme.png";
+        plot_scatter_series_png_impl(path, &series, PlotOptions::default()).unwrap();
+        let image = image::open(path).unwrap().to_rgb8();
+        for argb in PALETTE_TABLEAU10 {
+            let [_, r, g, b] = argb.to_be_bytes();
+            assert!(
+                image.pixels().any(|p| p.0 == [r, g, b]),
+                "Missing palette color {:08X}",
+                argb
+            );
+        }
+
+        for color_scheme in [PLOT_SCHEME_SET1, PLOT_SCHEME_PASTEL, PLOT_SCHEME_MONOCHROME] {
+            let opt = PlotOptions {
+                color_scheme,
+                ..Default::default()
+            };
+            plot_scatter_series_png_impl(path, &series, opt).unwrap();
+        }
+        let bad = PlotOptions {
+            color_scheme: 4,
+            ..Default::default()
+        };
+        let err = plot_scatter_series_png_impl(path, &series, bad).unwrap_err();
+        assert_eq!(err, "Unsupported color scheme: 4");
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_marker_skipping() {
+        let opt = PlotOptions {
+            width: 200,
+            height: 150,
+            auto_range: 0,
+            x_max: 10.0,
+            y_max: 10.0,
+            ..Default::default()
+        };
+
+        // Repeated opaque markers and markers far outside the image change nothing
+        let single = plot_scatter_rgba_impl(&[5.0], &[5.0], opt).unwrap();
+        let repeated = plot_scatter_rgba_impl(&[5.0, 5.0, 5.0, 1000.0], &[5.0, 5.0, 5.0, -1000.0], opt).unwrap();
+        assert_eq!(single, repeated);
+
+        // Translucent markers still blend when they overlap
+        let translucent = PlotOptions {
+            marker_alpha: 100,
+            ..opt
+        };
+        let once = plot_scatter_rgba_impl(&[5.0], &[5.0], translucent).unwrap();
+        let twice = plot_scatter_rgba_impl(&[5.0, 5.0], &[5.0, 5.0], translucent).unwrap();
+        assert_ne!(once, twice);
+    }
+
+    #[test]
+    fn test_huge_point_count() {
+        let path = CString::new("/tmp/test_huge_point_count.png").unwrap();
+        let xs = [1.0, 2.0];
+        let ys = [1.0, 2.0];
+        let opt = PlotOptions::default();
+
+        let result = unsafe { plot_scatter_png(path.as_ptr(), std::ptr::null(), std::ptr::null(), u64::MAX, opt) };
+        assert_eq!(result, 1);
+        let err = unsafe { data_slices::<f64>(std::ptr::null(), std::ptr::null(), u64::MAX) }.unwrap_err();
+        assert_eq!(err, "X data pointer is NULL");
+
+        // The count is rejected before any slice is created from the pointers
+        let result = unsafe { plot_scatter_png(path.as_ptr(), xs.as_ptr(), ys.as_ptr(), u64::MAX, opt) };
+        assert_eq!(result, 1);
+        let err = unsafe { data_slices(xs.as_ptr(), ys.as_ptr(), u64::MAX) }.unwrap_err();
+        assert_eq!(err, "Point count (n) is too large");
+        let result = unsafe { plot_scatter_interleaved_png(path.as_ptr(), xs.as_ptr(), u64::MAX / 2, opt) };
+        assert_eq!(result, 1);
+        assert!(fs::metadata("/tmp/test_huge_point_count.png").is_err());
+
+        assert_eq!(point_count::<f64>(2), Ok(2));
+        assert!(point_count::<f64>(isize::MAX as u64 / 4).is_err());
+    }
+
+    #[test]
+    fn test_scatter_gif() {
+        let xs: Vec<f64> = (0..10).map(|i| i as f64).collect();
+        let ys: Vec<Vec<f64>> = (0..3).map(|t| xs.iter().map(|x| x * t as f64).collect()).collect();
+        let frames: Vec<GifFrame> = ys
+            .iter()
+            .map(|ys| GifFrame {
+                xs: xs.as_ptr(),
+                ys: ys.as_ptr(),
+                n: xs.len() as u64,
+            })
+            .collect();
+        let opt = PlotOptions {
+            width: 160,
+            height: 120,
+            ..Default::default()
+        };
+
+        let path = CString::new("/tmp/test_scatter.gif").unwrap();
+        let result = unsafe { plot_scatter_gif(path.as_ptr(), frames.as_ptr(), frames.len(), 100, opt) };
+        assert_eq!(result, 0, "Expected success for three frames");
+
+        let bytes = fs::read("/tmp/test_scatter.gif").unwrap();
+        assert_eq!(&bytes[..6], b"GIF89a");
+        let mut decoder = gif::DecodeOptions::new().read_info(bytes.as_slice()).unwrap();
+        let mut count = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 10);
+            count += 1;
+        }
+        assert_eq!(count, 3);
+        fs::remove_file("/tmp/test_scatter.gif").ok();
+
+        let result = unsafe { plot_scatter_gif(path.as_ptr(), frames.as_ptr(), 0, 100, opt) };
+        assert_eq!(result, 1, "Expected failure for zero frames");
+    }
+
+    #[test]
+    fn test_quiver() {
+        let mut xs = Vec::new();
+        let mut ys = Vec::new();
+        for i in 0..3 {
+            for j in 0..3 {
+                xs.push(i as f64);
+                ys.push(j as f64);
+            }
+        }
+        let us = [1.0; 9];
+        let vs = [0.0; 9];
+
+        let path = CString::new("/tmp/test_quiver.png").unwrap();
+        let result = unsafe {
+            plot_quiver_png(
+                path.as_ptr(),
+                xs.as_ptr(),
+                ys.as_ptr(),
+                us.as_ptr(),
+                vs.as_ptr(),
+                9,
+                PlotOptions::default(),
+            )
+        };
+        assert_eq!(result, 0, "Expected success for a 3x3 grid");
+        let image = image::open("/tmp/test_quiver.png").unwrap().to_rgb8();
+        assert!(image.pixels().any(|p| p.0 == [0, 0, 255]));
+        fs::remove_file("/tmp/test_quiver.png").ok();
+
+        let bad_vs = [0.0, f64::NAN, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+        let err = plot_quiver_png_impl("/tmp/test_quiver.png", &xs, &ys, &us, &bad_vs, PlotOptions::default());
+        assert_eq!(err.unwrap_err(), "NaN value at index 1");
+    }
+
+    #[test]
+    fn test_parse_global_option() {
+        assert_eq!(parse_global_option("width", " 1200 "), Ok(1200));
+        assert_eq!(
+            parse_global_option("color_scheme", "Pastel"),
+            Ok(PLOT_SCHEME_PASTEL as u64)
+        );
+        assert_eq!(parse_global_option("color_scheme", "3"), Ok(3));
+        assert!(parse_global_option("width", "pastel").is_err());
+        assert!(parse_global_option("dpi", "-1").is_err());
+    }
+
+    #[test]
+    fn test_set_global_option_rejects_invalid_values() {
+        // Every case fails validation, so nothing is stored for the other tests to see
+        assert!(set_global_option("depth", 1).is_err());
+        assert!(set_global_option("width", 0).is_err());
+        assert!(set_global_option("dpi", u64::from(u32::MAX) + 1).is_err());
+        assert!(set_global_option("color_scheme", 256).is_err());
+        assert!(set_global_option("color_scheme", 99).is_err());
     }
 }
@@ -0,0 +1,38 @@
+//! PDF output: the rendered bitmap is embedded as an image on a single page.
+
+use crate::io_error;
+use printpdf::{ColorBits, ColorSpace, Image, ImageTransform, ImageXObject, Mm, PdfDocument, Px};
+
+// Resolution used for the page size when `PlotOptions::dpi` is 0; one pixel is one point
+const DEFAULT_PDF_DPI: u32 = 72;
+const MM_PER_INCH: f32 = 25.4;
+
+/// Writes `rgb` (`size.0` x `size.1` pixels) as a single-page PDF file at `path`.
+pub(crate) fn write_pdf(path: &str, rgb: Vec<u8>, (width, height): (u32, u32), dpi: u32) -> Result<(), String> {
+    let dpi = if dpi == 0 { DEFAULT_PDF_DPI } else { dpi } as f32;
+    let to_mm = |px: u32| Mm(px as f32 / dpi * MM_PER_INCH);
+
+    let (doc, page, layer) = PdfDocument::new("plotffi", to_mm(width), to_mm(height), "Chart");
+    let image = Image::from(ImageXObject {
+        width: Px(width as usize),
+        height: Px(height as usize),
+        color_space: ColorSpace::Rgb,
+        bits_per_component: ColorBits::Bit8,
+        interpolate: false,
+        image_data: rgb,
+        image_filter: None,
+        smask: None,
+        clipping_bbox: None,
+    });
+    image.add_to_layer(
+        doc.get_page(page).get_layer(layer),
+        ImageTransform {
+            dpi: Some(dpi),
+            ..Default::default()
+        },
+    );
+
+    let file = std::fs::File::create(path).map_err(io_error)?;
+    doc.save(&mut std::io::BufWriter::new(file))
+        .map_err(|e| format!("Failed to write PDF: {}", e))
+}
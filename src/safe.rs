@@ -0,0 +1,93 @@
+//! Safe Rust API for rendering charts without going through the C ABI.
+
+use crate::{RenderOptions, XLabels, draw_scatter, plot_scatter_png_impl, render_png_to_writer, with_render_defaults};
+
+/// Renders a scatter plot of `(x, y)` points to a PNG file.
+///
+/// A convenience for point sources such as a `Vec<(f64, f64)>` or a mapped
+/// iterator. The points are collected into separate X and Y vectors before
+/// rendering, so this allocates a copy of the data; callers that already hold
+/// slices should use [`plot_scatter_png_impl`] directly.
+pub fn plot_scatter_png_from_points<'a, I>(
+    path: &str,
+    points: I,
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String>
+where
+    I: IntoIterator<Item = (f64, f64)>,
+{
+    let (xs, ys): (Vec<f64>, Vec<f64>) = points.into_iter().unzip();
+    if xs.is_empty() {
+        return Err("Point count must be greater than zero".to_string());
+    }
+    plot_scatter_png_impl(path, &xs, &ys, opt)
+}
+
+/// Renders a scatter plot as PNG and writes it to `writer`.
+///
+/// The image is rendered and encoded in memory before anything is written, so on
+/// error `writer` receives no data. The writer is flushed on success.
+pub fn plot_scatter_png_to_writer<'a, W: std::io::Write>(
+    writer: W,
+    xs: &[f64],
+    ys: &[f64],
+    opt: impl Into<RenderOptions<'a>>,
+) -> Result<(), String> {
+    let opt = with_render_defaults(opt);
+    render_png_to_writer(writer, &opt, (opt.width, opt.height), |root| {
+        draw_scatter(root, xs, ys, &opt, XLabels::Numeric).map(|_| ())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlotOptions;
+    use std::fs;
+
+    #[test]
+    fn test_iterator_input() {
+        let points = (0..20).map(|i| {
+            let x = i as f64 * 0.5;
+            (x, x.sin())
+        });
+
+        plot_scatter_png_from_points("/tmp/test_safe_iter.png", points, PlotOptions::default()).unwrap();
+
+        assert!(fs::metadata("/tmp/test_safe_iter.png").is_ok());
+        fs::remove_file("/tmp/test_safe_iter.png").ok();
+    }
+
+    #[test]
+    fn test_empty_iterator() {
+        let err = plot_scatter_png_from_points("/tmp/test_safe_empty.png", std::iter::empty(), PlotOptions::default())
+            .unwrap_err();
+        assert_eq!(err, "Point count must be greater than zero");
+    }
+
+    #[test]
+    fn test_png_to_writer() {
+        let xs = [1.0, 2.0, 3.0];
+        let ys = [3.0, 1.0, 2.0];
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        plot_scatter_png_to_writer(&mut cursor, &xs, &ys, PlotOptions::default()).unwrap();
+
+        let png = cursor.into_inner();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(
+            png,
+            crate::plot_scatter_png_bytes_impl(&xs, &ys, PlotOptions::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_png_to_writer_error_writes_nothing() {
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        let opt = PlotOptions {
+            width: 0,
+            ..PlotOptions::default()
+        };
+        assert!(plot_scatter_png_to_writer(&mut cursor, &[1.0], &[1.0], opt).is_err());
+        assert!(cursor.into_inner().is_empty());
+    }
+}
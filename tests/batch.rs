@@ -0,0 +1,53 @@
+//! Batch rendering with `plot_scatter_png_batch_parallel()`.
+
+use plotffi::{BatchError, BatchSpec, PlotOptions, plot_batch_errors, plot_scatter_png_batch_parallel};
+use std::ffi::CStr;
+
+#[test]
+fn test_batch_reports_failed_specs() {
+    let xs = [1.0, 2.0, 3.0];
+    let ys = [3.0, 1.0, 2.0];
+    let paths = [
+        c"/tmp/test_batch_0.png",
+        c"/tmp/test_batch_1.png",
+        c"/tmp/test_batch_2.png",
+    ];
+    let mut specs: Vec<BatchSpec> = paths
+        .iter()
+        .map(|path| BatchSpec {
+            path: path.as_ptr(),
+            xs: xs.as_ptr(),
+            ys: ys.as_ptr(),
+            n: 3,
+            opt: PlotOptions::default(),
+        })
+        .collect();
+    specs[1].xs = std::ptr::null();
+
+    let failed = unsafe { plot_scatter_png_batch_parallel(specs.as_ptr(), specs.len()) };
+    assert_eq!(failed, 1);
+    assert!(std::fs::metadata("/tmp/test_batch_0.png").is_ok());
+    assert!(std::fs::metadata("/tmp/test_batch_2.png").is_ok());
+
+    assert_eq!(unsafe { plot_batch_errors(std::ptr::null_mut(), 0) }, 1);
+    let mut errors = [BatchError {
+        index: 0,
+        message: [0; 256],
+    }; 2];
+    assert_eq!(unsafe { plot_batch_errors(errors.as_mut_ptr(), errors.len()) }, 1);
+    assert_eq!(errors[0].index, 1);
+    let message = unsafe { CStr::from_ptr(errors[0].message.as_ptr()) };
+    assert!(!message.to_bytes().is_empty());
+
+    // A successful batch clears the failures
+    specs[1].xs = xs.as_ptr();
+    assert_eq!(
+        unsafe { plot_scatter_png_batch_parallel(specs.as_ptr(), specs.len()) },
+        0
+    );
+    assert_eq!(unsafe { plot_batch_errors(std::ptr::null_mut(), 0) }, 0);
+    assert_eq!(unsafe { plot_scatter_png_batch_parallel(std::ptr::null(), 1) }, -1);
+    for path in paths {
+        std::fs::remove_file(path.to_str().unwrap()).ok();
+    }
+}
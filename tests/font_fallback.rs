@@ -0,0 +1,33 @@
+//! Falling back to a system font when the bundled font cannot be loaded.
+
+use plotffi::{PlotOptions, plot_last_warning_message, plot_scatter_png, register_default_font_with_fallback};
+use std::ffi::CStr;
+
+#[test]
+fn test_font_fallback() {
+    let fallback = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/fonts/Inter-Regular.ttf");
+
+    // Without fallback paths the failure is reported as an error
+    let err = register_default_font_with_fallback(b"", &[]).unwrap_err();
+    assert_eq!(err, "Failed to register bundled font: invalid font data");
+
+    // Unusable font data falls back to the first readable font file
+    register_default_font_with_fallback(b"", &["/nonexistent.ttf", fallback]).unwrap();
+
+    let path = c"/tmp/test_font_fallback.png";
+    let xs = [1.0, 2.0];
+    let opt = PlotOptions {
+        use_system_font_fallback: 1,
+        ..PlotOptions::default()
+    };
+    let result = unsafe { plot_scatter_png(path.as_ptr(), xs.as_ptr(), xs.as_ptr(), 2, opt) };
+    std::fs::remove_file("/tmp/test_font_fallback.png").ok();
+    assert_eq!(result, 0);
+
+    let warning = plot_last_warning_message();
+    assert!(!warning.is_null());
+    assert_eq!(
+        unsafe { CStr::from_ptr(warning) }.to_str().unwrap(),
+        format!("Bundled font could not be loaded; using system font {}", fallback)
+    );
+}
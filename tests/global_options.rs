@@ -0,0 +1,139 @@
+//! Process-wide option defaults, themes and the base directory.
+//!
+//! These settings are process-wide, so these tests share one binary and take `LOCK` to
+//! run one at a time.
+
+use plotffi::{
+    PLOT_SCHEME_SET1, PlotOptions, plot_clear_base_directory, plot_options_default, plot_reset_global_options,
+    plot_scatter_png, plot_scatter_png_impl, plot_scatter_rgba_impl, plot_set_base_directory, plot_set_global_option,
+    plot_set_global_option_u32, plot_set_theme,
+};
+use std::sync::Mutex;
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[test]
+fn test_global_defaults_fill_zero_fields() {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    plot_reset_global_options();
+
+    assert_eq!(unsafe { plot_set_global_option_u32(c"width".as_ptr(), 320) }, 0);
+    assert_eq!(
+        unsafe { plot_set_global_option(c"height".as_ptr(), c"240".as_ptr()) },
+        0
+    );
+    assert_eq!(
+        unsafe { plot_set_global_option(c"color_scheme".as_ptr(), c"set1".as_ptr()) },
+        0
+    );
+
+    let opt = plot_options_default();
+    assert_eq!((opt.width, opt.height), (320, 240));
+    assert_eq!(opt.color_scheme, PLOT_SCHEME_SET1);
+    assert_eq!(opt.marker_radius, PlotOptions::default().marker_radius);
+
+    // Zero-sized options pick up the global size when rendering
+    let xs = [0.0, 1.0, 2.0];
+    let ys = [1.0, 0.0, 2.0];
+    let opt = PlotOptions {
+        width: 0,
+        height: 0,
+        ..PlotOptions::default()
+    };
+    let rgba = plot_scatter_rgba_impl(&xs, &ys, opt).unwrap();
+    assert_eq!(rgba.len(), 320 * 240 * 4);
+
+    // An explicit value still wins over the global default
+    let opt = PlotOptions {
+        width: 100,
+        height: 0,
+        ..PlotOptions::default()
+    };
+    let rgba = plot_scatter_rgba_impl(&xs, &ys, opt).unwrap();
+    assert_eq!(rgba.len(), 100 * 240 * 4);
+
+    plot_reset_global_options();
+    assert_eq!(plot_options_default().width, PlotOptions::default().width);
+}
+
+#[test]
+fn test_invalid_global_options_are_rejected() {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    plot_reset_global_options();
+
+    assert_eq!(unsafe { plot_set_global_option_u32(c"depth".as_ptr(), 1) }, 1);
+    assert_eq!(unsafe { plot_set_global_option_u32(c"width".as_ptr(), 0) }, 1);
+    assert_eq!(unsafe { plot_set_global_option(c"dpi".as_ptr(), c"high".as_ptr()) }, 1);
+    assert_eq!(unsafe { plot_set_global_option(std::ptr::null(), c"1".as_ptr()) }, 1);
+    assert_eq!(plot_options_default().width, PlotOptions::default().width);
+}
+
+#[test]
+fn test_dark_theme() {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = "/tmp/test_dark_theme.png";
+    let xs = [0.0, 1.0, 2.0];
+    let ys = [1.0, 0.0, 2.0];
+
+    assert_eq!(unsafe { plot_set_theme(c"dark".as_ptr()) }, 0);
+    let opt = plot_options_default();
+    assert_ne!(opt.background_color, 0);
+    assert_ne!(opt.hide_grid, 0);
+    plot_scatter_png_impl(path, &xs, &ys, opt).unwrap();
+    let image = image::open(path).unwrap().to_rgb8();
+    assert!(image.get_pixel(0, 0).0.iter().all(|&c| c < 0x40));
+
+    // Fields can still be overridden, and the theme does not touch PlotOptions::default()
+    let light = PlotOptions {
+        background_color: 0xFFFFFFFF,
+        ..plot_options_default()
+    };
+    plot_scatter_png_impl(path, &xs, &ys, light).unwrap();
+    assert_eq!(image::open(path).unwrap().to_rgb8().get_pixel(0, 0).0, [255, 255, 255]);
+    assert_eq!(PlotOptions::default().background_color, 0);
+
+    assert_eq!(unsafe { plot_set_theme(c"neon".as_ptr()) }, 1);
+    assert_eq!(unsafe { plot_set_theme(std::ptr::null()) }, 1);
+    assert_ne!(
+        plot_options_default().background_color,
+        0,
+        "a failed call keeps the theme"
+    );
+    assert_eq!(unsafe { plot_set_theme(c"default".as_ptr()) }, 0);
+    assert_eq!(plot_options_default().background_color, 0);
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn test_base_directory() {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let xs = [1.0, 2.0, 3.0];
+    let ys = [3.0, 1.0, 2.0];
+    let expected = std::path::Path::new("/tmp/test.png");
+    let _ = std::fs::remove_file(expected);
+
+    assert_eq!(unsafe { plot_set_base_directory(c"/tmp".as_ptr()) }, 0);
+    let result = unsafe {
+        plot_scatter_png(
+            c"test.png".as_ptr(),
+            xs.as_ptr(),
+            ys.as_ptr(),
+            3,
+            PlotOptions::default(),
+        )
+    };
+    assert_eq!(result, 0);
+    assert!(expected.exists());
+
+    // Absolute paths ignore the base directory
+    let absolute = c"/tmp/test_base_directory_absolute.png";
+    let result = unsafe { plot_scatter_png(absolute.as_ptr(), xs.as_ptr(), ys.as_ptr(), 3, PlotOptions::default()) };
+    assert_eq!(result, 0);
+    assert!(std::path::Path::new("/tmp/test_base_directory_absolute.png").exists());
+
+    // A missing directory is rejected and keeps the previous setting
+    assert_eq!(unsafe { plot_set_base_directory(c"/nonexistent/plotffi".as_ptr()) }, 1);
+    assert_eq!(unsafe { plot_set_base_directory(std::ptr::null()) }, 1);
+
+    plot_clear_base_directory();
+}
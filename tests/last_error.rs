@@ -0,0 +1,198 @@
+//! Reading, clearing and classifying the last error.
+//!
+//! The last error is process-wide, so these tests share one binary and take `LOCK` to
+//! run one at a time.
+
+use plotffi::{
+    ErrorBarOptions, GifFrame, PLOT_ERR_INFINITE_VALUE, PLOT_ERR_NAN_VALUE, PLOT_ERR_NONE, PLOT_ERR_OTHER, PlotOptions,
+    plot_band_png, plot_clear_last_error, plot_copy_last_error, plot_free_string, plot_has_error, plot_last_error_code,
+    plot_quantile_band_png, plot_quiver_png, plot_scatter_errorbars_ex, plot_scatter_f32_png, plot_scatter_gif,
+    plot_scatter_png, plot_take_error, poison_last_error,
+};
+use std::ffi::{CStr, c_char};
+use std::sync::Mutex;
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[test]
+fn test_take_error_outlives_later_errors() {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = c"/tmp/test_take_error.png";
+    let xs = [1.0, 2.0];
+    let ys = [1.0, 2.0];
+    let bad_range = PlotOptions {
+        auto_range: 0,
+        x_min: 2.0,
+        x_max: 1.0,
+        ..PlotOptions::default()
+    };
+
+    let result = unsafe { plot_scatter_png(path.as_ptr(), xs.as_ptr(), ys.as_ptr(), 2, bad_range) };
+    assert_eq!(result, 1);
+    let first = plot_take_error();
+    assert!(!first.is_null());
+    assert_eq!(plot_has_error(), 0, "taking the error clears it");
+    assert!(plot_take_error().is_null());
+
+    // A second failure stores a new message without touching the taken one
+    let result = unsafe { plot_scatter_png(std::ptr::null(), xs.as_ptr(), ys.as_ptr(), 2, PlotOptions::default()) };
+    assert_eq!(result, 1);
+    let second = plot_take_error();
+    assert!(!second.is_null());
+
+    let first_text = unsafe { CStr::from_ptr(first) }.to_str().unwrap();
+    assert!(first_text.starts_with("Invalid X range"), "{}", first_text);
+    assert_ne!(unsafe { CStr::from_ptr(second) }, unsafe { CStr::from_ptr(first) });
+
+    unsafe {
+        plot_free_string(first);
+        plot_free_string(second);
+    }
+}
+
+#[test]
+fn test_copy_last_error() {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = c"/tmp/test_copy_last_error.png";
+    let xs = [1.0, 2.0];
+    let opt = PlotOptions {
+        y_label_rotation: 30,
+        ..PlotOptions::default()
+    };
+    let result = unsafe { plot_scatter_png(path.as_ptr(), xs.as_ptr(), xs.as_ptr(), 2, opt) };
+    assert_eq!(result, 1);
+
+    let expected = "Unsupported label rotation: must be 0, \u{00B1}45, or \u{00B1}90".as_bytes();
+    let mut buf = [1 as c_char; 256];
+    let written = unsafe { plot_copy_last_error(buf.as_mut_ptr(), buf.len()) };
+    assert_eq!(written, expected.len());
+    assert_eq!(unsafe { CStr::from_ptr(buf.as_ptr()) }.to_bytes(), expected);
+
+    // A too-small buffer receives a NUL-terminated prefix
+    let mut buf = [1 as c_char; 16];
+    let written = unsafe { plot_copy_last_error(buf.as_mut_ptr(), buf.len()) };
+    assert_eq!(written, 15);
+    assert_eq!(buf[15], 0);
+    assert_eq!(unsafe { CStr::from_ptr(buf.as_ptr()) }.to_bytes(), &expected[..15]);
+
+    // Truncation never splits a multi-byte character
+    let split = expected.iter().position(|&b| b >= 0x80).unwrap();
+    let mut buf = [1 as c_char; 256];
+    let written = unsafe { plot_copy_last_error(buf.as_mut_ptr(), split + 2) };
+    assert_eq!(written, split);
+    assert_eq!(unsafe { CStr::from_ptr(buf.as_ptr()) }.to_bytes(), &expected[..split]);
+    assert_eq!(unsafe { plot_copy_last_error(std::ptr::null_mut(), 16) }, 0);
+}
+
+#[test]
+fn test_clear_and_has_error() {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let xs = [1.0, 2.0];
+    let ys = [1.0, 2.0];
+    let result = unsafe { plot_scatter_png(std::ptr::null(), xs.as_ptr(), ys.as_ptr(), 2, PlotOptions::default()) };
+    assert_eq!(result, 1);
+    assert_eq!(plot_has_error(), 1);
+
+    plot_clear_last_error();
+    assert_eq!(plot_has_error(), 0);
+    let mut buf = [1 as c_char; 8];
+    assert_eq!(unsafe { plot_copy_last_error(buf.as_mut_ptr(), buf.len()) }, 0);
+    assert_eq!(buf[0], 0);
+}
+
+#[test]
+fn test_last_error_code() {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = c"/tmp/test_last_error_code.png";
+    let xs32 = [1.0f32, f32::INFINITY, 3.0];
+    let ys32 = [1.0f32, 2.0, 3.0];
+    let opt = PlotOptions::default();
+    assert_eq!(opt.nan_policy, 0);
+
+    let result = unsafe { plot_scatter_f32_png(path.as_ptr(), xs32.as_ptr(), ys32.as_ptr(), 3, opt) };
+    assert_eq!(result, 1);
+    assert_eq!(plot_last_error_code(), PLOT_ERR_INFINITE_VALUE);
+
+    let xs = [1.0, 2.0, f64::NEG_INFINITY];
+    let ys = [1.0, f64::NAN, 3.0];
+    let result = unsafe { plot_scatter_png(path.as_ptr(), xs.as_ptr(), ys.as_ptr(), 2, opt) };
+    assert_eq!(result, 1);
+    assert_eq!(plot_last_error_code(), PLOT_ERR_NAN_VALUE);
+    let result = unsafe { plot_scatter_png(path.as_ptr(), xs.as_ptr(), [1.0, 2.0, 3.0].as_ptr(), 3, opt) };
+    assert_eq!(result, 1);
+    assert_eq!(plot_last_error_code(), PLOT_ERR_INFINITE_VALUE);
+
+    let result = unsafe { plot_scatter_png(std::ptr::null(), xs.as_ptr(), ys.as_ptr(), 3, opt) };
+    assert_eq!(result, 1);
+    assert_eq!(plot_last_error_code(), PLOT_ERR_OTHER);
+
+    // The code comes from the failing check, not the message, so context prefixes keep it
+    let frame = GifFrame {
+        xs: xs.as_ptr(),
+        ys: ys.as_ptr(),
+        n: 3,
+    };
+    let gif_path = c"/tmp/test_last_error_code.gif";
+    let result = unsafe { plot_scatter_gif(gif_path.as_ptr(), &frame, 1, 100, opt) };
+    assert_eq!(result, 1);
+    assert_eq!(plot_last_error_code(), PLOT_ERR_NAN_VALUE);
+    std::fs::remove_file("/tmp/test_last_error_code.gif").ok();
+
+    // The checks of the other chart types report the same codes
+    let finite = [1.0, 2.0, 3.0];
+    let nan = [1.0, f64::NAN, 3.0];
+    let inf = [1.0, 2.0, f64::INFINITY];
+    let (p, n) = (path.as_ptr(), 3);
+    let result = unsafe {
+        plot_quantile_band_png(
+            p,
+            finite.as_ptr(),
+            finite.as_ptr(),
+            nan.as_ptr(),
+            finite.as_ptr(),
+            n,
+            opt,
+        )
+    };
+    assert_eq!(result, 1);
+    assert_eq!(plot_last_error_code(), PLOT_ERR_NAN_VALUE);
+    let result = unsafe { plot_band_png(p, finite.as_ptr(), finite.as_ptr(), inf.as_ptr(), n, opt) };
+    assert_eq!(result, 1);
+    assert_eq!(plot_last_error_code(), PLOT_ERR_INFINITE_VALUE);
+    let result = unsafe { plot_quiver_png(p, finite.as_ptr(), finite.as_ptr(), inf.as_ptr(), nan.as_ptr(), n, opt) };
+    assert_eq!(result, 1);
+    assert_eq!(plot_last_error_code(), PLOT_ERR_NAN_VALUE);
+    let ebo = ErrorBarOptions {
+        y_err: inf.as_ptr(),
+        ..ErrorBarOptions::default()
+    };
+    let result = unsafe { plot_scatter_errorbars_ex(p, finite.as_ptr(), finite.as_ptr(), n, ebo, opt) };
+    assert_eq!(result, 1);
+    assert_eq!(plot_last_error_code(), PLOT_ERR_INFINITE_VALUE);
+
+    // Skipping non-finite points succeeds and clears the code
+    let skip = PlotOptions { nan_policy: 1, ..opt };
+    let result = unsafe { plot_scatter_f32_png(path.as_ptr(), xs32.as_ptr(), ys32.as_ptr(), 3, skip) };
+    assert_eq!(result, 0);
+    assert_eq!(plot_last_error_code(), PLOT_ERR_NONE);
+    std::fs::remove_file("/tmp/test_last_error_code.png").ok();
+}
+
+#[test]
+fn test_error_after_mutex_poisoned() {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    assert!(poison_last_error());
+
+    let xs = [1.0, 2.0];
+    let ys = [1.0, 2.0];
+    let result = unsafe { plot_scatter_png(std::ptr::null(), xs.as_ptr(), ys.as_ptr(), 2, PlotOptions::default()) };
+    assert_eq!(result, 1);
+    assert_eq!(plot_has_error(), 1);
+    let mut buf = [0 as c_char; 256];
+    let written = unsafe { plot_copy_last_error(buf.as_mut_ptr(), buf.len()) };
+    assert!(written > 0);
+    assert_eq!(unsafe { CStr::from_ptr(buf.as_ptr()) }.to_bytes().len(), written);
+
+    plot_clear_last_error();
+    assert_eq!(plot_has_error(), 0);
+}
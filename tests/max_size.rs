@@ -0,0 +1,32 @@
+//! Image size limits from `PLOTFFI_MAX_WIDTH` and `PLOTFFI_MAX_HEIGHT`.
+
+use plotffi::{PlotOptions, plot_scatter_png_impl};
+
+#[test]
+fn test_max_image_size() {
+    // SAFETY: no other thread of this test binary reads the environment
+    unsafe {
+        std::env::set_var("PLOTFFI_MAX_WIDTH", "100");
+        std::env::set_var("PLOTFFI_MAX_HEIGHT", "200");
+    }
+    let path = "/tmp/test_max_image_size.png";
+    let xs = [0.0, 1.0, 2.0];
+    let ys = [1.0, 0.0, 2.0];
+
+    let err = plot_scatter_png_impl(path, &xs, &ys, PlotOptions::default()).unwrap_err();
+    assert_eq!(err, "Width exceeds PLOTFFI_MAX_WIDTH limit");
+    let small = PlotOptions {
+        width: 100,
+        height: 100,
+        ..Default::default()
+    };
+    plot_scatter_png_impl(path, &xs, &ys, small).unwrap();
+    let tall = PlotOptions { height: 201, ..small };
+    let err = plot_scatter_png_impl(path, &xs, &ys, tall).unwrap_err();
+    assert_eq!(err, "Height exceeds PLOTFFI_MAX_HEIGHT limit");
+
+    // The variables are only read once
+    unsafe { std::env::remove_var("PLOTFFI_MAX_WIDTH") };
+    assert!(plot_scatter_png_impl(path, &xs, &ys, PlotOptions::default()).is_err());
+    std::fs::remove_file(path).ok();
+}
@@ -0,0 +1,27 @@
+//! A metadata sidecar that cannot be written is reported as a warning.
+
+use plotffi::{PlotOptions, plot_last_warning_message, plot_scatter_png};
+use std::ffi::CStr;
+
+#[test]
+fn test_unwritable_metadata_is_a_warning() {
+    // A directory in the way of the sidecar file
+    let dir = "/tmp/test_metadata_warning.png.json";
+    std::fs::create_dir_all(dir).unwrap();
+    let path = c"/tmp/test_metadata_warning.png";
+    let xs = [1.0, 2.0, 4.0];
+    let ys = [3.0, 1.0, 2.5];
+    let opt = PlotOptions {
+        write_metadata: 1,
+        ..PlotOptions::default()
+    };
+
+    let result = unsafe { plot_scatter_png(path.as_ptr(), xs.as_ptr(), ys.as_ptr(), 3, opt) };
+    assert_eq!(result, 0);
+    assert!(std::fs::metadata("/tmp/test_metadata_warning.png").is_ok());
+    let warning = unsafe { CStr::from_ptr(plot_last_warning_message()) };
+    assert!(warning.to_str().unwrap().contains("metadata file"), "{:?}", warning);
+
+    std::fs::remove_dir(dir).ok();
+    std::fs::remove_file("/tmp/test_metadata_warning.png").ok();
+}